@@ -1,5 +1,6 @@
 from sqlalchemy.orm import DeclarativeBase, relationship, Mapped, mapped_column
-from sqlalchemy import String, Integer, BigInteger, ForeignKey, Text, DateTime, func
+from sqlalchemy import String, Integer, BigInteger, Boolean, ForeignKey, Text, DateTime, Numeric, UniqueConstraint, func, text
+from sqlalchemy.dialects.postgresql import JSONB
 
 class Base(DeclarativeBase):
     pass
@@ -15,6 +16,9 @@ class Author(Base):
     city: Mapped[str | None] = mapped_column(String)
     age_range: Mapped[str | None] = mapped_column(String)
     gender: Mapped[str | None] = mapped_column(String)
+    department_code: Mapped[str | None] = mapped_column(String)
+    region_code: Mapped[str | None] = mapped_column(String)
+    pseudonymized_at: Mapped[DateTime | None] = mapped_column(DateTime(timezone=True))
 
     contributions = relationship("Contribution", back_populates="author")
 
@@ -40,7 +44,7 @@ class Question(Base):
     section: Mapped[str | None] = mapped_column(String)
     position: Mapped[int | None] = mapped_column(Integer)
     type: Mapped[str] = mapped_column(String)  # text, single_choice, multi_choice, scale, number, date, free_text
-    options_json: Mapped[str | None] = mapped_column(Text)
+    options_json: Mapped[dict | None] = mapped_column(JSONB)
 
     form = relationship("Form", back_populates="questions")
     options = relationship("Option", back_populates="question")
@@ -53,13 +57,15 @@ class Option(Base):
     code: Mapped[str] = mapped_column(String)
     label: Mapped[str] = mapped_column(Text)
     position: Mapped[int | None] = mapped_column(Integer)
-    meta_json: Mapped[str | None] = mapped_column(Text)
+    meta_json: Mapped[dict | None] = mapped_column(JSONB)
 
     question = relationship("Question", back_populates="options")
 
 # --- Contributions & Answers
 class Contribution(Base):
     __tablename__ = "contributions"
+    # une référence n'est unique qu'au sein de son formulaire (upsert de gdn_ingest)
+    __table_args__ = (UniqueConstraint("form_id", "source_contribution_id", name="uq_contributions_form_source_id"),)
     id: Mapped[int] = mapped_column(BigInteger, primary_key=True)
     source_contribution_id: Mapped[str | None] = mapped_column(String)
     author_id: Mapped[int | None] = mapped_column(BigInteger, ForeignKey("authors.id"))
@@ -69,8 +75,16 @@ class Contribution(Base):
     submitted_at: Mapped[str | None] = mapped_column(DateTime)
     title: Mapped[str | None] = mapped_column(String)
     import_batch_id: Mapped[str | None] = mapped_column(String)
+    # batch qui a créé la contribution (import_batch_id: dernier batch qui l'a écrite)
+    created_import_batch_id: Mapped[str | None] = mapped_column(String)
     raw_hash: Mapped[str | None] = mapped_column(String)
-    raw_json: Mapped[str | None] = mapped_column(Text)
+    raw_json: Mapped[dict | None] = mapped_column(JSONB)
+    # fichier et ligne (1-based) d'où vient la contribution, réécrits à chaque réimport
+    source_file: Mapped[str | None] = mapped_column(Text)
+    source_line: Mapped[int | None] = mapped_column(BigInteger)
+    # retrait sur la plateforme (gdn_ingest soft-delete, ligne à la corbeille): réponses conservées
+    deleted_at: Mapped[DateTime | None] = mapped_column(DateTime(timezone=True))
+    delete_reason: Mapped[str | None] = mapped_column(Text)
 
     author = relationship("Author", back_populates="contributions")
     form = relationship("Form", back_populates="contributions")
@@ -85,6 +99,11 @@ class Answer(Base):
     position: Mapped[int] = mapped_column(Integer, default=1)
     text: Mapped[str | None] = mapped_column(Text)
     value_json: Mapped[str | None] = mapped_column(Text)
+    value_num: Mapped[float | None] = mapped_column(Numeric)
+    value_date: Mapped[str | None] = mapped_column(DateTime)
+    skipped: Mapped[bool] = mapped_column(Boolean, default=False)
+    # réponse courte mutualisée (gdn_ingest --dictionary-texts): text NULL, valeur dans text_values
+    text_value_id: Mapped[int | None] = mapped_column(BigInteger, ForeignKey("text_values.id"))
 
     contribution = relationship("Contribution", back_populates="answers")
     question = relationship("Question", back_populates="answers")
@@ -119,6 +138,81 @@ class ContributionTopic(Base):
     contribution_id: Mapped[int] = mapped_column(BigInteger, ForeignKey("contributions.id"), primary_key=True)
     topic_id: Mapped[int] = mapped_column(BigInteger, ForeignKey("topics.id"), primary_key=True)
 
+# --- Runs d'ingestion (gdn_ingest): une ligne `running` par formulaire sert de verrou
+class ImportBatch(Base):
+    __tablename__ = "import_batches"
+    id: Mapped[int] = mapped_column(BigInteger, primary_key=True)
+    batch: Mapped[str] = mapped_column(String)
+    form_id: Mapped[int] = mapped_column(BigInteger, ForeignKey("forms.id"))
+    status: Mapped[str] = mapped_column(String)  # running | success | error | abandoned | deleted
+    started_at: Mapped[DateTime] = mapped_column(DateTime(timezone=True), server_default=func.now())
+    heartbeat_at: Mapped[DateTime | None] = mapped_column(DateTime(timezone=True))
+    finished_at: Mapped[DateTime | None] = mapped_column(DateTime(timezone=True))
+    host: Mapped[str | None] = mapped_column(String)
+    pid: Mapped[int | None] = mapped_column(Integer)
+    error: Mapped[str | None] = mapped_column(Text)
+    provenance: Mapped[dict] = mapped_column(JSONB, server_default=text("'{}'::jsonb"))
+    mapping_path: Mapped[str | None] = mapped_column(Text)
+    # lignes lues, lignes sans contribution écrite, valeurs en erreur (fin de run)
+    rows_total: Mapped[int | None] = mapped_column(BigInteger)
+    rows_skipped: Mapped[int | None] = mapped_column(BigInteger)
+    errors: Mapped[int | None] = mapped_column(BigInteger)
+
+class IngestCheckpoint(Base):
+    __tablename__ = "ingest_checkpoints"
+    batch: Mapped[str] = mapped_column(String, primary_key=True)
+    form_id: Mapped[int] = mapped_column(BigInteger, ForeignKey("forms.id"), primary_key=True)
+    file: Mapped[str] = mapped_column(Text, primary_key=True)
+    # enregistrements committés, ligne source du dernier (gdn_ingest --resume)
+    rows_done: Mapped[int] = mapped_column(BigInteger)
+    last_line: Mapped[int | None] = mapped_column(BigInteger)
+    complete: Mapped[bool] = mapped_column(Boolean, server_default=text("false"))
+    updated_at: Mapped[DateTime] = mapped_column(DateTime(timezone=True), server_default=func.now())
+
+class SourceFile(Base):
+    __tablename__ = "source_files"
+    id: Mapped[int] = mapped_column(BigInteger, primary_key=True)
+    form_id: Mapped[int] = mapped_column(BigInteger, ForeignKey("forms.id"))
+    # nom sans dossier, empreinte SHA-256 du contenu décompressé (gdn_ingest --skip-ingested)
+    file_name: Mapped[str] = mapped_column(Text)
+    path: Mapped[str] = mapped_column(Text)
+    checksum: Mapped[str] = mapped_column(String(64))
+    # empreinte des 64 Kio premiers octets: pré-tri de --skip-ingested avant la passe complète
+    head_checksum: Mapped[str | None] = mapped_column(String(64))
+    size_bytes: Mapped[int | None] = mapped_column(BigInteger)
+    rows: Mapped[int] = mapped_column(BigInteger)
+    batch: Mapped[str] = mapped_column(String)
+    completed_at: Mapped[DateTime] = mapped_column(DateTime(timezone=True), server_default=func.now())
+
+class TextValue(Base):
+    __tablename__ = "text_values"
+    id: Mapped[int] = mapped_column(BigInteger, primary_key=True)
+    value: Mapped[str] = mapped_column(Text, unique=True)
+    refcount: Mapped[int] = mapped_column(BigInteger, default=0)
+
+# --- Journal des lignes ingérées (gdn_ingest): première ingestion de chaque contenu (row_hash unique)
+class RawImport(Base):
+    __tablename__ = "raw_imports"
+    __table_args__ = (UniqueConstraint("form_id", "row_hash", name="uq_raw_imports_form_row_hash"),)
+    id: Mapped[int] = mapped_column(BigInteger, primary_key=True)
+    form_id: Mapped[int | None] = mapped_column(BigInteger, ForeignKey("forms.id"))
+    batch_id: Mapped[str] = mapped_column(String, index=True)
+    file_path: Mapped[str] = mapped_column(Text)
+    # numéro d'enregistrement dans le fichier, 1 = première ligne de données
+    row_number: Mapped[int] = mapped_column(BigInteger)
+    contribution_id: Mapped[int | None] = mapped_column(BigInteger, ForeignKey("contributions.id", ondelete="SET NULL"))
+    row_hash: Mapped[str] = mapped_column(String)
+    raw_json: Mapped[dict | None] = mapped_column(JSONB)
+    ingested_at: Mapped[DateTime] = mapped_column(DateTime(timezone=True), server_default=func.now())
+
+# --- Effacements RGPD (gdn_ingest erase): SHA-256 du email_hash demandé, jamais le hash lui-même
+class GdprErasure(Base):
+    __tablename__ = "gdpr_erasures"
+    id: Mapped[int] = mapped_column(BigInteger, primary_key=True)
+    erased_at: Mapped[DateTime] = mapped_column(DateTime(timezone=True), server_default=func.now())
+    email_hash_sha256: Mapped[str] = mapped_column(String(64))
+    rows_affected: Mapped[int] = mapped_column(BigInteger)
+
 # --- Statistiques
 class AnswerValidStats(Base):
     __tablename__ = "answer_valid_stats"
@@ -0,0 +1,89 @@
+// ---------- Tranches d'âge canoniques ----------
+//
+// Chaque export GDN code l'âge à sa façon: `25-34`, `25 à 34 ans`,
+// `Entre 25 et 34 ans`, `65 ans et plus`, ou une année de naissance.
+// `authors.age_range` reçoit toujours une des tranches de `BUCKETS`:
+//   1. correspondance explicite `defaults.author.age_range_map` (libellé → tranche);
+//   2. sinon bornes lues dans le libellé (intervalle inclus dans une tranche);
+//   3. sinon âge unique, ou année de naissance rapportée à `form.reference_year`.
+// Ce qui ne rentre dans aucune tranche est conservé tel quel (et compté).
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::collections::BTreeMap;
+
+/// Tranches canoniques (bornes incluses; la dernière est ouverte)
+pub const BUCKETS: [(u32, u32, &str); 5] = [
+    (18, 24, "18-24"),
+    (25, 34, "25-34"),
+    (35, 49, "35-49"),
+    (50, 64, "50-64"),
+    (65, u32::MAX, "65+"),
+];
+
+static NUMBER_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"\d+").unwrap());
+static OPEN_ENDED_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?i)\+|plus").unwrap());
+
+pub fn is_bucket(label: &str) -> bool {
+    BUCKETS.iter().any(|&(_, _, b)| b == label)
+}
+
+/// Tranche contenant tout l'intervalle [lo, hi]
+fn bucket_of(lo: u32, hi: u32) -> Option<&'static str> {
+    BUCKETS.iter().find(|&&(min, max, _)| min <= lo && hi <= max).map(|&(_, _, b)| b)
+}
+
+/// Tranche canonique de `raw`, ou `None` si elle ne peut pas être déterminée
+pub fn normalize<'m>(
+    raw: &str,
+    map: &'m BTreeMap<String, String>,
+    reference_year: Option<u32>,
+) -> Option<&'m str> {
+    let label = raw.trim().to_lowercase();
+    if let Some((_, bucket)) = map.iter().find(|(k, _)| k.trim().to_lowercase() == label) {
+        return Some(bucket.as_str());
+    }
+    let numbers: Vec<u32> = NUMBER_RE.find_iter(&label).filter_map(|m| m.as_str().parse().ok()).collect();
+    match numbers[..] {
+        [lo, hi] if lo <= hi && hi < 130 => bucket_of(lo, hi),
+        [age] if age < 130 && OPEN_ENDED_RE.is_match(&label) => bucket_of(age, u32::MAX),
+        [age] if age < 130 => bucket_of(age, age),
+        [year] if (1900..=reference_year?).contains(&year) => {
+            let age = reference_year? - year;
+            bucket_of(age, age)
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gdn_labels() {
+        let map = BTreeMap::new();
+        let n = |raw| normalize(raw, &map, Some(2019));
+        assert_eq!(n("25-34"), Some("25-34"));
+        assert_eq!(n("25 à 34 ans"), Some("25-34"));
+        assert_eq!(n("Entre 25 et 34 ans"), Some("25-34"));
+        assert_eq!(n("65 ans et plus"), Some("65+"));
+        assert_eq!(n("65+"), Some("65+"));
+        assert_eq!(n("42"), Some("35-49"));
+        assert_eq!(n("1990"), Some("25-34"));
+        // intervalle à cheval sur deux tranches, mineur, texte libre
+        assert_eq!(n("20-29"), None);
+        assert_eq!(n("15-17"), None);
+        assert_eq!(n("jeune"), None);
+        // sans année de référence, une année de naissance reste telle quelle
+        assert_eq!(normalize("1990", &map, None), None);
+    }
+
+    #[test]
+    fn explicit_map_wins() {
+        let map = BTreeMap::from([("Senior".to_string(), "65+".to_string())]);
+        assert_eq!(normalize(" senior ", &map, Some(2019)), Some("65+"));
+        assert!(is_bucket("50-64"));
+        assert!(!is_bucket("50 - 64"));
+    }
+}
@@ -0,0 +1,253 @@
+// ---------- Colonnes à plusieurs noms (exports de millésimes différents) ----------
+//
+// D'un export à l'autre, certaines colonnes changent de nom (2019-01:
+// `authorZipCode`, 2019-03: `authorPostalCode`). Le source_column d'une
+// question, les colonnes de defaults.author / defaults.contribution et les
+// `source.columns` de free_text acceptent donc une liste de candidats:
+//
+//   zipcode: [authorZipCode, authorPostalCode]
+//
+// Pour chaque fichier, le premier candidat présent dans l'en-tête l'emporte;
+// le mapping continue de le désigner par le premier nom de la liste (ce que
+// lisent validate, dry-run et l'ingestion). La résolution est affichée une
+// fois par fichier. Deux candidats présents à la fois: le premier gagne,
+// mais une ligne où les deux ont des valeurs non vides différentes est une
+// erreur (données ambiguës). raw_json garde les noms réels du fichier.
+
+use anyhow::Result;
+use csv::StringRecord;
+use serde::Deserialize;
+use std::fmt;
+use std::ops::Deref;
+
+use crate::input::ColumnAccessor;
+use crate::Mapping;
+
+/// Nom de colonne du mapping: un nom, ou une liste de candidats
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(try_from = "RawColumn")]
+pub struct ColumnRef {
+    /// premier candidat: nom sous lequel le mapping lit la colonne
+    name: String,
+    /// candidats suivants, dans l'ordre de préférence
+    aliases: Vec<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RawColumn {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl TryFrom<RawColumn> for ColumnRef {
+    type Error = String;
+
+    fn try_from(raw: RawColumn) -> Result<Self, String> {
+        let mut names = match raw {
+            RawColumn::One(name) => vec![name],
+            RawColumn::Many(names) => names,
+        };
+        if names.is_empty() {
+            return Err("liste de colonnes vide".to_string());
+        }
+        let name = names.remove(0);
+        Ok(ColumnRef { name, aliases: names })
+    }
+}
+
+impl Deref for ColumnRef {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.name
+    }
+}
+
+impl fmt::Display for ColumnRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.name)
+    }
+}
+
+impl ColumnRef {
+    pub fn as_str(&self) -> &str {
+        &self.name
+    }
+
+    /// Tous les noms acceptés, le nom du mapping en premier
+    pub fn candidates(&self) -> impl Iterator<Item = &str> {
+        std::iter::once(self.name.as_str()).chain(self.aliases.iter().map(String::as_str))
+    }
+}
+
+/// Colonnes du mapping qui ont des alias
+pub fn with_aliases(mapping: &Mapping) -> Vec<&ColumnRef> {
+    let a = &mapping.defaults.author;
+    let c = &mapping.defaults.contribution;
+    let fields = [
+        &a.source_author_id,
+        &a.name,
+        &a.email,
+        &a.email_hash,
+        &a.zipcode,
+        &a.city,
+        &a.age_range,
+        &a.gender,
+        &c.source_contribution_id,
+        &c.submitted_at,
+        &c.title,
+        &c.source,
+    ];
+    let questions = mapping.questions.iter().flat_map(|qm| {
+        qm.source_column.iter().chain(qm.source.iter().flat_map(|src| src.columns.iter()))
+    });
+    fields.into_iter().flatten().chain(questions).filter(|col| !col.aliases.is_empty()).collect()
+}
+
+/// Alias résolus pour un fichier
+#[derive(Debug, Default, PartialEq)]
+pub struct Resolution {
+    /// nom du mapping → colonne réelle (seulement quand elles diffèrent)
+    renamed: Vec<(String, String)>,
+    /// colonne retenue → autres candidats présents dans l'en-tête
+    rivals: Vec<(String, Vec<String>)>,
+}
+
+impl Resolution {
+    pub fn resolve(mapping: &Mapping, headers: &StringRecord) -> Self {
+        let mut out = Resolution::default();
+        for col in with_aliases(mapping) {
+            let mut present = col.candidates().filter(|c| headers.iter().any(|h| h == *c));
+            let Some(chosen) = present.next() else { continue };
+            if chosen != col.name && !out.renamed.iter().any(|(n, _)| *n == col.name) {
+                out.renamed.push((col.name.clone(), chosen.to_string()));
+            }
+            let others: Vec<String> = present.map(str::to_string).collect();
+            if !others.is_empty() && !out.rivals.iter().any(|(c, _)| c == chosen) {
+                out.rivals.push((chosen.to_string(), others));
+            }
+        }
+        out
+    }
+
+    /// Ce qui a été retenu pour le fichier (affiché une fois)
+    pub fn describe(&self) -> Vec<String> {
+        let renamed = self.renamed.iter().map(|(name, actual)| format!("'{name}' lue dans '{actual}'"));
+        let rivals = self.rivals.iter().map(|(chosen, others)| {
+            let others: Vec<String> = others.iter().map(|o| format!("'{o}'")).collect();
+            format!("'{chosen}' retenue, {} aussi présente(s): valeurs comparées ligne à ligne", others.join(", "))
+        });
+        renamed.chain(rivals).collect()
+    }
+
+    /// En-tête vu par le mapping: colonnes réelles + noms du mapping résolus
+    /// (contrôle des colonnes manquantes, cf. validate::check_headers)
+    pub fn headers(&self, headers: &StringRecord) -> StringRecord {
+        let mut out = headers.clone();
+        for (name, _) in &self.renamed {
+            out.push_field(name);
+        }
+        out
+    }
+
+    /// Candidats présents ensemble avec des valeurs non vides différentes
+    pub fn check(&self, row: &dyn ColumnAccessor) -> Result<()> {
+        for (chosen, others) in &self.rivals {
+            let Some(kept) = row.value(chosen) else { continue };
+            for other in others {
+                if let Some(v) = row.value(other).filter(|v| *v != kept) {
+                    anyhow::bail!("colonnes '{chosen}' et '{other}' (alias) toutes deux remplies, valeurs différentes: '{kept}' ≠ '{v}'");
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Ligne lue sous les noms du mapping
+    pub fn row<'r>(&'r self, row: &'r dyn ColumnAccessor) -> Aliased<'r> {
+        Aliased { row, resolution: self }
+    }
+}
+
+/// Ligne dont les colonnes à alias se lisent sous le nom du mapping
+pub struct Aliased<'r> {
+    row: &'r dyn ColumnAccessor,
+    resolution: &'r Resolution,
+}
+
+impl ColumnAccessor for Aliased<'_> {
+    fn cell(&self, col: &str) -> Option<&str> {
+        match self.resolution.renamed.iter().find(|(name, _)| name == col) {
+            Some((_, actual)) => self.row.cell(actual),
+            None => self.row.cell(col),
+        }
+    }
+
+    /// noms réels du fichier (raw_json, colonnes non lues)
+    fn columns(&self) -> Box<dyn Iterator<Item = (&str, &str)> + '_> {
+        self.row.columns()
+    }
+
+    fn line(&self) -> Option<u64> {
+        self.row.line()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::CsvRow;
+    use std::rc::Rc;
+
+    fn mapping() -> Mapping {
+        serde_yaml::from_str(
+            r#"
+form: { name: t }
+defaults:
+  author: { zipcode: [authorZipCode, authorPostalCode] }
+questions:
+  - { code: q1, prompt: Q, type: text, source_column: [avis, opinion] }
+  - { code: q2, prompt: Q, type: free_text, source: { columns: [titre, [corps, body]] } }
+"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn first_present_candidate_wins() {
+        let m = mapping();
+        assert_eq!(m.defaults.author.zipcode.as_deref(), Some("authorZipCode"));
+        let headers = StringRecord::from(vec!["authorPostalCode", "avis", "opinion", "titre", "body"]);
+        let resolution = Resolution::resolve(&m, &headers);
+        assert_eq!(
+            resolution.describe(),
+            vec![
+                "'authorZipCode' lue dans 'authorPostalCode'",
+                "'corps' lue dans 'body'",
+                "'avis' retenue, 'opinion' aussi présente(s): valeurs comparées ligne à ligne",
+            ]
+        );
+        let seen = resolution.headers(&headers);
+        assert!(crate::validate::check_headers("f.csv", &m, &seen).is_empty());
+
+        let headers = Rc::new(headers);
+        let row = CsvRow::new(headers.clone(), StringRecord::from(vec!["75001", "oui", "", "t", "c"]));
+        resolution.check(&row).unwrap();
+        let aliased = resolution.row(&row);
+        assert_eq!((aliased.cell("authorZipCode"), aliased.cell("corps")), (Some("75001"), Some("c")));
+        assert_eq!(aliased.columns().next(), Some(("authorPostalCode", "75001")));
+
+        let same = CsvRow::new(headers.clone(), StringRecord::from(vec!["75001", "oui", " oui", "t", "c"]));
+        resolution.check(&same).unwrap();
+        let clash = CsvRow::new(headers, StringRecord::from(vec!["75001", "oui", "non", "t", "c"]));
+        let err = resolution.check(&clash).unwrap_err().to_string();
+        assert_eq!(err, "colonnes 'avis' et 'opinion' (alias) toutes deux remplies, valeurs différentes: 'oui' ≠ 'non'");
+    }
+
+    #[test]
+    fn empty_candidate_list_is_refused() {
+        let err = serde_yaml::from_str::<ColumnRef>("[]").unwrap_err().to_string();
+        assert!(err.contains("liste de colonnes vide"), "{err}");
+    }
+}
@@ -0,0 +1,228 @@
+// ---------- Détecteur d'anomalies pendant l'ingestion ----------
+//
+// Statistiques bornées en mémoire, tenues fichier par fichier:
+//   - single_choice: top-K des options retenues (space-saving, K compteurs);
+//   - number/scale: min, max et moyenne au fil de l'eau.
+// Tous les `log_every` lignes (et en fin de fichier), des règles simples sont
+// évaluées; chacune n'avertit qu'une fois par run:
+//   - une option absorbe plus de `dominant_share` des réponses;
+//   - l'étendue d'une question numérique dépasse `range_blowup` fois celle
+//     du premier contrôle.
+// Seuils réglables dans la section `ingest.anomalies` du mapping.
+
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(deny_unknown_fields, default)]
+pub struct Thresholds {
+    pub enabled: bool,
+    /// réponses minimales avant d'évaluer une question
+    pub min_answers: u64,
+    /// part maximale d'une seule option (single_choice)
+    pub dominant_share: f64,
+    /// croissance maximale de l'étendue max - min (number/scale)
+    pub range_blowup: f64,
+    /// options suivies par question
+    pub top_k: usize,
+}
+
+impl Default for Thresholds {
+    fn default() -> Self {
+        Thresholds { enabled: true, min_answers: 500, dominant_share: 0.95, range_blowup: 100.0, top_k: 16 }
+    }
+}
+
+impl Thresholds {
+    pub fn problems(&self) -> Vec<String> {
+        let mut out = Vec::new();
+        if !(self.dominant_share > 0.0 && self.dominant_share <= 1.0) {
+            out.push(format!("ingest.anomalies.dominant_share: {} hors de ]0, 1]", self.dominant_share));
+        }
+        if self.range_blowup <= 1.0 {
+            out.push(format!("ingest.anomalies.range_blowup: {} doit être > 1", self.range_blowup));
+        }
+        if self.top_k == 0 {
+            out.push("ingest.anomalies.top_k: doit être ≥ 1".to_string());
+        }
+        out
+    }
+}
+
+/// Options les plus fréquentes, en K compteurs au plus (comptes majorés)
+#[derive(Debug, Default)]
+struct TopK {
+    counters: Vec<(String, u64)>,
+    total: u64,
+}
+
+impl TopK {
+    fn add(&mut self, label: &str, k: usize) {
+        self.total += 1;
+        if let Some(c) = self.counters.iter_mut().find(|(l, _)| l == label) {
+            c.1 += 1;
+        } else if self.counters.len() < k {
+            self.counters.push((label.to_string(), 1));
+        } else if let Some(min) = self.counters.iter_mut().min_by_key(|(_, n)| *n) {
+            // space-saving: le moins fréquent cède sa place et son compte
+            *min = (label.to_string(), min.1 + 1);
+        }
+    }
+
+    fn top(&self) -> Option<(&str, u64)> {
+        self.counters.iter().max_by_key(|(_, n)| *n).map(|(l, n)| (l.as_str(), *n))
+    }
+}
+
+#[derive(Debug)]
+struct Numeric {
+    n: u64,
+    min: f64,
+    max: f64,
+    mean: f64,
+    /// étendue au premier contrôle (au moins 1)
+    baseline: Option<(f64, f64)>,
+}
+
+impl Default for Numeric {
+    fn default() -> Self {
+        Numeric { n: 0, min: f64::INFINITY, max: f64::NEG_INFINITY, mean: 0.0, baseline: None }
+    }
+}
+
+impl Numeric {
+    fn add(&mut self, v: f64) {
+        self.n += 1;
+        self.min = self.min.min(v);
+        self.max = self.max.max(v);
+        self.mean += (v - self.mean) / self.n as f64;
+    }
+}
+
+/// Statistiques d'un fichier; `tripped` (partagé par le run) évite les répétitions
+#[derive(Debug, Default)]
+pub struct Detector<'a> {
+    thresholds: Thresholds,
+    choices: HashMap<&'a str, TopK>,
+    numbers: HashMap<&'a str, Numeric>,
+}
+
+impl<'a> Detector<'a> {
+    pub fn new(thresholds: Thresholds) -> Self {
+        Detector { thresholds, ..Default::default() }
+    }
+
+    pub fn choice(&mut self, code: &'a str, label: &str) {
+        if self.thresholds.enabled {
+            self.choices.entry(code).or_default().add(label, self.thresholds.top_k);
+        }
+    }
+
+    pub fn number(&mut self, code: &'a str, v: f64) {
+        if self.thresholds.enabled {
+            self.numbers.entry(code).or_default().add(v);
+        }
+    }
+
+    /// Règles franchies pour la première fois depuis le début du run
+    pub fn check(&mut self, tripped: &mut HashSet<String>) -> Vec<String> {
+        let t = &self.thresholds;
+        let mut out = Vec::new();
+        for (code, top) in &self.choices {
+            let Some((label, n)) = top.top().filter(|_| top.total >= t.min_answers) else { continue };
+            let share = n as f64 / top.total as f64;
+            if share > t.dominant_share && tripped.insert(format!("{code}:dominant")) {
+                out.push(format!(
+                    "question '{code}': l'option '{label}' absorbe {:.1} % des {} réponses (seuil {:.0} %) — mapping à vérifier",
+                    100.0 * share,
+                    top.total,
+                    100.0 * t.dominant_share
+                ));
+            }
+        }
+        for (code, num) in self.numbers.iter_mut().filter(|(_, num)| num.n >= t.min_answers) {
+            let Some((lo, hi)) = num.baseline else {
+                num.baseline = Some((num.min, num.max));
+                continue;
+            };
+            let growth = (num.max - num.min) / (hi - lo).max(1.0);
+            if growth > t.range_blowup && tripped.insert(format!("{code}:range")) {
+                out.push(format!(
+                    "question '{code}': étendue [{}, {}] ×{growth:.0} depuis [{lo}, {hi}] (moyenne {:.2}) — unité ou colonne à vérifier",
+                    num.min, num.max, num.mean
+                ));
+            }
+        }
+        out.sort();
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn thresholds() -> Thresholds {
+        Thresholds { min_answers: 10, ..Default::default() }
+    }
+
+    #[test]
+    fn top_k_is_bounded() {
+        let mut top = TopK::default();
+        for i in 0..1000 {
+            top.add(&format!("ville {i}"), 4);
+            top.add("Paris", 4);
+        }
+        assert_eq!(top.counters.len(), 4);
+        assert_eq!(top.top().map(|(l, _)| l), Some("Paris"));
+    }
+
+    #[test]
+    fn dominant_option_warns_once() {
+        let mut d = Detector::new(thresholds());
+        let mut tripped = HashSet::new();
+        for _ in 0..9 {
+            d.choice("q", "Oui");
+        }
+        assert!(d.check(&mut tripped).is_empty(), "pas assez de réponses");
+        for _ in 0..91 {
+            d.choice("q", "Oui");
+        }
+        d.choice("q", "Non");
+        let w = d.check(&mut tripped);
+        assert_eq!(w.len(), 1);
+        assert!(w[0].contains("'Oui' absorbe 99.0 %"));
+        assert!(d.check(&mut tripped).is_empty());
+
+        // règle déjà franchie dans un fichier précédent: silence
+        let mut next = Detector::new(thresholds());
+        for _ in 0..20 {
+            next.choice("q", "Oui");
+        }
+        assert!(next.check(&mut tripped).is_empty());
+    }
+
+    #[test]
+    fn numeric_range_blowup() {
+        let mut d = Detector::new(thresholds());
+        let mut tripped = HashSet::new();
+        for v in 18..60 {
+            d.number("age", v as f64);
+        }
+        assert!(d.check(&mut tripped).is_empty(), "premier contrôle: référence");
+        d.number("age", 35_000.0);
+        let w = d.check(&mut tripped);
+        assert_eq!(w.len(), 1);
+        assert!(w[0].starts_with("question 'age': étendue [18, 35000]"));
+    }
+
+    #[test]
+    fn disabled_detector_stays_silent() {
+        let mut d = Detector::new(Thresholds { enabled: false, ..thresholds() });
+        for _ in 0..100 {
+            d.choice("q", "Oui");
+        }
+        assert!(d.check(&mut HashSet::new()).is_empty());
+        assert!(!Thresholds { top_k: 0, ..thresholds() }.problems().is_empty());
+    }
+}
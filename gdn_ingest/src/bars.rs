@@ -0,0 +1,86 @@
+// ---------- Barres de progression (indicatif) ----------
+//
+// Une barre pour les fichiers, un spinner de lignes par fichier en cours.
+// Sans TTY ou avec `--no-progress`, les barres sont masquées et les lignes de
+// progression retombent sur stdout comme avant. Les barres sont effacées au
+// drop (y compris sur erreur) pour ne pas se mêler au message d'erreur.
+
+use indicatif::{MultiProgress, ProgressBar, ProgressDrawTarget, ProgressStyle};
+use std::io::IsTerminal;
+
+/// println! qui passe au-dessus des barres de progression
+macro_rules! say {
+    ($bars:expr, $($arg:tt)*) => { $bars.println(format!($($arg)*)) };
+}
+pub(crate) use say;
+
+pub struct Bars {
+    mp: MultiProgress,
+    files: ProgressBar,
+    hidden: bool,
+}
+
+impl Bars {
+    pub fn new(n_files: usize, enabled: bool) -> Self {
+        let hidden = !enabled || !std::io::stderr().is_terminal();
+        let mp = if hidden {
+            MultiProgress::with_draw_target(ProgressDrawTarget::hidden())
+        } else {
+            MultiProgress::new()
+        };
+        let files = mp.add(ProgressBar::new(n_files as u64));
+        files.set_style(
+            ProgressStyle::with_template("{prefix:.bold} [{bar:30}] {pos}/{len} fichiers · {elapsed}")
+                .expect("template valide")
+                .progress_chars("=> "),
+        );
+        files.set_prefix("ingest");
+        Bars { mp, files, hidden }
+    }
+
+    pub fn set_prefix(&self, prefix: &'static str) {
+        self.files.set_prefix(prefix);
+    }
+
+    /// Spinner de lignes pour un fichier (nombre de lignes inconnu d'avance)
+    pub fn rows(&self, path: &str) -> ProgressBar {
+        let bar = self.mp.add(ProgressBar::new_spinner());
+        bar.set_style(
+            ProgressStyle::with_template("  {spinner} {msg} · {pos} lignes · {elapsed} · {per_sec}")
+                .expect("template valide"),
+        );
+        bar.set_message(path.to_string());
+        bar
+    }
+
+    pub fn file_done(&self, rows: ProgressBar) {
+        rows.finish_and_clear();
+        self.mp.remove(&rows);
+        self.files.inc(1);
+    }
+
+    pub fn println(&self, msg: String) {
+        if self.hidden {
+            println!("{msg}");
+        } else {
+            let _ = self.mp.println(msg);
+        }
+    }
+
+    /// Ligne de progression périodique: inutile quand les barres sont visibles
+    pub fn progress_line(&self, msg: String) {
+        if self.hidden {
+            println!("{msg}");
+        }
+    }
+
+    pub fn finish(&self) {
+        self.files.finish();
+    }
+}
+
+impl Drop for Bars {
+    fn drop(&mut self) {
+        let _ = self.mp.clear();
+    }
+}
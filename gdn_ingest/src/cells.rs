@@ -0,0 +1,166 @@
+// ---------- Cellules des questions à choix et textes composés ----------
+//
+// Découpage des cellules en réponses, sans base ni cache: l'ingestion,
+// explain et dry-run s'en servent pour lire une ligne de la même façon.
+//
+// multi_choice (une colonne): valeurs séparées par `delimiter` (défaut `;`),
+// chacune sans espaces de bord, les vides écartés ("A; ;B;" → A, B).
+//
+// free_text: cellules des colonnes `source.columns` dans leur ordre, vides
+// (ou blanches) et absentes écartées, jointes par `joiner`; aucune → pas de
+// réponse.
+//
+// ranking: une colonne par rang (`source.columns`, dans l'ordre). Le rang
+// est celui de la colonne, même si une précédente est vide; une valeur déjà
+// classée plus haut dans la ligne est marquée (signalée, puis écrite).
+//
+// matrix: une sous-question `parent.ligne` par ligne de la grille, une
+// colonne chacune. Cellule absente: rien; vide: réponse skipped si
+// record_skips; sinon la valeur (sans espaces de bord) est cherchée dans les
+// options partagées.
+//
+// single_choice avec `split_comment` (séparateur littéral ou regex): la
+// cellule est coupée au premier séparateur, le début est le choix, la suite
+// le commentaire (texte de la réponse). Sans séparateur, ou sans rien
+// devant, la cellule entière reste le choix.
+
+use regex::Regex;
+use serde::Deserialize;
+
+/// Séparateur multi_choice sans `delimiter` dans le mapping
+pub const DEFAULT_DELIMITER: &str = ";";
+
+/// Valeurs d'une cellule multi_choice, dans l'ordre, vides écartées
+pub fn split_tokens<'r>(cell: &'r str, delimiter: &'r str) -> impl Iterator<Item = &'r str> {
+    cell.split(delimiter).map(str::trim).filter(|t| !t.is_empty())
+}
+
+/// Texte free_text: cellules non vides, sans espaces de bord, jointes
+pub fn join_non_empty<'r>(cells: impl IntoIterator<Item = Option<&'r str>>, joiner: &str) -> Option<String> {
+    let parts: Vec<&str> = cells.into_iter().flatten().map(str::trim).filter(|v| !v.is_empty()).collect();
+    (!parts.is_empty()).then(|| parts.join(joiner))
+}
+
+/// Rang renseigné d'un classement
+#[derive(Debug, PartialEq)]
+pub struct Rank<'r> {
+    /// 1 = premier choix
+    pub position: i32,
+    pub raw: &'r str,
+    /// valeur déjà classée à un rang précédent
+    pub repeated: bool,
+}
+
+/// Rangs non vides d'une ligne, cellules dans l'ordre des colonnes
+pub fn ranks<'r>(cells: impl IntoIterator<Item = Option<&'r str>>) -> Vec<Rank<'r>> {
+    let mut out: Vec<Rank> = Vec::new();
+    for (i, cell) in cells.into_iter().enumerate() {
+        let Some(raw) = cell.map(str::trim).filter(|v| !v.is_empty()) else { continue };
+        let repeated = out.iter().any(|r| r.raw == raw);
+        out.push(Rank { position: i as i32 + 1, raw, repeated });
+    }
+    out
+}
+
+/// Code de la sous-question d'une ligne de matrix
+pub fn child_code(parent: &str, row: &str) -> String {
+    format!("{parent}.{row}")
+}
+
+/// Cellule d'une ligne de matrix
+#[derive(Debug, PartialEq)]
+pub enum MatrixCell<'r> {
+    /// colonne absente du fichier
+    Absent,
+    Empty,
+    Value(&'r str),
+}
+
+impl<'r> MatrixCell<'r> {
+    pub fn of(cell: Option<&'r str>) -> Self {
+        match cell.map(str::trim) {
+            None => MatrixCell::Absent,
+            Some("") => MatrixCell::Empty,
+            Some(raw) => MatrixCell::Value(raw),
+        }
+    }
+}
+
+/// `split_comment`: séparateur littéral (`" — "`) ou `{ regex: "\\s+[—-]\\s+" }`
+#[derive(Deserialize, Debug, Clone)]
+#[serde(untagged)]
+pub enum SplitComment {
+    Separator(String),
+    Regex { regex: String },
+}
+
+impl SplitComment {
+    pub fn compile(&self) -> Result<Regex, regex::Error> {
+        match self {
+            SplitComment::Separator(sep) => Regex::new(&regex::escape(sep)),
+            SplitComment::Regex { regex } => Regex::new(regex),
+        }
+    }
+}
+
+/// Cellule single_choice → (choix, commentaire) au premier séparateur
+pub fn split_comment<'r>(re: &Regex, raw: &'r str) -> (&'r str, Option<&'r str>) {
+    match re.find(raw) {
+        Some(m) if !raw[..m.start()].trim().is_empty() => {
+            let comment = raw[m.end()..].trim();
+            (raw[..m.start()].trim(), (!comment.is_empty()).then_some(comment))
+        }
+        _ => (raw, None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn multi_choice_cells_are_split_on_the_delimiter() {
+        let tokens: Vec<&str> = split_tokens(" Option A; ;Option B;;Option C ;", DEFAULT_DELIMITER).collect();
+        assert_eq!(tokens, ["Option A", "Option B", "Option C"]);
+        let tokens: Vec<&str> = split_tokens("Vélo | Bus", " | ").collect();
+        assert_eq!(tokens, ["Vélo", "Bus"]);
+        assert_eq!(split_tokens(" ; ", DEFAULT_DELIMITER).count(), 0);
+    }
+
+    #[test]
+    fn free_text_joins_non_empty_cells_in_column_order() {
+        let cells = [Some(" Trop de taxes. "), Some("  "), None, Some("Moins d'élus.")];
+        assert_eq!(join_non_empty(cells, "\n\n").as_deref(), Some("Trop de taxes.\n\nMoins d'élus."));
+        assert_eq!(join_non_empty([None, Some("seul")], " | ").as_deref(), Some("seul"));
+        assert_eq!(join_non_empty([Some(""), Some(" \t"), None], "\n\n"), None);
+    }
+
+    #[test]
+    fn ranks_keep_their_column_and_flag_repeats() {
+        let rank = |position, raw, repeated| Rank { position, raw, repeated };
+        let got = ranks([Some("Santé"), Some(" "), None, Some(" École "), Some("Santé")]);
+        assert_eq!(got, [rank(1, "Santé", false), rank(4, "École", false), rank(5, "Santé", true)]);
+        assert!(ranks([None, Some("")]).is_empty());
+    }
+
+    #[test]
+    fn matrix_rows_expand_to_child_questions() {
+        assert_eq!(child_code("confiance", "maire"), "confiance.maire");
+        assert_eq!(MatrixCell::of(None), MatrixCell::Absent);
+        assert_eq!(MatrixCell::of(Some(" \t")), MatrixCell::Empty);
+        assert_eq!(MatrixCell::of(Some(" Plutôt confiance ")), MatrixCell::Value("Plutôt confiance"));
+    }
+
+    #[test]
+    fn comment_is_split_off_at_the_first_separator() {
+        let re = SplitComment::Regex { regex: r"\s+[—-]\s+".into() }.compile().unwrap();
+        assert_eq!(split_comment(&re, "Non - trop cher"), ("Non", Some("trop cher")));
+        assert_eq!(split_comment(&re, "Oui — mais seulement si — vraiment"), ("Oui", Some("mais seulement si — vraiment")));
+        assert_eq!(split_comment(&re, " - rien devant"), (" - rien devant", None));
+        assert_eq!(split_comment(&re, "Oui - "), ("Oui", None));
+        assert_eq!(split_comment(&re, "Oui"), ("Oui", None));
+        // séparateur littéral: pas interprété comme une regex
+        let re = SplitComment::Separator(" (".into()).compile().unwrap();
+        assert_eq!(split_comment(&re, "Autre (préciser)"), ("Autre", Some("préciser)")));
+    }
+}
@@ -0,0 +1,189 @@
+// ---------- --show-changes: ce qui a changé dans une contribution réimportée ----------
+//
+// Une référence déjà en base dont le raw_hash diffère est une contribution
+// modifiée par l'éditeur de l'export. Avant de la réécrire, son raw_json
+// stocké est comparé à la ligne entrante, colonne par colonne:
+//   added   → colonne absente (ou vide) en base, présente dans la ligne
+//   removed → l'inverse
+//   changed → valeurs différentes (anciennes et nouvelles, tronquées)
+// Une ligne JSON par contribution dans le fichier --show-changes, au plus
+// --max-changes lignes; le décompte par colonne, lui, porte sur tout le run
+// et le résumé affiche les colonnes les plus souvent modifiées.
+// Les clés techniques (`__original_headers`, `__truncated`) sont ignorées;
+// un raw_json fusionné (tableau, --merge-into-existing) est comparé à sa
+// dernière page.
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+/// Longueur maximale (caractères) d'une valeur dans le fichier de différences
+const MAX_VALUE_CHARS: usize = 200;
+
+/// Défaut de --max-changes
+pub const DEFAULT_MAX_CHANGES: usize = 10_000;
+
+#[derive(Serialize, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Kind {
+    Added,
+    Removed,
+    Changed,
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+pub struct Change {
+    pub column: String,
+    pub change: Kind,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub old: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub new: Option<String>,
+}
+
+fn cell(v: &Value) -> String {
+    let mut s = match v {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    };
+    if crate::values::truncate_chars(&mut s, MAX_VALUE_CHARS) {
+        s.push('…');
+    }
+    s
+}
+
+fn columns(v: &Value) -> Option<&Map<String, Value>> {
+    match v {
+        Value::Array(pages) => pages.last().and_then(Value::as_object),
+        v => v.as_object(),
+    }
+}
+
+/// Différences colonne par colonne, dans l'ordre des colonnes de la ligne entrante
+pub fn diff(stored: &Value, incoming: &Value) -> Vec<Change> {
+    let empty = Map::new();
+    let old = columns(stored).unwrap_or(&empty);
+    let new = columns(incoming).unwrap_or(&empty);
+    let technical = |k: &str| k.starts_with("__");
+    let mut out = Vec::new();
+    for (k, v) in new.iter().filter(|(k, _)| !technical(k)) {
+        match old.get(k) {
+            None => out.push(Change { column: k.clone(), change: Kind::Added, old: None, new: Some(cell(v)) }),
+            Some(o) if o != v => {
+                out.push(Change { column: k.clone(), change: Kind::Changed, old: Some(cell(o)), new: Some(cell(v)) })
+            }
+            Some(_) => {}
+        }
+    }
+    for (k, o) in old.iter().filter(|(k, _)| !technical(k) && !new.contains_key(*k)) {
+        out.push(Change { column: k.clone(), change: Kind::Removed, old: Some(cell(o)), new: None });
+    }
+    out
+}
+
+/// Fichier JSONL des différences + décompte par colonne (partagé entre threads)
+pub struct ChangeLog {
+    path: PathBuf,
+    writer: BufWriter<File>,
+    max: usize,
+    pub written: usize,
+    pub contributions: u64,
+    by_column: HashMap<String, u64>,
+}
+
+impl ChangeLog {
+    pub fn create(path: &Path, max: usize) -> Result<Self> {
+        let file = File::create(path).with_context(|| format!("--show-changes {path:?}"))?;
+        Ok(ChangeLog {
+            path: path.to_path_buf(),
+            writer: BufWriter::new(file),
+            max,
+            written: 0,
+            contributions: 0,
+            by_column: HashMap::new(),
+        })
+    }
+
+    pub fn record(&mut self, file: &str, reference: &str, changes: &[Change]) -> Result<()> {
+        if changes.is_empty() {
+            return Ok(());
+        }
+        self.contributions += 1;
+        for c in changes {
+            *self.by_column.entry(c.column.clone()).or_default() += 1;
+        }
+        if self.written < self.max {
+            let line = serde_json::json!({ "file": file, "reference": reference, "changes": changes });
+            writeln!(self.writer, "{line}")?;
+            self.written += 1;
+        }
+        Ok(())
+    }
+
+    /// Colonnes les plus souvent modifiées (à égalité: ordre alphabétique)
+    pub fn top_columns(&self, n: usize) -> Vec<(&str, u64)> {
+        let mut cols: Vec<(&str, u64)> = self.by_column.iter().map(|(k, v)| (k.as_str(), *v)).collect();
+        cols.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+        cols.truncate(n);
+        cols
+    }
+
+    pub fn finish(&mut self) -> Result<()> {
+        self.writer.flush().with_context(|| format!("écriture {:?}", self.path))
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn column_level_diff() {
+        let stored = json!({"reference": "c1", "Q1": "oui", "Q2": "ancien", "__original_headers": {"Q1": "Q1 ?"}});
+        let incoming = json!({"reference": "c1", "Q1": "non", "Q3": "ajout", "__truncated": 2});
+        assert_eq!(
+            diff(&stored, &incoming),
+            vec![
+                Change { column: "Q1".into(), change: Kind::Changed, old: Some("oui".into()), new: Some("non".into()) },
+                Change { column: "Q3".into(), change: Kind::Added, old: None, new: Some("ajout".into()) },
+                Change { column: "Q2".into(), change: Kind::Removed, old: Some("ancien".into()), new: None },
+            ]
+        );
+        // raw_json fusionné: dernière page
+        let pages = json!([{"reference": "c1", "Q1": "x"}, {"reference": "c1", "Q1": "non"}]);
+        assert_eq!(diff(&pages, &json!({"reference": "c1", "Q1": "non"})), vec![]);
+
+        let long = "mot ".repeat(100);
+        let d = diff(&json!({"T": long}), &json!({"T": "court"}));
+        let old = d[0].old.as_deref().unwrap();
+        assert_eq!(old.chars().count(), MAX_VALUE_CHARS);
+        assert!(old.ends_with("mot…"));
+    }
+
+    #[test]
+    fn cap_bounds_the_file_not_the_counts() {
+        let path = std::env::temp_dir().join(format!("gdn_changes_{}.jsonl", std::process::id()));
+        let mut log = ChangeLog::create(&path, 1).unwrap();
+        for r in ["c1", "c2", "c3"] {
+            log.record("a.csv", r, &diff(&json!({"Q1": "a", "Q2": "a"}), &json!({"Q1": "b", "Q2": r}))).unwrap();
+        }
+        log.record("a.csv", "c4", &diff(&json!({"Q2": "b"}), &json!({"Q2": "c"}))).unwrap();
+        log.record("a.csv", "c5", &[]).unwrap();
+        log.finish().unwrap();
+        let text = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(text.lines().count(), 1);
+        assert!(text.starts_with(r#"{"changes":[{"change":"changed","column":"Q1","new":"b","old":"a"}"#), "{text}");
+        assert_eq!(log.contributions, 4);
+        assert_eq!(log.top_columns(5), vec![("Q2", 4), ("Q1", 3)]);
+    }
+}
@@ -0,0 +1,149 @@
+// ---------- --resume: reprise d'une ingestion plantée, fichier par fichier ----------
+//
+// Table `ingest_checkpoints (batch, form_id, file)`: enregistrements déjà
+// committés du fichier (rows_done), ligne source du dernier (last_line) et
+// `complete`. Écrite dans la transaction d'ingestion juste avant son COMMIT,
+// comme le heartbeat: le point de reprise est exactement ce qui est en base.
+//
+// rows_done compte les enregistrements lus (lignes corbeille, inchangées ou
+// rejetées comprises), pas les lignes physiques: la reprise ne dépend ni des
+// champs multi-lignes ni des commentaires. `file` est le chemin tel que passé
+// en argument (même glob à la reprise).
+//
+// Au démarrage, s'il reste des points de reprise pour le batch:
+//   - --resume: fichiers complets sautés, fichier interrompu relu sans
+//     écriture jusqu'à rows_done puis ingéré;
+//   - --no-resume: points de reprise effacés, départ de zéro;
+//   - ni l'un ni l'autre: refus, le choix revient à l'opérateur.
+// Effacés en fin de run réussie. Les compteurs d'un run repris ne couvrent
+// que les lignes écrites par ce run.
+
+use anyhow::Result;
+use postgres::{Client, GenericClient};
+use std::collections::HashMap;
+
+/// Point de départ d'un fichier
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Resume {
+    /// depuis le début
+    Start,
+    /// déjà complet: sauté
+    Done,
+    /// interrompu: `rows` enregistrements déjà committés
+    After { rows: u64, line: Option<i64> },
+}
+
+#[derive(Debug, PartialEq)]
+enum Plan {
+    Fresh,
+    Resume,
+    Discard,
+}
+
+fn plan(saved: usize, resume: bool, no_resume: bool) -> Result<Plan, String> {
+    match (saved, resume, no_resume) {
+        (0, _, _) => Ok(Plan::Fresh),
+        (_, true, _) => Ok(Plan::Resume),
+        (_, _, true) => Ok(Plan::Discard),
+        (n, false, false) => Err(format!(
+            "{n} points de reprise laissés par un run interrompu de ce batch: \
+             --resume pour reprendre, --no-resume pour repartir de zéro"
+        )),
+    }
+}
+
+/// Points de reprise du batch
+pub struct Checkpoints {
+    batch: String,
+    form_id: i64,
+    plan: Plan,
+    saved: HashMap<String, Resume>,
+}
+
+impl Checkpoints {
+    /// Avant le verrou: lecture seule, refus si le choix n'est pas fait
+    pub fn load(conn: &mut impl GenericClient, form_id: i64, batch: &str, resume: bool, no_resume: bool) -> Result<Self> {
+        let exists: bool = conn.query_one("SELECT to_regclass('ingest_checkpoints') IS NOT NULL", &[])?.get(0);
+        if !exists {
+            anyhow::bail!("table ingest_checkpoints absente — appliquer les migrations (alembic upgrade head)");
+        }
+        let saved: HashMap<String, Resume> = conn
+            .query(
+                "SELECT file, rows_done, last_line, complete FROM ingest_checkpoints WHERE batch = $1 AND form_id = $2",
+                &[&batch, &form_id],
+            )?
+            .iter()
+            .map(|r| {
+                let at = match r.get::<_, bool>(3) {
+                    true => Resume::Done,
+                    false => Resume::After { rows: r.get::<_, i64>(1) as u64, line: r.get(2) },
+                };
+                (r.get(0), at)
+            })
+            .collect();
+        let plan = plan(saved.len(), resume, no_resume).map_err(anyhow::Error::msg)?;
+        Ok(Checkpoints { batch: batch.to_string(), form_id, plan, saved })
+    }
+
+    /// Sous le verrou: efface (--no-resume) ou annonce la reprise
+    pub fn begin(&mut self, conn: &mut Client) -> Result<()> {
+        match self.plan {
+            Plan::Fresh => {}
+            Plan::Discard => {
+                self.clear(conn)?;
+                println!("[reprise] --no-resume: {} points de reprise effacés, départ de zéro", self.saved.len());
+                self.saved.clear();
+            }
+            Plan::Resume => {
+                let done = self.saved.values().filter(|r| **r == Resume::Done).count();
+                println!(
+                    "[reprise] batch '{}': {done} fichiers complets sautés, {} interrompus repris",
+                    self.batch,
+                    self.saved.len() - done
+                );
+            }
+        }
+        Ok(())
+    }
+
+    pub fn resume(&self, file: &str) -> Resume {
+        self.saved.get(file).copied().unwrap_or(Resume::Start)
+    }
+
+    /// Dans la transaction, juste avant son COMMIT
+    pub fn save(&self, tx: &mut impl GenericClient, file: &str, rows: u64, line: Option<u64>, complete: bool) -> Result<()> {
+        tx.execute(
+            "INSERT INTO ingest_checkpoints (batch, form_id, file, rows_done, last_line, complete, updated_at)
+             VALUES ($1, $2, $3, $4, $5, $6, now())
+             ON CONFLICT (batch, form_id, file)
+             DO UPDATE SET rows_done = EXCLUDED.rows_done, last_line = EXCLUDED.last_line,
+                           complete = EXCLUDED.complete, updated_at = now()",
+            &[&self.batch, &self.form_id, &file, &(rows as i64), &line.map(|l| l as i64), &complete],
+        )?;
+        Ok(())
+    }
+
+    /// Run réussi (ou --no-resume): plus rien à reprendre
+    pub fn clear(&self, conn: &mut Client) -> Result<()> {
+        conn.execute(
+            "DELETE FROM ingest_checkpoints WHERE batch = $1 AND form_id = $2",
+            &[&self.batch, &self.form_id],
+        )?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn leftover_checkpoints_need_an_explicit_choice() {
+        assert_eq!(plan(0, false, false), Ok(Plan::Fresh));
+        assert_eq!(plan(0, true, false), Ok(Plan::Fresh));
+        assert_eq!(plan(3, true, false), Ok(Plan::Resume));
+        assert_eq!(plan(3, false, true), Ok(Plan::Discard));
+        let err = plan(3, false, false).unwrap_err();
+        assert!(err.contains("--resume pour reprendre"), "{err}");
+    }
+}
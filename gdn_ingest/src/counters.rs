@@ -0,0 +1,263 @@
+// ---------- Compteurs de résumé durables ----------
+//
+// Les compteurs d'une transaction en cours restent chez l'appelant jusqu'au
+// commit; ils ne rejoignent "committed" qu'une fois le COMMIT réussi, et c'est cet état
+// qui est écrit (atomiquement) dans `<artifacts>/<batch>.summary.json`.
+// Un run interrompu laisse donc un résumé partiel exact; un run terminé
+// le remplace par le résumé final (`status: complete`).
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use crate::input::SourceInfo;
+
+#[derive(Serialize, Default, Clone, Debug, PartialEq)]
+pub struct Counters {
+    pub rows_read: u64,
+    pub trashed: u64,
+    /// lignes à la corbeille dont la contribution en base a été marquée supprimée (cf. softdelete.rs)
+    pub trashed_deleted: u64,
+    pub contributions: u64,
+    /// contributions nouvelles (référence inconnue), parmi `contributions`
+    pub inserted: u64,
+    /// --on-existing skip: référence déjà en base, ligne laissée de côté
+    pub skipped_existing: u64,
+    /// lignes identiques à la contribution en base (raw_hash), non réécrites
+    pub unchanged: u64,
+    pub answers: BTreeMap<String, u64>,
+    pub skipped: BTreeMap<String, u64>,
+    /// single_choice: réponses portant un commentaire accolé
+    pub comments: BTreeMap<String, u64>,
+    pub bad_numbers: u64,
+    pub bad_dates: u64,
+    pub bad_submitted_at: u64,
+    pub bad_booleans: u64,
+    pub zipcodes_normalized: u64,
+    pub zipcodes_rejected: u64,
+    pub ages_normalized: u64,
+    pub ages_unbucketed: u64,
+    pub genders_normalized: u64,
+    pub genders_unmapped: u64,
+}
+
+impl Counters {
+    pub fn answer(&mut self, code: &str) {
+        *self.answers.entry(code.to_string()).or_default() += 1;
+    }
+
+    pub fn skip(&mut self, code: &str) {
+        *self.skipped.entry(code.to_string()).or_default() += 1;
+    }
+
+    pub fn comment(&mut self, code: &str) {
+        *self.comments.entry(code.to_string()).or_default() += 1;
+    }
+
+    /// Lignes lues sans contribution écrite (corbeille, rejets, inchangées…)
+    pub fn rows_skipped(&self) -> u64 {
+        self.rows_read.saturating_sub(self.contributions)
+    }
+
+    /// Valeurs illisibles ou rejetées
+    pub fn errors(&self) -> u64 {
+        self.bad_numbers + self.bad_dates + self.bad_submitted_at + self.bad_booleans + self.zipcodes_rejected
+    }
+
+    fn merge(&mut self, other: &Counters) {
+        self.rows_read += other.rows_read;
+        self.trashed += other.trashed;
+        self.trashed_deleted += other.trashed_deleted;
+        self.contributions += other.contributions;
+        self.inserted += other.inserted;
+        self.skipped_existing += other.skipped_existing;
+        self.unchanged += other.unchanged;
+        for (k, v) in &other.answers {
+            *self.answers.entry(k.clone()).or_default() += v;
+        }
+        for (k, v) in &other.skipped {
+            *self.skipped.entry(k.clone()).or_default() += v;
+        }
+        for (k, v) in &other.comments {
+            *self.comments.entry(k.clone()).or_default() += v;
+        }
+        self.bad_numbers += other.bad_numbers;
+        self.bad_dates += other.bad_dates;
+        self.bad_submitted_at += other.bad_submitted_at;
+        self.bad_booleans += other.bad_booleans;
+        self.zipcodes_normalized += other.zipcodes_normalized;
+        self.zipcodes_rejected += other.zipcodes_rejected;
+        self.ages_normalized += other.ages_normalized;
+        self.ages_unbucketed += other.ages_unbucketed;
+        self.genders_normalized += other.genders_normalized;
+        self.genders_unmapped += other.genders_unmapped;
+    }
+}
+
+/// Contributions dont submitted_at sort de la fenêtre plausible (par fichier)
+#[derive(Serialize, Default, Clone, Debug, PartialEq)]
+pub struct OutOfWindow {
+    pub count: u64,
+    /// quelques références, pour retrouver les lignes fautives
+    pub examples: Vec<String>,
+}
+
+impl OutOfWindow {
+    const MAX_EXAMPLES: usize = 5;
+
+    pub fn note(&mut self, reference: &str) {
+        self.count += 1;
+        if self.examples.len() < Self::MAX_EXAMPLES {
+            self.examples.push(reference.to_string());
+        }
+    }
+
+    pub fn merge(&mut self, other: &OutOfWindow) {
+        self.count += other.count;
+        let room = Self::MAX_EXAMPLES.saturating_sub(self.examples.len());
+        self.examples.extend(other.examples.iter().take(room).cloned());
+    }
+}
+
+pub struct DurableCounters {
+    batch: String,
+    path: Option<PathBuf>,
+    committed: Counters,
+    files_done: Vec<String>,
+    inputs: Vec<SourceInfo>,
+    out_of_window: BTreeMap<String, OutOfWindow>,
+}
+
+impl DurableCounters {
+    pub fn new(batch: &str, artifacts_dir: Option<PathBuf>) -> Result<Self> {
+        let path = match artifacts_dir {
+            Some(dir) => {
+                std::fs::create_dir_all(&dir).with_context(|| format!("création {:?}", dir))?;
+                Some(dir.join(format!("{}.summary.json", batch)))
+            }
+            None => None,
+        };
+        Ok(DurableCounters {
+            batch: batch.to_string(),
+            path,
+            committed: Counters::default(),
+            files_done: Vec::new(),
+            inputs: Vec::new(),
+            out_of_window: BTreeMap::new(),
+        })
+    }
+
+    /// À appeler juste après un COMMIT réussi, avec les compteurs de la transaction
+    pub fn committed(&mut self, pending: Counters, current_file: &str) -> Result<()> {
+        self.committed.merge(&pending);
+        self.flush("partial", Some(current_file))
+    }
+
+    /// Fichier terminé (et committé), avec sa provenance (compression, encodage…)
+    pub fn file_done(&mut self, pending: Counters, info: &SourceInfo) -> Result<()> {
+        self.files_done.push(info.path.clone());
+        self.inputs.push(info.clone());
+        self.committed(pending, &info.path)
+    }
+
+    /// Bilan submitted_at hors fenêtre d'un fichier (écrit avec le prochain flush)
+    pub fn out_of_window(&mut self, path: &str, w: &OutOfWindow) {
+        if w.count > 0 {
+            self.out_of_window.insert(path.to_string(), w.clone());
+        }
+    }
+
+    /// Fin normale: le résumé final remplace le partiel
+    pub fn finish(&mut self) -> Result<()> {
+        self.flush("complete", None)
+    }
+
+    pub fn totals(&self) -> &Counters {
+        &self.committed
+    }
+
+    fn flush(&self, status: &str, current_file: Option<&str>) -> Result<()> {
+        let Some(path) = &self.path else { return Ok(()) };
+        let doc = serde_json::json!({
+            "batch": self.batch,
+            "status": status,
+            "current_file": current_file,
+            "files_done": self.files_done,
+            "inputs": self.inputs,
+            "submitted_out_of_window": self.out_of_window,
+            "committed": self.committed,
+        });
+        // écriture atomique: fichier temporaire puis rename
+        let tmp = path.with_extension("json.tmp");
+        std::fs::write(&tmp, serde_json::to_vec_pretty(&doc)?).with_context(|| format!("écriture {:?}", tmp))?;
+        std::fs::rename(&tmp, path).with_context(|| format!("rename {:?}", path))?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read(path: &std::path::Path) -> serde_json::Value {
+        serde_json::from_slice(&std::fs::read(path).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn only_committed_counts_are_flushed() {
+        let dir = std::env::temp_dir().join(format!("gdn_counters_{}", std::process::id()));
+        let mut c = DurableCounters::new("b1", Some(dir.clone())).unwrap();
+        let path = dir.join("b1.summary.json");
+
+        let mut pending = Counters { rows_read: 10, contributions: 9, bad_dates: 2, bad_booleans: 1, ..Default::default() };
+        pending.answer("q1");
+        c.committed(std::mem::take(&mut pending), "a.csv").unwrap();
+        assert_eq!((c.totals().rows_skipped(), c.totals().errors()), (1, 3));
+
+        // lignes non committées: absentes du fichier
+        pending.rows_read = 5;
+        pending.answer("q1");
+        let doc = read(&path);
+        assert_eq!(doc["status"], "partial");
+        assert_eq!(doc["current_file"], "a.csv");
+        assert_eq!(doc["committed"]["rows_read"], 10);
+        assert_eq!(doc["committed"]["answers"]["q1"], 1);
+
+        let info = SourceInfo {
+            path: "a.csv".into(),
+            compression: crate::input::Compression::Gzip,
+            encoding: Some("windows-1252".into()),
+            member: None,
+            delimiter: Some(','),
+        };
+        let mut w = OutOfWindow::default();
+        for r in ["r1", "r2", "r3", "r4", "r5", "r6"] {
+            w.note(r);
+        }
+        c.out_of_window("a.csv", &w);
+        c.file_done(pending, &info).unwrap();
+        c.finish().unwrap();
+        let doc = read(&path);
+        assert_eq!(doc["status"], "complete");
+        assert_eq!(doc["committed"]["rows_read"], 15);
+        assert_eq!(doc["committed"]["answers"]["q1"], 2);
+        assert_eq!(doc["files_done"], serde_json::json!(["a.csv"]));
+        assert_eq!(doc["inputs"][0]["compression"], "gzip");
+        assert_eq!(doc["inputs"][0]["encoding"], "windows-1252");
+        assert_eq!(doc["submitted_out_of_window"]["a.csv"]["count"], 6);
+        assert_eq!(doc["submitted_out_of_window"]["a.csv"]["examples"].as_array().unwrap().len(), 5);
+        assert!(!dir.join("b1.summary.json.tmp").exists());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn without_artifacts_dir_nothing_is_written() {
+        let mut c = DurableCounters::new("b2", None).unwrap();
+        let pending = Counters { rows_read: 3, ..Default::default() };
+        c.committed(pending, "x.csv").unwrap();
+        c.finish().unwrap();
+        assert_eq!(c.totals().rows_read, 3);
+    }
+}
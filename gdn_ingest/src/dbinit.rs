@@ -0,0 +1,355 @@
+// ---------- db-init / db-drop: tables de l'ingestion sans Alembic ----------
+//
+// Une base neuve (tests, CI, jeu de données isolé dans un --schema) reçoit
+// les tables écrites par l'ingestion sans passer par les migrations Python:
+// DDL embarqué ci-dessous, aligné sur app/models.py et les INSERT du crate
+// (mêmes noms de colonnes, jsonb, index uniques dont dépendent les ON
+// CONFLICT). L'application web (topics, caches du tableau de bord) garde
+// ses migrations: db-init ne crée que le sous-ensemble de l'ingestion, et
+// une base gérée par Alembic n'en a pas besoin.
+//
+// Le schéma vient de l'option globale --schema (créé s'il manque). Tout
+// passe dans une transaction: une erreur ne laisse pas de tables à moitié
+// créées. Sans --if-not-exists, des tables déjà présentes arrêtent db-init
+// (base probablement migrée par Alembic); avec, seules les manquantes sont
+// créées. schema_version (une seule ligne) garde la version du DDL, montée
+// à SCHEMA_VERSION par --if-not-exists (les versions n'ajoutent que des
+// tables, ou des colonnes par ADD COLUMN IF NOT EXISTS; un index unique
+// élargi remplace l'ancien par DROP INDEX IF EXISTS); une base plus récente
+// que le binaire est signalée.
+//
+// db-drop supprime ces tables (et elles seules) du schéma, après --apply;
+// --cascade emporte aussi ce qui en dépend (vues, tables de l'application).
+
+use anyhow::Result;
+use postgres::GenericClient;
+
+use crate::session;
+
+/// Version du DDL ci-dessous, à incrémenter à chaque changement
+pub const SCHEMA_VERSION: i32 = 7;
+
+/// Tables créées par DDL, dans l'ordre de création (db-drop: ordre inverse)
+const TABLES: [&str; 14] = [
+    "schema_version",
+    "authors",
+    "forms",
+    "questions",
+    "options",
+    "contributions",
+    "text_values",
+    "answers",
+    "answer_options",
+    "import_batches",
+    "ingest_checkpoints",
+    "source_files",
+    "gdpr_erasures",
+    "raw_imports",
+];
+
+const DDL: &str = "
+CREATE TABLE IF NOT EXISTS schema_version (
+    version integer NOT NULL,
+    singleton boolean PRIMARY KEY DEFAULT true CHECK (singleton)
+);
+CREATE TABLE IF NOT EXISTS authors (
+    id bigserial PRIMARY KEY,
+    source_author_id varchar,
+    name varchar,
+    email_hash varchar,
+    zipcode varchar,
+    city varchar,
+    age_range varchar,
+    gender varchar,
+    department_code varchar,
+    region_code varchar,
+    pseudonymized_at timestamptz,
+    CONSTRAINT uq_authors_source_author_id UNIQUE (source_author_id),
+    CONSTRAINT uq_authors_email_hash UNIQUE (email_hash)
+);
+ALTER TABLE authors ADD COLUMN IF NOT EXISTS pseudonymized_at timestamptz;
+CREATE TABLE IF NOT EXISTS forms (
+    id bigserial PRIMARY KEY,
+    name varchar NOT NULL,
+    version varchar,
+    source varchar,
+    name_unaccent text,
+    tsv_name text
+);
+CREATE TABLE IF NOT EXISTS questions (
+    id bigserial PRIMARY KEY,
+    form_id bigint NOT NULL REFERENCES forms (id),
+    question_code varchar NOT NULL,
+    prompt text NOT NULL,
+    section varchar,
+    position integer,
+    type varchar NOT NULL,
+    options_json jsonb,
+    CONSTRAINT uq_questions_form_code UNIQUE (form_id, question_code)
+);
+CREATE TABLE IF NOT EXISTS options (
+    id bigserial PRIMARY KEY,
+    question_id bigint NOT NULL REFERENCES questions (id),
+    code varchar NOT NULL,
+    label text NOT NULL,
+    position integer,
+    meta_json jsonb,
+    CONSTRAINT uq_options_question_code UNIQUE (question_id, code)
+);
+CREATE TABLE IF NOT EXISTS contributions (
+    id bigserial PRIMARY KEY,
+    source_contribution_id varchar,
+    author_id bigint REFERENCES authors (id),
+    form_id bigint NOT NULL REFERENCES forms (id),
+    source varchar,
+    theme_id bigint,
+    submitted_at timestamp,
+    title varchar,
+    import_batch_id varchar,
+    created_import_batch_id varchar,
+    raw_hash varchar,
+    raw_json jsonb,
+    source_file text,
+    source_line bigint,
+    deleted_at timestamptz,
+    delete_reason text,
+    CONSTRAINT uq_contributions_form_source_id UNIQUE (form_id, source_contribution_id),
+    CONSTRAINT uq_contributions_raw_hash UNIQUE (raw_hash)
+);
+ALTER TABLE contributions ADD COLUMN IF NOT EXISTS deleted_at timestamptz;
+ALTER TABLE contributions ADD COLUMN IF NOT EXISTS delete_reason text;
+CREATE TABLE IF NOT EXISTS text_values (
+    id bigserial PRIMARY KEY,
+    value text NOT NULL UNIQUE,
+    refcount bigint NOT NULL DEFAULT 0
+);
+CREATE TABLE IF NOT EXISTS answers (
+    id bigserial PRIMARY KEY,
+    contribution_id bigint NOT NULL REFERENCES contributions (id),
+    question_id bigint NOT NULL REFERENCES questions (id),
+    position integer NOT NULL DEFAULT 1,
+    text text,
+    value_json text,
+    value_num numeric,
+    value_date timestamp,
+    skipped boolean NOT NULL DEFAULT false,
+    text_value_id bigint REFERENCES text_values (id),
+    CONSTRAINT uq_answers_contribution_question_position UNIQUE (contribution_id, question_id, position)
+);
+CREATE INDEX IF NOT EXISTS idx_answers_text_value_id ON answers (text_value_id) WHERE text_value_id IS NOT NULL;
+CREATE TABLE IF NOT EXISTS answer_options (
+    answer_id bigint REFERENCES answers (id),
+    option_id bigint REFERENCES options (id),
+    PRIMARY KEY (answer_id, option_id)
+);
+CREATE TABLE IF NOT EXISTS import_batches (
+    id bigserial PRIMARY KEY,
+    batch varchar NOT NULL,
+    form_id bigint NOT NULL REFERENCES forms (id),
+    status varchar NOT NULL,
+    started_at timestamptz NOT NULL DEFAULT now(),
+    heartbeat_at timestamptz,
+    finished_at timestamptz,
+    host varchar,
+    pid integer,
+    error text,
+    provenance jsonb NOT NULL DEFAULT '{}'::jsonb,
+    mapping_path text,
+    rows_total bigint,
+    rows_skipped bigint,
+    errors bigint
+);
+CREATE UNIQUE INDEX IF NOT EXISTS uq_import_batches_running_form ON import_batches (form_id) WHERE status = 'running';
+CREATE TABLE IF NOT EXISTS ingest_checkpoints (
+    batch varchar NOT NULL,
+    form_id bigint NOT NULL REFERENCES forms (id),
+    file text NOT NULL,
+    rows_done bigint NOT NULL,
+    last_line bigint,
+    complete boolean NOT NULL DEFAULT false,
+    updated_at timestamptz NOT NULL DEFAULT now(),
+    PRIMARY KEY (batch, form_id, file)
+);
+CREATE TABLE IF NOT EXISTS source_files (
+    id bigserial PRIMARY KEY,
+    form_id bigint NOT NULL REFERENCES forms (id),
+    file_name text NOT NULL,
+    path text NOT NULL,
+    checksum varchar(64) NOT NULL,
+    size_bytes bigint,
+    rows bigint NOT NULL,
+    batch varchar NOT NULL,
+    completed_at timestamptz NOT NULL DEFAULT now()
+);
+ALTER TABLE source_files ADD COLUMN IF NOT EXISTS head_checksum varchar(64);
+CREATE TABLE IF NOT EXISTS gdpr_erasures (
+    id bigserial PRIMARY KEY,
+    erased_at timestamptz NOT NULL DEFAULT now(),
+    email_hash_sha256 varchar(64) NOT NULL,
+    rows_affected bigint NOT NULL
+);
+CREATE TABLE IF NOT EXISTS raw_imports (
+    id bigserial PRIMARY KEY,
+    form_id bigint REFERENCES forms (id),
+    batch_id varchar NOT NULL,
+    file_path text NOT NULL,
+    row_number bigint NOT NULL,
+    contribution_id bigint REFERENCES contributions (id) ON DELETE SET NULL,
+    row_hash varchar NOT NULL,
+    raw_json jsonb,
+    ingested_at timestamptz NOT NULL DEFAULT now()
+);
+ALTER TABLE raw_imports ADD COLUMN IF NOT EXISTS form_id bigint REFERENCES forms (id);
+UPDATE raw_imports r SET form_id = c.form_id FROM contributions c WHERE c.id = r.contribution_id AND r.form_id IS NULL;
+DROP INDEX IF EXISTS uq_raw_imports_row_hash;
+CREATE UNIQUE INDEX IF NOT EXISTS uq_raw_imports_form_row_hash ON raw_imports (form_id, row_hash);
+CREATE INDEX IF NOT EXISTS idx_raw_imports_batch_id ON raw_imports (batch_id)";
+
+/// Instructions du DDL, une par `;`
+fn statements() -> impl Iterator<Item = &'static str> {
+    DDL.split(';').map(str::trim).filter(|s| !s.is_empty())
+}
+
+/// Tables de TABLES présentes dans le schéma
+fn existing_tables(client: &mut impl GenericClient, schema: &str) -> Result<Vec<String>> {
+    let tables: Vec<String> = TABLES.iter().map(|t| t.to_string()).collect();
+    Ok(client
+        .query(
+            "SELECT t FROM unnest($2::text[]) AS t
+             WHERE EXISTS (SELECT 1 FROM information_schema.tables WHERE table_schema = $1 AND table_name = t)",
+            &[&schema, &tables],
+        )?
+        .iter()
+        .map(|r| r.get(0))
+        .collect())
+}
+
+fn quote_ident(client: &mut impl GenericClient, ident: &str) -> Result<String> {
+    Ok(client.query_one("SELECT quote_ident($1)", &[&ident])?.get(0))
+}
+
+fn exec(client: &mut impl GenericClient, sql: &str, verbose: bool) -> Result<()> {
+    if verbose {
+        println!("{sql};");
+    }
+    client.batch_execute(sql)?;
+    Ok(())
+}
+
+/// Crée schéma et tables; renvoie la version lue dans schema_version
+pub fn init(client: &mut impl GenericClient, schema: &str, if_not_exists: bool, verbose: bool) -> Result<i32> {
+    let mut tx = client.transaction()?;
+    let existing = existing_tables(&mut tx, schema)?;
+    if !existing.is_empty() && !if_not_exists {
+        anyhow::bail!(
+            "schéma '{schema}': tables déjà présentes ({}) — --if-not-exists pour ne créer que les manquantes",
+            existing.join(", ")
+        );
+    }
+    let quoted = quote_ident(&mut tx, schema)?;
+    exec(&mut tx, &format!("CREATE SCHEMA IF NOT EXISTS {quoted}"), verbose)?;
+    // search_path de la session (cf. session.rs): résolu à nouveau une fois le schéma créé
+    for sql in statements() {
+        exec(&mut tx, sql, verbose)?;
+    }
+    tx.execute(
+        "INSERT INTO schema_version (version) SELECT $1 WHERE NOT EXISTS (SELECT 1 FROM schema_version)",
+        &[&SCHEMA_VERSION],
+    )?;
+    // versions successives: tables ajoutées seulement, créées ci-dessus si elles manquaient
+    tx.execute("UPDATE schema_version SET version = $1 WHERE version < $1", &[&SCHEMA_VERSION])?;
+    let version: i32 = tx.query_one("SELECT version FROM schema_version", &[])?.get(0);
+    tx.commit()?;
+    Ok(version)
+}
+
+/// Supprime les tables de TABLES présentes; renvoie leurs noms
+pub fn drop(client: &mut impl GenericClient, schema: &str, cascade: bool, verbose: bool) -> Result<Vec<String>> {
+    let mut tx = client.transaction()?;
+    let mut existing = existing_tables(&mut tx, schema)?;
+    if existing.is_empty() {
+        return Ok(existing);
+    }
+    existing.reverse();
+    let quoted = quote_ident(&mut tx, schema)?;
+    let mut names = Vec::with_capacity(existing.len());
+    for table in &existing {
+        names.push(format!("{quoted}.{}", quote_ident(&mut tx, table)?));
+    }
+    let sql = format!("DROP TABLE {}{}", names.join(", "), if cascade { " CASCADE" } else { "" });
+    exec(&mut tx, &sql, verbose).map_err(|e| {
+        e.context(format!("schéma '{schema}': suppression refusée (objets dépendants? --cascade pour les emporter)"))
+    })?;
+    tx.commit()?;
+    Ok(existing)
+}
+
+pub fn run_db_init(if_not_exists: bool, verbose: bool) -> Result<()> {
+    let schema = session::opts().schema;
+    let mut conn = crate::open_conn()?;
+    let version = init(&mut conn, &schema, if_not_exists, verbose)?;
+    if version != SCHEMA_VERSION {
+        println!("⚠️  schéma '{schema}': schema_version = {version}, DDL du binaire en version {SCHEMA_VERSION}");
+    }
+    println!("[db-init] ✅ schéma '{schema}': {} tables prêtes (version {version})", TABLES.len());
+    Ok(())
+}
+
+pub fn run_db_drop(cascade: bool, apply: bool, verbose: bool) -> Result<()> {
+    let schema = session::opts().schema;
+    let mut conn = crate::open_conn()?;
+    if !apply {
+        let existing = existing_tables(&mut conn, &schema)?;
+        if existing.is_empty() {
+            println!("[db-drop] schéma '{schema}': aucune table de l'ingestion");
+        } else {
+            println!("[db-drop] schéma '{schema}': {} tables à supprimer ({})", existing.len(), existing.join(", "));
+            println!("[dry-run] aucune modification — relancer avec --apply");
+        }
+        return Ok(());
+    }
+    let dropped = drop(&mut conn, &schema, cascade, verbose)?;
+    if dropped.is_empty() {
+        println!("[db-drop] schéma '{schema}': aucune table de l'ingestion");
+        return Ok(());
+    }
+    println!("[db-drop] ✅ schéma '{schema}': {} tables supprimées", dropped.len());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pool;
+
+    #[test]
+    fn ddl_creates_every_table() {
+        let created: Vec<&str> = statements()
+            .filter_map(|s| s.strip_prefix("CREATE TABLE IF NOT EXISTS "))
+            .filter_map(|s| s.split_whitespace().next())
+            .collect();
+        assert_eq!(created, TABLES);
+        // rejouable: IF [NOT] EXISTS, ou complément des seules lignes encore vides
+        let idempotent = |s: &str| s.contains("IF NOT EXISTS") || s.contains("IF EXISTS") || s.starts_with("UPDATE ") && s.ends_with("IS NULL");
+        assert!(statements().all(idempotent));
+    }
+
+    #[test]
+    #[ignore = "nécessite TEST_DATABASE_URL"]
+    fn init_then_drop_in_a_fresh_schema() {
+        let schema = "gdn_test_dbinit";
+        let mut conn = pool::test_conn(Some(schema));
+
+        assert_eq!(init(&mut conn, schema, false, false).unwrap(), SCHEMA_VERSION);
+        session::check_tables(&mut conn, schema, &["text_values"]).unwrap();
+        let err = init(&mut conn, schema, false, false).unwrap_err().to_string();
+        assert!(err.contains("--if-not-exists"), "{err}");
+        assert_eq!(init(&mut conn, schema, true, false).unwrap(), SCHEMA_VERSION);
+        let rows: i64 = conn.query_one("SELECT COUNT(*) FROM schema_version", &[]).unwrap().get(0);
+        assert_eq!(rows, 1);
+
+        assert_eq!(drop(&mut conn, schema, false, false).unwrap().len(), TABLES.len());
+        assert!(existing_tables(&mut conn, schema).unwrap().is_empty());
+        conn.batch_execute(&format!("DROP SCHEMA {schema}")).unwrap();
+    }
+}
@@ -0,0 +1,242 @@
+// ---------- --dictionary-texts: réponses texte courtes mutualisées ----------
+//
+// Des centaines de milliers de réponses identiques ("RAS", "aucune", "ne sait
+// pas") occupent une place disproportionnée. Mode optionnel: une réponse
+// text/free_text d'au plus N caractères (--dictionary-max-chars, défaut 32)
+// est stockée une seule fois dans `text_values (id, value, refcount)`; la
+// réponse porte `answers.text_value_id` et `answers.text` reste NULL.
+//
+// Lecture: COALESCE(a.text, tv.value) avec LEFT JOIN text_values tv ON
+// tv.id = a.text_value_id (export et stats le font; requêtes d'analyse: idem).
+// Le mode est consigné dans la provenance du batch (import_batches.provenance).
+//
+// Écriture: les ids sont résolus sur une connexion à part, hors des
+// transactions d'ingestion (pas de verrou de ligne partagé entre threads);
+// refcount est recalculé en fin de run pour les valeurs utilisées.
+// Une réponse réécrite sans le mode garde son text_value_id: `text`, non NULL,
+// l'emporte à la lecture; `dictionary-compact` remet refcount d'aplomb.
+//
+// `gdn_ingest dictionary-compact [--apply]` convertit les réponses existantes
+// (aperçu avec estimation de la place gagnée sans --apply).
+
+use anyhow::Result;
+use postgres::{Client, GenericClient};
+use std::collections::{HashMap, HashSet};
+
+use crate::prepared::PreparedStatements;
+
+/// Défaut de --dictionary-max-chars
+pub const DEFAULT_MAX_CHARS: usize = 32;
+
+/// Surcoût approximatif d'une ligne text_values (en-tête de tuple, id, refcount, index)
+const ENTRY_OVERHEAD: i64 = 64;
+/// answers.text_value_id
+const REFERENCE_BYTES: i64 = 8;
+
+/// Réponses concernées: questions texte, valeur courte, pas encore encodée
+const ELIGIBLE: &str = "a.\"text\" IS NOT NULL AND a.text_value_id IS NULL
+     AND char_length(a.\"text\") <= $1
+     AND a.question_id IN (SELECT id FROM questions WHERE type IN ('text', 'free_text'))";
+
+/// Le mode suppose la migration text_values (table + answers.text_value_id)
+pub fn check_schema(conn: &mut impl GenericClient) -> Result<()> {
+    let ok: bool = conn
+        .query_one(
+            "SELECT to_regclass('text_values') IS NOT NULL
+                AND EXISTS (SELECT 1 FROM information_schema.columns
+                            WHERE table_name = 'answers' AND column_name = 'text_value_id')",
+            &[],
+        )?
+        .get(0);
+    if !ok {
+        anyhow::bail!(
+            "--dictionary-texts: table text_values ou colonne answers.text_value_id absente — appliquer les migrations (alembic upgrade head)"
+        );
+    }
+    Ok(())
+}
+
+/// Dictionnaire partagé par les fichiers d'une ingestion
+pub struct TextDictionary {
+    conn: Client,
+    max_chars: usize,
+    ids: HashMap<String, i64>,
+    used: HashSet<i64>,
+    pub encoded: u64,
+}
+
+impl TextDictionary {
+    pub fn new(conn: Client, max_chars: usize) -> Self {
+        TextDictionary { conn, max_chars, ids: HashMap::new(), used: HashSet::new(), encoded: 0 }
+    }
+
+    /// id de la valeur si elle est assez courte pour le dictionnaire
+    pub fn id(&mut self, text: &str) -> Result<Option<i64>> {
+        if text.chars().count() > self.max_chars {
+            return Ok(None);
+        }
+        let id = match self.ids.get(text) {
+            Some(&id) => id,
+            None => {
+                let id: i64 = self
+                    .conn
+                    .query_one(
+                        "WITH ins AS (
+                             INSERT INTO text_values (value) VALUES ($1) ON CONFLICT (value) DO NOTHING RETURNING id
+                         )
+                         SELECT id FROM ins UNION ALL SELECT id FROM text_values WHERE value = $1 LIMIT 1",
+                        &[&text],
+                    )?
+                    .get(0);
+                self.ids.insert(text.to_string(), id);
+                id
+            }
+        };
+        self.used.insert(id);
+        self.encoded += 1;
+        Ok(Some(id))
+    }
+
+    /// refcount exact des valeurs utilisées pendant le run
+    pub fn finish(&mut self) -> Result<()> {
+        let used: Vec<i64> = self.used.iter().copied().collect();
+        self.conn.execute(
+            "UPDATE text_values tv SET refcount = (SELECT COUNT(*) FROM answers a WHERE a.text_value_id = tv.id)
+             WHERE tv.id = ANY($1)",
+            &[&used],
+        )?;
+        Ok(())
+    }
+
+    pub fn values(&self) -> usize {
+        self.used.len()
+    }
+}
+
+/// Réponse texte mutualisée: $1 contribution, $2 question, $3 position, $4 text_values.id
+pub const ANSWER_TEXT_VALUE: &str = "INSERT INTO answers (contribution_id, question_id, position, text_value_id)
+     VALUES ($1, $2, $3, $4)
+     ON CONFLICT (contribution_id, question_id, position)
+     DO UPDATE SET skipped = false, \"text\" = NULL, text_value_id = EXCLUDED.text_value_id";
+
+/// Réponse texte en clair: mêmes paramètres, $4 texte
+pub const ANSWER_TEXT: &str = "INSERT INTO answers (contribution_id, question_id, position, \"text\")
+     VALUES ($1, $2, $3, $4)
+     ON CONFLICT (contribution_id, question_id, position)
+     DO UPDATE SET skipped = false, \"text\" = EXCLUDED.\"text\"";
+
+/// Réponse texte, via le dictionnaire si la valeur y a sa place
+pub fn write_text_answer(
+    tx: &mut impl GenericClient,
+    stmts: &PreparedStatements,
+    dictionary: Option<&std::sync::Mutex<TextDictionary>>,
+    contrib_id: i64,
+    qid: i64,
+    pos: i32,
+    text: &str,
+) -> Result<()> {
+    let id = match dictionary {
+        Some(d) => d.lock().unwrap().id(text)?,
+        None => None,
+    };
+    match id {
+        Some(id) => tx.execute(&stmts.answer_text_value, &[&contrib_id, &qid, &pos, &id])?,
+        None => tx.execute(&stmts.answer_text, &[&contrib_id, &qid, &pos, &text])?,
+    };
+    Ok(())
+}
+
+/// Estimation de `dictionary-compact`
+#[derive(Debug, Default, PartialEq)]
+struct Estimate {
+    answers: i64,
+    distinct: i64,
+    /// octets de texte actuellement stockés dans answers
+    bytes: i64,
+    /// octets des valeurs distinctes
+    distinct_bytes: i64,
+}
+
+impl Estimate {
+    /// octets gagnés (négatif: le dictionnaire coûte plus qu'il ne rapporte)
+    fn saved(&self) -> i64 {
+        self.bytes - self.distinct_bytes - self.distinct * ENTRY_OVERHEAD - self.answers * REFERENCE_BYTES
+    }
+}
+
+pub fn run_dictionary_compact(max_chars: usize, chunk: i64, apply: bool) -> Result<()> {
+    let mut conn = crate::open_conn()?;
+    check_schema(&mut conn)?;
+    let max = max_chars as i32;
+    let r = conn.query_one(
+        &format!(
+            "SELECT COALESCE(SUM(n), 0)::int8, COUNT(*), COALESCE(SUM(n * b), 0)::int8, COALESCE(SUM(b), 0)::int8
+             FROM (SELECT COUNT(*) AS n, octet_length(a.\"text\") AS b
+                   FROM answers a WHERE {ELIGIBLE} GROUP BY a.\"text\") s"
+        ),
+        &[&max],
+    )?;
+    let est = Estimate { answers: r.get(0), distinct: r.get(1), bytes: r.get(2), distinct_bytes: r.get(3) };
+    println!(
+        "[dictionary] {} réponses texte ≤ {max_chars} caractères, {} valeurs distinctes, {} Ko de texte → gain estimé ≈ {} Ko",
+        est.answers,
+        est.distinct,
+        est.bytes / 1024,
+        est.saved() / 1024
+    );
+    if !apply {
+        println!("[dry-run] aucune modification — relancer avec --apply");
+        return Ok(());
+    }
+
+    conn.execute(
+        &format!(
+            "INSERT INTO text_values (value)
+             SELECT DISTINCT a.\"text\" FROM answers a WHERE {ELIGIBLE}
+             ON CONFLICT (value) DO NOTHING"
+        ),
+        &[&max],
+    )?;
+    // par tranches d'ids: pas de verrou sur toute la table answers
+    let (lo, hi): (Option<i64>, Option<i64>) = {
+        let r = conn.query_one("SELECT MIN(id), MAX(id) FROM answers", &[])?;
+        (r.get(0), r.get(1))
+    };
+    let mut converted = 0u64;
+    if let (Some(lo), Some(hi)) = (lo, hi) {
+        let mut start = lo;
+        while start <= hi {
+            let end = start + chunk - 1;
+            converted += conn.execute(
+                &format!(
+                    "UPDATE answers a SET text_value_id = tv.id, \"text\" = NULL
+                     FROM text_values tv
+                     WHERE a.id BETWEEN $2 AND $3 AND tv.value = a.\"text\" AND {ELIGIBLE}"
+                ),
+                &[&max, &start, &end],
+            )?;
+            start = end + 1;
+        }
+    }
+    conn.execute(
+        "UPDATE text_values tv SET refcount = (SELECT COUNT(*) FROM answers a WHERE a.text_value_id = tv.id)",
+        &[],
+    )?;
+    println!("[dictionary] ✅ {converted} réponses converties");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn estimate_counts_references_and_entries() {
+        // 10 000 × "RAS" (3 octets): le dictionnaire coûte plus cher que le texte
+        let ras = Estimate { answers: 10_000, distinct: 1, bytes: 30_000, distinct_bytes: 3 };
+        assert_eq!(ras.saved(), 30_000 - 3 - ENTRY_OVERHEAD - 80_000);
+        // 10 000 × "ne sait pas trop quoi répondre" (31 octets)
+        let long = Estimate { answers: 10_000, distinct: 1, bytes: 310_000, distinct_bytes: 31 };
+        assert!(long.saved() > 200_000);
+    }
+}
@@ -0,0 +1,156 @@
+// ---------- --dry-run: lecture des fichiers sans base de données ----------
+//
+// Chaque fichier est ouvert comme pour l'ingestion (délimiteur deviné,
+// encodage, commentaires), ses en-têtes confrontés au mapping, puis ses
+// lignes (toutes, ou les --dry-run-rows premières) passent par les décisions
+// de l'ingestion (cf. explain::answered): corbeille, conditions if_column,
+// record_skips, correspondance des options, analyse des valeurs. Résultat:
+// par question, le nombre de lignes qui auraient produit une réponse, et par
+// fichier les colonnes du mapping absentes et celles qu'aucune question ne
+// lit (cf. unmapped.rs). Les règles `rules:` du mapping sont contrôlées
+// comme à l'ingestion, rapport par question et par règle (cf. rules.rs).
+// Aucune connexion n'est ouverte. La couverture sert aussi à `validate`.
+
+use anyhow::Result;
+
+use crate::bars::{say, Bars};
+use crate::input::ColumnAccessor;
+use crate::normalize::NormalizeCaches;
+use crate::{aliases, explain, input, is_trashed, rules, stats, unmapped, validate, Mapping};
+
+/// Lignes lues et réponses par question (ordre du mapping)
+#[derive(Debug, Default)]
+pub struct Coverage {
+    pub rows: usize,
+    pub trashed: usize,
+    pub answered: Vec<usize>,
+}
+
+impl Coverage {
+    pub fn new(mapping: &Mapping) -> Self {
+        Coverage { answered: vec![0; mapping.questions.len()], ..Default::default() }
+    }
+
+    /// Compte une ligne lue; false si elle est à la corbeille (rien d'autre à en tirer)
+    pub fn count(&mut self, mapping: &Mapping, normalize: &NormalizeCaches, row: &dyn ColumnAccessor) -> bool {
+        self.rows += 1;
+        if is_trashed(row) {
+            self.trashed += 1;
+            return false;
+        }
+        for (n, yes) in self.answered.iter_mut().zip(explain::answered(mapping, normalize, row)) {
+            *n += yes as usize;
+        }
+        true
+    }
+
+    /// Part des lignes lues qui produisent `n` réponses, en %
+    pub fn rate(&self, n: usize) -> f64 {
+        if self.rows == 0 { 0.0 } else { 100.0 * n as f64 / self.rows as f64 }
+    }
+
+    pub fn table(&self, mapping: &Mapping) -> String {
+        let rows: Vec<Vec<String>> = mapping
+            .questions
+            .iter()
+            .zip(&self.answered)
+            .map(|(qm, &n)| {
+                let rate = self.rate(n);
+                let flag = if n == 0 { "⚠️ aucune" } else { "" };
+                vec![qm.code.clone(), qm.qtype.clone(), n.to_string(), format!("{rate:.1} %"), flag.to_string()]
+            })
+            .collect();
+        stats::render_table(&["question", "type", "réponses", "couverture", ""], &rows)
+    }
+}
+
+pub fn run(
+    files: &[String],
+    mapping: &Mapping,
+    read_opts: &input::ReadOptions,
+    max_rows: Option<usize>,
+    rules_mode: rules::RulesMode,
+    bars: &Bars,
+) -> Result<()> {
+    // motifs déjà vérifiés par validate_mapping
+    let normalize = NormalizeCaches::build(&mapping.questions, &mapping.defaults.transforms).map_err(anyhow::Error::msg)?;
+    let quality = rules::RuleSet::build(&mapping.questions).map_err(anyhow::Error::msg)?;
+    let mut quality_report = rules::Report::default();
+    let mut coverage = Coverage::new(mapping);
+    let mut missing = 0usize;
+    bars.set_prefix("dry-run");
+    for path in files {
+        let progress = bars.rows(path);
+        let rows_in = input::Rows::open(path, read_opts)?;
+        let resolution = aliases::Resolution::resolve(mapping, rows_in.headers());
+        for note in resolution.describe() {
+            say!(bars, "  [alias] {path}: {note}");
+        }
+        let problems = validate::check_headers(path, mapping, &resolution.headers(rows_in.headers()));
+        if problems.is_empty() {
+            say!(bars, "  ✓ {path}: toutes les colonnes du mapping présentes");
+        }
+        for p in &problems {
+            say!(bars, "  ⚠️  {}: question '{}': colonne {} absente", p.file, p.question, p.column_note());
+        }
+        missing += problems.len();
+        let mut unread = unmapped::Unmapped::new(mapping, rows_in.headers().iter());
+        for row in rows_in.take(max_rows.unwrap_or(usize::MAX)) {
+            let row = row?;
+            let row = resolution.row(row.as_ref());
+            progress.inc(1);
+            if !coverage.count(mapping, &normalize, &row) {
+                continue;
+            }
+            if let Err(e) = resolution.check(&row) {
+                let at = row.line().map(|l| format!(" ligne {l}")).unwrap_or_default();
+                say!(bars, "  ⚠️  {path}{at}: {e}");
+            }
+            unread.count(&row);
+            if !quality.is_empty() {
+                let violations = quality.check(&row, &normalize);
+                for v in quality_report.note(&violations) {
+                    say!(bars, "  ⚠️  {path}: {}", v.describe());
+                }
+            }
+        }
+        if let Some(msg) = unread.report(path) {
+            say!(bars, "  {msg}");
+        }
+        bars.file_done(progress);
+    }
+    bars.finish();
+
+    let sample = match max_rows {
+        Some(n) => format!(" (au plus {n} par fichier)"),
+        None => String::new(),
+    };
+    println!(
+        "[dry-run] {} fichiers, {} lignes lues{sample} dont {} à la corbeille, {missing} colonnes manquantes",
+        files.len(),
+        coverage.rows,
+        coverage.trashed
+    );
+    print!("{}", coverage.table(mapping));
+    if !quality.is_empty() {
+        print!("{}", quality_report.render(&quality, rules_mode));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn questions_without_answers_are_flagged() {
+        let mapping: Mapping = serde_yaml::from_str(
+            "form: { name: t }\nquestions:\n  - { code: q1, prompt: Q, type: text, source_column: Q1 }\n  - { code: q2, prompt: Q, type: text, source_column: Q2 }\n",
+        )
+        .unwrap();
+        let coverage = Coverage { rows: 4, trashed: 0, answered: vec![3, 0] };
+        let table = coverage.table(&mapping);
+        assert!(table.contains("| q1       | text | 3        | 75.0 %     |"), "{table}");
+        assert!(table.contains("| q2       | text | 0        | 0.0 %      | ⚠️ aucune |"), "{table}");
+    }
+}
@@ -0,0 +1,155 @@
+// ---------- Encodage des fichiers d'entrée ----------
+//
+// Les exports administratifs anciens sont souvent en Windows-1252 ou
+// ISO-8859-1. Les 16 premiers Kio (après décompression) sont confiés à
+// chardetng; si l'encodage deviné n'est pas UTF-8, tout le flux est transcodé
+// à la volée. `--encoding` (ou `ingest.encoding` dans le mapping) force un
+// encodage et court-circuite la détection.
+
+use encoding_rs::{Decoder, Encoding, UTF_8};
+use std::io::{self, Cursor, Read};
+
+/// Taille de l'échantillon soumis à la détection
+const SAMPLE: usize = 16 * 1024;
+/// Octets lus à chaque remplissage du décodeur
+const CHUNK: usize = 8192;
+
+/// Encodage d'après son libellé WHATWG (`utf-8`, `windows-1252`, `latin1`…)
+pub fn parse_label(label: &str) -> anyhow::Result<&'static Encoding> {
+    Encoding::for_label(label.trim().as_bytes())
+        .ok_or_else(|| anyhow::anyhow!("encodage inconnu: '{label}' (ex: utf-8, windows-1252, iso-8859-1)"))
+}
+
+/// Flux `inner` en UTF-8: encodage forcé, sinon deviné sur un échantillon.
+/// Renvoie aussi l'encodage retenu.
+pub fn to_utf8(
+    mut inner: Box<dyn Read>,
+    forced: Option<&'static Encoding>,
+) -> io::Result<(Box<dyn Read>, &'static Encoding)> {
+    let (reader, encoding): (Box<dyn Read>, _) = match forced {
+        Some(enc) => (inner, enc),
+        None => {
+            let mut sample = Vec::with_capacity(SAMPLE);
+            inner.by_ref().take(SAMPLE as u64).read_to_end(&mut sample)?;
+            let enc = detect(&sample, sample.len() < SAMPLE);
+            (Box::new(Cursor::new(sample).chain(inner)), enc)
+        }
+    };
+    if encoding == UTF_8 {
+        return Ok((reader, encoding));
+    }
+    Ok((Box::new(EncodingReader::new(reader, encoding)), encoding))
+}
+
+fn detect(sample: &[u8], is_last: bool) -> &'static Encoding {
+    let mut detector = chardetng::EncodingDetector::new();
+    detector.feed(sample, is_last);
+    detector.guess(Some(b"fr"), true)
+}
+
+/// Transcodage vers UTF-8 d'un flux dans un autre encodage
+pub struct EncodingReader<R: Read> {
+    inner: R,
+    decoder: Decoder,
+    input: Vec<u8>,
+    output: Vec<u8>,
+    pos: usize,
+    done: bool,
+}
+
+impl<R: Read> EncodingReader<R> {
+    pub fn new(inner: R, encoding: &'static Encoding) -> Self {
+        EncodingReader {
+            inner,
+            // BOM éventuel retiré (et prioritaire sur l'encodage annoncé)
+            decoder: encoding.new_decoder(),
+            input: vec![0; CHUNK],
+            output: Vec::new(),
+            pos: 0,
+            done: false,
+        }
+    }
+
+    fn refill(&mut self) -> io::Result<()> {
+        let n = self.inner.read(&mut self.input)?;
+        let last = n == 0;
+        let capacity = self.decoder.max_utf8_buffer_length(n).unwrap_or(n * 3 + 16);
+        let mut text = String::with_capacity(capacity);
+        // capacité maximale réservée: toute l'entrée est consommée d'un coup
+        let (_, read, _) = self.decoder.decode_to_string(&self.input[..n], &mut text, last);
+        debug_assert_eq!(read, n);
+        self.output = text.into_bytes();
+        self.pos = 0;
+        self.done = last;
+        Ok(())
+    }
+}
+
+impl<R: Read> Read for EncodingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.pos == self.output.len() {
+            if self.done {
+                return Ok(0);
+            }
+            self.refill()?;
+        }
+        let n = buf.len().min(self.output.len() - self.pos);
+        buf[..n].copy_from_slice(&self.output[self.pos..self.pos + n]);
+        self.pos += n;
+        Ok(n)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read_all(bytes: Vec<u8>, forced: Option<&'static Encoding>) -> (String, &'static Encoding) {
+        let (mut r, enc) = to_utf8(Box::new(Cursor::new(bytes)), forced).unwrap();
+        let mut s = String::new();
+        r.read_to_string(&mut s).unwrap();
+        (s, enc)
+    }
+
+    #[test]
+    fn windows_1252_is_detected_and_transcoded() {
+        let text = "département;réponse\nCôte-d'Or;très défavorable à la hausse\n".repeat(50);
+        let (bytes, _, _) = encoding_rs::WINDOWS_1252.encode(&text);
+        let (s, enc) = read_all(bytes.into_owned(), None);
+        assert_eq!(enc, encoding_rs::WINDOWS_1252);
+        assert_eq!(s, text);
+    }
+
+    #[test]
+    fn utf8_passes_through() {
+        let text = "département;réponse\nÎle-de-France;oui\n";
+        let (s, enc) = read_all(text.as_bytes().to_vec(), None);
+        assert_eq!(enc, UTF_8);
+        assert_eq!(s, text);
+    }
+
+    #[test]
+    fn forced_encoding_skips_detection() {
+        let latin1 = parse_label("latin1").unwrap();
+        let (s, _) = read_all(vec![b'c', 0xE9, b'\n'], Some(latin1));
+        assert_eq!(s, "cé\n");
+        assert!(parse_label("klingon").is_err());
+    }
+
+    #[test]
+    fn multibyte_output_split_across_small_reads() {
+        let text = "é".repeat(CHUNK * 2);
+        let (bytes, _, _) = encoding_rs::WINDOWS_1252.encode(&text);
+        let mut r = EncodingReader::new(Cursor::new(bytes.into_owned()), encoding_rs::WINDOWS_1252);
+        let mut out = Vec::new();
+        let mut buf = [0u8; 3];
+        loop {
+            let n = r.read(&mut buf).unwrap();
+            if n == 0 {
+                break;
+            }
+            out.extend_from_slice(&buf[..n]);
+        }
+        assert_eq!(String::from_utf8(out).unwrap(), text);
+    }
+}
@@ -0,0 +1,380 @@
+// ---------- Préchargement des contributions existantes ----------
+//
+// Paires (reference, raw_hash) déjà en base pour le formulaire, lues par
+// paquets via un curseur serveur. Sous le budget mémoire: HashMap complète.
+// Au-dessus: filtre de Bloom des références, les positifs (vrais ou faux)
+// étant résolus par un SELECT ponctuel.
+//
+// Une référence n'est unique qu'au sein de son formulaire: l'upsert des
+// contributions vise l'index unique (form_id, source_contribution_id), dont
+// la présence est vérifiée au démarrage (`check_upsert_index`).
+
+use anyhow::Result;
+use postgres::Client;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+
+/// Lignes lues par aller-retour sur le curseur serveur
+const FETCH_CHUNK: i32 = 50_000;
+/// Estimation grossière d'une entrée (référence + hash hex + surcoût HashMap)
+const BYTES_PER_ENTRY: usize = 160;
+/// Taux de faux positifs visé pour le filtre de Bloom
+const BLOOM_FP_RATE: f64 = 0.01;
+
+pub enum ExistingContributions {
+    Full(HashMap<String, Option<String>>),
+    Bloom(BloomFilter),
+}
+
+impl ExistingContributions {
+    /// Construit le cache à partir des paires (reference, raw_hash), en
+    /// choisissant la stratégie selon `count` et le budget.
+    pub fn from_pairs<I>(count: usize, budget_bytes: usize, pairs: I) -> Result<Self>
+    where
+        I: IntoIterator<Item = Result<(String, Option<String>)>>,
+    {
+        if count.saturating_mul(BYTES_PER_ENTRY) <= budget_bytes {
+            let mut map = HashMap::with_capacity(count);
+            for pair in pairs {
+                let (reference, hash) = pair?;
+                map.insert(reference, hash);
+            }
+            Ok(ExistingContributions::Full(map))
+        } else {
+            let mut bloom = BloomFilter::new(count, BLOOM_FP_RATE);
+            for pair in pairs {
+                bloom.insert(&pair?.0);
+            }
+            Ok(ExistingContributions::Bloom(bloom))
+        }
+    }
+
+    pub fn strategy(&self) -> &'static str {
+        match self {
+            ExistingContributions::Full(_) => "hashmap",
+            ExistingContributions::Bloom(_) => "bloom",
+        }
+    }
+
+    /// Empreinte mémoire approximative, en octets
+    pub fn footprint(&self) -> usize {
+        match self {
+            ExistingContributions::Full(map) => map.len() * BYTES_PER_ENTRY,
+            ExistingContributions::Bloom(bloom) => bloom.bits.len() * 8,
+        }
+    }
+
+    /// `Some(raw_hash)` si la référence existe déjà. En mode Bloom, `resolve`
+    /// n'est appelé que pour les références probablement présentes.
+    pub fn lookup<F>(&self, reference: &str, resolve: F) -> Result<Option<Option<String>>>
+    where
+        F: FnOnce(&str) -> Result<Option<Option<String>>>,
+    {
+        match self {
+            ExistingContributions::Full(map) => Ok(map.get(reference).cloned()),
+            ExistingContributions::Bloom(bloom) => {
+                if bloom.may_contain(reference) {
+                    resolve(reference)
+                } else {
+                    Ok(None)
+                }
+            }
+        }
+    }
+
+    /// Enregistre une contribution écrite pendant l'ingestion
+    pub fn record(&mut self, reference: &str, hash: &str) {
+        match self {
+            ExistingContributions::Full(map) => {
+                map.insert(reference.to_string(), Some(hash.to_string()));
+            }
+            ExistingContributions::Bloom(bloom) => bloom.insert(reference),
+        }
+    }
+
+    /// Annule des `record` (écritures annulées par ROLLBACK), du plus récent au
+    /// plus ancien: état d'avant rétabli. Sans effet en mode Bloom (une
+    /// référence en trop n'y coûte qu'un SELECT).
+    pub fn restore(&mut self, written: impl IntoIterator<Item = (String, Option<Option<String>>)>) {
+        let ExistingContributions::Full(map) = self else {
+            return;
+        };
+        for (reference, previous) in written {
+            match previous {
+                Some(hash) => map.insert(reference, hash),
+                None => map.remove(&reference),
+            };
+        }
+    }
+}
+
+/// Insertion d'une contribution; une référence déjà connue du formulaire est réécrite.
+/// $1 form_id, $2 référence, $3 raw_json, $4 raw_hash, $5 author_id,
+/// $6 import_batch_id, $7 submitted_at, $8 title, $9 source_file, $10 source_line.
+/// created_import_batch_id n'est posé qu'à l'insertion (cf. rollback.rs).
+pub const UPSERT_CONTRIBUTION: &str =
+    "INSERT INTO contributions (form_id, source_contribution_id, raw_json, raw_hash, author_id, import_batch_id, submitted_at, title,
+                                source_file, source_line, created_import_batch_id)
+     VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $6)
+     ON CONFLICT (form_id, source_contribution_id) DO UPDATE SET raw_json = EXCLUDED.raw_json, raw_hash = EXCLUDED.raw_hash,
+         author_id = COALESCE(EXCLUDED.author_id, contributions.author_id),
+         import_batch_id = EXCLUDED.import_batch_id,
+         submitted_at = COALESCE(EXCLUDED.submitted_at, contributions.submitted_at),
+         title = COALESCE(EXCLUDED.title, contributions.title),
+         source_file = EXCLUDED.source_file, source_line = EXCLUDED.source_line
+     RETURNING id";
+
+/// Insertion seule (--on-existing skip|error): aucune ligne renvoyée si la
+/// référence est déjà en base. Mêmes paramètres que UPSERT_CONTRIBUTION.
+pub const INSERT_CONTRIBUTION: &str =
+    "INSERT INTO contributions (form_id, source_contribution_id, raw_json, raw_hash, author_id, import_batch_id, submitted_at, title,
+                                source_file, source_line, created_import_batch_id)
+     VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $6)
+     ON CONFLICT (form_id, source_contribution_id) DO NOTHING
+     RETURNING id";
+
+/// Provenance d'une contribution (audit): fichier tel que passé en ligne de
+/// commande (nom du membre pour un zip) et ligne où commence l'enregistrement.
+/// raw_hash est le hash de cette ligne. Réécrite à chaque écriture: la
+/// dernière ligne lue l'emporte.
+pub struct Origin<'a> {
+    pub file: &'a str,
+    pub line: Option<i64>,
+}
+
+/// Index uniques (non partiels) de contributions, colonnes triées
+fn unique_indexes(conn: &mut impl postgres::GenericClient) -> Result<Vec<(String, Vec<String>)>> {
+    let rows = conn.query(
+        "SELECT c.relname::text,
+                ARRAY(SELECT a.attname::text FROM unnest(i.indkey) k
+                      JOIN pg_attribute a ON a.attrelid = i.indrelid AND a.attnum = k
+                      ORDER BY a.attname)
+         FROM pg_index i JOIN pg_class c ON c.oid = i.indexrelid
+         WHERE i.indrelid = 'contributions'::regclass AND i.indisunique AND i.indpred IS NULL",
+        &[],
+    )?;
+    Ok(rows.iter().map(|r| (r.get(0), r.get(1))).collect())
+}
+
+/// L'upsert suppose l'index unique (form_id, source_contribution_id)
+pub fn check_upsert_index(conn: &mut impl postgres::GenericClient) -> Result<()> {
+    let indexes = unique_indexes(conn)?;
+    if !indexes.iter().any(|(_, cols)| cols == &["form_id", "source_contribution_id"]) {
+        anyhow::bail!(
+            "contributions: index unique (form_id, source_contribution_id) absent — l'upsert en dépend. \
+             Appliquer les migrations (alembic upgrade head) ou: \
+             CREATE UNIQUE INDEX uq_contributions_form_source_id ON contributions (form_id, source_contribution_id)"
+        );
+    }
+    if let Some((name, _)) = indexes.iter().find(|(_, cols)| cols == &["source_contribution_id"]) {
+        println!(
+            "[preload] ⚠️  index unique '{name}' sur source_contribution_id seul: deux formulaires \
+             ne pourront pas partager une référence (DROP INDEX / DROP CONSTRAINT après migration)"
+        );
+    }
+    Ok(())
+}
+
+/// Charge les contributions existantes du formulaire par paquets de `FETCH_CHUNK`.
+pub fn preload_existing(conn: &mut Client, form_id: i64, budget_bytes: usize) -> Result<ExistingContributions> {
+    let count: i64 = conn
+        .query_one("SELECT COUNT(*) FROM contributions WHERE form_id = $1", &[&form_id])?
+        .get(0);
+
+    let mut tx = conn.transaction()?;
+    let portal = tx.bind(
+        "SELECT source_contribution_id, raw_hash FROM contributions
+         WHERE form_id = $1 AND source_contribution_id IS NOT NULL",
+        &[&form_id],
+    )?;
+    let mut chunk = tx.query_portal(&portal, FETCH_CHUNK)?.into_iter();
+    let pairs = std::iter::from_fn(|| loop {
+        if let Some(row) = chunk.next() {
+            return Some(Ok((row.get::<_, String>(0), row.get::<_, Option<String>>(1))));
+        }
+        match tx.query_portal(&portal, FETCH_CHUNK) {
+            Ok(rows) if rows.is_empty() => return None,
+            Ok(rows) => chunk = rows.into_iter(),
+            Err(e) => return Some(Err(e.into())),
+        }
+    });
+    let existing = ExistingContributions::from_pairs(count as usize, budget_bytes, pairs)?;
+    tx.commit()?;
+
+    println!(
+        "[preload] {} contributions existantes — stratégie {} (~{} Ko)",
+        count,
+        existing.strategy(),
+        existing.footprint() / 1024
+    );
+    Ok(existing)
+}
+
+/// Résolution ponctuelle d'une référence (positifs du filtre de Bloom)
+pub fn select_existing(conn: &mut impl postgres::GenericClient, form_id: i64, reference: &str) -> Result<Option<Option<String>>> {
+    let row = conn.query_opt(
+        "SELECT raw_hash FROM contributions WHERE form_id = $1 AND source_contribution_id = $2",
+        &[&form_id, &reference],
+    )?;
+    Ok(row.map(|r| r.get(0)))
+}
+
+// ---------- Filtre de Bloom minimal ----------
+
+pub struct BloomFilter {
+    bits: Vec<u64>,
+    nbits: u64,
+    k: u32,
+}
+
+impl BloomFilter {
+    pub fn new(expected: usize, fp_rate: f64) -> Self {
+        let n = expected.max(1) as f64;
+        let ln2 = std::f64::consts::LN_2;
+        let m = (-(n * fp_rate.ln()) / (ln2 * ln2)).ceil().max(64.0) as u64;
+        let k = ((m as f64 / n) * ln2).round().clamp(1.0, 16.0) as u32;
+        BloomFilter { bits: vec![0; m.div_ceil(64) as usize], nbits: m, k }
+    }
+
+    fn indexes(&self, key: &str) -> impl Iterator<Item = u64> + '_ {
+        let h1 = hash_with(key, 0x9e37_79b9);
+        let h2 = hash_with(key, 0x85eb_ca6b) | 1;
+        (0..self.k as u64).map(move |i| h1.wrapping_add(i.wrapping_mul(h2)) % self.nbits)
+    }
+
+    pub fn insert(&mut self, key: &str) {
+        let idx: Vec<u64> = self.indexes(key).collect();
+        for i in idx {
+            self.bits[(i / 64) as usize] |= 1 << (i % 64);
+        }
+    }
+
+    pub fn may_contain(&self, key: &str) -> bool {
+        self.indexes(key).all(|i| self.bits[(i / 64) as usize] & (1 << (i % 64)) != 0)
+    }
+}
+
+fn hash_with(key: &str, seed: u64) -> u64 {
+    let mut h = DefaultHasher::new();
+    seed.hash(&mut h);
+    key.hash(&mut h);
+    h.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pool;
+
+    fn pairs(n: usize) -> impl Iterator<Item = Result<(String, Option<String>)>> {
+        (0..n).map(|i| Ok((format!("ref-{i}"), Some(format!("hash-{i}")))))
+    }
+
+    #[test]
+    fn below_budget_loads_full_map() {
+        let existing = ExistingContributions::from_pairs(100, 100 * BYTES_PER_ENTRY, pairs(100)).unwrap();
+        assert_eq!(existing.strategy(), "hashmap");
+        let hit = existing.lookup("ref-42", |_| panic!("pas de SELECT en mode hashmap")).unwrap();
+        assert_eq!(hit, Some(Some("hash-42".into())));
+        assert_eq!(existing.lookup("ref-999", |_| unreachable!()).unwrap(), None);
+    }
+
+    #[test]
+    fn above_budget_falls_back_to_bloom() {
+        // budget volontairement abaissé pour forcer le filtre de Bloom
+        let existing = ExistingContributions::from_pairs(1000, 1024, pairs(1000)).unwrap();
+        assert_eq!(existing.strategy(), "bloom");
+        assert!(existing.footprint() < 1000 * BYTES_PER_ENTRY);
+
+        // toutes les références présentes passent par la résolution ponctuelle
+        for i in 0..1000 {
+            let reference = format!("ref-{i}");
+            let hit = existing
+                .lookup(&reference, |r| Ok(Some(Some(format!("resolved-{r}")))))
+                .unwrap();
+            assert_eq!(hit, Some(Some(format!("resolved-ref-{i}"))));
+        }
+
+        // les absentes ne déclenchent un SELECT que sur faux positif (~1%)
+        let mut selects = 0;
+        for i in 1000..11_000 {
+            let hit = existing
+                .lookup(&format!("ref-{i}"), |_| {
+                    selects += 1;
+                    Ok(None)
+                })
+                .unwrap();
+            assert_eq!(hit, None);
+        }
+        assert!(selects < 500, "trop de faux positifs: {selects}");
+    }
+
+    #[test]
+    fn recorded_references_are_found() {
+        let mut existing = ExistingContributions::from_pairs(10, 0, pairs(10)).unwrap();
+        existing.record("nouvelle", "h");
+        let hit = existing.lookup("nouvelle", |_| Ok(Some(Some("h".into())))).unwrap();
+        assert_eq!(hit, Some(Some("h".into())));
+    }
+
+    #[test]
+    fn restore_undoes_rolled_back_records() {
+        let mut existing = ExistingContributions::from_pairs(10, 10 * BYTES_PER_ENTRY, pairs(10)).unwrap();
+        existing.record("ref-1", "réécrite");
+        existing.record("nouvelle", "h");
+        existing.restore([("ref-1".to_string(), Some(Some("hash-1".to_string()))), ("nouvelle".to_string(), None)]);
+        assert_eq!(existing.lookup("ref-1", |_| unreachable!()).unwrap(), Some(Some("hash-1".into())));
+        assert_eq!(existing.lookup("nouvelle", |_| unreachable!()).unwrap(), None);
+    }
+
+    /// Base jetable (même variable que les tests Python): tables temporaires
+    /// uniquement, qui masquent celles du schéma public pendant la session.
+    #[test]
+    #[ignore = "nécessite TEST_DATABASE_URL"]
+    fn same_reference_in_two_forms_stays_separate() {
+        let mut conn = pool::test_conn(None);
+        conn.batch_execute(
+            "CREATE TEMP TABLE contributions (id bigserial primary key, form_id bigint not null,
+                 source_contribution_id varchar unique, raw_json text, raw_hash varchar, author_id bigint,
+                 import_batch_id varchar, submitted_at timestamp, title varchar, source_file text, source_line bigint,
+                 created_import_batch_id varchar)",
+        )
+        .unwrap();
+        let err = check_upsert_index(&mut conn).unwrap_err().to_string();
+        assert!(err.contains("CREATE UNIQUE INDEX uq_contributions_form_source_id"), "{err}");
+
+        conn.batch_execute(
+            "CREATE UNIQUE INDEX ON contributions (form_id, source_contribution_id);
+             ALTER TABLE contributions DROP CONSTRAINT contributions_source_contribution_id_key",
+        )
+        .unwrap();
+        check_upsert_index(&mut conn).unwrap();
+
+        let mut upsert = |form_id: i64, raw: &str| -> i64 {
+            let (author, submitted, title) = (None::<i64>, None::<chrono::NaiveDateTime>, None::<&str>);
+            let line = Some(2i64);
+            conn.query_one(
+                UPSERT_CONTRIBUTION,
+                &[&form_id, &"r1", &raw, &raw, &author, &"test", &submitted, &title, &"a.csv", &line],
+            )
+                .unwrap()
+                .get(0)
+        };
+        let a = upsert(1, r#"{"reference":"r1","Q1":"a"}"#);
+        let b = upsert(2, r#"{"reference":"r1","Q9":"b"}"#);
+        assert_ne!(a, b);
+        // réimport du formulaire 1: même contribution, celle du formulaire 2 intacte
+        assert_eq!(upsert(1, r#"{"reference":"r1","Q1":"a2"}"#), a);
+        let stored: Vec<(i64, String)> = conn
+            .query("SELECT form_id, raw_json FROM contributions ORDER BY form_id", &[])
+            .unwrap()
+            .iter()
+            .map(|r| (r.get(0), r.get(1)))
+            .collect();
+        assert_eq!(
+            stored,
+            vec![(1, r#"{"reference":"r1","Q1":"a2"}"#.to_string()), (2, r#"{"reference":"r1","Q9":"b"}"#.to_string())]
+        );
+    }
+}
@@ -0,0 +1,435 @@
+// ---------- Sous-commande explain (une ligne, sans base de données) ----------
+//
+// Rejoue sur une seule ligne CSV les décisions de l'ingestion (corbeille,
+// questions passées, correspondance des options, analyse des valeurs) à
+// partir des mêmes helpers que `ingest_file`, et affiche le détail par question.
+
+use anyhow::Result;
+use std::borrow::Cow;
+use std::path::PathBuf;
+use std::rc::Rc;
+
+use crate::aliases;
+use crate::input::{normalise_headers, ColumnAccessor, CsvRow};
+use crate::normalize::NormalizeCaches;
+use crate::{
+    cells, col_value, is_trashed, load_mapping, options, question_cells_empty, validate_mapping, values,
+    Mapping, QType, QuestionMap, ScaleOutcome,
+};
+
+pub fn run_explain(mapping_path: &PathBuf, header: &str, row: &str, delimiter: char) -> Result<()> {
+    let mapping = load_mapping(mapping_path)?;
+    validate_mapping(&mapping, false)?;
+
+    let row = parse_row(header, row, delimiter)?;
+    let resolution = aliases::Resolution::resolve(&mapping, row.headers());
+    for note in resolution.describe() {
+        println!("[alias] {note}");
+    }
+    let row = resolution.row(&row);
+    resolution.check(&row)?;
+    for line in explain_row(&mapping, &row) {
+        println!("{line}");
+    }
+    Ok(())
+}
+
+/// En-tête + ligne → un CSV d'une ligne
+fn parse_row(header: &str, row: &str, delimiter: char) -> Result<CsvRow> {
+    let data = format!("{header}\n{row}\n");
+    let mut rdr = csv::ReaderBuilder::new()
+        .delimiter(delimiter as u8)
+        .flexible(true)
+        .from_reader(data.as_bytes());
+    let mut headers = rdr.headers()?.clone();
+    normalise_headers(&mut headers);
+    let rec = rdr
+        .records()
+        .next()
+        .ok_or_else(|| anyhow::anyhow!("ligne vide"))??;
+    if rec.len() != headers.len() {
+        println!("⚠️  {} colonnes dans l'en-tête, {} dans la ligne", headers.len(), rec.len());
+    }
+    Ok(CsvRow::new(Rc::new(headers), rec))
+}
+
+pub fn explain_row(mapping: &Mapping, row: &dyn ColumnAccessor) -> Vec<String> {
+    // motifs déjà vérifiés par validate_mapping
+    let rules = NormalizeCaches::build(&mapping.questions, &mapping.defaults.transforms).unwrap_or_default();
+    let mut out = Vec::new();
+    if is_trashed(row) {
+        out.push("ligne à la corbeille (trashed/trashedStatus) → ignorée".to_string());
+        return out;
+    }
+    let reference = col_value(row, Some("reference")).unwrap_or("(aucune)");
+    out.push(format!("contribution: {reference}"));
+
+    for qm in &mapping.questions {
+        out.push(format!("{} ({})", qm.code, qm.qtype));
+        let truthy = (qm.qtype == "multi_choice" && qm.options_from_columns()).then(|| qm.truthy_values());
+        if qm.record_skips && question_cells_empty(qm, row, truthy.as_deref()) == Some(true) {
+            out.push("  → answer skipped (cellules vides, record_skips)".to_string());
+            continue;
+        }
+        for line in explain_question(qm, &rules, row, truthy.as_deref()).lines {
+            out.push(format!("  {line}"));
+        }
+    }
+    out
+}
+
+/// Pour chaque question du mapping (même ordre): la ligne donnerait-elle une
+/// réponse? Mêmes décisions que `explain_row`, plus les conditions if_column
+/// (dry-run: couverture par question)
+pub fn answered(mapping: &Mapping, rules: &NormalizeCaches, row: &dyn ColumnAccessor) -> Vec<bool> {
+    if is_trashed(row) {
+        return vec![false; mapping.questions.len()];
+    }
+    mapping
+        .questions
+        .iter()
+        .map(|qm| {
+            let truthy = (qm.qtype == "multi_choice" && qm.options_from_columns()).then(|| qm.truthy_values());
+            rules.applies(qm, row)
+                && question_cells_empty(qm, row, truthy.as_deref()) != Some(true)
+                && explain_question(qm, rules, row, truthy.as_deref()).answered
+        })
+        .collect()
+}
+
+/// Détail d'une question pour une ligne, et si une réponse serait écrite
+struct Explained {
+    lines: Vec<String>,
+    answered: bool,
+}
+
+/// Cellule source unique (après `normalize`), ou ligne expliquant son absence
+fn source_cell<'a>(
+    qm: &QuestionMap,
+    rules: &NormalizeCaches,
+    row: &'a dyn ColumnAccessor,
+    out: &mut Vec<String>,
+) -> Option<Cow<'a, str>> {
+    let Some(col) = qm.source_column.as_deref() else {
+        out.push("→ rien (pas de source_column)".to_string());
+        return None;
+    };
+    match row.cell(col) {
+        Some(v) => {
+            out.push(format!("cellule '{col}': {v:?}"));
+            let normalized = rules.apply(&qm.code, v);
+            if normalized != v {
+                out.push(format!("normalize → {normalized:?}"));
+            }
+            Some(normalized)
+        }
+        None => {
+            out.push(format!("→ rien (colonne '{col}' absente)"));
+            None
+        }
+    }
+}
+
+/// single_choice/multi_choice: `value_map` d'abord, puis l'option correspondante
+/// (une valeur traduite peut aussi désigner un code d'option); vrai si une option est retenue
+fn match_choice(qm: &QuestionMap, raw: &str, fallback_dynamic: bool) -> (String, bool) {
+    let value_map = qm.value_map();
+    let Some(translated) = value_map.translate(raw) else {
+        return match_option(qm, raw, fallback_dynamic);
+    };
+    match qm.options.iter().find(|o| o.label != translated && o.code == translated) {
+        Some(o) if !qm.options_from_values => (format!("'{raw}' → value_map → option déclarée '{}'", o.code), true),
+        _ => {
+            let (line, found) = match_option(qm, translated, fallback_dynamic);
+            (format!("'{raw}' → value_map {line}"), found)
+        }
+    }
+}
+
+/// Option déclarée correspondant exactement au libellé; vrai si une option est retenue
+fn match_option(qm: &QuestionMap, raw: &str, fallback_dynamic: bool) -> (String, bool) {
+    if qm.options_from_values {
+        return (format!("'{raw}' → option dynamique code '{}'", options::normalize_code(raw)), true);
+    }
+    match qm.options.iter().find(|o| o.label == raw) {
+        Some(o) => (format!("'{raw}' → option déclarée '{}'", o.code), true),
+        None if fallback_dynamic => (
+            format!("'{raw}' → absente des options déclarées, création dynamique code '{}'", options::normalize_code(raw)),
+            true,
+        ),
+        None => (format!("'{raw}' → absente des options déclarées, ignorée"), false),
+    }
+}
+
+fn explain_question(
+    qm: &QuestionMap,
+    rules: &NormalizeCaches,
+    row: &dyn ColumnAccessor,
+    truthy: Option<&[String]>,
+) -> Explained {
+    let mut out = Vec::new();
+    let mut answered = false;
+    match qm.kind() {
+        Some(QType::SingleChoice) => {
+            if let Some(v) = source_cell(qm, rules, row, &mut out) {
+                let splitter = qm.split_comment.as_ref().and_then(|s| s.compile().ok());
+                let (raw, comment) = match &splitter {
+                    Some(re) => cells::split_comment(re, v.trim()),
+                    None => (v.trim(), None),
+                };
+                match raw {
+                    "" => out.push("→ rien (vide)".to_string()),
+                    raw => {
+                        let (line, found) = match_choice(qm, raw, true);
+                        out.push(format!("→ {line}"));
+                        answered = found;
+                    }
+                }
+                if let Some(c) = comment {
+                    out.push(format!("→ commentaire (texte de la réponse) {c:?}"));
+                }
+            }
+        }
+        Some(QType::MultiChoice) if qm.options_from_columns() => {
+            let truthy = truthy.unwrap_or_default();
+            let mut checked = Vec::new();
+            for opt in &qm.options {
+                let Some(col) = opt.source_column.as_deref() else { continue };
+                match row.cell(col) {
+                    Some(v) => {
+                        let on = truthy.contains(&v.trim().to_lowercase());
+                        out.push(format!("cellule '{col}': {v:?} → {}", if on { "cochée" } else { "non cochée" }));
+                        if on {
+                            checked.push(opt.code.as_str());
+                        }
+                    }
+                    None => out.push(format!("colonne '{col}' absente")),
+                }
+            }
+            if checked.is_empty() {
+                out.push("→ rien (aucune option cochée)".to_string());
+            } else {
+                out.push(format!("→ options [{}]", checked.join(", ")));
+                answered = true;
+            }
+        }
+        Some(QType::MultiChoice) => {
+            if let Some(v) = source_cell(qm, rules, row, &mut out) {
+                let tokens: Vec<&str> = cells::split_tokens(&v, qm.multi_delimiter()).collect();
+                if tokens.is_empty() {
+                    out.push("→ rien (vide)".to_string());
+                }
+                for raw in tokens {
+                    let (line, found) = match_choice(qm, raw, false);
+                    out.push(format!("→ {line}"));
+                    answered |= found;
+                }
+            }
+        }
+        Some(QType::Ranking) => {
+            let cols = qm.source.as_ref().map(|s| s.columns.as_slice()).unwrap_or_default();
+            for (rank, col) in cols.iter().enumerate() {
+                match col_value(row, Some(col)) {
+                    Some(raw) => {
+                        let (line, found) = match_option(qm, raw, false);
+                        out.push(format!("rang {} ('{col}'): {line}", rank + 1));
+                        answered |= found;
+                    }
+                    None => out.push(format!("rang {} ('{col}'): rien", rank + 1)),
+                }
+            }
+        }
+        Some(QType::Matrix) => {
+            for (code, mrow) in qm.matrix_children() {
+                match cells::MatrixCell::of(row.cell(&mrow.source_column)) {
+                    cells::MatrixCell::Absent => out.push(format!("{code}: colonne '{}' absente", mrow.source_column)),
+                    cells::MatrixCell::Empty if qm.record_skips => out.push(format!("{code}: vide → answer skipped")),
+                    cells::MatrixCell::Empty => out.push(format!("{code}: rien (vide)")),
+                    cells::MatrixCell::Value(raw) => {
+                        let (line, found) = match_option(qm, raw, false);
+                        out.push(format!("{code}: {line}"));
+                        answered |= found;
+                    }
+                }
+            }
+        }
+        Some(QType::FreeText) => match qm.free_text_value(row) {
+            Some(text) => {
+                out.push(format!("→ texte {text:?}"));
+                answered = true;
+            }
+            None => out.push("→ rien (colonnes vides ou absentes)".to_string()),
+        },
+        Some(QType::Number) => {
+            if let Some(v) = source_cell(qm, rules, row, &mut out) {
+                answered = !v.trim().is_empty();
+                match (v.trim(), values::parse_number(&v)) {
+                    ("", _) => out.push("→ rien (vide)".to_string()),
+                    (raw, Some(n)) if qm.violates_range(n) => {
+                        out.push(format!("→ texte {raw:?}, value_num {n} hors de {} (--range-violations)", qm.range_label()))
+                    }
+                    (raw, Some(n)) => out.push(format!("→ texte {raw:?}, value_num {n}")),
+                    (raw, None) => out.push(format!("→ texte {raw:?}, value_num NULL (nombre illisible)")),
+                }
+            }
+        }
+        Some(QType::Date) => {
+            if let Some(v) = source_cell(qm, rules, row, &mut out) {
+                answered = !v.trim().is_empty();
+                match (v.trim(), values::parse_date(&v, &qm.date_formats())) {
+                    ("", _) => out.push("→ rien (vide)".to_string()),
+                    (raw, Some(d)) => out.push(format!("→ texte {:?}, value_date {d}", qm.date_text(raw, Some(d)))),
+                    (raw, None) => out.push(format!("→ texte {raw:?}, value_date NULL (date illisible)")),
+                }
+            }
+        }
+        Some(QType::Boolean) => {
+            if let Some(v) = source_cell(qm, rules, row, &mut out) {
+                match qm.boolean_values().parse(&v) {
+                    Some(values::BoolAnswer::Unknown) if !qm.allow_unknown => {
+                        out.push("→ rien (vide ou NSP, allow_unknown=false)".to_string())
+                    }
+                    Some(b) => {
+                        out.push(format!("→ texte '{}', value_num {:?}", b.as_str(), b.as_num()));
+                        answered = true;
+                    }
+                    None => out.push("→ rien (valeur oui/non non reconnue)".to_string()),
+                }
+            }
+        }
+        Some(QType::Scale) => {
+            if let Some(v) = source_cell(qm, rules, row, &mut out) {
+                let raw = v.trim();
+                if raw.is_empty() {
+                    out.push("→ rien (vide)".to_string());
+                } else {
+                    let outcome = qm.scale_outcome(raw);
+                    answered = matches!(outcome, ScaleOutcome::InRange(_) | ScaleOutcome::Clamped(_));
+                    out.push(match outcome {
+                        ScaleOutcome::InRange(n) => format!("→ texte {raw:?}, value_num {n}"),
+                        ScaleOutcome::Clamped(n) => format!("→ hors bornes, ramenée: texte {raw:?}, value_num {n}"),
+                        ScaleOutcome::Skipped(Some(_)) => "→ rien (hors bornes)".to_string(),
+                        ScaleOutcome::Skipped(None) => "→ rien (illisible)".to_string(),
+                        ScaleOutcome::Error(_) => "→ ERREUR: l'ingestion s'arrêterait (on_out_of_range: error)".to_string(),
+                    });
+                }
+            }
+        }
+        Some(QType::Text) => {
+            if let Some(v) = source_cell(qm, rules, row, &mut out) {
+                match v.trim() {
+                    "" => out.push("→ rien (vide)".to_string()),
+                    raw => {
+                        out.push(format!("→ texte {raw:?}"));
+                        answered = true;
+                    }
+                }
+            }
+        }
+        None => out.push(format!("→ rien (type '{}' inconnu)", qm.qtype)),
+    }
+    Explained { lines: out, answered }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mapping() -> Mapping {
+        serde_yaml::from_str(
+            r#"
+form: { name: test }
+questions:
+  - code: q_choice
+    prompt: Choix
+    type: single_choice
+    source_column: Q1
+    split_comment: " — "
+    value_map:
+      "Tout à fait": oui
+      "Bien sûr": Oui
+    options:
+      - { code: oui, label: Oui }
+  - code: q_scale
+    prompt: Note
+    type: scale
+    source_column: Q2
+    scale_min: 1
+    scale_max: 5
+    on_out_of_range: clamp
+  - code: q_bool
+    prompt: Accord
+    type: boolean
+    source_column: Q3
+  - code: q_age
+    prompt: Âge
+    type: number
+    source_column: Q4
+    min: 0
+    max: 120
+  - code: confiance
+    prompt: Confiance
+    type: matrix
+    rows:
+      - { code: maire, label: Maire, source_column: C1 }
+      - { code: depute, label: Député, source_column: C2 }
+    options:
+      - { code: oui, label: Oui }
+"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn explains_each_question() {
+        let row = parse_row("reference,Q1,Q2,Q3", "ref-1,Oui,9,NSP", ',').unwrap();
+        let lines = explain_row(&mapping(), &row);
+        assert_eq!(lines[0], "contribution: ref-1");
+        assert!(lines.contains(&"  → 'Oui' → option déclarée 'oui'".to_string()));
+        assert!(lines.contains(&"  → hors bornes, ramenée: texte \"9\", value_num 5".to_string()));
+        assert!(lines.contains(&"  → rien (vide ou NSP, allow_unknown=false)".to_string()));
+    }
+
+    #[test]
+    fn single_choice_comment_is_split_off() {
+        let row = parse_row("reference,Q1", "ref-1,Oui — mais seulement si…", ',').unwrap();
+        let lines = explain_row(&mapping(), &row);
+        assert!(lines.contains(&"  → 'Oui' → option déclarée 'oui'".to_string()));
+        assert!(lines.contains(&"  → commentaire (texte de la réponse) \"mais seulement si…\"".to_string()));
+    }
+
+    #[test]
+    fn value_map_translates_before_lookup() {
+        let row = parse_row("reference,Q1", "ref-1,Tout à fait", ',').unwrap();
+        let lines = explain_row(&mapping(), &row);
+        assert!(lines.contains(&"  → 'Tout à fait' → value_map → option déclarée 'oui'".to_string()));
+        let row = parse_row("reference,Q1", "ref-1,Bien sûr", ',').unwrap();
+        let lines = explain_row(&mapping(), &row);
+        assert!(lines.contains(&"  → 'Bien sûr' → value_map 'Oui' → option déclarée 'oui'".to_string()));
+    }
+
+    #[test]
+    fn number_outside_min_max_is_flagged() {
+        let row = parse_row("reference,Q4", "ref-1,430", ',').unwrap();
+        let lines = explain_row(&mapping(), &row);
+        assert!(lines.contains(&"  → texte \"430\", value_num 430 hors de [0, 120] (--range-violations)".to_string()));
+        let row = parse_row("reference,Q4", "ref-1,43", ',').unwrap();
+        assert!(explain_row(&mapping(), &row).contains(&"  → texte \"43\", value_num 43".to_string()));
+    }
+
+    #[test]
+    fn matrix_rows_are_child_questions() {
+        let row = parse_row("reference,C1,C2", "ref-1,Oui,", ',').unwrap();
+        let lines = explain_row(&mapping(), &row);
+        assert!(lines.contains(&"  confiance.maire: 'Oui' → option déclarée 'oui'".to_string()));
+        assert!(lines.contains(&"  confiance.depute: rien (vide)".to_string()));
+    }
+
+    #[test]
+    fn trashed_rows_are_ignored() {
+        let row = parse_row("reference;trashed;Q1", "ref-1;1;Oui", ';').unwrap();
+        let lines = explain_row(&mapping(), &row);
+        assert_eq!(lines.len(), 1);
+        assert!(lines[0].contains("corbeille"));
+    }
+}
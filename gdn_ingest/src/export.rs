@@ -0,0 +1,139 @@
+// ---------- Sous-commande export (inverse de run_ingest) ----------
+//
+// Reconstruit un CSV à partir de forms/questions/contributions/answers/
+// answer_options (et text_values, cf. --dictionary-texts) pour auditer ce qui
+// a réellement été chargé. --exclude-deleted écarte les contributions
+// supprimées (deleted_at, cf. softdelete.rs).
+
+use anyhow::Result;
+use clap::ValueEnum;
+use postgres::fallible_iterator::FallibleIterator;
+use postgres::types::ToSql;
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::Path;
+
+use crate::{forms, open_conn, sanitize::write_safe_record, softdelete};
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub enum ExportFormat {
+    /// Une ligne par contribution, une colonne par question
+    Wide,
+    /// Une ligne par réponse
+    Long,
+}
+
+/// Valeurs d'une contribution: question_code → valeurs (options multiples)
+type Values = HashMap<String, Vec<String>>;
+
+pub fn run_export(
+    form: &str,
+    version: Option<&str>,
+    output: Option<&Path>,
+    batch: Option<&str>,
+    format: ExportFormat,
+    exclude_deleted: bool,
+) -> Result<()> {
+    let mut conn = open_conn()?;
+    if exclude_deleted {
+        softdelete::require_columns(&mut conn)?;
+    }
+
+    let form_id = forms::find_form_id(&mut conn, form, version)?;
+
+    let codes: Vec<String> = conn
+        .query(
+            "SELECT question_code FROM questions WHERE form_id = $1 ORDER BY position NULLS LAST, id",
+            &[&form_id],
+        )?
+        .iter()
+        .map(|r| r.get(0))
+        .collect();
+
+    let sink: Box<dyn Write> = match output {
+        Some(p) => Box::new(std::fs::File::create(p)?),
+        None => Box::new(std::io::stdout().lock()),
+    };
+    let mut w = csv::Writer::from_writer(sink);
+    match format {
+        ExportFormat::Wide => {
+            let mut header = vec!["source_contribution_id".to_string()];
+            header.extend(codes.iter().cloned());
+            write_safe_record(&mut w, &header)?;
+        }
+        ExportFormat::Long => write_safe_record(&mut w, ["source_contribution_id", "question_code", "value"])?,
+    }
+
+    // Une ligne SQL par (réponse, option), triée par contribution
+    let params: [&(dyn ToSql + Sync); 2] = [&form_id, &batch];
+    let deleted_filter = if exclude_deleted { " AND c.deleted_at IS NULL" } else { "" };
+    let sql = format!(
+        "SELECT c.id, c.source_contribution_id, q.question_code, COALESCE(o.label, a.\"text\", tv.value)
+         FROM contributions c
+         LEFT JOIN answers a ON a.contribution_id = c.id
+         LEFT JOIN text_values tv ON tv.id = a.text_value_id
+         LEFT JOIN questions q ON q.id = a.question_id
+         LEFT JOIN answer_options ao ON ao.answer_id = a.id
+         LEFT JOIN options o ON o.id = ao.option_id
+         WHERE c.form_id = $1 AND ($2::text IS NULL OR c.import_batch_id = $2){deleted_filter}
+         ORDER BY c.id, q.position NULLS LAST, q.id, a.position, o.position NULLS LAST, o.id"
+    );
+    let mut it = conn.query_raw(sql.as_str(), params)?;
+
+    let mut current: Option<(i64, String)> = None;
+    let mut values = Values::new();
+    let mut order: Vec<String> = Vec::new();
+    let mut n = 0usize;
+    while let Some(row) = it.next()? {
+        let cid: i64 = row.get(0);
+        if current.as_ref().map(|(id, _)| *id) != Some(cid) {
+            if let Some((_, reference)) = current.take() {
+                write_contribution(&mut w, format, &reference, &codes, &values, &order)?;
+                n += 1;
+            }
+            let reference: Option<String> = row.get(1);
+            current = Some((cid, reference.unwrap_or_default()));
+            values.clear();
+            order.clear();
+        }
+        let code: Option<String> = row.get(2);
+        let value: Option<String> = row.get(3);
+        if let (Some(code), Some(value)) = (code, value) {
+            if !values.contains_key(&code) {
+                order.push(code.clone());
+            }
+            values.entry(code).or_default().push(value);
+        }
+    }
+    if let Some((_, reference)) = current.take() {
+        write_contribution(&mut w, format, &reference, &codes, &values, &order)?;
+        n += 1;
+    }
+    w.flush()?;
+
+    eprintln!("[export] {} contributions exportées (form id={})", n, form_id);
+    Ok(())
+}
+
+fn write_contribution<W: Write>(
+    w: &mut csv::Writer<W>,
+    format: ExportFormat,
+    reference: &str,
+    codes: &[String],
+    values: &Values,
+    order: &[String],
+) -> Result<()> {
+    match format {
+        ExportFormat::Wide => {
+            let mut rec = vec![reference.to_string()];
+            rec.extend(codes.iter().map(|c| values.get(c).map(|v| v.join(";")).unwrap_or_default()));
+            write_safe_record(w, &rec)?;
+        }
+        ExportFormat::Long => {
+            for code in order {
+                write_safe_record(w, [reference, code.as_str(), values[code].join(";").as_str()])?;
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,188 @@
+// ---------- Normalisation forms.version / forms.source ----------
+//
+// NULL et '' sont équivalents: on écrit toujours NULL, on compare via COALESCE.
+// Les doublons hérités (même nom, version NULL vs '') peuvent être fusionnés
+// avec la sous-commande merge-forms.
+
+use anyhow::Result;
+use postgres::Client;
+
+/// `None` pour une valeur absente, vide ou blanche; sinon la valeur trimée
+pub fn normalize_opt(v: Option<&str>) -> Option<String> {
+    v.map(str::trim).filter(|s| !s.is_empty()).map(str::to_string)
+}
+
+/// Deux versions (ou sources) désignent-elles le même formulaire ?
+pub fn same_key(a: Option<&str>, b: Option<&str>) -> bool {
+    normalize_opt(a) == normalize_opt(b)
+}
+
+/// Formulaire existant par nom + version (NULL et '' équivalents)
+pub fn find_form_id(conn: &mut Client, name: &str, version: Option<&str>) -> Result<i64> {
+    let rows = conn.query("SELECT id, version FROM forms WHERE name = $1 ORDER BY id", &[&name])?;
+    match rows.iter().find(|r| same_key(r.get(1), version)) {
+        Some(r) => Ok(r.get(0)),
+        None => anyhow::bail!("formulaire '{}' version '{}' introuvable", name, version.unwrap_or("")),
+    }
+}
+
+/// Groupe de formulaires identiques à la normalisation près (ids triés)
+pub struct DuplicateForms {
+    pub name: String,
+    pub version: Option<String>,
+    pub source: Option<String>,
+    pub ids: Vec<i64>,
+}
+
+pub fn find_duplicate_forms(conn: &mut Client) -> Result<Vec<DuplicateForms>> {
+    let rows = conn.query(
+        "SELECT name, NULLIF(TRIM(COALESCE(version,'')),''), NULLIF(TRIM(COALESCE(source,'')),''),
+                array_agg(id ORDER BY id)
+         FROM forms
+         GROUP BY 1, 2, 3
+         HAVING COUNT(*) > 1
+         ORDER BY 1",
+        &[],
+    )?;
+    Ok(rows
+        .iter()
+        .map(|r| DuplicateForms { name: r.get(0), version: r.get(1), source: r.get(2), ids: r.get(3) })
+        .collect())
+}
+
+/// Avertissement au démarrage de l'ingestion
+pub fn warn_duplicate_forms(conn: &mut Client) -> Result<()> {
+    let dups = find_duplicate_forms(conn)?;
+    if dups.is_empty() {
+        return Ok(());
+    }
+    println!("⚠️  {} formulaires en double (version/source NULL vs ''):", dups.len());
+    for d in &dups {
+        println!(
+            "  '{}' version='{}' source='{}' → ids {:?}",
+            d.name,
+            d.version.as_deref().unwrap_or(""),
+            d.source.as_deref().unwrap_or(""),
+            d.ids
+        );
+    }
+    println!("  → lancer `gdn_ingest merge-forms --apply` pour les fusionner");
+    Ok(())
+}
+
+/// Fusionne chaque groupe de doublons dans le plus petit id: questions,
+/// options, réponses et contributions sont repointées, puis le doublon supprimé.
+pub fn run_merge_forms(apply: bool) -> Result<()> {
+    let mut conn = crate::open_conn()?;
+    let dups = find_duplicate_forms(&mut conn)?;
+    if dups.is_empty() {
+        println!("[merge-forms] aucun doublon");
+        return Ok(());
+    }
+
+    for d in &dups {
+        let keep = d.ids[0];
+        for &dup in &d.ids[1..] {
+            println!("[merge-forms] '{}': form {} → {}", d.name, dup, keep);
+            if !apply {
+                continue;
+            }
+            let mut tx = conn.transaction()?;
+            merge_form_into(&mut tx, dup, keep)?;
+            tx.commit()?;
+        }
+        if apply {
+            conn.execute(
+                "UPDATE forms SET version = NULLIF(TRIM(version),''), source = NULLIF(TRIM(source),'') WHERE id = $1",
+                &[&keep],
+            )?;
+        }
+    }
+
+    if apply {
+        println!("[merge-forms] ✅ {} groupes fusionnés", dups.len());
+    } else {
+        println!("[dry-run] aucune modification — relancer avec --apply");
+    }
+    Ok(())
+}
+
+fn merge_form_into(tx: &mut postgres::Transaction, dup: i64, keep: i64) -> Result<()> {
+    let questions = tx.query("SELECT id, question_code FROM questions WHERE form_id = $1", &[&dup])?;
+    for q in &questions {
+        let dup_qid: i64 = q.get(0);
+        let code: String = q.get(1);
+        let keep_q = tx.query_opt(
+            "SELECT id FROM questions WHERE form_id = $1 AND question_code = $2",
+            &[&keep, &code],
+        )?;
+        let Some(keep_q) = keep_q else {
+            tx.execute("UPDATE questions SET form_id = $1 WHERE id = $2", &[&keep, &dup_qid])?;
+            continue;
+        };
+        let keep_qid: i64 = keep_q.get(0);
+
+        // options: même code → liaisons repointées, sinon option déplacée
+        let options = tx.query("SELECT id, code FROM options WHERE question_id = $1", &[&dup_qid])?;
+        for o in &options {
+            let dup_oid: i64 = o.get(0);
+            let ocode: String = o.get(1);
+            let keep_o = tx.query_opt(
+                "SELECT id FROM options WHERE question_id = $1 AND code = $2",
+                &[&keep_qid, &ocode],
+            )?;
+            match keep_o {
+                Some(keep_o) => {
+                    let keep_oid: i64 = keep_o.get(0);
+                    tx.execute(
+                        "INSERT INTO answer_options (answer_id, option_id)
+                         SELECT answer_id, $1 FROM answer_options WHERE option_id = $2
+                         ON CONFLICT (answer_id, option_id) DO NOTHING",
+                        &[&keep_oid, &dup_oid],
+                    )?;
+                    tx.execute("DELETE FROM answer_options WHERE option_id = $1", &[&dup_oid])?;
+                    tx.execute("DELETE FROM options WHERE id = $1", &[&dup_oid])?;
+                }
+                None => {
+                    tx.execute("UPDATE options SET question_id = $1 WHERE id = $2", &[&keep_qid, &dup_oid])?;
+                }
+            }
+        }
+
+        tx.execute("UPDATE answers SET question_id = $1 WHERE question_id = $2", &[&keep_qid, &dup_qid])?;
+        tx.execute("DELETE FROM question_stats WHERE question_id = $1", &[&dup_qid])?;
+        tx.execute("DELETE FROM questions WHERE id = $1", &[&dup_qid])?;
+    }
+
+    tx.execute("UPDATE contributions SET form_id = $1 WHERE form_id = $2", &[&keep, &dup])?;
+    tx.execute("DELETE FROM dashboard_cache WHERE form_id IN ($1, $2)", &[&(keep as i32), &(dup as i32)])?;
+    tx.execute("DELETE FROM forms WHERE id = $1", &[&dup])?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalize_writes_null_for_empty() {
+        assert_eq!(normalize_opt(None), None);
+        assert_eq!(normalize_opt(Some("")), None);
+        assert_eq!(normalize_opt(Some("   ")), None);
+        assert_eq!(normalize_opt(Some(" v1 ")), Some("v1".into()));
+    }
+
+    #[test]
+    fn null_and_empty_matrix() {
+        let values = [None, Some(""), Some("  ")];
+        for a in values {
+            for b in values {
+                assert!(same_key(a, b), "{a:?} vs {b:?}");
+            }
+            assert!(!same_key(a, Some("v1")), "{a:?} vs v1");
+            assert!(!same_key(Some("v1"), a), "v1 vs {a:?}");
+        }
+        assert!(same_key(Some("v1"), Some("v1")));
+        assert!(!same_key(Some("v1"), Some("v2")));
+    }
+}
@@ -0,0 +1,198 @@
+// ---------- erase: effacement RGPD des données d'un auteur ----------
+//
+// Demande d'effacement identifiée par `authors.email_hash` (hash salé de
+// l'email, cf. pii.rs). Dans une transaction:
+//   - auteurs: champs personnels remis à NULL (name, email_hash, zipcode,
+//     city, age_range, gender, plus department_code/region_code déduits du
+//     code postal et source_author_id, identifiant du compte sur la
+//     plateforme). La ligne reste: les contributions y sont rattachées;
+//   - contributions de ces auteurs: raw_json (copie de la ligne source, avec
+//     les colonnes auteur) remis à NULL, dans raw_imports aussi; contributions
+//     et réponses gardées pour que les statistiques agrégées restent justes;
+//   - --erase-answers: réponses (et leurs options) de ces contributions
+//     supprimées en plus (effacement complet; refcount de text_values remis
+//     d'aplomb pour les valeurs concernées).
+// Chaque effacement est consigné dans gdpr_erasures (date, SHA-256 du hash
+// demandé, lignes touchées), sans conserver le hash lui-même.
+// --dry-run: requête de sélection affichée et lignes comptées, rien d'écrit.
+//
+// L'effacement ne vaut que pour la base: un nouvel import du fichier source
+// recrée l'auteur. Retirer la personne de l'export avant de réingérer.
+
+use anyhow::Result;
+use postgres::GenericClient;
+use sha2::{Digest, Sha256};
+
+/// Sélection des auteurs concernés (affichée en --dry-run)
+const SELECT_AUTHORS: &str = "SELECT id FROM authors WHERE email_hash = $1";
+
+/// Lignes touchées par un effacement
+#[derive(Debug, Default, PartialEq)]
+pub struct Erased {
+    pub authors: u64,
+    pub contributions: u64,
+    pub answers: u64,
+}
+
+impl Erased {
+    pub fn rows(&self) -> u64 {
+        self.authors + self.contributions + self.answers
+    }
+}
+
+/// Empreinte consignée dans gdpr_erasures
+fn audit_digest(email_hash: &str) -> String {
+    hex::encode(Sha256::digest(email_hash.trim().as_bytes()))
+}
+
+pub fn erase(client: &mut impl GenericClient, email_hash: &str, erase_answers: bool, dry_run: bool) -> Result<Erased> {
+    let mut tx = client.transaction()?;
+    let audited: bool = tx.query_one("SELECT to_regclass('gdpr_erasures') IS NOT NULL", &[])?.get(0);
+    if !audited && !dry_run {
+        anyhow::bail!("table gdpr_erasures absente (alembic upgrade head, ou gdn_ingest db-init --if-not-exists)");
+    }
+    let authors: Vec<i64> = tx.query(SELECT_AUTHORS, &[&email_hash.trim()])?.iter().map(|r| r.get(0)).collect();
+    let contributions: Vec<i64> = tx
+        .query("SELECT id FROM contributions WHERE author_id = ANY($1)", &[&authors])?
+        .iter()
+        .map(|r| r.get(0))
+        .collect();
+    let mut erased = Erased { authors: authors.len() as u64, contributions: contributions.len() as u64, answers: 0 };
+
+    if dry_run {
+        if erase_answers {
+            let n: i64 = tx
+                .query_one("SELECT COUNT(*) FROM answers WHERE contribution_id = ANY($1)", &[&contributions])?
+                .get(0);
+            erased.answers = n as u64;
+        }
+        return Ok(erased);
+    }
+
+    tx.execute(
+        "UPDATE authors SET name = NULL, email_hash = NULL, zipcode = NULL, city = NULL, age_range = NULL,
+                gender = NULL, department_code = NULL, region_code = NULL, source_author_id = NULL
+         WHERE id = ANY($1)",
+        &[&authors],
+    )?;
+    tx.execute("UPDATE contributions SET raw_json = NULL WHERE id = ANY($1)", &[&contributions])?;
+    if crate::rawimports::has_table(&mut tx)? {
+        tx.execute("UPDATE raw_imports SET raw_json = NULL WHERE contribution_id = ANY($1)", &[&contributions])?;
+    }
+    if erase_answers {
+        tx.execute(
+            "DELETE FROM answer_options WHERE answer_id IN (SELECT id FROM answers WHERE contribution_id = ANY($1))",
+            &[&contributions],
+        )?;
+        let deleted = tx.query(
+            "DELETE FROM answers WHERE contribution_id = ANY($1) RETURNING text_value_id",
+            &[&contributions],
+        )?;
+        erased.answers = deleted.len() as u64;
+        let text_values: Vec<i64> = deleted.iter().filter_map(|r| r.get(0)).collect();
+        if !text_values.is_empty() {
+            tx.execute(
+                "UPDATE text_values tv SET refcount = (SELECT COUNT(*) FROM answers a WHERE a.text_value_id = tv.id)
+                 WHERE tv.id = ANY($1)",
+                &[&text_values],
+            )?;
+        }
+    }
+    tx.execute(
+        "INSERT INTO gdpr_erasures (email_hash_sha256, rows_affected) VALUES ($1, $2)",
+        &[&audit_digest(email_hash), &(erased.rows() as i64)],
+    )?;
+    tx.commit()?;
+    Ok(erased)
+}
+
+pub fn run_erase(email_hash: &str, erase_answers: bool, dry_run: bool) -> Result<()> {
+    if email_hash.trim().is_empty() {
+        anyhow::bail!("--email-hash vide");
+    }
+    let mut conn = crate::open_conn()?;
+    let erased = erase(&mut conn, email_hash, erase_answers, dry_run)?;
+    let answers = match (erase_answers, dry_run) {
+        (false, _) => String::new(),
+        (true, true) => format!(", {} réponses à supprimer", erased.answers),
+        (true, false) => format!(", {} réponses supprimées", erased.answers),
+    };
+    if dry_run {
+        println!("[dry-run] {SELECT_AUTHORS}  -- $1 = '{}'", email_hash.trim());
+        println!(
+            "[dry-run] {} auteurs, {} contributions (raw_json){answers} — relancer sans --dry-run pour effacer",
+            erased.authors, erased.contributions
+        );
+        return Ok(());
+    }
+    if erased.authors == 0 {
+        println!("[erase] aucun auteur avec ce hash (demande consignée dans gdpr_erasures)");
+        return Ok(());
+    }
+    println!(
+        "[erase] ✅ {} lignes: {} auteurs anonymisés, {} contributions sans raw_json{answers} (consigné dans gdpr_erasures)",
+        erased.rows(),
+        erased.authors,
+        erased.contributions
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{dbinit, pool};
+
+    #[test]
+    fn audit_keeps_only_a_digest() {
+        let digest = audit_digest(" abc ");
+        assert_eq!(digest, audit_digest("abc"));
+        assert_eq!(digest.len(), 64);
+        assert_ne!(digest, "abc");
+    }
+
+    #[test]
+    #[ignore = "nécessite TEST_DATABASE_URL"]
+    fn authors_are_scrubbed_and_answers_kept() {
+        let schema = "gdn_test_gdpr";
+        let mut conn = pool::test_conn(Some(schema));
+        dbinit::init(&mut conn, schema, false, false).unwrap();
+        conn.batch_execute(
+            "INSERT INTO forms (id, name) VALUES (1, 'f');
+             INSERT INTO questions (id, form_id, question_code, prompt, type) VALUES (1, 1, 'q1', 'Q', 'text');
+             INSERT INTO authors (id, name, email_hash, zipcode) VALUES (1, 'Marie', 'h1', '75001'), (2, 'Paul', 'h2', '69001');
+             INSERT INTO contributions (id, form_id, author_id, raw_json) VALUES (1, 1, 1, '{\"cp\": \"75001\"}'), (2, 1, 2, '{}');
+             INSERT INTO answers (contribution_id, question_id, \"text\") VALUES (1, 1, 'a'), (2, 1, 'b');
+             INSERT INTO raw_imports (form_id, batch_id, file_path, row_number, contribution_id, row_hash, raw_json)
+             VALUES (1, 'b', 'f.csv', 1, 1, 'x1', '{\"cp\": \"75001\"}');",
+        )
+        .unwrap();
+
+        let preview = erase(&mut conn, "h1", true, true).unwrap();
+        assert_eq!(preview, Erased { authors: 1, contributions: 1, answers: 1 });
+        let untouched: i64 = conn.query_one("SELECT COUNT(*) FROM authors WHERE email_hash = 'h1'", &[]).unwrap().get(0);
+        assert_eq!(untouched, 1);
+
+        assert_eq!(erase(&mut conn, "h1", false, false).unwrap().rows(), 2);
+        let row = conn
+            .query_one(
+                "SELECT a.name IS NULL AND a.email_hash IS NULL AND a.zipcode IS NULL, c.raw_json IS NULL,
+                        (SELECT COUNT(*) FROM answers)
+                 FROM authors a JOIN contributions c ON c.author_id = a.id WHERE a.id = 1",
+                &[],
+            )
+            .unwrap();
+        assert!(row.get::<_, bool>(0) && row.get::<_, bool>(1));
+        assert_eq!(row.get::<_, i64>(2), 2);
+        let audit: bool = conn.query_one("SELECT raw_json IS NULL FROM raw_imports", &[]).unwrap().get(0);
+        assert!(audit);
+        let other: Option<String> = conn.query_one("SELECT name FROM authors WHERE id = 2", &[]).unwrap().get(0);
+        assert_eq!(other.as_deref(), Some("Paul"));
+
+        assert_eq!(erase(&mut conn, "h2", true, false).unwrap(), Erased { authors: 1, contributions: 1, answers: 1 });
+        let log: Vec<i64> =
+            conn.query("SELECT rows_affected FROM gdpr_erasures ORDER BY id", &[]).unwrap().iter().map(|r| r.get(0)).collect();
+        assert_eq!(log, [2, 3]);
+        conn.batch_execute(&format!("DROP SCHEMA {schema} CASCADE")).unwrap();
+    }
+}
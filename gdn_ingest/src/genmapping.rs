@@ -0,0 +1,187 @@
+// ---------- Sous-commande gen-mapping: squelette de mapping depuis un CSV ----------
+//
+// Une question par colonne de l'en-tête, type deviné:
+//   - nom du genre `age`, `zip`, `code_postal` → number;
+//   - `date` dans le nom → date;
+//   - plus de MAX_OPTIONS valeurs distinctes sur les SAMPLE_ROWS premières
+//     lignes → free_text;
+//   - sinon single_choice, options statiques relevées sur l'échantillon
+//     (libellé = valeur brute, code = slug, comme les options dynamiques).
+// Les champs devinés portent un commentaire `# TODO` à relire; les colonnes
+// techniques (reference, trashed…) sont à retirer à la main.
+
+use anyhow::Result;
+use std::collections::HashSet;
+use std::io::Write;
+use std::path::Path;
+
+use crate::{input, options, values};
+
+/// Lignes lues pour deviner types et options
+const SAMPLE_ROWS: usize = 200;
+/// Au-delà: free_text
+const MAX_OPTIONS: usize = 10;
+
+/// Colonne et valeurs non vides distinctes de l'échantillon (MAX_OPTIONS + 1 au plus)
+struct Column {
+    header: String,
+    values: Vec<String>,
+}
+
+#[derive(Debug, PartialEq)]
+enum Guess {
+    Number,
+    Date,
+    FreeText,
+    SingleChoice,
+}
+
+impl Guess {
+    fn qtype(&self) -> &'static str {
+        match self {
+            Guess::Number => "number",
+            Guess::Date => "date",
+            Guess::FreeText => "free_text",
+            Guess::SingleChoice => "single_choice",
+        }
+    }
+}
+
+fn guess(column: &Column) -> Guess {
+    let name = values::fold(&column.header);
+    let words: Vec<&str> = name.split(|c: char| !c.is_ascii_alphanumeric()).filter(|w| !w.is_empty()).collect();
+    if words.iter().any(|w| matches!(*w, "age" | "zip" | "zipcode")) || words.windows(2).any(|w| w == ["code", "postal"]) {
+        Guess::Number
+    } else if name.contains("date") {
+        Guess::Date
+    } else if column.values.len() > MAX_OPTIONS {
+        Guess::FreeText
+    } else {
+        Guess::SingleChoice
+    }
+}
+
+/// Chaîne YAML entre guillemets (une chaîne JSON est un scalaire YAML valide)
+fn quote(s: &str) -> String {
+    serde_json::Value::from(s).to_string()
+}
+
+/// `base`, puis `base-2`, `base-3`… si déjà pris (cf. options::suffixed_code)
+fn unique(base: String, taken: &mut HashSet<String>) -> String {
+    let mut code = base.clone();
+    let mut n = 1;
+    while !taken.insert(code.clone()) {
+        n += 1;
+        code = options::suffixed_code(&base, n);
+    }
+    code
+}
+
+fn render(source: &str, form_name: &str, columns: &[Column], sampled: usize) -> String {
+    let mut out = String::new();
+    out.push_str(&format!("# Généré par `gdn_ingest gen-mapping` depuis {source} ({sampled} lignes échantillonnées)\n"));
+    out.push_str("# TODO: relire types et options, retirer les colonnes techniques (reference, trashed…)\n");
+    out.push_str(&format!("form:\n  name: {}  # TODO: nom du formulaire\n", quote(form_name)));
+    out.push_str("questions:\n");
+    let mut codes = HashSet::new();
+    for column in columns {
+        let g = guess(column);
+        let code = unique(options::normalize_code(&values::fold(&column.header)).replace('-', "_"), &mut codes);
+        let why = match g {
+            Guess::Number => "d'après le nom de colonne".to_string(),
+            Guess::Date => "`date` dans le nom de colonne".to_string(),
+            Guess::FreeText => format!("plus de {MAX_OPTIONS} valeurs distinctes"),
+            Guess::SingleChoice => format!("{} valeurs distinctes", column.values.len()),
+        };
+        out.push_str(&format!("  - code: {}\n", quote(&code)));
+        out.push_str(&format!("    prompt: {}  # TODO: libellé de la question\n", quote(&column.header)));
+        out.push_str(&format!("    type: {}  # TODO: deviné ({why})\n", g.qtype()));
+        out.push_str(&format!("    source_column: {}\n", quote(&column.header)));
+        if g == Guess::SingleChoice {
+            if column.values.is_empty() {
+                out.push_str("    # TODO: aucune valeur dans l'échantillon, options créées à l'ingestion\n");
+                continue;
+            }
+            out.push_str("    options:  # TODO: valeurs de l'échantillon, compléter si besoin\n");
+            let mut option_codes = HashSet::new();
+            for label in &column.values {
+                let code = unique(options::normalize_code(label), &mut option_codes);
+                out.push_str(&format!("      - {{ code: {}, label: {} }}\n", quote(&code), quote(label)));
+            }
+        }
+    }
+    out
+}
+
+pub fn run_gen_mapping(csv: &str, output: &Path) -> Result<()> {
+    if output.exists() {
+        anyhow::bail!("{output:?} existe déjà: le supprimer ou choisir un autre --output");
+    }
+    let opts = input::ReadOptions { delimiter: input::DelimiterChoice::Auto, ..Default::default() };
+    let rows = input::Rows::open(csv, &opts)?;
+    let mut columns: Vec<Column> =
+        rows.headers().iter().map(|h| Column { header: h.to_string(), values: Vec::new() }).collect();
+    let mut seen: Vec<HashSet<String>> = vec![HashSet::new(); columns.len()];
+    let mut sampled = 0;
+    for row in rows.take(SAMPLE_ROWS) {
+        let row = row?;
+        sampled += 1;
+        for (column, seen) in columns.iter_mut().zip(seen.iter_mut()) {
+            let Some(v) = row.cell(&column.header).map(str::trim).filter(|v| !v.is_empty()) else { continue };
+            if column.values.len() <= MAX_OPTIONS && seen.insert(v.to_string()) {
+                column.values.push(v.to_string());
+            }
+        }
+    }
+
+    let form_name = Path::new(csv).file_stem().map_or(csv.into(), |s| s.to_string_lossy()).to_string();
+    let yaml = render(csv, &form_name, &columns, sampled);
+    std::fs::File::create(output)?.write_all(yaml.as_bytes())?;
+    let free = columns.iter().filter(|c| guess(c) == Guess::FreeText).count();
+    println!(
+        "[gen-mapping] ✅ {} questions écrites dans {output:?} ({free} free_text, {sampled} lignes échantillonnées) — relire les `# TODO`",
+        columns.len()
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn column(header: &str, values: &[&str]) -> Column {
+        Column { header: header.into(), values: values.iter().map(|v| v.to_string()).collect() }
+    }
+
+    #[test]
+    fn types_guessed_from_names_and_values() {
+        assert_eq!(guess(&column("Âge", &[])), Guess::Number);
+        assert_eq!(guess(&column("Code postal", &[])), Guess::Number);
+        assert_eq!(guess(&column("zip", &[])), Guess::Number);
+        assert_eq!(guess(&column("page", &[])), Guess::SingleChoice);
+        assert_eq!(guess(&column("Date de dépôt", &[])), Guess::Date);
+        let many: Vec<String> = (0..=MAX_OPTIONS).map(|i| format!("v{i}")).collect();
+        let many: Vec<&str> = many.iter().map(String::as_str).collect();
+        assert_eq!(guess(&column("Q1", &many)), Guess::FreeText);
+        assert_eq!(guess(&column("Q1", &many[..MAX_OPTIONS])), Guess::SingleChoice);
+    }
+
+    #[test]
+    fn skeleton_is_a_valid_mapping() {
+        let columns = [
+            column("age", &["34"]),
+            column("Êtes-vous d'accord ?", &["Oui", "Non", "oui"]),
+            column("Q: \"libre\"", &[]),
+        ];
+        let yaml = render("export.csv", "export", &columns, 3);
+        assert!(yaml.contains("type: number  # TODO"), "{yaml}");
+        let mapping: crate::Mapping = serde_yaml::from_str(&yaml).unwrap();
+        assert_eq!(mapping.form.name, "export");
+        let q = &mapping.questions[1];
+        assert_eq!((q.code.as_str(), q.qtype.as_str()), ("etes_vous_d_accord", "single_choice"));
+        let codes: Vec<&str> = q.options.iter().map(|o| o.code.as_str()).collect();
+        assert_eq!(codes, ["oui", "non", "oui-2"]);
+        assert_eq!(mapping.questions[2].source_column.as_deref(), Some("Q: \"libre\""));
+        crate::validate_mapping(&mapping, false).unwrap();
+    }
+}
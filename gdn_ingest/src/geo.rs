@@ -0,0 +1,149 @@
+// ---------- Département et région déduits du code postal ----------
+//
+// Table intégrée (l'outil fonctionne hors ligne): départements par région,
+// codes INSEE des régions depuis 2016. Le département vient du code postal
+// normalisé (cf. values::normalize_zipcode):
+//   - métropole: deux premiers chiffres;
+//   - Corse: 200xx-201xx → 2A, 202xx-206xx → 2B (2A/2B acceptés tels quels);
+//   - outre-mer: trois premiers chiffres (971 à 974, 976).
+// Monaco, collectivités d'outre-mer, codes inconnus: département et région NULL.
+//
+// `--geo-lookup fichier.csv` complète ou corrige la table, colonnes
+// `zipcode_prefix,department_code,region_code` (région vide: celle de la
+// table intégrée). Le préfixe le plus long qui correspond l'emporte.
+
+use anyhow::{Context, Result};
+use std::path::Path;
+
+/// Région (code INSEE) → départements
+const REGIONS: [(&str, &[&str]); 18] = [
+    ("01", &["971"]),
+    ("02", &["972"]),
+    ("03", &["973"]),
+    ("04", &["974"]),
+    ("06", &["976"]),
+    ("11", &["75", "77", "78", "91", "92", "93", "94", "95"]),
+    ("24", &["18", "28", "36", "37", "41", "45"]),
+    ("27", &["21", "25", "39", "58", "70", "71", "89", "90"]),
+    ("28", &["14", "27", "50", "61", "76"]),
+    ("32", &["02", "59", "60", "62", "80"]),
+    ("44", &["08", "10", "51", "52", "54", "55", "57", "67", "68", "88"]),
+    ("52", &["44", "49", "53", "72", "85"]),
+    ("53", &["22", "29", "35", "56"]),
+    ("75", &["16", "17", "19", "23", "24", "33", "40", "47", "64", "79", "86", "87"]),
+    ("76", &["09", "11", "12", "30", "31", "32", "34", "46", "48", "65", "66", "81", "82"]),
+    ("84", &["01", "03", "07", "15", "26", "38", "42", "43", "63", "69", "73", "74"]),
+    ("93", &["04", "05", "06", "13", "83", "84"]),
+    ("94", &["2A", "2B"]),
+];
+
+fn region_of(department: &str) -> Option<(&'static str, &'static str)> {
+    REGIONS
+        .iter()
+        .find_map(|(region, depts)| depts.iter().find(|&&d| d == department).map(|&d| (d, *region)))
+}
+
+/// (département, région) selon la table intégrée
+fn builtin(zipcode: &str) -> Option<(&'static str, &'static str)> {
+    let department = match zipcode.get(..2)? {
+        "20" => match zipcode.get(2..3)? {
+            "0" | "1" => "2A",
+            _ => "2B",
+        },
+        "97" => zipcode.get(..3)?,
+        d => d,
+    };
+    region_of(department)
+}
+
+struct Override {
+    prefix: String,
+    department: String,
+    region: String,
+}
+
+#[derive(Default)]
+pub struct GeoLookup {
+    overrides: Vec<Override>,
+}
+
+impl GeoLookup {
+    /// Table intégrée complétée par le CSV `--geo-lookup`
+    pub fn load(path: &Path) -> Result<Self> {
+        let mut rdr = csv::Reader::from_path(path).with_context(|| format!("--geo-lookup {path:?}"))?;
+        let headers = rdr.headers()?.clone();
+        let col = |name: &str| {
+            headers
+                .iter()
+                .position(|h| h.trim() == name)
+                .with_context(|| format!("--geo-lookup {path:?}: colonne '{name}' absente"))
+        };
+        let (p, d, r) = (col("zipcode_prefix")?, col("department_code")?, col("region_code")?);
+        let mut overrides = Vec::new();
+        for (i, rec) in rdr.records().enumerate() {
+            let rec = rec?;
+            let field = |j: usize| rec.get(j).unwrap_or("").trim().to_string();
+            let (prefix, department, mut region) = (field(p), field(d), field(r));
+            if prefix.is_empty() || department.is_empty() {
+                anyhow::bail!("--geo-lookup {path:?} ligne {}: zipcode_prefix et department_code requis", i + 2);
+            }
+            if region.is_empty() {
+                region = region_of(&department)
+                    .map(|(_, r)| r.to_string())
+                    .with_context(|| format!("--geo-lookup {path:?} ligne {}: région inconnue pour '{department}'", i + 2))?;
+            }
+            overrides.push(Override { prefix, department, region });
+        }
+        Ok(GeoLookup { overrides })
+    }
+
+    pub fn overrides(&self) -> usize {
+        self.overrides.len()
+    }
+
+    /// (department_code, region_code) d'un code postal normalisé
+    pub fn resolve(&self, zipcode: &str) -> Option<(&str, &str)> {
+        self.overrides
+            .iter()
+            .filter(|o| zipcode.starts_with(o.prefix.as_str()))
+            .max_by_key(|o| o.prefix.len())
+            .map(|o| (o.department.as_str(), o.region.as_str()))
+            .or_else(|| builtin(zipcode))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builtin_table() {
+        let geo = GeoLookup::default();
+        assert_eq!(geo.resolve("75011"), Some(("75", "11")));
+        assert_eq!(geo.resolve("01000"), Some(("01", "84")));
+        assert_eq!(geo.resolve("20000"), Some(("2A", "94")));
+        assert_eq!(geo.resolve("20200"), Some(("2B", "94")));
+        assert_eq!(geo.resolve("2B033"), Some(("2B", "94")));
+        assert_eq!(geo.resolve("97400"), Some(("974", "04")));
+        assert_eq!(geo.resolve("97600"), Some(("976", "06")));
+        assert_eq!(geo.resolve("97500"), None);
+        assert_eq!(geo.resolve("98000"), None);
+        let departments: usize = REGIONS.iter().map(|(_, d)| d.len()).sum();
+        assert_eq!(departments, 101);
+    }
+
+    #[test]
+    fn csv_overrides_longest_prefix() {
+        let path = std::env::temp_dir().join(format!("gdn_geo_{}.csv", std::process::id()));
+        std::fs::write(&path, "zipcode_prefix,department_code,region_code\n97133,977,\n42,42,84\n42620,03,\n")
+            .unwrap();
+        let err = GeoLookup::load(&path).err().unwrap();
+        assert!(err.to_string().contains("région inconnue pour '977'"));
+        std::fs::write(&path, "zipcode_prefix,department_code,region_code\n97133,977,977\n42620,03,\n").unwrap();
+        let geo = GeoLookup::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(geo.resolve("97133"), Some(("977", "977")));
+        assert_eq!(geo.resolve("42620"), Some(("03", "84")));
+        assert_eq!(geo.resolve("42000"), Some(("42", "84")));
+    }
+}
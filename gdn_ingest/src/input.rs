@@ -0,0 +1,1125 @@
+// ---------- Formats d'entrée: CSV et JSON Lines ----------
+//
+// Le format est déduit de l'extension (`.jsonl`/`.ndjson`, éventuellement
+// suivie de `.gz`; `.xlsx`/`.ods` pour les classeurs; tout le reste est lu
+// comme CSV). Les lignes des deux formats
+// exposent la même interface `ColumnAccessor`: l'ingestion, `explain` et les
+// helpers (corbeille, auteurs, cellules vides) cherchent les colonnes par nom.
+//
+// En JSON Lines chaque ligne porte ses propres clés: les valeurs non textuelles
+// sont converties en texte (nombres, booléens, JSON brut pour tableaux et
+// objets, `null` → cellule vide). Les en-têtes du fichier sont ceux de la
+// première ligne, utilisés pour la vérification des colonnes du mapping.
+//
+// Classeurs (calamine): première feuille par défaut (`--sheet` sinon), première
+// ligne = en-tête. Cellules vides → "", nombres sans notation scientifique,
+// dates au format YYYY-MM-DD. Les `.xls` (format binaire) sont refusés.
+//
+// Flux: `InputSource` ouvre le fichier (brut, `.gz`, ou premier membre `.csv`
+// d'un `.zip`), le transcode en UTF-8, retire un BOM initial et garde ces
+// informations (`SourceInfo`) pour le résumé de l'ingestion. L'empreinte
+// SHA-256 (`Checksum`) est calculée au fil de la lecture, sur les octets
+// décompressés avant transcodage (un .gz recompressé garde la même); classeurs:
+// octets du fichier. Celle des HEAD_BYTES premiers octets (`head_hex`) est
+// tenue en même temps: --skip-ingested la compare avant lecture. Les en-têtes
+// CSV et classeur passent par `normalise_headers` (espaces superflus).
+//
+// CSV: le délimiteur est deviné sur un échantillon qui s'arrête à une fin
+// d'enregistrement (jamais au milieu d'un champ multi-ligne entre guillemets),
+// et l'en-tête est confronté au nombre de champs dominant des premières
+// lignes: un décalage (mauvais délimiteur, guillemet mal fermé) arrête la
+// lecture au lieu de produire des lignes décalées jusqu'à la fin du fichier.
+// Avec un caractère de commentaire (`--skip-comments`, `defaults.comment_char`),
+// les lignes qui commencent par lui (espaces admis avant) sont vidées avant
+// tout le reste, y compris l'échantillon du délimiteur.
+//
+// Chaque ligne lue porte son numéro de ligne dans le fichier (`line()`, 1-based,
+// en-tête compris, début de l'enregistrement pour un champ multi-ligne): en CSV
+// il est compté sur les sauts de ligne du flux (`LineIndex`), le lecteur CSV
+// ne comptant pas les lignes vides.
+
+use anyhow::{Context, Result};
+use calamine::{open_workbook_auto, Data, Reader};
+use csv::StringRecord;
+use flate2::read::GzDecoder;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use sha2::{Digest, Sha256};
+use std::fmt;
+use std::fs::File;
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::io::{BufRead, BufReader, Cursor, Lines, Read};
+use std::rc::Rc;
+use zip::read::ZipArchive;
+
+use crate::encoding;
+
+/// Séparateurs reconnus par `sniff_delimiter` (à égalité: dans cet ordre)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Delimiter {
+    Comma,
+    Semicolon,
+    Tab,
+    Pipe,
+    Colon,
+}
+
+impl Delimiter {
+    const ALL: [Delimiter; 5] = [Delimiter::Comma, Delimiter::Semicolon, Delimiter::Tab, Delimiter::Pipe, Delimiter::Colon];
+
+    pub fn as_char(self) -> char {
+        match self {
+            Delimiter::Comma => ',',
+            Delimiter::Semicolon => ';',
+            Delimiter::Tab => '\t',
+            Delimiter::Pipe => '|',
+            Delimiter::Colon => ':',
+        }
+    }
+}
+
+impl From<Delimiter> for u8 {
+    fn from(d: Delimiter) -> u8 {
+        d.as_char() as u8
+    }
+}
+
+impl fmt::Display for Delimiter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Delimiter::Tab => f.write_str("tabulation"),
+            d => write!(f, "'{}'", d.as_char()),
+        }
+    }
+}
+
+/// `--delimiter` / `ingest.delimiter`: caractère ASCII imposé, ou `auto`
+/// (deviné sur le début de chaque fichier); `\t` ou `tab` pour la tabulation
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(try_from = "String")]
+pub enum DelimiterChoice {
+    Auto,
+    Fixed(char),
+}
+
+impl std::str::FromStr for DelimiterChoice {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        match s {
+            "auto" => Ok(DelimiterChoice::Auto),
+            "\\t" | "tab" => Ok(DelimiterChoice::Fixed('\t')),
+            _ => {
+                let mut chars = s.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) if c.is_ascii() => Ok(DelimiterChoice::Fixed(c)),
+                    (Some(c), None) => Err(format!("'{c}' n'est pas un caractère ASCII")),
+                    _ => Err(format!("délimiteur '{s}': un caractère ASCII ou 'auto' attendu")),
+                }
+            }
+        }
+    }
+}
+
+impl TryFrom<String> for DelimiterChoice {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, String> {
+        s.parse()
+    }
+}
+
+impl fmt::Display for DelimiterChoice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DelimiterChoice::Auto => f.write_str("auto"),
+            DelimiterChoice::Fixed(c) => write!(f, "{c}"),
+        }
+    }
+}
+
+/// Options de lecture communes aux formats
+#[derive(Clone, Copy)]
+pub struct ReadOptions<'a> {
+    pub delimiter: DelimiterChoice,
+    /// Classeurs: feuille à lire (défaut: la première)
+    pub sheet: Option<&'a str>,
+    /// Encodage forcé (défaut: détection automatique)
+    pub encoding: Option<&'static encoding_rs::Encoding>,
+    /// CSV: lignes de commentaire à ignorer (ex: `#`)
+    pub comment: Option<u8>,
+}
+
+impl Default for ReadOptions<'_> {
+    fn default() -> Self {
+        ReadOptions { delimiter: DelimiterChoice::Fixed(','), sheet: None, encoding: None, comment: None }
+    }
+}
+
+/// Taille minimale de l'échantillon lu pour deviner le délimiteur
+const SNIFF_SAMPLE: usize = 8192;
+/// Début du contenu dont l'empreinte (`Checksum::head_hex`) sert de pré-tri
+pub const HEAD_BYTES: usize = 64 * 1024;
+/// Échantillon prolongé au plus jusque-là (champ multi-ligne démesuré)
+const SNIFF_MAX: usize = 1 << 20;
+/// Premières lignes confrontées au nombre de colonnes de l'en-tête
+const SHAPE_SAMPLE: usize = 100;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum InputFormat {
+    Csv,
+    JsonLines,
+    Workbook,
+}
+
+impl InputFormat {
+    pub fn detect(path: &str) -> Self {
+        let lower = path.to_lowercase();
+        let stem = lower.strip_suffix(".gz").unwrap_or(&lower);
+        if stem.ends_with(".jsonl") || stem.ends_with(".ndjson") {
+            InputFormat::JsonLines
+        } else if [".xlsx", ".ods", ".xls"].iter().any(|ext| stem.ends_with(ext)) {
+            InputFormat::Workbook
+        } else {
+            InputFormat::Csv
+        }
+    }
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Compression {
+    None,
+    Gzip,
+    Zip,
+}
+
+/// Provenance d'un fichier lu, reprise dans le résumé de l'ingestion
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct SourceInfo {
+    pub path: String,
+    pub compression: Compression,
+    /// Encodage d'origine (`None` pour les classeurs)
+    pub encoding: Option<String>,
+    /// Membre effectivement lu (archive zip) ou feuille (classeur)
+    pub member: Option<String>,
+    /// Séparateur effectivement utilisé (CSV)
+    pub delimiter: Option<char>,
+}
+
+/// Empreintes SHA-256 du contenu lu (complète une fois le flux épuisé) et
+/// de ses HEAD_BYTES premiers octets
+#[derive(Clone, Default)]
+pub struct Checksum(Rc<RefCell<Digests>>);
+
+#[derive(Default)]
+struct Digests {
+    full: Sha256,
+    head: Sha256,
+    len: usize,
+}
+
+impl Checksum {
+    fn update(&self, bytes: &[u8]) {
+        let mut d = self.0.borrow_mut();
+        d.full.update(bytes);
+        let take = HEAD_BYTES.saturating_sub(d.len).min(bytes.len());
+        d.head.update(&bytes[..take]);
+        d.len += bytes.len();
+    }
+
+    pub fn hex(&self) -> String {
+        hex::encode(self.0.borrow().full.clone().finalize())
+    }
+
+    pub fn head_hex(&self) -> String {
+        hex::encode(self.0.borrow().head.clone().finalize())
+    }
+}
+
+struct HashingReader<R: Read> {
+    inner: R,
+    sum: Checksum,
+}
+
+impl<R: Read> Read for HashingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.sum.update(&buf[..n]);
+        Ok(n)
+    }
+}
+
+/// Octets du fichier, décompressés (premier membre CSV pour un zip)
+fn raw_reader(path: &str) -> Result<(Box<dyn Read>, Compression, Option<String>)> {
+    Ok(if path.ends_with(".gz") {
+        (Box::new(BufReader::new(GzDecoder::new(File::open(path)?))), Compression::Gzip, None)
+    } else if path.ends_with(".zip") {
+        let (buf, name) = first_csv_member(path)?;
+        (Box::new(Cursor::new(buf)), Compression::Zip, Some(name))
+    } else {
+        (Box::new(BufReader::new(File::open(path)?)), Compression::None, None)
+    })
+}
+
+/// Empreintes d'un fichier sans l'ingérer (mêmes octets que pendant la
+/// lecture), sur ses `limit` premiers octets au plus
+fn digest(path: &str, limit: u64) -> Result<Checksum> {
+    let reader: Box<dyn Read> = match InputFormat::detect(path) {
+        InputFormat::Workbook => Box::new(BufReader::new(File::open(path)?)),
+        _ => raw_reader(path)?.0,
+    };
+    let sum = Checksum::default();
+    let mut hashing = HashingReader { inner: reader.take(limit), sum: sum.clone() };
+    std::io::copy(&mut hashing, &mut std::io::sink()).with_context(|| format!("lecture {path}"))?;
+    Ok(sum)
+}
+
+/// Empreinte du contenu entier (une passe complète)
+pub fn checksum(path: &str) -> Result<String> {
+    Ok(digest(path, u64::MAX)?.hex())
+}
+
+/// Empreinte des HEAD_BYTES premiers octets seulement
+pub fn head_checksum(path: &str) -> Result<String> {
+    Ok(digest(path, HEAD_BYTES as u64)?.head_hex())
+}
+
+/// Flux d'entrée décompressé et transcodé en UTF-8
+pub struct InputSource {
+    pub info: SourceInfo,
+    pub checksum: Checksum,
+    reader: Box<dyn Read>,
+}
+
+impl InputSource {
+    pub fn open(path: &str, forced: Option<&'static encoding_rs::Encoding>) -> Result<Self> {
+        let lower = path.to_lowercase();
+        if lower.ends_with(".xls") {
+            anyhow::bail!("{path}: format .xls non supporté, convertir d'abord en .xlsx ou .csv");
+        } else if lower.ends_with(".xlsx") || lower.ends_with(".ods") {
+            // classeur: pas lisible en flux, voir Rows::open
+            anyhow::bail!("{path}: classeur, à ouvrir feuille par feuille");
+        }
+        let (raw, compression, member) = raw_reader(path)?;
+        let checksum = Checksum::default();
+        let raw = Box::new(HashingReader { inner: raw, sum: checksum.clone() });
+        let (reader, detected) = encoding::to_utf8(raw, forced)?;
+        let reader: Box<dyn Read> = Box::new(BomStripReader::new(reader));
+        if forced.is_none() && detected != encoding_rs::UTF_8 {
+            eprintln!("[encodage] {path}: {} détecté → transcodage UTF-8", detected.name());
+        }
+        let info = SourceInfo {
+            path: path.to_string(),
+            compression,
+            encoding: Some(detected.name().to_string()),
+            member,
+            delimiter: None,
+        };
+        Ok(InputSource { info, checksum, reader })
+    }
+}
+
+impl Read for InputSource {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        self.reader.read(buf)
+    }
+}
+
+/// Retire le BOM UTF-8 (`EF BB BF`) en tête de flux, s'il est présent
+pub struct BomStripReader<R: Read> {
+    inner: R,
+    /// octets lus pour vérifier le BOM, à rendre s'il n'y en avait pas
+    head: Vec<u8>,
+    pos: usize,
+    checked: bool,
+}
+
+impl<R: Read> BomStripReader<R> {
+    pub fn new(inner: R) -> Self {
+        BomStripReader { inner, head: Vec::new(), pos: 0, checked: false }
+    }
+}
+
+impl<R: Read> Read for BomStripReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        if !self.checked {
+            self.checked = true;
+            self.inner.by_ref().take(3).read_to_end(&mut self.head)?;
+            if self.head == b"\xEF\xBB\xBF" {
+                self.head.clear();
+            }
+        }
+        if self.pos < self.head.len() {
+            let n = buf.len().min(self.head.len() - self.pos);
+            buf[..n].copy_from_slice(&self.head[self.pos..self.pos + n]);
+            self.pos += n;
+            return Ok(n);
+        }
+        self.inner.read(buf)
+    }
+}
+
+/// Vide les lignes dont le premier octet non blanc est `comment` (seul le saut
+/// de ligne reste: le lecteur CSV les ignore et les numéros de ligne ne
+/// bougent pas). Une ligne à l'intérieur d'un champ multi-ligne entre
+/// guillemets n'est jamais touchée.
+pub struct CommentSkipReader<R: BufRead> {
+    inner: R,
+    comment: u8,
+    line: Vec<u8>,
+    pos: usize,
+    in_quotes: bool,
+}
+
+impl<R: BufRead> CommentSkipReader<R> {
+    pub fn new(inner: R, comment: u8) -> Self {
+        CommentSkipReader { inner, comment, line: Vec::new(), pos: 0, in_quotes: false }
+    }
+}
+
+impl<R: BufRead> BufRead for CommentSkipReader<R> {
+    fn fill_buf(&mut self) -> std::io::Result<&[u8]> {
+        while self.pos == self.line.len() {
+            self.line.clear();
+            self.pos = 0;
+            if self.inner.read_until(b'\n', &mut self.line)? == 0 {
+                break;
+            }
+            let first = self.line.iter().find(|b| !matches!(b, b' ' | b'\t'));
+            if !self.in_quotes && first == Some(&self.comment) {
+                let eol = self.line.iter().rposition(|b| !matches!(b, b'\r' | b'\n')).map_or(0, |i| i + 1);
+                self.line.drain(..eol);
+                continue;
+            }
+            self.in_quotes ^= self.line.iter().filter(|&&b| b == b'"').count() % 2 == 1;
+        }
+        Ok(&self.line[self.pos..])
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.pos = (self.pos + amt).min(self.line.len());
+    }
+}
+
+impl<R: BufRead> Read for CommentSkipReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let avail = self.fill_buf()?;
+        let n = buf.len().min(avail.len());
+        buf[..n].copy_from_slice(&avail[..n]);
+        self.consume(n);
+        Ok(n)
+    }
+}
+
+/// Lignes non vides lues par le lecteur CSV et pas encore dépassées:
+/// (position de leur saut de ligne, numéro de ligne).
+///
+/// La position d'un enregistrement donnée par le lecteur CSV est celle où il
+/// a commencé à lire, lignes vides (et `\n` d'un CRLF) comprises: l'enregistrement
+/// commence sur la première ligne non vide qui se termine après elle.
+struct LineIndex {
+    lines: VecDeque<(u64, u64)>,
+    /// ligne en cours de lecture
+    line: u64,
+    /// rien d'autre que `\r` sur la ligne en cours jusqu'ici
+    blank: bool,
+    read: u64,
+}
+
+impl Default for LineIndex {
+    fn default() -> Self {
+        LineIndex { lines: VecDeque::new(), line: 1, blank: true, read: 0 }
+    }
+}
+
+impl LineIndex {
+    fn note(&mut self, bytes: &[u8]) {
+        for (i, &b) in bytes.iter().enumerate() {
+            match b {
+                b'\n' => {
+                    if !self.blank {
+                        self.lines.push_back((self.read + i as u64, self.line));
+                    }
+                    self.line += 1;
+                    self.blank = true;
+                }
+                b'\r' => {}
+                _ => self.blank = false,
+            }
+        }
+        self.read += bytes.len() as u64;
+        // fin du flux sans saut de ligne final
+        if bytes.is_empty() && !self.blank {
+            self.lines.push_back((u64::MAX, self.line));
+            self.blank = true;
+        }
+    }
+
+    /// Ligne (1-based) de l'enregistrement lu à partir de `byte`;
+    /// positions croissantes d'un appel à l'autre
+    fn line_at(&mut self, byte: u64) -> u64 {
+        while self.lines.front().is_some_and(|&(end, _)| end <= byte) {
+            self.lines.pop_front();
+        }
+        self.lines.front().map_or(self.line, |&(_, line)| line)
+    }
+}
+
+/// Note les lignes du flux dans le `LineIndex` partagé
+struct CountLines<R> {
+    inner: R,
+    index: Rc<RefCell<LineIndex>>,
+}
+
+impl<R: Read> Read for CountLines<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.index.borrow_mut().note(&buf[..n]);
+        Ok(n)
+    }
+}
+
+/// En-têtes nettoyés: espaces de début/fin retirés, suites d'espaces
+/// intérieures réduites à une espace (BOM résiduel compris)
+pub fn normalise_headers(headers: &mut StringRecord) {
+    *headers = headers
+        .iter()
+        .map(|h| {
+            h.trim_start_matches('\u{FEFF}')
+                .split_whitespace()
+                .collect::<Vec<_>>()
+                .join(" ")
+        })
+        .collect();
+}
+
+/// Premier membre `.csv` d'une archive zip (contenu, nom)
+fn first_csv_member(path: &str) -> Result<(Vec<u8>, String)> {
+    let mut zip = ZipArchive::new(File::open(path)?)?;
+    for i in 0..zip.len() {
+        let mut zf = zip.by_index(i)?;
+        if zf.name().to_lowercase().ends_with(".csv") {
+            let name = zf.name().to_string();
+            let mut buf = Vec::new();
+            zf.read_to_end(&mut buf)?;
+            return Ok((buf, name));
+        }
+    }
+    anyhow::bail!("zip sans CSV");
+}
+
+/// (lecteur, délimiteur retenu, index des lignes)
+type CsvReader = (csv::Reader<Box<dyn Read>>, char, Rc<RefCell<LineIndex>>);
+
+/// Lecteur CSV: commentaires retirés, délimiteur imposé ou deviné (`auto`)
+fn open_csv(source: InputSource, opts: &ReadOptions) -> Result<CsvReader> {
+    let mut reader: Box<dyn Read> = match opts.comment {
+        Some(c) => Box::new(CommentSkipReader::new(BufReader::new(source.reader), c)),
+        None => source.reader,
+    };
+    let (reader, delim): (Box<dyn Read>, char) = match opts.delimiter {
+        DelimiterChoice::Fixed(c) => (reader, c),
+        DelimiterChoice::Auto => {
+            let (primed, sniffed) = sniff_delimiter(&mut reader)?;
+            eprintln!("[délimiteur] {}: {sniffed} (auto)", source.info.path);
+            (Box::new(Cursor::new(primed).chain(reader)), sniffed.as_char())
+        }
+    };
+    let index = Rc::new(RefCell::new(LineIndex::default()));
+    let reader: Box<dyn Read> = Box::new(CountLines { inner: reader, index: Rc::clone(&index) });
+    let rdr = csv::ReaderBuilder::new()
+        .delimiter(delim as u8)
+        .has_headers(true)
+        .flexible(true)
+        .from_reader(reader);
+    Ok((rdr, delim, index))
+}
+
+/// Accès aux cellules d'une ligne par nom de colonne
+pub trait ColumnAccessor {
+    /// Cellule brute, `None` si la colonne est absente de la ligne
+    fn cell(&self, col: &str) -> Option<&str>;
+
+    /// (colonne, valeur): ordre du fichier en CSV, ordre des clés en JSON Lines
+    fn columns(&self) -> Box<dyn Iterator<Item = (&str, &str)> + '_>;
+
+    /// Ligne du fichier (1-based, en-tête compris) où commence l'enregistrement
+    fn line(&self) -> Option<u64>;
+
+    /// Valeur (trim, non vide)
+    fn value(&self, col: &str) -> Option<&str> {
+        self.cell(col).map(str::trim).filter(|v| !v.is_empty())
+    }
+}
+
+pub struct CsvRow {
+    headers: Rc<StringRecord>,
+    rec: StringRecord,
+}
+
+impl CsvRow {
+    pub fn new(headers: Rc<StringRecord>, rec: StringRecord) -> Self {
+        CsvRow { headers, rec }
+    }
+
+    pub fn headers(&self) -> &StringRecord {
+        &self.headers
+    }
+}
+
+impl ColumnAccessor for CsvRow {
+    fn cell(&self, col: &str) -> Option<&str> {
+        let ix = self.headers.iter().position(|h| h == col)?;
+        // ligne plus courte que l'en-tête (flexible): cellule vide
+        Some(self.rec.get(ix).unwrap_or(""))
+    }
+
+    fn columns(&self) -> Box<dyn Iterator<Item = (&str, &str)> + '_> {
+        Box::new(self.headers.iter().zip(self.rec.iter()))
+    }
+
+    fn line(&self) -> Option<u64> {
+        self.rec.position().map(|p| p.line())
+    }
+}
+
+pub struct JsonRow {
+    cells: Vec<(String, String)>,
+    line: Option<u64>,
+}
+
+impl JsonRow {
+    pub fn parse(line: &str) -> Result<Self> {
+        let obj: Map<String, Value> = serde_json::from_str(line)?;
+        let cells = obj
+            .into_iter()
+            .map(|(k, v)| {
+                let text = match v {
+                    Value::String(s) => s,
+                    Value::Null => String::new(),
+                    other => other.to_string(),
+                };
+                (k, text)
+            })
+            .collect();
+        Ok(JsonRow { cells, line: None })
+    }
+}
+
+impl ColumnAccessor for JsonRow {
+    fn cell(&self, col: &str) -> Option<&str> {
+        self.cells.iter().find(|(k, _)| k == col).map(|(_, v)| v.as_str())
+    }
+
+    fn columns(&self) -> Box<dyn Iterator<Item = (&str, &str)> + '_> {
+        Box::new(self.cells.iter().map(|(k, v)| (k.as_str(), v.as_str())))
+    }
+
+    fn line(&self) -> Option<u64> {
+        self.line
+    }
+}
+
+/// Lignes d'un fichier d'entrée, quel que soit son format
+pub struct Rows {
+    path: String,
+    info: SourceInfo,
+    checksum: Checksum,
+    headers: Rc<StringRecord>,
+    source: Source,
+}
+
+enum Source {
+    Csv {
+        head: std::vec::IntoIter<StringRecord>,
+        rest: csv::StringRecordsIntoIter<Box<dyn Read>>,
+        lines: Rc<RefCell<LineIndex>>,
+    },
+    Sheet(std::vec::IntoIter<StringRecord>),
+    JsonLines {
+        lines: Lines<BufReader<Box<dyn Read>>>,
+        first: Option<JsonRow>,
+        line_no: usize,
+    },
+}
+
+impl Rows {
+    pub fn open(path: &str, opts: &ReadOptions) -> Result<Self> {
+        match InputFormat::detect(path) {
+            InputFormat::Csv => {
+                let source = InputSource::open(path, opts.encoding)?;
+                let mut info = source.info.clone();
+                let checksum = source.checksum.clone();
+                let (mut rdr, delimiter, lines) = open_csv(source, opts)?;
+                info.delimiter = Some(delimiter);
+                let mut headers = rdr.headers()?.clone();
+                normalise_headers(&mut headers);
+                let headers = Rc::new(headers);
+                let mut rest = rdr.into_records();
+                let head = rest.by_ref().take(SHAPE_SAMPLE).collect::<csv::Result<Vec<_>>>()?;
+                check_shape(path, headers.len(), &head)?;
+                let source = Source::Csv { head: head.into_iter(), rest, lines };
+                Ok(Rows { path: path.to_string(), info, checksum, headers, source })
+            }
+            InputFormat::Workbook => {
+                let (sheet, records) = read_sheet(path, opts.sheet)?;
+                let mut records = records.into_iter();
+                let mut headers = records.next().unwrap_or_default();
+                normalise_headers(&mut headers);
+                let headers = Rc::new(headers);
+                let info = SourceInfo {
+                    path: path.to_string(),
+                    compression: Compression::None,
+                    encoding: None,
+                    member: Some(sheet),
+                    delimiter: None,
+                };
+                let checksum = Checksum::default();
+                checksum.update(&std::fs::read(path)?);
+                Ok(Rows { path: path.to_string(), info, checksum, headers, source: Source::Sheet(records) })
+            }
+            // pas de sniff_delimiter: une ligne = un objet JSON
+            InputFormat::JsonLines => {
+                let input = InputSource::open(path, opts.encoding)?;
+                let info = input.info.clone();
+                let checksum = input.checksum.clone();
+                let mut source = Source::JsonLines {
+                    lines: BufReader::new(input.reader).lines(),
+                    first: None,
+                    line_no: 0,
+                };
+                let first = next_json_row(path, &mut source)?;
+                let headers = first
+                    .as_ref()
+                    .map(|r| r.cells.iter().map(|(k, _)| k.as_str()).collect())
+                    .unwrap_or_default();
+                if let Source::JsonLines { first: slot, .. } = &mut source {
+                    *slot = first;
+                }
+                Ok(Rows { path: path.to_string(), info, checksum, headers: Rc::new(headers), source })
+            }
+        }
+    }
+
+    /// En-tête CSV, ou clés de la première ligne en JSON Lines
+    pub fn headers(&self) -> &StringRecord {
+        &self.headers
+    }
+
+    pub fn info(&self) -> &SourceInfo {
+        &self.info
+    }
+
+    /// Empreinte du contenu, un handle à consulter une fois les lignes épuisées
+    pub fn checksum(&self) -> Checksum {
+        self.checksum.clone()
+    }
+}
+
+/// Devine le délimiteur en ne comptant que les occurrences hors guillemets.
+/// Un candidat présent le même nombre de fois sur chaque ligne de l'échantillon
+/// l'emporte sur un candidat plus fréquent mais irrégulier (`:` des heures).
+/// Moins de 2 occurrences: `,` par défaut, avec avertissement. Renvoie aussi
+/// les octets lus, à rechaîner devant le reste du flux.
+pub fn sniff_delimiter<R: Read>(mut r: R) -> std::io::Result<(Vec<u8>, Delimiter)> {
+    let mut buf = Vec::new();
+    let mut chunk = vec![0u8; SNIFF_SAMPLE];
+    // prolonger la lecture jusqu'à une fin d'enregistrement
+    let end = loop {
+        let n = r.read(&mut chunk)?;
+        buf.extend_from_slice(&chunk[..n]);
+        let boundary = last_record_end(&buf);
+        match boundary {
+            _ if n == 0 => break boundary.unwrap_or(buf.len()),
+            Some(b) if b >= SNIFF_SAMPLE => break b,
+            _ if buf.len() >= SNIFF_MAX => break buf.len(),
+            _ => {}
+        }
+    };
+
+    // par candidat: total, occurrences sur la ligne en cours, régularité
+    let mut stats = Delimiter::ALL.map(|d| (d, 0usize, 0usize, None::<usize>, true));
+    let mut in_quotes = false;
+    let mut line_empty = true;
+    for &b in &buf[..end] {
+        if b == b'"' {
+            in_quotes = !in_quotes;
+        } else if !in_quotes && b == b'\n' {
+            if !line_empty {
+                for (_, _, line, first, regular) in stats.iter_mut() {
+                    *regular &= first.is_none_or(|f| f == *line);
+                    first.get_or_insert(*line);
+                    *line = 0;
+                }
+            }
+            line_empty = true;
+            continue;
+        } else if !in_quotes {
+            if let Some(s) = stats.iter_mut().find(|s| u8::from(s.0) == b) {
+                s.1 += 1;
+                s.2 += 1;
+            }
+        }
+        line_empty &= b == b'\r';
+    }
+    // régulier d'abord, puis le plus fréquent; à égalité, l'ordre de Delimiter::ALL
+    let (best, total, ..) = stats
+        .iter()
+        .fold(stats[0], |best, &s| if (s.4 && s.1 > 0, s.1) > (best.4 && best.1 > 0, best.1) { s } else { best });
+    if total < 2 {
+        eprintln!("[délimiteur] ⚠️  aucun séparateur répété dans l'échantillon → ','");
+        return Ok((buf, Delimiter::Comma));
+    }
+    Ok((buf, best))
+}
+
+/// Position juste après le dernier saut de ligne hors guillemets
+fn last_record_end(buf: &[u8]) -> Option<usize> {
+    let mut in_quotes = false;
+    let mut end = None;
+    for (i, &b) in buf.iter().enumerate() {
+        match b {
+            b'"' => in_quotes = !in_quotes,
+            b'\n' if !in_quotes => end = Some(i + 1),
+            _ => {}
+        }
+    }
+    end
+}
+
+/// L'en-tête doit avoir le nombre de champs le plus fréquent des premières lignes
+fn check_shape(path: &str, n_headers: usize, records: &[StringRecord]) -> Result<()> {
+    let mut freq = std::collections::BTreeMap::<usize, usize>::new();
+    for rec in records {
+        *freq.entry(rec.len()).or_default() += 1;
+    }
+    let Some((&modal, &count)) = freq.iter().max_by_key(|(_, &c)| c) else {
+        return Ok(());
+    };
+    if modal != n_headers && freq.get(&n_headers).copied().unwrap_or(0) < count {
+        anyhow::bail!(
+            "{path}: l'en-tête a {n_headers} colonnes mais {count} des {} premières lignes en ont {modal} \
+             (délimiteur mal deviné, ou champ entre guillemets mal fermé ?)",
+            records.len()
+        );
+    }
+    Ok(())
+}
+
+/// Nom de la feuille lue et toutes ses lignes, converties en texte
+fn read_sheet(path: &str, sheet: Option<&str>) -> Result<(String, Vec<StringRecord>)> {
+    if path.to_lowercase().ends_with(".xls") {
+        anyhow::bail!("{path}: format .xls non supporté, convertir d'abord en .xlsx ou .csv");
+    }
+    let mut wb = open_workbook_auto(path).with_context(|| format!("ouverture classeur {path}"))?;
+    let names = wb.sheet_names();
+    let name = match sheet {
+        Some(s) if names.iter().any(|n| n == s) => s.to_string(),
+        Some(s) => anyhow::bail!("{path}: feuille '{s}' introuvable (feuilles: {})", names.join(", ")),
+        None => names.first().cloned().ok_or_else(|| anyhow::anyhow!("{path}: classeur sans feuille"))?,
+    };
+    let range = wb.worksheet_range(&name).with_context(|| format!("{path}: lecture feuille '{name}'"))?;
+    // numéro de ligne de la feuille, comme pour un CSV (en-tête = 1)
+    let records = range.rows().enumerate().map(|(i, cells)| {
+        let mut rec: StringRecord = cells.iter().map(sheet_cell).collect();
+        let mut pos = csv::Position::new();
+        pos.set_line(i as u64 + 1);
+        rec.set_position(Some(pos));
+        rec
+    });
+    Ok((name, records.collect()))
+}
+
+fn sheet_cell(cell: &Data) -> String {
+    match cell {
+        Data::Empty | Data::Error(_) => String::new(),
+        Data::String(s) => s.clone(),
+        Data::Int(i) => i.to_string(),
+        // Display de f64: jamais de notation scientifique; 42.0 → "42"
+        Data::Float(f) if f.fract() == 0.0 && f.abs() < 1e15 => format!("{}", *f as i64),
+        Data::Float(f) => f.to_string(),
+        Data::Bool(b) => b.to_string(),
+        Data::DateTime(dt) => match dt.as_datetime() {
+            Some(d) if dt.is_datetime() => d.format("%Y-%m-%d").to_string(),
+            _ => dt.as_f64().to_string(),
+        },
+        Data::DateTimeIso(s) => s.get(..10).filter(|d| d.len() == 10).unwrap_or(s).to_string(),
+        Data::DurationIso(s) => s.clone(),
+    }
+}
+
+fn next_json_row(path: &str, source: &mut Source) -> Result<Option<JsonRow>> {
+    let Source::JsonLines { lines, first, line_no } = source else {
+        return Ok(None);
+    };
+    if let Some(row) = first.take() {
+        return Ok(Some(row));
+    }
+    for line in lines.by_ref() {
+        *line_no += 1;
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let mut row = JsonRow::parse(&line).with_context(|| format!("{path}: ligne {line_no}: objet JSON invalide"))?;
+        row.line = Some(*line_no as u64);
+        return Ok(Some(row));
+    }
+    Ok(None)
+}
+
+impl Iterator for Rows {
+    type Item = Result<Box<dyn ColumnAccessor>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match &mut self.source {
+            Source::Csv { head, rest, lines } => {
+                let rec = head.next().map(Ok).or_else(|| rest.next())?;
+                Some(rec.map_err(Into::into).map(|mut rec| {
+                    if let Some(mut pos) = rec.position().cloned() {
+                        pos.set_line(lines.borrow_mut().line_at(pos.byte()));
+                        rec.set_position(Some(pos));
+                    }
+                    Box::new(CsvRow::new(Rc::clone(&self.headers), rec)) as Box<dyn ColumnAccessor>
+                }))
+            }
+            Source::Sheet(records) => {
+                let rec = records.next()?;
+                Some(Ok(Box::new(CsvRow::new(Rc::clone(&self.headers), rec)) as Box<dyn ColumnAccessor>))
+            }
+            Source::JsonLines { .. } => next_json_row(&self.path, &mut self.source)
+                .transpose()
+                .map(|row| row.map(|r| Box::new(r) as Box<dyn ColumnAccessor>)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Fichier temporaire propre au test
+    fn temp_file(name: &str, bytes: &[u8]) -> String {
+        let dir = std::env::temp_dir().join(format!("gdn_input_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(name);
+        std::fs::write(&path, bytes).unwrap();
+        path.to_string_lossy().into_owned()
+    }
+
+    fn read_source(path: &str) -> (String, SourceInfo) {
+        let mut src = InputSource::open(path, None).unwrap();
+        let mut s = String::new();
+        src.read_to_string(&mut s).unwrap();
+        (s, src.info)
+    }
+
+    const CSV: &str = "reference,avis\nr1,oui\n";
+
+    #[test]
+    fn plain_file_is_read_as_is() {
+        let (s, info) = read_source(&temp_file("plain.csv", CSV.as_bytes()));
+        assert_eq!(s, CSV);
+        assert_eq!(info.compression, Compression::None);
+        assert_eq!(info.encoding.as_deref(), Some("UTF-8"));
+        assert_eq!(info.member, None);
+    }
+
+    #[test]
+    fn bom_is_stripped_and_headers_cleaned() {
+        let data = b"\xEF\xBB\xBFReference ; Avis   du\tcitoyen;note\nr1;oui;3\n";
+        let rows = Rows::open(&temp_file("bom.csv", data), &ReadOptions { delimiter: DelimiterChoice::Fixed(';'), ..Default::default() }).unwrap();
+        assert_eq!(rows.headers(), &StringRecord::from(vec!["Reference", "Avis du citoyen", "note"]));
+        let row = rows.into_iter().next().unwrap().unwrap();
+        assert_eq!(row.cell("Reference"), Some("r1"));
+
+        // sans BOM: les trois premiers octets sont rendus
+        let mut s = String::new();
+        BomStripReader::new(&b"ab"[..]).read_to_string(&mut s).unwrap();
+        assert_eq!(s, "ab");
+    }
+
+    #[test]
+    fn comment_lines_are_skipped() {
+        let data = "# export du 01/02\n  # généré par l'agrégateur\nreference;avis\nr1;\"ligne 1\n# pas un commentaire\";x\n#r2;non\nr3;oui\n";
+        let mut s = String::new();
+        CommentSkipReader::new(data.as_bytes(), b'#').read_to_string(&mut s).unwrap();
+        assert_eq!(s, "\n\nreference;avis\nr1;\"ligne 1\n# pas un commentaire\";x\n\nr3;oui\n");
+
+        // délimiteur deviné après retrait des commentaires (les `,` du préambule ne comptent pas)
+        let data = "# a,b,c,d,e,f\r\nreference;avis\r\nr1;oui\r\n# fin\r\nr2;\"sur\r\ndeux lignes\"\r\n\r\nr3;non";
+        let opts = ReadOptions { delimiter: DelimiterChoice::Auto, comment: Some(b'#'), ..Default::default() };
+        let rows = Rows::open(&temp_file("comments.csv", data.as_bytes()), &opts).unwrap();
+        assert_eq!(rows.headers(), &StringRecord::from(vec!["reference", "avis"]));
+        let rows: Vec<_> = rows.map(Result::unwrap).collect();
+        assert_eq!(rows[0].cell("avis"), Some("oui"));
+        // numéros de ligne du fichier d'origine, commentaires compris
+        assert_eq!(rows.iter().map(|r| r.line()).collect::<Vec<_>>(), vec![Some(3), Some(5), Some(8)]);
+    }
+
+    #[test]
+    fn gzip_is_decompressed() {
+        let mut gz = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        std::io::Write::write_all(&mut gz, CSV.as_bytes()).unwrap();
+        let (s, info) = read_source(&temp_file("data.csv.gz", &gz.finish().unwrap()));
+        assert_eq!(s, CSV);
+        assert_eq!(info.compression, Compression::Gzip);
+    }
+
+    #[test]
+    fn checksum_ignores_compression() {
+        let plain = temp_file("sum.csv", CSV.as_bytes());
+        let mut gz = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::best());
+        std::io::Write::write_all(&mut gz, CSV.as_bytes()).unwrap();
+        let gz = temp_file("sum.csv.gz", &gz.finish().unwrap());
+        let expected = hex::encode(Sha256::digest(CSV));
+        assert_eq!(checksum(&plain).unwrap(), expected);
+        assert_eq!(checksum(&gz).unwrap(), expected);
+        // pendant la lecture: complète une fois les lignes épuisées
+        let rows = Rows::open(&gz, &ReadOptions::default()).unwrap();
+        let sum = rows.checksum();
+        assert_eq!(rows.count(), 1);
+        assert_eq!(sum.hex(), expected);
+        assert_eq!(sum.head_hex(), expected);
+    }
+
+    #[test]
+    fn head_checksum_covers_only_the_first_bytes() {
+        // deux fichiers qui ne diffèrent qu'au-delà de HEAD_BYTES
+        let body: String = (0..HEAD_BYTES / 8).map(|i| format!("{i:07}\n")).collect();
+        let paths = ["1", "2"].map(|last| temp_file(&format!("head{last}.csv"), format!("n\n{body}{last}\n").as_bytes()));
+        let head = hex::encode(Sha256::digest(&format!("n\n{body}").as_bytes()[..HEAD_BYTES]));
+        assert_eq!(paths.each_ref().map(|p| head_checksum(p).unwrap()), [head.clone(), head.clone()]);
+        assert_ne!(checksum(&paths[0]).unwrap(), checksum(&paths[1]).unwrap());
+        // pendant la lecture: les deux empreintes d'un seul passage
+        let rows = Rows::open(&paths[0], &ReadOptions::default()).unwrap();
+        let sum = rows.checksum();
+        assert_eq!(rows.count(), HEAD_BYTES / 8 + 1);
+        assert_eq!((sum.head_hex(), sum.hex()), (head, checksum(&paths[0]).unwrap()));
+    }
+
+    #[test]
+    fn zip_reads_first_csv_member() {
+        let mut zw = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        let opts = zip::write::FileOptions::default();
+        for (name, body) in [("LISEZMOI.txt", "pas du csv"), ("export/DATA.CSV", CSV), ("autre.csv", "x\n")] {
+            zw.start_file(name, opts).unwrap();
+            std::io::Write::write_all(&mut zw, body.as_bytes()).unwrap();
+        }
+        let bytes = zw.finish().unwrap().into_inner();
+        let (s, info) = read_source(&temp_file("archive.zip", &bytes));
+        assert_eq!(s, CSV);
+        assert_eq!(info.compression, Compression::Zip);
+        assert_eq!(info.member.as_deref(), Some("export/DATA.CSV"));
+
+        let mut zw = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        zw.start_file("notes.txt", opts).unwrap();
+        let empty = temp_file("sans_csv.zip", &zw.finish().unwrap().into_inner());
+        assert_eq!(InputSource::open(&empty, None).err().unwrap().to_string(), "zip sans CSV");
+    }
+
+    #[test]
+    fn format_follows_extension() {
+        assert_eq!(InputFormat::detect("export.jsonl"), InputFormat::JsonLines);
+        assert_eq!(InputFormat::detect("export.NDJSON.gz"), InputFormat::JsonLines);
+        assert_eq!(InputFormat::detect("export.csv.gz"), InputFormat::Csv);
+        assert_eq!(InputFormat::detect("export.zip"), InputFormat::Csv);
+        assert_eq!(InputFormat::detect("Réponses.XLSX"), InputFormat::Workbook);
+        assert_eq!(InputFormat::detect("export.ods"), InputFormat::Workbook);
+    }
+
+    #[test]
+    fn sheet_cells_as_text() {
+        assert_eq!(sheet_cell(&Data::Empty), "");
+        assert_eq!(sheet_cell(&Data::Float(75001.0)), "75001");
+        assert_eq!(sheet_cell(&Data::Float(1.5e20)), "150000000000000000000");
+        assert_eq!(sheet_cell(&Data::Float(0.25)), "0.25");
+        assert_eq!(sheet_cell(&Data::DateTimeIso("2019-02-21T10:00:00".into())), "2019-02-21");
+    }
+
+    /// ~1 Ko de lignes `;` puis un avis multi-ligne plein de virgules,
+    /// entre guillemets, à cheval sur la limite des 8 Ko
+    fn multiline_fixture() -> Vec<u8> {
+        let mut s = String::from("reference;avis;note\n");
+        for i in 0..80 {
+            s += &format!("r{i};court;{}\n", i % 5);
+        }
+        s += &format!("r80;\"{}\";3\n", "un, deux, \"\"trois\"\"\n".repeat(500));
+        for i in 81..90 {
+            s += &format!("r{i};court;{}\n", i % 5);
+        }
+        s.into_bytes()
+    }
+
+    #[test]
+    fn sniff_prefers_regular_separators() {
+        let sniff = |s: &str| sniff_delimiter(s.as_bytes()).unwrap().1;
+        assert_eq!(sniff("a|b|c\n1|2|3\n"), Delimiter::Pipe);
+        assert_eq!(sniff("a:b\n1:2\n"), Delimiter::Colon);
+        assert_eq!(sniff("a\tb\n1\t2\n"), Delimiter::Tab);
+        // les `:` des heures sont plus nombreux mais irréguliers
+        assert_eq!(sniff("id|date\n1|01/02/2024 10:00:00\n2|\n"), Delimiter::Pipe);
+        assert_eq!(sniff("a;b\n\"x|y|z\";2\n"), Delimiter::Semicolon);
+        // rien de répété: virgule
+        assert_eq!(sniff("texte\nlibre\n"), Delimiter::Comma);
+        assert_eq!(u8::from(Delimiter::Pipe), b'|');
+        assert_eq!("auto".parse(), Ok(DelimiterChoice::Auto));
+        assert_eq!("tab".parse(), Ok(DelimiterChoice::Fixed('\t')));
+        assert_eq!("|".parse(), Ok(DelimiterChoice::Fixed('|')));
+        assert!("§".parse::<DelimiterChoice>().is_err());
+        assert!(";;".parse::<DelimiterChoice>().is_err());
+    }
+
+    #[test]
+    fn sniff_sample_ends_on_a_record_boundary() {
+        let data = multiline_fixture();
+        let mut src = data.as_slice();
+        let (primed, delim) = sniff_delimiter(&mut src).unwrap();
+        assert_eq!(delim, Delimiter::Semicolon);
+        assert!(last_record_end(&primed).is_some_and(|end| end > SNIFF_SAMPLE));
+
+        let chained = std::io::Cursor::new(primed).chain(src);
+        let mut rdr = csv::ReaderBuilder::new().delimiter(delim.into()).flexible(true).from_reader(chained);
+        let recs: Vec<StringRecord> = rdr.records().map(Result::unwrap).collect();
+        assert_eq!(recs.len(), 90);
+        assert!(recs.iter().all(|r| r.len() == 3));
+        assert!(recs[80][1].contains("\"trois\"\nun"));
+        assert_eq!(&recs[89][0], "r89");
+    }
+
+    #[test]
+    fn header_must_match_modal_field_count() {
+        let rows = |n: usize, len: usize| vec![StringRecord::from(vec!["x"; len]); n];
+        assert!(check_shape("f.csv", 3, &rows(10, 3)).is_ok());
+        assert!(check_shape("f.csv", 3, &[]).is_ok());
+        let mut mixed = rows(6, 3);
+        mixed.extend(rows(4, 1));
+        assert!(check_shape("f.csv", 3, &mixed).is_ok());
+        let err = check_shape("f.csv", 3, &rows(10, 5)).unwrap_err();
+        assert!(err.to_string().contains("l'en-tête a 3 colonnes mais 10 des 10 premières lignes en ont 5"));
+    }
+
+    #[test]
+    fn legacy_xls_is_refused() {
+        let err = Rows::open("vieux.xls", &ReadOptions::default()).err().unwrap();
+        assert!(err.to_string().contains("convertir"));
+    }
+
+    #[test]
+    fn json_and_csv_rows_read_alike() {
+        let json = JsonRow::parse(r#"{"reference":"r1","age":42,"ok":true,"vide":null}"#).unwrap();
+        let headers = Rc::new(StringRecord::from(vec!["reference", "age", "ok", "vide"]));
+        let csv = CsvRow::new(headers, StringRecord::from(vec!["r1", "42", "true", ""]));
+        for row in [&json as &dyn ColumnAccessor, &csv] {
+            assert_eq!(row.cell("reference"), Some("r1"));
+            assert_eq!(row.value("age"), Some("42"));
+            assert_eq!(row.cell("ok"), Some("true"));
+            assert_eq!(row.cell("vide"), Some(""));
+            assert_eq!(row.value("vide"), None);
+            assert_eq!(row.cell("absente"), None);
+        }
+        assert_eq!(json.columns().count(), csv.columns().count());
+    }
+}
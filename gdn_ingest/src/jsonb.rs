@@ -0,0 +1,126 @@
+// ---------- Colonnes JSON: jsonb ou text ----------
+//
+// contributions.raw_json, questions.options_json (meta de la question) et
+// options.meta_json (meta de l'option) sont en jsonb depuis la migration
+// b93f6d2e8c15; les valeurs sont alors liées en serde_json::Value. Une base
+// pas encore migrée les a en text: le type de chaque colonne est lu au
+// démarrage, et la valeur est liée en texte JSON pour une colonne text
+// (avertissement avec la commande de migration). Tout autre type (ou colonne
+// absente) arrête le run avant la première écriture.
+//
+// En lecture, `raw_json::text` convient aux deux types.
+
+use anyhow::Result;
+use postgres::types::{to_sql_checked, IsNull, ToSql, Type};
+use postgres::GenericClient;
+use serde_json::Value;
+use std::borrow::Cow;
+use std::error::Error;
+
+const MIGRATION_HINT: &str = "appliquer les migrations (alembic upgrade head)";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Kind {
+    /// json ou jsonb
+    Json,
+    /// text ou varchar (base pas encore migrée)
+    Text,
+}
+
+impl Kind {
+    fn from_data_type(column: &str, data_type: Option<&str>) -> Result<Self> {
+        match data_type {
+            Some("jsonb" | "json") => Ok(Kind::Json),
+            Some("text" | "character varying") => Ok(Kind::Text),
+            Some(other) => anyhow::bail!("colonne {column} de type {other}, jsonb attendu — {MIGRATION_HINT}"),
+            None => anyhow::bail!("colonne {column} absente — {MIGRATION_HINT}"),
+        }
+    }
+
+    /// Paramètre pour une colonne de ce type; `text`: forme texte déjà calculée
+    pub fn param<'a>(self, value: Option<&'a Value>, text: Option<&'a str>) -> JsonParam<'a> {
+        match self {
+            Kind::Json => JsonParam::Json(value),
+            Kind::Text => JsonParam::Text(text.map(Cow::Borrowed).or_else(|| value.map(|v| Cow::Owned(v.to_string())))),
+        }
+    }
+}
+
+/// Types des trois colonnes JSON écrites par l'ingestion
+#[derive(Debug, Clone, Copy)]
+pub struct JsonColumns {
+    pub raw_json: Kind,
+    pub question_meta: Kind,
+    pub option_meta: Kind,
+}
+
+const COLUMNS: [(&str, &str); 3] = [("contributions", "raw_json"), ("questions", "options_json"), ("options", "meta_json")];
+
+pub fn detect(conn: &mut impl GenericClient) -> Result<JsonColumns> {
+    let mut kinds = Vec::new();
+    for (table, column) in COLUMNS {
+        let data_type: Option<String> = conn
+            .query_opt(
+                "SELECT data_type FROM information_schema.columns
+                 WHERE table_schema = current_schema() AND table_name = $1 AND column_name = $2",
+                &[&table, &column],
+            )?
+            .map(|r| r.get(0));
+        kinds.push(Kind::from_data_type(&format!("{table}.{column}"), data_type.as_deref())?);
+    }
+    let columns = JsonColumns { raw_json: kinds[0], question_meta: kinds[1], option_meta: kinds[2] };
+    let text: Vec<String> = COLUMNS
+        .iter()
+        .zip(&kinds)
+        .filter(|(_, k)| **k == Kind::Text)
+        .map(|((t, c), _)| format!("{t}.{c}"))
+        .collect();
+    if !text.is_empty() {
+        println!("⚠️  colonnes JSON encore en text ({}): écrites en texte — {MIGRATION_HINT} pour passer en jsonb", text.join(", "));
+    }
+    Ok(columns)
+}
+
+/// Valeur JSON liée selon le type de la colonne (NULL si absente)
+#[derive(Debug)]
+pub enum JsonParam<'a> {
+    Json(Option<&'a Value>),
+    Text(Option<Cow<'a, str>>),
+}
+
+impl ToSql for JsonParam<'_> {
+    fn to_sql(&self, ty: &Type, out: &mut postgres::types::private::BytesMut) -> Result<IsNull, Box<dyn Error + Sync + Send>> {
+        match self {
+            JsonParam::Json(v) => v.to_sql(ty, out),
+            JsonParam::Text(s) => s.as_deref().to_sql(ty, out),
+        }
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        <Value as ToSql>::accepts(ty) || <&str as ToSql>::accepts(ty)
+    }
+
+    to_sql_checked!();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn column_types_decide_the_binding() {
+        assert_eq!(Kind::from_data_type("c.raw_json", Some("jsonb")).unwrap(), Kind::Json);
+        assert_eq!(Kind::from_data_type("c.raw_json", Some("text")).unwrap(), Kind::Text);
+        let err = Kind::from_data_type("c.raw_json", Some("bytea")).unwrap_err().to_string();
+        assert!(err.contains("alembic upgrade head"), "{err}");
+        assert!(Kind::from_data_type("o.meta_json", None).is_err());
+
+        let meta = serde_json::json!({"k": 1});
+        assert!(matches!(Kind::Json.param(Some(&meta), None), JsonParam::Json(Some(_))));
+        match Kind::Text.param(Some(&meta), None) {
+            JsonParam::Text(Some(s)) => assert_eq!(s, r#"{"k":1}"#),
+            other => panic!("{other:?}"),
+        }
+        assert!(matches!(Kind::Text.param(None, None), JsonParam::Text(None)));
+    }
+}
@@ -0,0 +1,324 @@
+// ---------- Sous-commande lint: mapping légal mais suspect ----------
+//
+// validate_mapping refuse ce qui ne peut pas s'ingérer; lint signale ce qui
+// s'ingère mais trahit souvent une erreur de mapping. Chaque constat porte un
+// code (cf. LINTS) que la liste `allow:` du mapping fait taire:
+//
+//   allow: [M001, M005]
+//
+// M006 (options_from_values sur une colonne de texte libre) lit un
+// échantillon des fichiers passés par --csv; sans fichier, il n'est pas
+// contrôlé. Sortie lisible, ou JSON (--format json) pour l'outillage.
+// Aucun constat ne change le code de sortie: lint conseille, validate juge.
+
+use anyhow::Result;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+
+use crate::input::ColumnAccessor;
+use crate::validate::ReportFormat;
+use crate::{aliases, expand_globs, input, load_mapping, Mapping, QuestionMap};
+
+/// Codes et description, dans l'ordre d'affichage
+pub const LINTS: [(&str, &str); 8] = [
+    ("M001", "question sans position"),
+    ("M002", "section absente de certaines questions seulement"),
+    ("M003", "section reprise après une autre (questions non contiguës)"),
+    ("M004", "sections qui ne diffèrent que par la casse ou les espaces"),
+    ("M005", "single_choice avec trop d'options déclarées"),
+    ("M006", "options_from_values sur une colonne de texte libre (échantillon --csv)"),
+    ("M007", "intitulé (prompt) repris par plusieurs questions"),
+    ("M008", "meta qui n'est pas un objet JSON"),
+];
+
+/// M005: options déclarées au-delà desquelles une liste fermée est suspecte
+const MAX_STATIC_OPTIONS: usize = 30;
+/// M006: valeurs non vides de l'échantillon en dessous desquelles on ne juge pas
+const MIN_SAMPLE_VALUES: usize = 20;
+/// M006: part de valeurs distinctes d'une colonne de texte libre
+const FREE_TEXT_RATIO: f64 = 0.5;
+
+#[derive(Debug, Serialize, PartialEq)]
+pub struct Finding {
+    pub code: &'static str,
+    /// question concernée (None: tout le mapping)
+    pub question: Option<String>,
+    pub message: String,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct Report {
+    pub findings: Vec<Finding>,
+    /// constats tus par `allow:`
+    pub allowed: usize,
+    /// codes de `allow:` qui ne sont pas des codes de lint
+    pub unknown_allow: Vec<String>,
+}
+
+/// Valeurs non vides et distinctes par colonne, sur un échantillon
+#[derive(Debug, Default)]
+pub struct Sample {
+    columns: HashMap<String, (usize, HashSet<String>)>,
+}
+
+impl Sample {
+    /// Colonnes sous options_from_values des --sample-rows premières lignes de chaque fichier
+    pub fn read(files: &[String], mapping: &Mapping, read_opts: &input::ReadOptions, max_rows: usize) -> Result<Self> {
+        let wanted: HashSet<&str> =
+            mapping.questions.iter().filter(|qm| dynamic_single_column(qm)).filter_map(|qm| qm.source_column.as_deref()).collect();
+        let mut sample = Sample::default();
+        for path in files {
+            let rows = input::Rows::open(path, read_opts)?;
+            let resolution = aliases::Resolution::resolve(mapping, rows.headers());
+            for row in rows.take(max_rows) {
+                let row = row?;
+                let row = resolution.row(row.as_ref());
+                for &col in &wanted {
+                    if let Some(v) = row.cell(col).map(str::trim).filter(|v| !v.is_empty()) {
+                        sample.note(col, v);
+                    }
+                }
+            }
+        }
+        Ok(sample)
+    }
+
+    fn note(&mut self, column: &str, value: &str) {
+        let (n, distinct) = self.columns.entry(column.to_string()).or_default();
+        *n += 1;
+        distinct.insert(value.to_string());
+    }
+
+    /// (valeurs non vides, part de valeurs distinctes)
+    fn ratio(&self, column: &str) -> Option<(usize, f64)> {
+        self.columns.get(column).map(|(n, distinct)| (*n, distinct.len() as f64 / *n as f64))
+    }
+}
+
+/// Question à options créées depuis les valeurs d'une seule colonne
+fn dynamic_single_column(qm: &QuestionMap) -> bool {
+    qm.options_from_values && matches!(qm.qtype.as_str(), "single_choice" | "multi_choice") && !qm.options_from_columns()
+}
+
+/// Section normalisée pour M004 (casse et espaces)
+fn section_key(section: &str) -> String {
+    section.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase()
+}
+
+pub fn lint(mapping: &Mapping, sample: Option<&Sample>) -> Report {
+    let mut findings = Vec::new();
+    let mut push = |code, question: Option<&str>, message: String| {
+        findings.push(Finding { code, question: question.map(str::to_string), message })
+    };
+
+    for qm in &mapping.questions {
+        if qm.position.is_none() {
+            push("M001", Some(&qm.code), format!("question '{}' sans position (ordre d'affichage indéterminé)", qm.code));
+        }
+    }
+
+    // sections: absentes par endroits, reprises, ou orthographiées de plusieurs façons
+    let without: Vec<&str> = mapping.questions.iter().filter(|qm| qm.section.is_none()).map(|qm| qm.code.as_str()).collect();
+    if !without.is_empty() && without.len() < mapping.questions.len() {
+        push("M002", None, format!("{} question(s) sans section alors que d'autres en ont: {}", without.len(), without.join(", ")));
+    }
+    let mut closed: HashSet<&str> = HashSet::new();
+    let mut current: Option<&str> = None;
+    for qm in &mapping.questions {
+        let Some(section) = qm.section.as_deref() else { continue };
+        if current != Some(section) {
+            if closed.contains(section) {
+                push("M003", Some(&qm.code), format!("section '{section}' reprise à la question '{}' après une autre section", qm.code));
+            }
+            if let Some(previous) = current {
+                closed.insert(previous);
+            }
+            current = Some(section);
+        }
+    }
+    let mut spellings: HashMap<String, Vec<&str>> = HashMap::new();
+    for section in mapping.questions.iter().filter_map(|qm| qm.section.as_deref()) {
+        let variants = spellings.entry(section_key(section)).or_default();
+        if !variants.contains(&section) {
+            variants.push(section);
+        }
+    }
+    let mut variants: Vec<&Vec<&str>> = spellings.values().filter(|v| v.len() > 1).collect();
+    variants.sort();
+    for v in variants {
+        let quoted: Vec<String> = v.iter().map(|s| format!("'{s}'")).collect();
+        push("M004", None, format!("sections {} (même section écrite de {} façons)", quoted.join(", "), v.len()));
+    }
+
+    for qm in &mapping.questions {
+        if qm.qtype == "single_choice" && qm.options.len() > MAX_STATIC_OPTIONS {
+            push(
+                "M005",
+                Some(&qm.code),
+                format!(
+                    "single_choice '{}' avec {} options déclarées (> {MAX_STATIC_OPTIONS}): liste à découper, ou question text?",
+                    qm.code,
+                    qm.options.len()
+                ),
+            );
+        }
+        if let (Some(sample), Some(col), true) = (sample, qm.source_column.as_deref(), dynamic_single_column(qm)) {
+            if let Some((n, ratio)) = sample.ratio(col).filter(|&(n, ratio)| n >= MIN_SAMPLE_VALUES && ratio > FREE_TEXT_RATIO) {
+                push(
+                    "M006",
+                    Some(&qm.code),
+                    format!(
+                        "'{}': options_from_values sur '{col}', {:.0} % de valeurs distinctes sur {n}: texte libre (une option par réponse)?",
+                        qm.code,
+                        100.0 * ratio
+                    ),
+                );
+            }
+        }
+    }
+
+    let mut prompts: HashMap<String, Vec<&str>> = HashMap::new();
+    for qm in &mapping.questions {
+        prompts.entry(qm.prompt.trim().to_lowercase()).or_default().push(&qm.code);
+    }
+    for qm in &mapping.questions {
+        let codes = &prompts[&qm.prompt.trim().to_lowercase()];
+        // un constat par groupe, sur sa première question
+        if codes.len() > 1 && codes[0] == qm.code {
+            push("M007", Some(&qm.code), format!("intitulé '{}' repris par les questions {}", qm.prompt.trim(), codes.join(", ")));
+        }
+    }
+
+    for qm in &mapping.questions {
+        if qm.meta.as_ref().is_some_and(|m| !m.is_object()) {
+            push("M008", Some(&qm.code), format!("question '{}': meta n'est pas un objet JSON", qm.code));
+        }
+        for opt in qm.options.iter().filter(|o| o.meta.as_ref().is_some_and(|m| !m.is_object())) {
+            push("M008", Some(&qm.code), format!("question '{}', option '{}': meta n'est pas un objet JSON", qm.code, opt.code));
+        }
+    }
+
+    let known: HashSet<&str> = LINTS.iter().map(|(code, _)| *code).collect();
+    let (findings, hushed): (Vec<Finding>, Vec<Finding>) =
+        findings.into_iter().partition(|f| !mapping.allow.iter().any(|a| a == f.code));
+    Report {
+        findings,
+        allowed: hushed.len(),
+        unknown_allow: mapping.allow.iter().filter(|a| !known.contains(a.as_str())).cloned().collect(),
+    }
+}
+
+pub fn run_lint(
+    mapping_path: &PathBuf,
+    csv_globs: &[String],
+    delimiter: input::DelimiterChoice,
+    sample_rows: usize,
+    format: ReportFormat,
+) -> Result<()> {
+    let mapping = load_mapping(mapping_path)?;
+    let files = expand_globs(csv_globs)?;
+    let sample = match files.is_empty() {
+        true => None,
+        false => Some(Sample::read(&files, &mapping, &input::ReadOptions { delimiter, ..Default::default() }, sample_rows)?),
+    };
+    let report = lint(&mapping, sample.as_ref());
+
+    if format == ReportFormat::Json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+        return Ok(());
+    }
+    for f in &report.findings {
+        println!("  {} {}", f.code, f.message);
+    }
+    for a in &report.unknown_allow {
+        println!("  ⚠️  allow: '{a}' n'est pas un code de lint");
+    }
+    if sample.is_none() {
+        println!("[lint] M006 non contrôlé (pas de --csv)");
+    }
+    let codes: Vec<&str> = LINTS.iter().map(|(code, _)| *code).filter(|c| report.findings.iter().any(|f| f.code == *c)).collect();
+    for (code, what) in LINTS.iter().filter(|(code, _)| codes.contains(code)) {
+        println!("[lint] {code}: {what}");
+    }
+    println!(
+        "[lint] {} constats ({} tus par allow:){}",
+        report.findings.len(),
+        report.allowed,
+        if report.findings.is_empty() { " ✅" } else { " — allow: [CODE] dans le mapping pour en taire un" }
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mapping(yaml: &str) -> Mapping {
+        serde_yaml::from_str(yaml).unwrap()
+    }
+
+    fn codes(report: &Report) -> Vec<&str> {
+        report.findings.iter().map(|f| f.code).collect()
+    }
+
+    #[test]
+    fn suspicious_mapping_is_flagged_and_allow_hushes() {
+        let yaml = r#"
+form: { name: t }
+questions:
+  - { code: q1, prompt: Votre avis, type: text, source_column: a, section: Santé, position: 1 }
+  - { code: q2, prompt: Autre, type: text, source_column: b, section: Transports, position: 2 }
+  - { code: q3, prompt: "votre avis ", type: text, source_column: c, section: "santé", position: 3, meta: [1] }
+  - { code: q4, prompt: Q4, type: text, source_column: d }
+"#;
+        let report = lint(&mapping(yaml), None);
+        assert_eq!(codes(&report), vec!["M001", "M002", "M004", "M007", "M008"]);
+        assert_eq!(report.findings[3].message, "intitulé 'Votre avis' repris par les questions q1, q3");
+
+        let hushed = lint(&mapping(&yaml.replace("form: { name: t }", "form: { name: t }\nallow: [M001, M008, M999]")), None);
+        assert_eq!(codes(&hushed), vec!["M002", "M004", "M007"]);
+        assert_eq!((hushed.allowed, hushed.unknown_allow.clone()), (2, vec!["M999".to_string()]));
+    }
+
+    #[test]
+    fn reopened_section_is_flagged() {
+        let report = lint(
+            &mapping(
+                r#"
+form: { name: t }
+allow: [M001]
+questions:
+  - { code: q1, prompt: A, type: text, source_column: a, section: S1 }
+  - { code: q2, prompt: B, type: text, source_column: b, section: S2 }
+  - { code: q3, prompt: C, type: text, source_column: c, section: S1 }
+"#,
+            ),
+            None,
+        );
+        assert_eq!(codes(&report), vec!["M003"]);
+        assert_eq!(report.findings[0].question.as_deref(), Some("q3"));
+    }
+
+    #[test]
+    fn free_text_column_under_options_from_values() {
+        let m = mapping(
+            r#"
+form: { name: t }
+allow: [M001]
+questions:
+  - { code: ville, prompt: Ville, type: single_choice, source_column: v, options_from_values: true, options: [{ code: paris, label: Paris }] }
+"#,
+        );
+        let mut sample = Sample::default();
+        for i in 0..MIN_SAMPLE_VALUES {
+            sample.note("v", &format!("réponse libre {}", i % 15));
+        }
+        assert_eq!(codes(&lint(&m, Some(&sample))), vec!["M006"]);
+        let mut repeated = Sample::default();
+        for i in 0..MIN_SAMPLE_VALUES {
+            repeated.note("v", ["Paris", "Lyon"][i % 2]);
+        }
+        assert!(lint(&m, Some(&repeated)).findings.is_empty());
+    }
+}
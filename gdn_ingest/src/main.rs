@@ -1,93 +1,644 @@
 use anyhow::{Context, Result};
-use clap::{Parser, Subcommand};
-use csv::StringRecord;
-use flate2::read::GzDecoder;
+use clap::{Args, Parser, Subcommand};
 use glob::glob;
-use postgres::{Client, NoTls, Row};
+use postgres::Client;
 use regex::Regex;
 use serde::Deserialize;
 use serde_json::json;
 use sha2::{Digest, Sha256};
 use std::{
     borrow::Cow,
-    collections::{HashMap, HashSet},
+    collections::{BTreeMap, HashMap, HashSet},
+    sync::{Arc, Mutex},
     env,
-    fs::File,
-    io::{BufRead, BufReader, Read},
     path::PathBuf,
     time::Instant,
 };
-use zip::read::ZipArchive;
-use std::io::Cursor;
 use once_cell::sync::Lazy;
+use rayon::prelude::*;
+
+use bars::say;
+use aliases::ColumnRef;
+use input::ColumnAccessor;
+
+mod age;
+mod aliases;
+mod anomalies;
+mod bars;
+mod cells;
+mod changes;
+mod checkpoint;
+mod counters;
+mod dbinit;
+mod dictionary;
+mod dryrun;
+mod encoding;
+mod existing;
+mod explain;
+mod export;
+mod forms;
+mod gdpr;
+mod genmapping;
+mod geo;
+mod input;
+mod maintenance;
+mod maxerrors;
+mod jsonb;
+mod lint;
+mod merge;
+mod normalize;
+mod options;
+mod overlay;
+mod pii;
+mod policy;
+mod pool;
+mod prepared;
+mod pseudonymize;
+mod rawimports;
+mod rawjson;
+mod rejects;
+mod retry;
+mod rollback;
+mod rules;
+mod runlock;
+mod sanitize;
+mod session;
+mod settings;
+mod softdelete;
+mod sourcefiles;
+mod stats;
+mod status;
+mod subject;
+mod tls;
+mod unmapped;
+mod validate;
+mod values;
 
 #[derive(Parser)]
 #[command(name = "gdn_ingest", version, about = "Ingestion Grand Débat (Rust + PostgreSQL)")]
 struct Cli {
     #[command(subcommand)]
     cmd: Cmd,
+    /// Connexion PostgreSQL chiffrée (défaut: sslmode de DATABASE_URL, sinon disable), cf. tls.rs
+    #[arg(long, global = true, value_enum)]
+    db_tls: Option<tls::TlsMode>,
+    /// Certificat PEM de l'autorité du serveur (verify-ca, verify-full)
+    #[arg(long, global = true)]
+    db_ca_cert: Option<PathBuf>,
+    /// Délai max d'une requête (ms, 0: sans limite), cf. session.rs
+    #[arg(long, global = true, default_value_t = session::DEFAULT_STATEMENT_TIMEOUT_MS)]
+    db_statement_timeout_ms: u64,
+    /// Attente max d'un verrou (ms, 0: sans limite)
+    #[arg(long, global = true, default_value_t = 0)]
+    db_lock_timeout_ms: u64,
+    /// Schéma PostgreSQL des tables (un jeu de données par schéma), cf. session.rs
+    #[arg(long, global = true, default_value = session::DEFAULT_SCHEMA)]
+    schema: String,
 }
 
 #[derive(Subcommand)]
 enum Cmd {
     /// Ingérer des CSV selon un mapping YAML
-    Ingest {
-        /// Un ou plusieurs chemins/globs CSV
+    Ingest(Box<IngestArgs>),
+    /// Vérifier mapping, en-têtes et premières lignes des CSV sans toucher à la base (CI)
+    Validate {
+        /// Un ou plusieurs chemins/globs CSV (ou JSON Lines: .jsonl/.ndjson, .gz accepté)
         #[arg(long)]
         csv: Vec<String>,
         /// Mapping YAML
         #[arg(long)]
         mapping: PathBuf,
-        /// Nom de batch
-        #[arg(long, default_value = "import_rust")]
-        batch: String,
-        /// Commit toutes les N lignes
-        #[arg(long, default_value_t = 10_000)]
-        commit_every: usize,
-        /// Logs toutes les N lignes
-        #[arg(long, default_value_t = 2_000)]
-        log_every: usize,
+        /// Délimiteur CSV (`auto`: deviné sur le début de chaque fichier)
+        #[arg(long, default_value = ",")]
+        delimiter: input::DelimiterChoice,
+        /// Valider les codes d'options après réécriture au format slug
+        #[arg(long, default_value_t = false)]
+        normalize_option_codes: bool,
+        /// Lignes lues à blanc par fichier (défaut: toutes)
+        #[arg(long)]
+        sample_rows: Option<usize>,
+        /// text: tableau; json: rapport (erreurs, avertissements, couverture) sur stdout
+        #[arg(long, value_enum, default_value_t = validate::ReportFormat::Text)]
+        format: validate::ReportFormat,
+    },
+    /// Signaler ce qui est légal mais suspect dans un mapping (codes M…, `allow:` pour en taire), cf. lint.rs
+    Lint {
+        /// Mapping YAML
+        #[arg(long)]
+        mapping: PathBuf,
+        /// CSV d'échantillon (chemins/globs), pour juger options_from_values (M006)
+        #[arg(long)]
+        csv: Vec<String>,
+        /// Délimiteur CSV (`auto`: deviné sur le début de chaque fichier)
+        #[arg(long, default_value = ",")]
+        delimiter: input::DelimiterChoice,
+        /// Lignes lues par fichier d'échantillon
+        #[arg(long, default_value_t = 1000)]
+        sample_rows: usize,
+        /// text: constats lisibles; json: rapport pour l'outillage
+        #[arg(long, value_enum, default_value_t = validate::ReportFormat::Text)]
+        format: validate::ReportFormat,
+    },
+    /// Détailler le traitement d'une seule ligne CSV (sans base de données)
+    Explain {
+        /// Mapping YAML
+        #[arg(long)]
+        mapping: PathBuf,
+        /// Ligne d'en-tête CSV
+        #[arg(long)]
+        header: String,
+        /// Ligne de données CSV
+        #[arg(long)]
+        row: String,
         #[arg(long, default_value = ",")]
         delimiter: char,
-        /// Mode validation uniquement (pas d'écriture DB)
+    },
+    /// Reconstruire un CSV à partir de la base
+    Export {
+        /// Nom du formulaire
+        #[arg(long)]
+        form: String,
+        /// Version du formulaire (NULL et '' équivalents)
+        #[arg(long)]
+        version: Option<String>,
+        /// Fichier CSV de sortie (stdout si absent)
+        #[arg(long)]
+        output: Option<PathBuf>,
+        /// Restreindre à un batch d'import
+        #[arg(long)]
+        batch: Option<String>,
+        /// wide: une ligne par contribution; long: une ligne par réponse
+        #[arg(long, value_enum, default_value_t = export::ExportFormat::Wide)]
+        format: export::ExportFormat,
+        /// Écarter les contributions supprimées (deleted_at renseigné, cf. softdelete.rs)
+        #[arg(long, default_value_t = false)]
+        exclude_deleted: bool,
+    },
+    /// Statistiques de réponse par formulaire et par question
+    Stats {
+        /// Nom du formulaire
+        #[arg(long, required_unless_present = "mapping")]
+        form: Option<String>,
+        /// Version du formulaire (NULL et '' équivalents)
+        #[arg(long)]
+        version: Option<String>,
+        /// Mapping YAML dont la section `form` désigne le formulaire
+        #[arg(long, conflicts_with = "form")]
+        mapping: Option<PathBuf>,
+        /// Nombre d'options les plus fréquentes par question à choix
+        #[arg(long, default_value_t = 5)]
+        top: i64,
+        /// Sortie JSON au lieu du tableau
+        #[arg(long, default_value_t = false)]
+        json: bool,
+        /// Compter aussi les contributions supprimées (écartées par défaut)
+        #[arg(long, default_value_t = false)]
+        include_deleted: bool,
+        /// Restreindre aux contributions d'un batch d'import (--batch de l'ingestion)
+        #[arg(long)]
+        batch: Option<String>,
+    },
+    /// Fusionner les formulaires en double (version/source NULL vs '')
+    MergeForms {
+        /// Appliquer la fusion (sinon simple aperçu)
+        #[arg(long, default_value_t = false)]
+        apply: bool,
+    },
+    /// Mutualiser les réponses texte courtes existantes dans text_values (cf. dictionary.rs)
+    DictionaryCompact {
+        /// Longueur maximale (caractères) d'une valeur mutualisée
+        #[arg(long, default_value_t = dictionary::DEFAULT_MAX_CHARS)]
+        max_chars: usize,
+        /// Réponses converties par UPDATE (tranches d'ids)
+        #[arg(long, default_value_t = 50_000)]
+        chunk: i64,
+        /// Convertir (sinon simple estimation de la place gagnée)
+        #[arg(long, default_value_t = false)]
+        apply: bool,
+    },
+    /// Supprimer les contributions d'un batch d'import et leurs réponses (cf. rollback.rs)
+    DeleteBatch {
+        /// Nom du batch (--batch de l'ingestion)
+        #[arg(long)]
+        batch: String,
+        /// Compter seulement les lignes à supprimer
+        #[arg(long, default_value_t = false)]
+        dry_run: bool,
+        /// Supprimer aussi les contributions antérieures que le batch a réécrites
+        #[arg(long, default_value_t = false)]
+        include_updated: bool,
+        /// Contributions supprimées par transaction
+        #[arg(long, default_value_t = rollback::DEFAULT_CHUNK)]
+        chunk: usize,
+    },
+    /// Générer un squelette de mapping YAML depuis l'en-tête et les premières lignes d'un CSV
+    GenMapping {
+        /// CSV source (délimiteur deviné)
+        #[arg(long)]
+        csv: String,
+        /// Mapping YAML à écrire (refusé s'il existe déjà)
+        #[arg(long)]
+        output: PathBuf,
+    },
+    /// Vérifier la connexion PostgreSQL (SELECT 1 sur une connexion du pool)
+    Ping,
+    /// Effacement RGPD: données personnelles des auteurs d'un email_hash (cf. gdpr.rs)
+    Erase {
+        /// authors.email_hash de la personne (hash salé de son email)
+        #[arg(long)]
+        email_hash: String,
+        /// Supprimer aussi les réponses de ses contributions (effacement complet)
+        #[arg(long, default_value_t = false)]
+        erase_answers: bool,
+        /// Afficher la sélection et compter les lignes, sans rien effacer
+        #[arg(long, default_value_t = false)]
+        dry_run: bool,
+    },
+    /// Marquer des contributions supprimées (deleted_at), réponses conservées, cf. softdelete.rs
+    SoftDelete {
+        /// Nom du formulaire
+        #[arg(long)]
+        form: String,
+        /// Version du formulaire (NULL et '' équivalents)
+        #[arg(long)]
+        version: Option<String>,
+        /// Références (source_contribution_id), répétable ou séparées par des virgules
+        #[arg(long = "source-contribution-id", value_delimiter = ',', required = true)]
+        source_contribution_ids: Vec<String>,
+        /// Motif enregistré dans delete_reason
+        #[arg(long)]
+        reason: Option<String>,
+    },
+    /// Remplacer les données personnelles des auteurs par des identifiants synthétiques, cf. pseudonymize.rs
+    Pseudonymize {
+        /// Clé HMAC des pseudonymes (à conserver: même sel, mêmes pseudonymes)
+        #[arg(long)]
+        salt: String,
+        /// Compter les auteurs à traiter et afficher quelques pseudonymes, sans rien écrire
         #[arg(long, default_value_t = false)]
         dry_run: bool,
+        /// Retraiter aussi les auteurs déjà pseudonymisés (email_hash haché de nouveau)
+        #[arg(long, default_value_t = false)]
+        force: bool,
+    },
+    /// Droit d'accès RGPD: fiche et réponses des auteurs d'un email_hash, cf. subject.rs
+    ExportSubject {
+        /// authors.email_hash de la personne (hash salé de son email)
+        #[arg(long)]
+        email_hash: String,
+        /// Fichier à écrire (défaut: subject_export_<début du hash>.json|.csv)
+        #[arg(long)]
+        output: Option<PathBuf>,
+        /// json: auteur et contributions imbriquées; csv: une ligne par réponse
+        #[arg(long, value_enum, default_value_t = subject::SubjectFormat::Json)]
+        format: subject::SubjectFormat,
+        /// Champs masqués avant écriture, séparés par des virgules (ex: name,zipcode,value)
+        #[arg(long, value_delimiter = ',')]
+        redact_fields: Vec<String>,
+    },
+    /// Créer les tables de l'ingestion dans --schema (base neuve, sans Alembic), cf. dbinit.rs
+    DbInit {
+        /// Ne créer que les tables manquantes (sinon: refus si l'une existe déjà)
+        #[arg(long, default_value_t = false)]
+        if_not_exists: bool,
+        /// Afficher chaque instruction exécutée
+        #[arg(long, default_value_t = false)]
+        verbose: bool,
+    },
+    /// Supprimer les tables de l'ingestion de --schema
+    DbDrop {
+        /// Supprimer aussi les objets qui en dépendent (DROP ... CASCADE)
+        #[arg(long, default_value_t = false)]
+        cascade: bool,
+        /// Supprimer (sinon simple liste des tables concernées)
+        #[arg(long, default_value_t = false)]
+        apply: bool,
+        /// Afficher chaque instruction exécutée
+        #[arg(long, default_value_t = false)]
+        verbose: bool,
     },
 }
 
+#[derive(Args)]
+struct IngestArgs {
+    /// Un ou plusieurs chemins/globs CSV (ou JSON Lines: .jsonl/.ndjson, .gz accepté)
+    #[arg(long)]
+    csv: Vec<String>,
+    /// Classeurs .xlsx/.ods: feuille à lire (défaut: la première)
+    #[arg(long)]
+    sheet: Option<String>,
+
+    /// Mapping YAML
+    #[arg(long)]
+    mapping: PathBuf,
+    /// Surcouche fusionnée dans --mapping (questions par code, cf. overlay.rs)
+    #[arg(long)]
+    mapping_overlay: Option<PathBuf>,
+    /// Nom de batch
+    #[arg(long, default_value = "import_rust")]
+    batch: String,
+    /// Commit toutes les N lignes (défaut: 10000)
+    #[arg(long, value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..))]
+    commit_every: Option<usize>,
+    /// Logs toutes les N lignes (défaut: 2000)
+    #[arg(long, value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..))]
+    log_every: Option<usize>,
+    /// Délimiteur CSV (défaut: ","; `auto`: deviné sur le début de chaque fichier)
+    #[arg(long)]
+    delimiter: Option<input::DelimiterChoice>,
+    /// Encodage des fichiers (utf-8, windows-1252, iso-8859-1…); défaut: détection automatique
+    #[arg(long)]
+    encoding: Option<String>,
+    /// Mode validation uniquement: lit les fichiers, couverture par question, sans base (cf. dryrun.rs)
+    #[arg(long, default_value_t = false)]
+    dry_run: bool,
+    /// --dry-run: lignes lues par fichier (défaut: toutes)
+    #[arg(long, requires = "dry_run")]
+    dry_run_rows: Option<usize>,
+    /// Budget mémoire (Mo) du préchargement des contributions existantes;
+    /// au-delà, bascule sur un filtre de Bloom (défaut: 256)
+    #[arg(long)]
+    preload_budget_mb: Option<usize>,
+    /// Erreur fatale sur une valeur `number` illisible (= --on-error bad_number=abort)
+    #[arg(long, default_value_t = false)]
+    strict_numbers: bool,
+    /// Port HTTP d'un endpoint JSON de suivi (lecture seule) + /healthz
+    #[arg(long)]
+    status_port: Option<u16>,
+    /// Adresse d'écoute de l'endpoint de suivi
+    #[arg(long, default_value = "127.0.0.1")]
+    status_bind: String,
+    /// Pas de barres de progression (CI, sortie redirigée)
+    #[arg(long, default_value_t = false)]
+    no_progress: bool,
+    /// Ignorer (avec avertissement) les questions de type inconnu au lieu d'échouer
+    #[arg(long, default_value_t = false)]
+    allow_unknown_types: bool,
+    /// Colonnes du mapping absentes d'un fichier: avertissement et questions ignorées
+    /// pour ce fichier, au lieu d'une erreur (comme optional_column pour toutes)
+    #[arg(long, default_value_t = false)]
+    allow_missing_columns: bool,
+    /// Nombre de fichiers ingérés en parallèle (défaut: 1)
+    #[arg(long)]
+    parallel: Option<usize>,
+    /// Connexions du pool pour les fichiers (défaut: --parallel), cf. pool.rs
+    #[arg(long)]
+    db_pool_size: Option<usize>,
+    /// Réécrit les codes d'options déclarés au format slug (comme les codes
+    /// dynamiques); base neuve: sur une base existante, les anciens codes
+    /// restent à côté des nouveaux, cf. options.rs
+    #[arg(long, default_value_t = false)]
+    normalize_option_codes: bool,
+    /// Auteur déjà connu (même source_author_id ou email_hash): fusion des champs
+    #[arg(long, value_enum, default_value_t = AuthorConflict::Fill)]
+    author_conflict: AuthorConflict,
+    /// Sel du hash des emails (`defaults.author.email`); défaut: EMAIL_HASH_SALT
+    #[arg(long)]
+    email_salt: Option<String>,
+    /// number hors de [min, max]: warn (compter), error (arrêter), drop (pas de réponse)
+    /// (= --on-error out_of_range=warn|abort|drop)
+    #[arg(long, value_enum)]
+    range_violations: Option<RangePolicy>,
+    /// Afficher en fin d'ingestion les valeurs brutes absentes de gender_map
+    #[arg(long)]
+    report_unmapped_values: bool,
+    /// Code postal auteur invalide: ligne écartée dans --rejects-dir (= --on-error bad_zipcode=reject)
+    #[arg(long)]
+    zipcode_strict: bool,
+    /// Action par catégorie d'erreur, répétable: `bad_date=warn:100`, `bad_number=abort`
+    /// (catégories et précédence: cf. policy.rs)
+    #[arg(long = "on-error", value_parser = policy::parse_on_error)]
+    on_error: Vec<(policy::Category, policy::Rule)>,
+    /// Référence déjà en base pour le formulaire: prolonger la contribution
+    /// (raw_json en tableau, nouvelles réponses ajoutées) au lieu de la remplacer
+    #[arg(long)]
+    merge_into_existing: bool,
+    /// CSV `zipcode_prefix,department_code,region_code` complétant la table
+    /// intégrée des départements/régions (cf. geo.rs)
+    #[arg(long)]
+    geo_lookup: Option<PathBuf>,
+    /// Format strftime imposé pour submitted_at (`%s`: secondes epoch);
+    /// sans lui: ISO 8601, epoch puis jj/mm/aaaa [hh:mm[:ss]]
+    #[arg(long)]
+    submitted_at_format: Option<String>,
+    /// Dossier où écrire le résumé `<batch>.summary.json` (mis à jour à chaque commit)
+    #[arg(long)]
+    artifacts_dir: Option<PathBuf>,
+    /// Ingestion `running` sans commit depuis plus de N secondes: considérée plantée
+    #[arg(long, default_value_t = runlock::DEFAULT_STALE_SECS)]
+    stale_lock_secs: u64,
+    /// Reprendre le verrou d'une ingestion plantée (marquée abandoned);
+    /// sans ce flag: sortie avec le code 75 (cf. runlock.rs)
+    #[arg(long)]
+    steal_stale_lock: bool,
+    /// JSONL des différences colonne par colonne des contributions modifiées
+    /// (référence déjà en base, raw_hash différent), cf. changes.rs
+    #[arg(long)]
+    show_changes: Option<PathBuf>,
+    /// Nombre maximal de contributions écrites dans --show-changes
+    #[arg(long, default_value_t = changes::DEFAULT_MAX_CHANGES)]
+    max_changes: usize,
+    /// CSV: ignorer les lignes commençant par `#` (préambules d'export);
+    /// autre caractère: defaults.comment_char du mapping
+    #[arg(long)]
+    skip_comments: bool,
+    /// Réponses texte courtes stockées une seule fois dans text_values
+    /// (answers.text_value_id), cf. dictionary.rs
+    #[arg(long)]
+    dictionary_texts: bool,
+    /// Longueur maximale (caractères) d'une réponse mutualisée
+    #[arg(long, default_value_t = dictionary::DEFAULT_MAX_CHARS)]
+    dictionary_max_chars: usize,
+    /// Réécrire aussi les lignes inchangées (raw_hash identique à celui en base),
+    /// ex: après une modification du mapping
+    #[arg(long)]
+    force: bool,
+    /// Sauter les lignes identiques (même hash) à une ligne déjà consignée
+    /// dans raw_imports par un import précédent, cf. rawimports.rs
+    #[arg(long)]
+    skip_duplicates: bool,
+    /// Reprendre un run interrompu de ce batch: fichiers complets sautés,
+    /// fichier interrompu repris après sa dernière ligne committée (cf. checkpoint.rs)
+    #[arg(long, conflicts_with = "no_resume")]
+    resume: bool,
+    /// Effacer les points de reprise du batch et repartir de zéro
+    #[arg(long)]
+    no_resume: bool,
+    /// Nouveaux essais d'une écriture annulée par un deadlock (40P01), cf. retry.rs
+    #[arg(long, default_value_t = retry::DEFAULT_ATTEMPTS)]
+    db_retry_attempts: u32,
+    /// Attente avant le premier nouvel essai (ms), doublée à chaque essai
+    #[arg(long, default_value_t = retry::DEFAULT_BASE_MS)]
+    db_retry_base_ms: u64,
+    /// Sauter les fichiers dont le contenu (SHA-256) a déjà été ingéré
+    /// pour le formulaire, cf. sourcefiles.rs
+    #[arg(long, alias = "incremental")]
+    skip_ingested: bool,
+    /// Réingérer même un contenu déjà ingéré (annule --skip-ingested)
+    #[arg(long)]
+    force_reimport: bool,
+    /// Référence déjà en base pour le formulaire: réécrire (update), laisser
+    /// intacte (skip: ajout seul) ou arrêter (error)
+    #[arg(long, value_enum, default_value_t = OnExisting::Update)]
+    on_existing: OnExisting,
+    /// Ligne qui enfreint une règle `rules:` du mapping: warn (compter),
+    /// error (arrêter), quarantine (écartée dans --rejects-dir), cf. rules.rs
+    #[arg(long, value_enum, default_value_t = rules::RulesMode::Warn)]
+    rules: rules::RulesMode,
+    /// Lignes en erreur tolérées par fichier (sautées); au-delà, fichier en
+    /// échec. 0: arrêt à la première erreur; négatif: sans plafond (cf. maxerrors.rs)
+    #[arg(long, default_value_t = 0, allow_negative_numbers = true)]
+    max_errors: i64,
+    /// Fichier en échec (--max-errors dépassé): arrêter le run au lieu de
+    /// passer au fichier suivant
+    #[arg(long)]
+    abort_on_file_error: bool,
+    /// Dossier des lignes écartées: `<dossier>/<chemin du fichier>.rejects.csv`, colonnes
+    /// d'origine + `_reject_reason` + `_source_line` (cf. rejects.rs)
+    #[arg(long, default_value = "rejects")]
+    rejects_dir: PathBuf,
+    /// Ne pas stocker raw_json (NULL; hash calculé sur les valeurs), cf. rawjson.rs
+    #[arg(long)]
+    no_raw: bool,
+    /// Fin d'ingestion: ANALYZE des tables écrites (statistiques du planificateur), cf. maintenance.rs
+    #[arg(long)]
+    post_ingest_analyze: bool,
+    /// Fin d'ingestion: VACUUM ANALYZE (place des lignes réécrites récupérée en plus)
+    #[arg(long)]
+    post_ingest_vacuum: bool,
+    /// Tables traitées par --post-ingest-*, séparées par des virgules (ex: answers,answer_options)
+    #[arg(long, value_delimiter = ',')]
+    vacuum_tables: Vec<String>,
+}
+
 #[derive(Deserialize, Debug)]
 struct Mapping {
     form: FormInfo,
     #[serde(default)]
     defaults: Defaults,
+    /// Réglages d'exécution par défaut (les flags CLI l'emportent)
+    #[serde(default)]
+    ingest: settings::IngestDefaults,
     questions: Vec<QuestionMap>,
+    /// colonnes du fichier volontairement non lues (pas signalées, cf. unmapped.rs)
+    #[serde(default)]
+    ignore_columns: Vec<String>,
+    /// codes de `lint` tolérés pour ce mapping (ex: M001), cf. lint.rs
+    #[serde(default)]
+    allow: Vec<String>,
 }
 
-#[derive(Deserialize, Debug, Default)]
+#[derive(Deserialize, Debug)]
 struct Defaults {
     #[serde(default)]
     author: AuthorMap,
     #[serde(default)]
     contribution: ContributionMap,
+    #[serde(default)]
+    raw_json: rawjson::RawJsonOptions,
+    /// plafond d'options créées dynamiquement par question (cf. QuestionMap)
+    #[serde(default = "default_max_dynamic_options")]
+    max_dynamic_options: i64,
+    /// CSV: lignes commençant par ce caractère ignorées (cf. --skip-comments)
+    #[serde(default)]
+    comment_char: Option<char>,
+    /// transformations des questions sans `transforms:` (cf. normalize.rs)
+    #[serde(default)]
+    transforms: Vec<normalize::Transform>,
+}
+
+fn default_max_dynamic_options() -> i64 { 500 }
+
+impl Default for Defaults {
+    fn default() -> Self {
+        Defaults {
+            author: AuthorMap::default(),
+            contribution: ContributionMap::default(),
+            raw_json: rawjson::RawJsonOptions::default(),
+            max_dynamic_options: default_max_dynamic_options(),
+            comment_char: None,
+            transforms: Vec::new(),
+        }
+    }
 }
 
 #[derive(Deserialize, Debug, Default, PartialEq)]
 struct AuthorMap {
-    source_author_id: Option<String>,
-    name: Option<String>,
-    email_hash: Option<String>,
-    zipcode: Option<String>,
-    city: Option<String>,
-    age_range: Option<String>,
-    gender: Option<String>,
+    source_author_id: Option<ColumnRef>,
+    name: Option<ColumnRef>,
+    /// colonne d'emails en clair: seul le hash salé est stocké (email_hash)
+    email: Option<ColumnRef>,
+    /// colonne déjà hachée, reprise telle quelle (prioritaire sur `email`)
+    email_hash: Option<ColumnRef>,
+    zipcode: Option<ColumnRef>,
+    city: Option<ColumnRef>,
+    age_range: Option<ColumnRef>,
+    gender: Option<ColumnRef>,
+    /// libellés d'âge propres à l'export → tranche canonique (cf. age.rs)
+    #[serde(default)]
+    age_range_map: BTreeMap<String, String>,
+    /// valeurs brutes de genre → code canonique (casse et accents ignorés)
+    #[serde(default)]
+    gender_map: BTreeMap<String, String>,
 }
 
 #[derive(Deserialize, Debug, Default)]
 struct ContributionMap {
-    source_contribution_id: Option<String>,
-    submitted_at: Option<String>,
-    title: Option<String>,
-    source: Option<String>,
+    source_contribution_id: Option<ColumnRef>,
+    submitted_at: Option<ColumnRef>,
+    title: Option<ColumnRef>,
+    source: Option<ColumnRef>,
+    /// dates plausibles de submitted_at (bornes incluses), ex: [2018-11-01, 2019-12-31]
+    #[serde(default)]
+    submitted_between: Option<[String; 2]>,
+    #[serde(default)]
+    out_of_window: WindowPolicy,
+    /// longueur maximale du titre en caractères (défaut: 255), au-delà tronqué
+    #[serde(default)]
+    title_max_chars: Option<usize>,
+    /// titre identique à la première réponse texte (text ou free_text): non stocké
+    #[serde(default)]
+    dedup_title_against_text: bool,
+}
+
+const DEFAULT_TITLE_MAX_CHARS: usize = 255;
+
+/// `defaults.contribution.out_of_window`: sort d'une contribution dont
+/// submitted_at sort de `submitted_between`
+#[derive(Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+enum WindowPolicy {
+    /// contribution écrite avec sa date, comptée dans le résumé
+    #[default]
+    KeepAndFlag,
+    /// contribution écrite sans date (submitted_at NULL)
+    NullTimestamp,
+    /// ligne ignorée (recopiée dans rejects/)
+    Skip,
+    /// ingestion arrêtée
+    Fail,
+}
+
+impl WindowPolicy {
+    /// Équivalent dans la politique d'erreurs (catégorie out_of_window)
+    fn action(self) -> policy::Action {
+        match self {
+            WindowPolicy::KeepAndFlag => policy::Action::Warn,
+            WindowPolicy::NullTimestamp => policy::Action::Drop,
+            WindowPolicy::Skip => policy::Action::Reject,
+            WindowPolicy::Fail => policy::Action::Abort,
+        }
+    }
+}
+
+impl ContributionMap {
+    /// Fenêtre à appliquer (aucune si submitted_at n'est pas mappé)
+    fn window(&self) -> Result<Option<values::DateWindow>, String> {
+        match (&self.submitted_at, &self.submitted_between) {
+            (Some(_), Some(bounds)) => values::DateWindow::parse(bounds).map(Some),
+            _ => Ok(None),
+        }
+    }
 }
 
 #[derive(Deserialize, Debug)]
@@ -97,6 +648,9 @@ struct FormInfo {
     version: Option<String>,
     #[serde(default)]
     source: Option<String>,
+    /// année de la consultation (années de naissance → tranches d'âge)
+    #[serde(default)]
+    reference_year: Option<u32>,
 }
 
 #[derive(Deserialize, Debug)]
@@ -105,7 +659,7 @@ struct QuestionMap {
     code: String,
     prompt: String,
     #[serde(rename = "type")]
-    qtype: String, // "text", "free_text", "single_choice", "multi_choice", "number","scale","date"
+    qtype: String, // "text", "free_text", "single_choice", "multi_choice", "ranking", "matrix", "number","scale","date","boolean"
     #[serde(default)]
     section: Option<String>,
     #[serde(default)]
@@ -115,9 +669,26 @@ struct QuestionMap {
 
     // text/number/scale/date/single_choice (source unique)
     #[serde(default)]
-    source_column: Option<String>,
+    source_column: Option<ColumnRef>,
+    /// colonne(s) absente(s) d'un fichier: avertissement et question ignorée
+    /// pour ce fichier, au lieu d'une erreur (cf. validate::required_columns)
+    #[serde(default)]
+    optional_column: bool,
+    /// question conditionnelle: cellule brute d'une autre colonne (cf. normalize.rs)
+    #[serde(default)]
+    if_column: Option<String>,
+    #[serde(default)]
+    if_value: Option<String>,
+    #[serde(default)]
+    if_not_value: Option<String>,
+    #[serde(default)]
+    if_regex: Option<String>,
+    /// valeur substituée à une cellule vide ou une colonne absente
+    /// (single_choice/multi_choice: après value_map, avant la recherche d'option)
+    #[serde(default)]
+    default_value: Option<String>,
 
-    // free_text (concat colonnes)
+    // free_text (concat colonnes) / ranking (colonnes de rang, dans l'ordre)
     #[serde(default)]
     source: Option<FreeTextSource>,
 
@@ -125,20 +696,334 @@ struct QuestionMap {
     #[serde(default)]
     options: Vec<OptionSpec>,
 
+    // matrix: une sous-question par ligne de grille, options partagées
+    #[serde(default)]
+    rows: Vec<MatrixRow>,
+
     // dynamiques
     #[serde(default)]
     options_from_values: bool,
+    /// plafond d'options dynamiques de la question (défaut: defaults.max_dynamic_options)
+    #[serde(default)]
+    max_dynamic_options: Option<i64>,
     #[serde(default)]
     delimiter: Option<String>,
+
+    // transformations puis règles regex appliquées à la valeur brute (cf. normalize.rs);
+    // transforms absent: defaults.transforms
+    #[serde(default)]
+    transforms: Option<Vec<normalize::Transform>>,
+    #[serde(default)]
+    normalize: Vec<normalize::NormalizeRule>,
+    #[serde(default = "default_true")]
+    normalize_trim: bool,
+    /// contrôles de qualité de la cellule (required, pattern, max_length,
+    /// allowed_values), cf. rules.rs et --rules
+    #[serde(default)]
+    rules: Option<rules::Rules>,
+
+    // single_choice/multi_choice: valeur brute → libellé (ou code) d'option, avant recherche
+    #[serde(default)]
+    value_map: HashMap<String, String>,
+    #[serde(default)]
+    value_map_case_insensitive: bool,
+
+    // single_choice: commentaire accolé au choix ("Oui — mais seulement si…")
+    #[serde(default)]
+    split_comment: Option<cells::SplitComment>,
+
+    // date: formats strftime essayés dans l'ordre (défaut: voir values.rs)
+    #[serde(default)]
+    date_formats: Option<Vec<String>>,
+    #[serde(default = "default_true")]
+    day_first: bool,
+    // date: format principal (+ repli) → texte réécrit en ISO-8601 (%Y-%m-%d)
+    #[serde(default)]
+    date_format: Option<String>,
+    #[serde(default)]
+    date_format_fallbacks: Vec<String>,
+
+    // cellule vide d'une question vue → answer `skipped` (sinon rien n'est écrit)
+    #[serde(default)]
+    record_skips: bool,
+
+    // scale: bornes entières et politique hors bornes
+    #[serde(default)]
+    scale_min: Option<i64>,
+    #[serde(default)]
+    scale_max: Option<i64>,
+    #[serde(default)]
+    on_out_of_range: OutOfRange,
+
+    // number: plage plausible (cf. --range-violations); scale: scale_min/scale_max
+    #[serde(default)]
+    min: Option<f64>,
+    #[serde(default)]
+    max: Option<f64>,
+
+    // boolean: valeurs reconnues (défauts FR dans values.rs) et état "unknown"
+    #[serde(default)]
+    true_values: Option<Vec<String>>,
+    #[serde(default)]
+    false_values: Option<Vec<String>>,
+    #[serde(default)]
+    unknown_values: Option<Vec<String>>,
+    #[serde(default)]
+    allow_unknown: bool,
+}
+
+/// Types de questions pris en charge (`QuestionMap.qtype`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum QType {
+    Text,
+    FreeText,
+    SingleChoice,
+    MultiChoice,
+    Ranking,
+    Matrix,
+    Number,
+    Scale,
+    Date,
+    Boolean,
+}
+
+impl QType {
+    const ALL: [QType; 10] = [
+        QType::Text,
+        QType::FreeText,
+        QType::SingleChoice,
+        QType::MultiChoice,
+        QType::Ranking,
+        QType::Matrix,
+        QType::Number,
+        QType::Scale,
+        QType::Date,
+        QType::Boolean,
+    ];
+
+    fn as_str(self) -> &'static str {
+        match self {
+            QType::Text => "text",
+            QType::FreeText => "free_text",
+            QType::SingleChoice => "single_choice",
+            QType::MultiChoice => "multi_choice",
+            QType::Ranking => "ranking",
+            QType::Matrix => "matrix",
+            QType::Number => "number",
+            QType::Scale => "scale",
+            QType::Date => "date",
+            QType::Boolean => "boolean",
+        }
+    }
+
+    fn parse(s: &str) -> Option<QType> {
+        QType::ALL.into_iter().find(|t| t.as_str() == s)
+    }
+}
+
+#[derive(Deserialize, Debug, Default, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+enum OutOfRange {
+    /// Ramener la valeur dans les bornes
+    Clamp,
+    /// Ne pas écrire de réponse
+    #[default]
+    Skip,
+    /// Interrompre l'ingestion
+    Error,
+}
+
+/// `--range-violations`: sort d'une valeur number hors de [min, max]
+#[derive(Clone, Copy, Debug, Default, PartialEq, clap::ValueEnum)]
+enum RangePolicy {
+    /// réponse écrite, violation comptée
+    #[default]
+    Warn,
+    /// ingestion arrêtée à la première violation
+    Error,
+    /// réponse non écrite
+    Drop,
+}
+
+impl RangePolicy {
+    /// Équivalent dans la politique d'erreurs (catégorie out_of_range)
+    fn action(self) -> policy::Action {
+        match self {
+            RangePolicy::Warn => policy::Action::Warn,
+            RangePolicy::Error => policy::Action::Abort,
+            RangePolicy::Drop => policy::Action::Drop,
+        }
+    }
+}
+
+/// Valeurs hors de [min, max] d'une question, cumulées sur les lignes
+#[derive(Debug, Default, Clone, Copy)]
+struct RangeViolation {
+    count: usize,
+    /// plus petite et plus grande valeur fautive
+    extremes: Option<(f64, f64)>,
+}
+
+impl RangeViolation {
+    fn note(&mut self, v: f64) {
+        self.count += 1;
+        self.extremes = Some(self.extremes.map_or((v, v), |(lo, hi)| (lo.min(v), hi.max(v))));
+    }
+
+    fn merge(&mut self, other: RangeViolation) {
+        self.count += other.count;
+        self.extremes = match (self.extremes, other.extremes) {
+            (Some((a, b)), Some((c, d))) => Some((a.min(c), b.max(d))),
+            (a, b) => a.or(b),
+        };
+    }
+
+    fn line(&self, qm: &QuestionMap) -> String {
+        let (lo, hi) = self.extremes.unwrap_or_default();
+        format!("  {:<24} {:>8} hors de {} (min {lo}, max {hi})", qm.code, self.count, qm.range_label())
+    }
+}
+
+/// Décision pour une valeur d'échelle (partagée par l'ingestion et explain)
+#[derive(Debug, PartialEq)]
+enum ScaleOutcome {
+    InRange(i64),
+    /// Hors bornes, ramenée dans [scale_min, scale_max]
+    Clamped(i64),
+    /// Hors bornes ou illisible (`None`): pas de réponse
+    Skipped(Option<i64>),
+    /// Hors bornes ou illisible avec `on_out_of_range: error`
+    Error(Option<i64>),
+}
+
+impl QuestionMap {
+    /// Type pris en charge, `None` si inconnu
+    fn kind(&self) -> Option<QType> {
+        QType::parse(&self.qtype)
+    }
+
+    /// multi_choice "une colonne par option": les options déclarent leur source_column
+    /// (validate_mapping exige alors qu'elles le fassent toutes)
+    fn options_from_columns(&self) -> bool {
+        !self.options.is_empty() && self.options.iter().any(|o| o.source_column.is_some())
+    }
+
+    /// Formats de date de la question (ou défauts selon `day_first`)
+    fn date_formats(&self) -> Vec<String> {
+        if let Some(f) = &self.date_format {
+            return std::iter::once(f).chain(&self.date_format_fallbacks).cloned().collect();
+        }
+        match &self.date_formats {
+            Some(f) => f.clone(),
+            None if self.day_first => values::DEFAULT_DATE_FORMATS_DAY_FIRST.iter().map(|s| s.to_string()).collect(),
+            None => values::DEFAULT_DATE_FORMATS_MONTH_FIRST.iter().map(|s| s.to_string()).collect(),
+        }
+    }
+
+    /// matrix: (code de la sous-question `parent.ligne`, ligne)
+    fn matrix_children(&self) -> impl Iterator<Item = (String, &MatrixRow)> {
+        self.rows.iter().map(move |r| (cells::child_code(&self.code, &r.code), r))
+    }
+
+    /// date: texte stocké (ISO-8601 si `date_format` est donné et la date lue)
+    fn date_text<'r>(&self, raw: &'r str, parsed: Option<chrono::NaiveDateTime>) -> Cow<'r, str> {
+        match parsed {
+            Some(d) if self.date_format.is_some() => Cow::Owned(d.format("%Y-%m-%d").to_string()),
+            _ => Cow::Borrowed(raw),
+        }
+    }
+
+    /// default_value, pour une cellule vide ou une colonne absente
+    fn default_cell(&self) -> Option<Cow<'_, str>> {
+        self.default_value.as_deref().map(Cow::Borrowed)
+    }
+
+    fn value_map(&self) -> values::ValueMap {
+        values::ValueMap::new(&self.value_map, self.value_map_case_insensitive)
+    }
+
+    /// Séparateur des valeurs multiples (multi_choice en une colonne)
+    fn multi_delimiter(&self) -> &str {
+        self.delimiter.as_deref().unwrap_or(cells::DEFAULT_DELIMITER)
+    }
+
+    /// free_text: cellules non vides des colonnes sources, jointes
+    fn free_text_value(&self, row: &dyn ColumnAccessor) -> Option<String> {
+        let src = self.source.as_ref()?;
+        cells::join_non_empty(src.columns.iter().map(|col| row.cell(col)), &src.joiner)
+    }
+
+    /// Plafond d'options dynamiques effectif
+    fn dynamic_limit(&self, defaults: &Defaults) -> i64 {
+        self.max_dynamic_options.unwrap_or(defaults.max_dynamic_options)
+    }
+
+    /// number: valeur hors de [min, max] (bornes absentes: pas de limite)
+    fn violates_range(&self, v: f64) -> bool {
+        self.min.is_some_and(|min| v < min) || self.max.is_some_and(|max| v > max)
+    }
+
+    fn range_label(&self) -> String {
+        let bound = |b: Option<f64>| b.map_or("…".to_string(), |b| b.to_string());
+        format!("[{}, {}]", bound(self.min), bound(self.max))
+    }
+
+    fn scale_outcome(&self, raw: &str) -> ScaleOutcome {
+        let (min, max) = (self.scale_min.unwrap_or(i64::MIN), self.scale_max.unwrap_or(i64::MAX));
+        match values::parse_integer(raw) {
+            Some(v) if (min..=max).contains(&v) => ScaleOutcome::InRange(v),
+            Some(v) => match self.on_out_of_range {
+                OutOfRange::Clamp => ScaleOutcome::Clamped(v.clamp(min, max)),
+                OutOfRange::Skip => ScaleOutcome::Skipped(Some(v)),
+                OutOfRange::Error => ScaleOutcome::Error(Some(v)),
+            },
+            None if self.on_out_of_range == OutOfRange::Error => ScaleOutcome::Error(None),
+            None => ScaleOutcome::Skipped(None),
+        }
+    }
+
+    /// Jeux oui/non/NSP d'une question boolean
+    fn boolean_values(&self) -> values::BooleanValues {
+        values::BooleanValues::new(
+            self.true_values.as_deref(),
+            self.false_values.as_deref(),
+            self.unknown_values.as_deref(),
+        )
+    }
+
+    /// Valeurs considérées comme cochées (`meta.truthy_values`, insensible à la casse)
+    fn truthy_values(&self) -> Vec<String> {
+        self.meta
+            .as_ref()
+            .and_then(|m| m.get("truthy_values"))
+            .and_then(|v| v.as_array())
+            .map(|arr| {
+                arr.iter()
+                    .filter_map(|v| v.as_str())
+                    .map(|s| s.trim().to_lowercase())
+                    .collect()
+            })
+            .unwrap_or_else(|| DEFAULT_TRUTHY.iter().map(|s| s.to_string()).collect())
+    }
 }
 
+const DEFAULT_TRUTHY: [&str; 6] = ["1", "true", "oui", "yes", "vrai", "x"];
+
 #[derive(Deserialize, Debug)]
 struct FreeTextSource {
-    columns: Vec<String>,
+    columns: Vec<ColumnRef>,
     #[serde(default = "default_joiner")]
     joiner: String,
 }
 fn default_joiner() -> String { "\n\n".into() }
+fn default_true() -> bool { true }
+
+#[derive(Deserialize, Debug)]
+struct MatrixRow {
+    code: String,
+    label: String,
+    source_column: String,
+}
 
 #[derive(Deserialize, Debug)]
 struct OptionSpec {
@@ -156,14 +1041,58 @@ fn main() -> Result<()> {
     dotenv::dotenv().ok(); // Charger .env si disponible
     
     let cli = Cli::parse();
-    match cli.cmd {
-        Cmd::Ingest { csv, mapping, batch, commit_every, log_every, delimiter, dry_run } => {
-           run_ingest(csv, mapping, batch, commit_every, log_every, delimiter, dry_run)
+    tls::configure(cli.db_tls, cli.db_ca_cert);
+    session::configure(session::SessionOpts {
+        statement_timeout_ms: cli.db_statement_timeout_ms,
+        lock_timeout_ms: cli.db_lock_timeout_ms,
+        schema: cli.schema,
+    });
+    let res = match cli.cmd {
+        Cmd::Ingest(args) => run_ingest(*args),
+        Cmd::Validate { csv, mapping, delimiter, normalize_option_codes, sample_rows, format } => {
+            validate::run_validate(&csv, &mapping, delimiter, normalize_option_codes, sample_rows, format)
+        }
+        Cmd::Lint { mapping, csv, delimiter, sample_rows, format } => {
+            lint::run_lint(&mapping, &csv, delimiter, sample_rows, format)
+        }
+        Cmd::Explain { mapping, header, row, delimiter } => explain::run_explain(&mapping, &header, &row, delimiter),
+        Cmd::Export { form, version, output, batch, format, exclude_deleted } => {
+            export::run_export(&form, version.as_deref(), output.as_deref(), batch.as_deref(), format, exclude_deleted)
+        }
+        Cmd::Stats { form, version, mapping, top, json, include_deleted, batch } => {
+            stats::run_stats(stats::StatsArgs { form, version, mapping, top, json, include_deleted, batch })
         }
+        Cmd::MergeForms { apply } => forms::run_merge_forms(apply),
+        Cmd::DictionaryCompact { max_chars, chunk, apply } => dictionary::run_dictionary_compact(max_chars, chunk, apply),
+        Cmd::DeleteBatch { batch, dry_run, include_updated, chunk } => {
+            rollback::run_delete_batch(&batch, dry_run, include_updated, chunk)
+        }
+        Cmd::GenMapping { csv, output } => genmapping::run_gen_mapping(&csv, &output),
+        Cmd::Ping => pool::run_ping(),
+        Cmd::Erase { email_hash, erase_answers, dry_run } => gdpr::run_erase(&email_hash, erase_answers, dry_run),
+        Cmd::SoftDelete { form, version, source_contribution_ids, reason } => {
+            softdelete::run_soft_delete(&form, version.as_deref(), &source_contribution_ids, reason.as_deref())
+        }
+        Cmd::Pseudonymize { salt, dry_run, force } => pseudonymize::run_pseudonymize(&salt, force, dry_run),
+        Cmd::ExportSubject { email_hash, output, format, redact_fields } => {
+            subject::run_export_subject(&email_hash, output.as_deref(), format, &redact_fields)
+        }
+        Cmd::DbInit { if_not_exists, verbose } => dbinit::run_db_init(if_not_exists, verbose),
+        Cmd::DbDrop { cascade, apply, verbose } => dbinit::run_db_drop(cascade, apply, verbose),
+    };
+    // formulaire verrouillé: code distinct, à réessayer plus tard
+    if let Some(locked) = res.as_ref().err().and_then(|e| e.downcast_ref::<runlock::Locked>()) {
+        eprintln!("Error: {locked}");
+        std::process::exit(runlock::EXIT_LOCKED);
     }
+    if let Some(hint) = res.as_ref().err().and_then(session::timeout_hint) {
+        eprintln!("⏱️  {hint}");
+    }
+    res
 }
 
-fn get_database_url() -> Result<String> {
+/// URL de connexion et sslmode qu'elle demande (retiré de l'URL, cf. tls.rs)
+fn get_database_url() -> Result<(String, Option<tls::TlsMode>)> {
     env::var("DATABASE_URL")
         .with_context(|| "DATABASE_URL manquante dans .env")
         .and_then(|url| {
@@ -172,45 +1101,72 @@ fn get_database_url() -> Result<String> {
                 let clean_url = url
                     .replace("postgresql+psycopg2://", "postgres://")
                     .replace("postgresql://", "postgres://");
-                Ok(clean_url)
+                tls::split_sslmode(&clean_url)
+            } else if tls::is_key_value(&url) {
+                // forme libpq `host=… dbname=…`
+                tls::split_sslmode(&url)
             } else {
-                anyhow::bail!("DATABASE_URL doit commencer par 'postgresql' ou 'postgres', trouvé: {}", url)
+                anyhow::bail!("DATABASE_URL doit commencer par 'postgresql' ou 'postgres' (ou être de la forme host=… dbname=…), trouvé: {}", url)
             }
         })
 }
 
 fn open_conn() -> Result<Client> {
-    let db_url = get_database_url()?;
-    println!("[db] Connexion à PostgreSQL via .env");
-    let client = Client::connect(&db_url, NoTls)?;
+    let (db_url, url_mode) = get_database_url()?;
+    let mode = tls::resolve(url_mode);
+    println!("[db] Connexion à PostgreSQL via .env (TLS: {})", mode.as_str());
+    let mut client = tls::open_conn_tls(&db_url, mode)?;
+    session::configure_session(&mut client, &session::opts())?;
     Ok(client)
 }
 
-fn sniff_delimiter<R: Read>(mut r: R) -> std::io::Result<(Vec<u8>, u8)> {
-    use std::io::Read;
-    let mut buf = vec![0u8; 8192];
-    let n = r.read(&mut buf)?;
-    buf.truncate(n);
-    let sample = std::str::from_utf8(&buf).unwrap_or("");
-    let count = |c: char| sample.matches(c).count();
-    let mut best = (count(','), b',');
-    for (c, b) in [( ';', b';'), ('\t', b'\t')] {
-        let k = count(c);
-        if k > best.0 { best = (k, b); }
-    }
-    Ok((buf, best.1))
-}
-
 // ---------- Validation préventive ----------
 
-fn validate_mapping(mapping: &Mapping) -> Result<()> {
+fn validate_mapping(mapping: &Mapping, allow_unknown_types: bool) -> Result<()> {
     println!("[validation] Vérification de la configuration YAML...");
+    let (errors, warnings) = mapping_problems(mapping, allow_unknown_types);
+
+    // Affichage résultats
+    if !warnings.is_empty() {
+        println!("[validation] ⚠️  {} avertissements:", warnings.len());
+        for w in warnings {
+            println!("  {}", w);
+        }
+    }
+    
+    if !errors.is_empty() {
+        println!("[validation] ❌ {} erreurs critiques:", errors.len());
+        for e in errors {
+            println!("  {}", e);
+        }
+        anyhow::bail!("Configuration YAML invalide - corrigez les erreurs ci-dessus");
+    }
     
+    println!("[validation] ✅ Configuration validée");
+    Ok(())
+}
+
+/// Erreurs et avertissements du mapping, sans rien afficher (cf. validate --format json)
+fn mapping_problems(mapping: &Mapping, allow_unknown_types: bool) -> (Vec<String>, Vec<String>) {
     let mut errors = Vec::new();
     let mut warnings = Vec::new();
+    if mapping.defaults.transforms.iter().any(|t| matches!(t, normalize::Transform::Replace { from, .. } if from.is_empty())) {
+        errors.push("defaults.transforms: replace avec `from` vide".to_string());
+    }
     
     for (i, qm) in mapping.questions.iter().enumerate() {
         let qpos = format!("question[{}] '{}' ({})", i, qm.code, qm.qtype);
+
+        // Type inconnu: erreur (ou question ignorée avec --allow-unknown-types)
+        if qm.kind().is_none() {
+            let valid: Vec<&str> = QType::ALL.iter().map(|t| t.as_str()).collect();
+            let msg = format!("{}: type '{}' inconnu (valeurs possibles: {})", qpos, qm.qtype, valid.join(", "));
+            if allow_unknown_types {
+                warnings.push(format!("{msg} → question IGNORÉE (--allow-unknown-types)"));
+            } else {
+                errors.push(msg);
+            }
+        }
         
         // ⚠️ VALIDATION CRITIQUE: single_choice avec options_from_values
         if qm.qtype == "single_choice" {
@@ -220,12 +1176,10 @@ fn validate_mapping(mapping: &Mapping) -> Result<()> {
                         "{}: single_choice + options_from_values=true SANS options prédéfinies!",
                         qpos
                     ));
-                    errors.push(format!(
-                        "  → RISQUE: Chaque réponse unique créera une option séparée"
-                    ));
-                    errors.push(format!(
-                        "  → SOLUTION: Ajouter des options prédéfinies OU utiliser options_from_values=false"
-                    ));
+                    errors.push("  → RISQUE: Chaque réponse unique créera une option séparée".to_string());
+                    errors.push(
+                        "  → SOLUTION: Ajouter des options prédéfinies OU utiliser options_from_values=false".to_string(),
+                    );
                 } else {
                     warnings.push(format!(
                         "{}: single_choice + options_from_values=true avec {} options définies",
@@ -239,111 +1193,437 @@ fn validate_mapping(mapping: &Mapping) -> Result<()> {
             }
         }
         
-        // Validation multi_choice
-        if qm.qtype == "multi_choice" {
-            if !qm.options_from_values && qm.options.is_empty() {
+        // Options déclarées: codes et libellés uniques (sinon écrasées par l'ON CONFLICT)
+        for dup in options::duplicate_declared(qm) {
+            errors.push(format!("{}: {}", qpos, dup));
+        }
+
+        // Codes déclarés: même format que les codes dynamiques (slug). Avertissement
+        // seulement: des mappings livrés ont des codes déjà en base (options.code),
+        // qu'une réécriture ici dédoublerait tant qu'ils ne sont pas migrés
+        for opt in qm.options.iter().filter(|o| !options::is_normalized(&o.code)) {
+            warnings.push(format!(
+                "{}: code d'option '{}' non normalisé (format slug: '{}'), à migrer avec options.code en base",
+                qpos, opt.code, options::normalize_code(&opt.code)
+            ));
+        }
+
+        // Validation multi_choice
+        if qm.qtype == "multi_choice" {
+            if !qm.options_from_values && qm.options.is_empty() {
                 errors.push(format!("{}: multi_choice sans options ni options_from_values", qpos));
             }
+            if qm.options_from_columns() {
+                if qm.source_column.is_some() {
+                    errors.push(format!(
+                        "{}: source_column de question incompatible avec des options portant leur propre source_column",
+                        qpos
+                    ));
+                }
+                // Mode une colonne par option: chaque option doit avoir sa colonne
+                for opt in qm.options.iter().filter(|o| o.source_column.is_none()) {
+                    errors.push(format!(
+                        "{}: option '{}' sans source_column (mode une colonne par option)",
+                        qpos, opt.code
+                    ));
+                }
+            } else if qm.source_column.is_none() {
+                errors.push(format!("{}: multi_choice nécessite source_column", qpos));
+            }
         }
         
-        // Validation free_text
-        if qm.qtype == "free_text" {
-            if qm.source.is_none() {
-                errors.push(format!("{}: free_text nécessite 'source.columns'", qpos));
+        // Validation ranking: colonnes de rang ordonnées + options
+        if qm.qtype == "ranking" {
+            match &qm.source {
+                Some(src) if !src.columns.is_empty() => {}
+                _ => errors.push(format!("{}: ranking nécessite 'source.columns' (une colonne par rang)", qpos)),
             }
+            if !qm.options_from_values && qm.options.is_empty() {
+                errors.push(format!("{}: ranking sans options ni options_from_values", qpos));
+            }
+        }
+
+        // Validation matrix: lignes avec colonne source, options partagées
+        if qm.qtype == "matrix" {
+            if qm.rows.is_empty() {
+                errors.push(format!("{}: matrix nécessite 'rows' (code, label, source_column)", qpos));
+            }
+            if !qm.options_from_values && qm.options.is_empty() {
+                errors.push(format!("{}: matrix sans options ni options_from_values", qpos));
+            }
+            let mut seen = HashSet::new();
+            for row in &qm.rows {
+                if !seen.insert(row.code.as_str()) {
+                    errors.push(format!("{}: ligne '{}' déclarée plusieurs fois", qpos, row.code));
+                }
+            }
+        }
+
+        // Validation free_text
+        if qm.qtype == "free_text" && qm.source.is_none() {
+            errors.push(format!("{}: free_text nécessite 'source.columns'", qpos));
         }
         
-        // Validation colonnes source standard
-        if matches!(qm.qtype.as_str(), "text" | "number" | "scale" | "date") {
-            if qm.source_column.is_none() {
-                errors.push(format!("{}: {} nécessite source_column", qpos, qm.qtype));
+        // Validation scale: bornes obligatoires et cohérentes
+        if qm.qtype == "scale" {
+            match (qm.scale_min, qm.scale_max) {
+                (Some(min), Some(max)) if min > max => {
+                    errors.push(format!("{}: scale_min ({}) > scale_max ({})", qpos, min, max));
+                }
+                (Some(_), Some(_)) => {}
+                _ => errors.push(format!("{}: scale nécessite scale_min et scale_max", qpos)),
+            }
+            // deux jeux de bornes pour une même échelle: un seul fait foi
+            if qm.min.is_some() || qm.max.is_some() {
+                errors.push(format!(
+                    "{}: min/max et scale_min/scale_max sont exclusifs (scale: bornes scale_min/scale_max, hors bornes: on_out_of_range)",
+                    qpos
+                ));
+            }
+        }
+
+        // Règles de normalisation: motifs compilables, types à cellule unique
+        for rule in &qm.normalize {
+            if let Err(e) = Regex::new(&rule.pattern) {
+                errors.push(format!("{}: normalize: motif '{}' invalide ({})", qpos, rule.pattern, e));
+            }
+        }
+        let single_cell = matches!(qm.qtype.as_str(), "text" | "number" | "date" | "boolean" | "scale" | "single_choice")
+            || (qm.qtype == "multi_choice" && !qm.options_from_columns());
+        if !qm.normalize.is_empty() && !single_cell {
+            warnings.push(format!("{}: normalize ignoré (réservé aux questions à cellule unique)", qpos));
+        }
+        if qm.transforms.as_ref().is_some_and(|t| !t.is_empty()) && !single_cell {
+            warnings.push(format!("{}: transforms ignoré (réservé aux questions à cellule unique)", qpos));
+        }
+        if qm.transforms.iter().flatten().any(|t| matches!(t, normalize::Transform::Replace { from, .. } if from.is_empty())) {
+            errors.push(format!("{}: transforms: replace avec `from` vide", qpos));
+        }
+
+        // Règles de qualité: motif compilable, cellule unique
+        if let Some(rules) = &qm.rules {
+            errors.extend(rules.problems(qm).into_iter().map(|p| format!("{}: {}", qpos, p)));
+        }
+
+        // Table de traduction des valeurs
+        if !qm.value_map.is_empty() {
+            if !matches!(qm.qtype.as_str(), "single_choice" | "multi_choice") {
+                warnings.push(format!("{}: value_map ignorée (réservée à single_choice et multi_choice)", qpos));
+            }
+            if qm.value_map_case_insensitive {
+                let mut seen: HashMap<String, &str> = HashMap::new();
+                for (k, v) in &qm.value_map {
+                    match seen.insert(k.trim().to_lowercase(), v) {
+                        Some(other) if other != v => errors.push(format!(
+                            "{}: value_map: '{}' traduite à la fois en '{}' et '{}' (casse ignorée)",
+                            qpos, k.trim().to_lowercase(), other, v
+                        )),
+                        _ => {}
+                    }
+                }
+            }
+            if !qm.options_from_values && !qm.options.is_empty() {
+                for v in qm.value_map.values() {
+                    let v = v.trim();
+                    if !qm.options.iter().any(|o| o.label == v || o.code == v) {
+                        warnings.push(format!("{}: value_map → '{}' ne correspond à aucune option déclarée", qpos, v));
+                    }
+                }
+            }
+        }
+
+        // Question conditionnelle: colonne lue par une autre question, condition donnée
+        let conditions = [&qm.if_value, &qm.if_not_value, &qm.if_regex].iter().filter(|c| c.is_some()).count();
+        match &qm.if_column {
+            None if conditions > 0 => {
+                errors.push(format!("{}: if_value/if_not_value/if_regex nécessitent if_column", qpos));
+            }
+            None => {}
+            Some(col) => {
+                if conditions == 0 {
+                    errors.push(format!("{}: if_column '{}' sans if_value, if_not_value ni if_regex", qpos, col));
+                }
+                let mapped = mapping.questions.iter()
+                    .filter(|other| other.code != qm.code)
+                    .any(|other| validate::question_columns(other).any(|c| c == col));
+                if !mapped {
+                    errors.push(format!("{}: if_column '{}' n'est la source_column d'aucune autre question", qpos, col));
+                }
+                if let Some(Err(e)) = qm.if_regex.as_deref().map(Regex::new) {
+                    errors.push(format!("{}: if_regex invalide ({})", qpos, e));
+                }
+            }
+        }
+
+        // Valeur par défaut
+        if let Some(d) = &qm.default_value {
+            if !single_cell {
+                warnings.push(format!("{}: default_value ignorée (réservée aux questions à cellule unique)", qpos));
+            } else if qm.qtype == "single_choice"
+                && !qm.options_from_values
+                && !qm.options.iter().any(|o| o.label == d.trim() || o.code == d.trim())
+            {
+                warnings.push(format!(
+                    "{}: default_value '{}' ne correspond à aucune option déclarée (option dynamique créée pour chaque ligne sans valeur)",
+                    qpos, d
+                ));
+            }
+        }
+
+        // Plafond d'options dynamiques
+        if let Some(max) = qm.max_dynamic_options {
+            if max < 1 {
+                errors.push(format!("{}: max_dynamic_options doit être ≥ 1", qpos));
+            } else if !qm.options_from_values {
+                warnings.push(format!(
+                    "{}: max_dynamic_options sans options_from_values (options bornées par le YAML)",
+                    qpos
+                ));
+            }
+        }
+
+        // Commentaire accolé (single_choice)
+        if let Some(split) = &qm.split_comment {
+            if qm.qtype != "single_choice" {
+                warnings.push(format!("{}: split_comment ignoré (réservé à single_choice)", qpos));
+            }
+            if let Err(e) = split.compile() {
+                errors.push(format!("{}: split_comment invalide ({})", qpos, e));
+            }
+        }
+
+        // Plage number
+        if let (Some(min), Some(max)) = (qm.min, qm.max) {
+            if min > max {
+                warnings.push(format!("{}: min ({}) > max ({}) → toute valeur sera hors plage", qpos, min, max));
+            }
+        }
+        if let Some(max) = qm.scale_max.filter(|&m| qm.qtype == "scale" && m > 10_000) {
+            warnings.push(format!("{}: maximum d'échelle {} inhabituellement grand (> 10000)", qpos, max));
+        }
+        if (qm.min.is_some() || qm.max.is_some()) && !matches!(qm.qtype.as_str(), "number" | "scale") {
+            warnings.push(format!("{}: min/max ignorés (réservés à number)", qpos));
+        }
+
+        // Validation boolean: une valeur ne peut appartenir qu'à un seul jeu
+        if qm.qtype == "boolean" {
+            let overlaps = qm.boolean_values().overlaps();
+            if !overlaps.is_empty() {
+                errors.push(format!("{}: valeurs à la fois dans plusieurs jeux oui/non/NSP: {:?}", qpos, overlaps));
+            }
+        }
+
+        // date: formats strftime vérifiés sur une date témoin
+        if qm.qtype == "date" {
+            if qm.date_format.is_some() && qm.date_formats.is_some() {
+                errors.push(format!("{}: date_format et date_formats sont exclusifs", qpos));
+            }
+            if qm.date_format.is_none() && !qm.date_format_fallbacks.is_empty() {
+                warnings.push(format!("{}: date_format_fallbacks sans date_format → ignorés", qpos));
+            }
+            for f in qm.date_format.iter().chain(&qm.date_format_fallbacks) {
+                if let Err(e) = values::check_date_format(f) {
+                    errors.push(format!("{}: date_format '{}' invalide ({})", qpos, f, e));
+                }
             }
         }
+
+        // Validation colonnes source standard
+        if matches!(qm.qtype.as_str(), "text" | "number" | "scale" | "date" | "boolean") && qm.source_column.is_none() {
+            errors.push(format!("{}: {} nécessite source_column", qpos, qm.qtype));
+        }
     }
     
-    // Affichage résultats
-    if !warnings.is_empty() {
-        println!("[validation] ⚠️  {} avertissements:", warnings.len());
-        for w in warnings {
-            println!("  {}", w);
+    // Copier-coller de question: codes en double, colonnes lues deux fois
+    let (dup_errors, dup_warnings) = validate::duplicate_questions(mapping);
+    errors.extend(dup_errors);
+    warnings.extend(dup_warnings);
+
+    // Tranches d'âge
+    let author = &mapping.defaults.author;
+    for (label, bucket) in &author.age_range_map {
+        if !age::is_bucket(bucket) {
+            let valid: Vec<&str> = age::BUCKETS.iter().map(|b| b.2).collect();
+            errors.push(format!(
+                "defaults.author.age_range_map: '{label}' → '{bucket}' n'est pas une tranche ({})",
+                valid.join(", ")
+            ));
         }
     }
-    
-    if !errors.is_empty() {
-        println!("[validation] ❌ {} erreurs critiques:", errors.len());
-        for e in errors {
-            println!("  {}", e);
+    if !author.gender_map.is_empty() && author.gender.is_none() {
+        warnings.push("defaults.author.gender_map sans colonne gender → ignorée".to_string());
+    }
+    if author.age_range.is_some() && mapping.form.reference_year.is_none() {
+        warnings.push("defaults.author.age_range sans form.reference_year → années de naissance conservées telles quelles".to_string());
+    }
+
+    // Fenêtre plausible de submitted_at
+    let contribution = &mapping.defaults.contribution;
+    if let Err(e) = contribution.window() {
+        errors.push(format!("defaults.contribution.submitted_between: {e}"));
+    }
+    if contribution.submitted_between.is_some() && contribution.submitted_at.is_none() {
+        warnings.push("defaults.contribution.submitted_between sans submitted_at → fenêtre ignorée".to_string());
+    }
+    if contribution.title_max_chars == Some(0) {
+        errors.push("defaults.contribution.title_max_chars: doit être ≥ 1".to_string());
+    }
+    if contribution.dedup_title_against_text {
+        if contribution.title.is_none() {
+            warnings.push("defaults.contribution.dedup_title_against_text sans title → ignoré".to_string());
+        } else if !mapping.questions.iter().any(|qm| matches!(qm.qtype.as_str(), "text" | "free_text")) {
+            warnings.push("defaults.contribution.dedup_title_against_text: aucune question text/free_text → ignoré".to_string());
         }
-        anyhow::bail!("Configuration YAML invalide - corrigez les erreurs ci-dessus");
     }
-    
-    println!("[validation] ✅ Configuration validée");
-    Ok(())
+
+    if mapping.defaults.max_dynamic_options < 1 {
+        errors.push("defaults.max_dynamic_options: doit être ≥ 1".to_string());
+    }
+    if let Some(c) = mapping.defaults.comment_char {
+        if !c.is_ascii() || c.is_ascii_whitespace() || c == '"' {
+            errors.push(format!("defaults.comment_char: '{c}' invalide (caractère ASCII visible, hors guillemet)"));
+        }
+    }
+
+    // Section `ingest:` (réglages d'exécution)
+    errors.extend(mapping.ingest.problems());
+    (errors, warnings)
 }
 
 // ---------- Helpers SQL (PostgreSQL) ----------
 
+#[derive(Clone)]
 struct Caches {
     qid_by_code: HashMap<String, i64>,
     opt_by_qid_label: HashMap<(i64, String), i64>,
+    opt_by_qid_code: HashMap<(i64, String), i64>,
     dyn_seen: HashSet<(i64, String)>,
+    /// source_author_id → auteur déjà vu (tous fichiers confondus)
+    author_by_source: HashMap<String, CachedAuthor>,
 }
 
 fn preload_form(conn: &mut Client, f: &FormInfo) -> Result<i64> {
+    // NULL et '' sont équivalents en lecture (le plus ancien formulaire l'emporte)
     let rows = conn.query(
-        "SELECT id FROM forms WHERE name=$1 AND COALESCE(version,'')=COALESCE($2,'') AND COALESCE(source,'')=COALESCE($3,'')",
-        &[&f.name, &f.version, &f.source],
+        "SELECT id, version, source FROM forms WHERE name=$1 ORDER BY id",
+        &[&f.name],
     )?;
-    
-    if let Some(row) = rows.first() {
+    let found = rows.iter().find(|r| {
+        forms::same_key(r.get(1), f.version.as_deref()) && forms::same_key(r.get(2), f.source.as_deref())
+    });
+    if let Some(row) = found {
         return Ok(row.get(0));
     }
     
+    // … et on écrit toujours NULL, jamais ''
+    let version = forms::normalize_opt(f.version.as_deref());
+    let source = forms::normalize_opt(f.source.as_deref());
     let row = conn.query_one(
         "INSERT INTO forms(name,version,source) VALUES($1,$2,$3) RETURNING id",
-        &[&f.name, &f.version, &f.source],
+        &[&f.name, &version, &source],
     )?;
     
     Ok(row.get(0))
 }
 
-fn preload_questions_and_options(conn: &mut Client, form_id: i64, mapping: &Mapping) -> Result<Caches> {
+fn preload_questions_and_options(
+    conn: &mut Client,
+    form_id: i64,
+    mapping: &Mapping,
+    json: &jsonb::JsonColumns,
+) -> Result<Caches> {
     let mut caches = Caches {
         qid_by_code: HashMap::new(),
         opt_by_qid_label: HashMap::new(),
+        opt_by_qid_code: HashMap::new(),
         dyn_seen: HashSet::new(),
+        author_by_source: HashMap::new(),
     };
     
-    // questions
-    for qm in &mapping.questions {
-        let qid = ensure_question(conn, form_id, qm)?;
-        caches.qid_by_code.insert(qm.code.clone(), qid);
-        
+    // questions (types inconnus ignorés, cf. --allow-unknown-types)
+    for qm in mapping.questions.iter().filter(|qm| qm.kind().is_some()) {
+        let mut qids = Vec::new();
+        if qm.qtype == "matrix" {
+            // une sous-question single_choice par ligne, regroupées par section + meta
+            let section = qm.section.as_deref().unwrap_or(&qm.prompt);
+            for (code, row) in qm.matrix_children() {
+                let mut meta = match &qm.meta {
+                    Some(serde_json::Value::Object(m)) => m.clone(),
+                    _ => serde_json::Map::new(),
+                };
+                meta.insert("matrix".into(), qm.code.clone().into());
+                meta.insert("matrix_row".into(), row.code.clone().into());
+                let prompt = format!("{} — {}", qm.prompt, row.label);
+                let q = NewQuestion {
+                    code: &code,
+                    prompt: &prompt,
+                    section: Some(section),
+                    position: qm.position,
+                    qtype: "single_choice",
+                    meta: Some(serde_json::Value::Object(meta)),
+                };
+                let qid = ensure_question(conn, form_id, &q, json.question_meta)?;
+                caches.qid_by_code.insert(code, qid);
+                qids.push(qid);
+            }
+        } else {
+            let qid = ensure_question(conn, form_id, &NewQuestion::from(qm), json.question_meta)?;
+            caches.qid_by_code.insert(qm.code.clone(), qid);
+            qids.push(qid);
+        }
+
         // options statiques
-        for opt in &qm.options {
-            let oid = ensure_option(conn, qid, &opt.code, &opt.label, opt.position)?;
-            caches.opt_by_qid_label.insert((qid, opt.label.clone()), oid);
+        for qid in qids {
+            for opt in &qm.options {
+                let meta = json.option_meta.param(opt.meta.as_ref(), None);
+                let oid = ensure_option(conn, qid, &opt.code, &opt.label, opt.position, &meta)?;
+                caches.opt_by_qid_label.insert((qid, opt.label.clone()), oid);
+                caches.opt_by_qid_code.insert((qid, opt.code.clone()), oid);
+            }
         }
     }
     
     Ok(caches)
 }
 
-fn ensure_question(conn: &mut Client, form_id: i64, qm: &QuestionMap) -> Result<i64> {
+/// Question à créer (question du mapping ou sous-question de matrix)
+struct NewQuestion<'a> {
+    code: &'a str,
+    prompt: &'a str,
+    section: Option<&'a str>,
+    position: Option<i32>,
+    qtype: &'a str,
+    meta: Option<serde_json::Value>,
+}
+
+impl<'a> From<&'a QuestionMap> for NewQuestion<'a> {
+    fn from(qm: &'a QuestionMap) -> Self {
+        NewQuestion {
+            code: &qm.code,
+            prompt: &qm.prompt,
+            section: qm.section.as_deref(),
+            position: qm.position,
+            qtype: &qm.qtype,
+            meta: qm.meta.clone(),
+        }
+    }
+}
+
+fn ensure_question(conn: &mut Client, form_id: i64, q: &NewQuestion, meta_kind: jsonb::Kind) -> Result<i64> {
     let rows = conn.query(
         "SELECT id FROM questions WHERE form_id=$1 AND question_code=$2",
-        &[&form_id, &qm.code],
+        &[&form_id, &q.code],
     )?;
     
     if let Some(row) = rows.first() {
         return Ok(row.get(0));
     }
     
-    let meta_json = qm.meta.as_ref().map(|v| v.to_string());
+    let meta_json = meta_kind.param(q.meta.as_ref(), None);
     let row = conn.query_one(
         "INSERT INTO questions(form_id,question_code,prompt,section,position,type,options_json)
          VALUES($1,$2,$3,$4,$5,$6,$7) RETURNING id",
-        &[&form_id, &qm.code, &qm.prompt, &qm.section, &qm.position, &qm.qtype, &meta_json],
+        &[&form_id, &q.code, &q.prompt, &q.section, &q.position, &q.qtype, &meta_json],
     )?;
     
     Ok(row.get(0))
@@ -357,152 +1637,579 @@ fn slugify(s: &str) -> String {
     collapsed.trim_matches('-').to_string()
 }
 
+/// Option déclarée par libellé; une valeur issue de `value_map` peut aussi
+/// désigner directement le code de l'option
+fn declared_option(caches: &Caches, qid: i64, raw: &str, translated: bool) -> Option<i64> {
+    let key = (qid, raw.to_string());
+    caches.opt_by_qid_label.get(&key)
+        .or_else(|| if translated { caches.opt_by_qid_code.get(&key) } else { None })
+        .copied()
+}
+
+/// Options créées dynamiquement pendant l'ingestion, partagées entre threads.
+/// Elles sont écrites en autocommit sur une connexion dédiée pour être
+/// visibles de toutes les transactions en cours.
+struct DynamicOptions {
+    conn: Client,
+    created: HashMap<(i64, String), i64>,
+}
+
 fn ensure_dynamic_option_with_limits(
-    tx: &mut postgres::Transaction, 
-    caches: &mut Caches, 
+    caches: &mut Caches,
+    dynamic: &Mutex<DynamicOptions>,
     qid: i64, 
     label: &str,
-    question_code: &str
+    question_code: &str,
+    max_options: i64,
+    retry: retry::Retry,
 ) -> Result<i64> {
-    if caches.dyn_seen.contains(&(qid, label.to_string())) {
-        if let Some(&oid) = caches.opt_by_qid_label.get(&(qid, label.to_string())) {
+    let key = (qid, label.to_string());
+    if caches.dyn_seen.contains(&key) {
+        if let Some(&oid) = caches.opt_by_qid_label.get(&key) {
             return Ok(oid);
         }
     }
-    
-    // 🛡️ LIMITE DE SÉCURITÉ: Vérifier le nombre d'options existantes
-    let count_row = tx.query_one(
-        "SELECT COUNT(*) FROM options WHERE question_id = $1", 
-        &[&qid]
-    )?;
-    let option_count: i64 = count_row.get(0);
-    
-    const MAX_DYNAMIC_OPTIONS: i64 = 500; // Limite raisonnable
-    
-    if option_count >= MAX_DYNAMIC_OPTIONS {
-        anyhow::bail!(
-            "🚨 LIMITE ATTEINTE: Question '{}' a déjà {} options (limite: {})\n\
-             → Probable erreur de configuration: single_choice + options_from_values\n\
-             → Chaque réponse unique crée une option séparée\n\
-             → SOLUTION: Définir des options prédéfinies dans le YAML",
-            question_code, option_count, MAX_DYNAMIC_OPTIONS
-        );
-    }
-    
-    if option_count > 50 {
-        println!(
-            "⚠️  ATTENTION: Question '{}' a {} options dynamiques (réponses uniques)",
-            question_code, option_count
-        );
-    }
-    
-    let code = {
-        let mut c = slugify(label);
-        if c.is_empty() { c = "na".into(); }
-        if c.len() > 64 { c.truncate(64); }
-        c
+
+    let mut dynamic = dynamic.lock().unwrap();
+    let oid = match dynamic.created.get(&key) {
+        Some(&oid) => oid,
+        None => {
+            // 🛡️ LIMITE DE SÉCURITÉ: Vérifier le nombre d'options existantes
+            let count_row = dynamic.conn.query_one(
+                "SELECT COUNT(*) FROM options WHERE question_id = $1", 
+                &[&qid]
+            )?;
+            let option_count: i64 = count_row.get(0);
+
+            if option_count >= max_options {
+                anyhow::bail!(
+                    "🚨 LIMITE ATTEINTE: Question '{}' a déjà {} options (limite: {}, max_dynamic_options)\n\
+                     → Probable erreur de configuration: single_choice + options_from_values\n\
+                     → Chaque réponse unique crée une option séparée\n\
+                     → SOLUTION: Définir des options prédéfinies dans le YAML",
+                    question_code, option_count, max_options
+                );
+            }
+
+            if option_count > 50 {
+                println!(
+                    "⚠️  ATTENTION: Question '{}' a {} options dynamiques (réponses uniques)",
+                    question_code, option_count
+                );
+            }
+
+            // Slug déjà pris par un autre libellé ("Oui !" / "Oui…"): code suffixé
+            let base = options::normalize_code(label);
+            let mut n = 1;
+            let (code, found) = loop {
+                let code = options::suffixed_code(&base, n);
+                let existing = dynamic.conn.query_opt(
+                    "SELECT id, label FROM options WHERE question_id = $1 AND code = $2",
+                    &[&qid, &code],
+                )?;
+                match existing {
+                    Some(r) if !options::same_label(r.get(1), label) => n += 1,
+                    r => break (code, r.map(|r| r.get::<_, i64>(0))),
+                }
+            };
+            if n > 1 && found.is_none() {
+                println!(
+                    "[options] question '{}': '{}' → code '{}' ('{}' déjà pris par un autre libellé)",
+                    question_code, label, code, base
+                );
+            }
+            let oid = match found {
+                Some(oid) => oid,
+                None => retry::retry_on_deadlock(
+                    || ensure_option(&mut dynamic.conn, qid, &code, label, None, &jsonb::JsonParam::Json(None)),
+                    retry.attempts,
+                    retry.base_ms,
+                )?,
+            };
+            dynamic.created.insert(key.clone(), oid);
+            oid
+        }
     };
-    
-    let oid = ensure_option_tx(tx, qid, &code, label, None)?;
-    caches.opt_by_qid_label.insert((qid, label.to_string()), oid);
-    caches.dyn_seen.insert((qid, label.to_string()));
+    caches.opt_by_qid_label.insert(key.clone(), oid);
+    caches.dyn_seen.insert(key);
     Ok(oid)
 }
 
 // ---------- Autres fonctions (adaptées pour PostgreSQL) ----------
 
-fn ensure_option(conn: &mut Client, question_id: i64, code: &str, label: &str, position: Option<i32>) -> Result<i64> {
+fn ensure_option(
+    conn: &mut Client,
+    question_id: i64,
+    code: &str,
+    label: &str,
+    position: Option<i32>,
+    meta: &jsonb::JsonParam,
+) -> Result<i64> {
     let row = conn.query_one(
-        "INSERT INTO options(question_id, code, label, position)
-         VALUES ($1, $2, $3, $4)
+        "INSERT INTO options(question_id, code, label, position, meta_json)
+         VALUES ($1, $2, $3, $4, $5)
          ON CONFLICT(question_id, code) DO UPDATE SET
              label = EXCLUDED.label,
-             position = COALESCE(EXCLUDED.position, options.position)
+             position = COALESCE(EXCLUDED.position, options.position),
+             meta_json = COALESCE(EXCLUDED.meta_json, options.meta_json)
          RETURNING id",
-        &[&question_id, &code, &label, &position],
+        &[&question_id, &code, &label, &position, meta],
     )?;
     
     Ok(row.get(0))
 }
 
-fn ensure_option_tx(tx: &mut postgres::Transaction, question_id: i64, code: &str, label: &str, position: Option<i32>) -> Result<i64> {
-    let row = tx.query_one(
-        "INSERT INTO options(question_id, code, label, position)
-         VALUES ($1, $2, $3, $4)
-         ON CONFLICT(question_id, code) DO UPDATE SET
-             label = EXCLUDED.label,
-             position = COALESCE(EXCLUDED.position, options.position)
-         RETURNING id",
-        &[&question_id, &code, &label, &position],
+/// Référence de la ligne: colonne `reference`, à défaut la première colonne
+fn row_reference(row: &dyn ColumnAccessor) -> Option<String> {
+    row.cell("reference").or_else(|| row.columns().next().map(|(_, v)| v)).map(|s| s.trim().to_string())
+}
+
+/// Ligne mise à la corbeille dans l'export (`trashed` ou `trashedStatus` ≠ kept)
+fn is_trashed(row: &dyn ColumnAccessor) -> bool {
+    if let Some(v) = row.cell("trashed") {
+        let s = v.trim().to_lowercase();
+        if matches!(s.as_str(), "1" | "true" | "yes" | "vrai") {
+            return true;
+        }
+    }
+    if let Some(v) = row.cell("trashedStatus") {
+        let s = v.trim().to_lowercase();
+        if !s.is_empty() && s != "kept" {
+            return true;
+        }
+    }
+    false
+}
+
+/// État des colonnes sources d'une question pour la ligne: `None` si aucune
+/// n'existe dans le fichier (question non proposée), `Some(true)` si toutes
+/// sont vides (question vue mais passée), `Some(false)` sinon.
+fn question_cells_empty(
+    qm: &QuestionMap,
+    row: &dyn ColumnAccessor,
+    truthy: Option<&[String]>,
+) -> Option<bool> {
+    let cols: Vec<&str> = if let Some(src) = &qm.source {
+        src.columns.iter().map(ColumnRef::as_str).collect()
+    } else if qm.options_from_columns() {
+        qm.options.iter().filter_map(|o| o.source_column.as_deref()).collect()
+    } else {
+        qm.source_column.iter().map(ColumnRef::as_str).collect()
+    };
+    let cells: Vec<&str> = cols.iter()
+        .filter_map(|c| row.cell(c))
+        .map(str::trim)
+        .collect();
+    if cells.is_empty() {
+        return None;
+    }
+    Some(match truthy {
+        // case à cocher: vue mais passée si aucune option cochée
+        Some(t) => !cells.iter().any(|v| t.contains(&v.to_lowercase())),
+        None => cells.iter().all(|v| v.is_empty()),
+    })
+}
+
+// ---------- Auteurs ----------
+
+/// Valeur (trim, non vide) de la colonne `col` pour la ligne courante
+fn col_value<'a>(row: &'a dyn ColumnAccessor, col: Option<&str>) -> Option<&'a str> {
+    row.value(col?)
+}
+
+/// Champs auteur hors clés de déduplication, dans l'ordre des colonnes SQL
+const AUTHOR_FIELDS: [&str; 8] = [
+    "name", "email_hash", "zipcode", "city", "age_range", "gender", "department_code", "region_code",
+];
+
+/// `--on-existing`: que faire d'une ligne dont la référence est déjà en base
+#[derive(Clone, Copy, Debug, Default, PartialEq, clap::ValueEnum)]
+enum OnExisting {
+    /// la ligne est ignorée, contribution et réponses en base intactes
+    Skip,
+    /// la contribution est réécrite (upsert)
+    #[default]
+    Update,
+    /// arrêt sur le premier doublon
+    Error,
+}
+
+/// `--author-conflict`: que faire des champs d'un auteur déjà connu
+#[derive(Clone, Copy, Debug, Default, PartialEq, clap::ValueEnum)]
+enum AuthorConflict {
+    /// les valeurs non vides de la ligne remplacent les anciennes
+    Overwrite,
+    /// seuls les champs encore vides sont complétés
+    #[default]
+    Fill,
+    /// l'auteur existant est repris tel quel
+    Skip,
+}
+
+impl AuthorConflict {
+    fn as_str(self) -> &'static str {
+        match self {
+            AuthorConflict::Overwrite => "overwrite",
+            AuthorConflict::Fill => "fill",
+            AuthorConflict::Skip => "skip",
+        }
+    }
+
+    fn merge(self, old: &[Option<String>; 8], new: &[Option<&str>; 8]) -> [Option<String>; 8] {
+        std::array::from_fn(|i| match self {
+            AuthorConflict::Overwrite => new[i].map(str::to_string).or_else(|| old[i].clone()),
+            AuthorConflict::Fill => old[i].clone().or_else(|| new[i].map(str::to_string)),
+            AuthorConflict::Skip => old[i].clone(),
+        })
+    }
+
+    /// Clause `DO UPDATE SET` (toujours un UPDATE, pour que RETURNING renvoie l'id);
+    /// un auteur pseudonymisé est repris tel quel (pas de PII réécrite)
+    fn set_clause(self) -> String {
+        AUTHOR_FIELDS
+            .iter()
+            .map(|f| {
+                let value = match self {
+                    AuthorConflict::Overwrite => format!("COALESCE(EXCLUDED.{f}, authors.{f})"),
+                    AuthorConflict::Fill => format!("COALESCE(authors.{f}, EXCLUDED.{f})"),
+                    AuthorConflict::Skip => format!("authors.{f}"),
+                };
+                format!("{f} = CASE WHEN authors.pseudonymized_at IS NULL THEN {value} ELSE authors.{f} END")
+            })
+            .collect::<Vec<_>>()
+            .join(",\n                 ")
+    }
+
+    /// $1 source_author_id, $2… AUTHOR_FIELDS; `key`: colonne de déduplication ("" = aucune)
+    fn upsert_sql(self, key: &str) -> String {
+        let conflict_sql = if key.is_empty() {
+            String::new()
+        } else {
+            format!("ON CONFLICT ({key}) DO UPDATE SET\n                 {}", self.set_clause())
+        };
+        format!(
+            "INSERT INTO authors (source_author_id, name, email_hash, zipcode, city, age_range, gender, department_code, region_code)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+             {conflict_sql}
+             RETURNING id, (xmax = 0) AS inserted, name, email_hash, zipcode, city, age_range, gender, department_code, region_code"
+        )
+    }
+}
+
+/// Auteur déjà en cache: $1 id, $2… AUTHOR_FIELDS (auteur pseudonymisé: rien d'écrit)
+const UPDATE_AUTHOR: &str = "UPDATE authors SET name = $2, email_hash = $3, zipcode = $4, city = $5, age_range = $6, gender = $7,
+         department_code = $8, region_code = $9
+     WHERE id = $1 AND pseudonymized_at IS NULL";
+
+/// Champs auteur normalisés avant écriture (comptés dans le bilan du fichier)
+#[derive(Default)]
+struct CleanedAuthor {
+    zipcode: Option<String>,
+    age_range: Option<String>,
+    gender: Option<String>,
+    /// déduits du code postal (cf. geo.rs)
+    department_code: Option<String>,
+    region_code: Option<String>,
+}
+
+/// Auteur déjà vu pendant l'ingestion: id + champs tels qu'en base
+#[derive(Clone)]
+struct CachedAuthor {
+    id: i64,
+    fields: [Option<String>; 8],
+}
+
+/// Crée ou fusionne l'auteur de la ligne (clé: source_author_id, sinon email_hash).
+/// L'email en clair éventuel n'est utilisé que haché avec `email_salt`;
+/// code postal, tranche d'âge, département et région viennent de l'appelant.
+/// Renvoie `None` si aucune colonne auteur n'est renseignée, sinon l'id et
+/// `true` si l'auteur vient d'être créé. Un auteur déjà vu (même
+/// source_author_id) est fusionné en mémoire: UPDATE seulement si un champ change.
+fn ensure_author(
+    tx: &mut postgres::Transaction,
+    caches: &mut Caches,
+    am: &AuthorMap,
+    conflict: AuthorConflict,
+    email_salt: Option<&str>,
+    cleaned: &CleanedAuthor,
+    row: &dyn ColumnAccessor,
+) -> Result<Option<(i64, bool)>> {
+    let get = |col: &Option<ColumnRef>| col_value(row, col.as_deref());
+    let source_author_id = get(&am.source_author_id);
+    // hash fourni par la source, sinon calculé depuis l'email en clair
+    let hashed = get(&am.email).zip(email_salt).map(|(email, salt)| pii::hash_email(salt, email));
+    let email_hash = get(&am.email_hash).or(hashed.as_deref());
+    let fields = [
+        get(&am.name),
+        email_hash,
+        cleaned.zipcode.as_deref(),
+        get(&am.city),
+        cleaned.age_range.as_deref(),
+        cleaned.gender.as_deref(),
+        cleaned.department_code.as_deref(),
+        cleaned.region_code.as_deref(),
+    ];
+    if source_author_id.is_none() && fields.iter().all(Option::is_none) {
+        return Ok(None);
+    }
+
+    if let Some(cached) = source_author_id.and_then(|s| caches.author_by_source.get_mut(s)) {
+        let merged = conflict.merge(&cached.fields, &fields);
+        if merged != cached.fields {
+            let [name, email_hash, zipcode, city, age_range, gender, department_code, region_code] = &merged;
+            tx.execute(
+                UPDATE_AUTHOR,
+                &[&cached.id, name, email_hash, zipcode, city, age_range, gender, department_code, region_code],
+            )?;
+            cached.fields = merged;
+        }
+        return Ok(Some((cached.id, false)));
+    }
+
+    let key = if source_author_id.is_some() {
+        "source_author_id"
+    } else if fields[1].is_some() {
+        "email_hash"
+    } else {
+        // Pas de clé de déduplication: un auteur par contribution
+        ""
+    };
+    let sql = conflict.upsert_sql(key);
+    let [name, email_hash, zipcode, city, age_range, gender, department_code, region_code] = fields;
+    let r = tx.query_one(
+        sql.as_str(),
+        &[&source_author_id, &name, &email_hash, &zipcode, &city, &age_range, &gender, &department_code, &region_code],
     )?;
-    
-    Ok(row.get(0))
+    let id: i64 = r.get(0);
+    let inserted: bool = r.get(1);
+    if let Some(source) = source_author_id {
+        let fields = std::array::from_fn(|i| r.get(i + 2));
+        caches.author_by_source.insert(source.to_string(), CachedAuthor { id, fields });
+    }
+    Ok(Some((id, inserted)))
 }
 
-fn sha256_rowjson(rec: &serde_json::Value) -> String {
+fn sha256_rowjson(raw_text: &str) -> String {
     let mut hasher = Sha256::new();
-    hasher.update(rec.to_string().as_bytes());
+    hasher.update(raw_text.as_bytes());
     hex::encode(hasher.finalize())
 }
 
-enum AnyReader {
-    Plain(BufReader<File>),
-    Gz(BufReader<GzDecoder<File>>),
-    Zip(Box<dyn Read>),
+fn load_mapping(mapping_path: &PathBuf) -> Result<Mapping> {
+    let mapping_str = std::fs::read_to_string(mapping_path)
+        .with_context(|| format!("lecture mapping {:?}", mapping_path))?;
+    let mapping: Mapping = serde_yaml::from_str(&mapping_str)?;
+    Ok(mapping)
 }
 
-fn open_any(path: &str) -> Result<Box<dyn Read>> {
-    if path.ends_with(".gz") {
-        let f = File::open(path)?;
-        let gz = GzDecoder::new(f);
-        Ok(Box::new(BufReader::new(gz)))
-    } else if path.ends_with(".zip") {
-        let f = File::open(path)?;
-        let mut zip = ZipArchive::new(f)?;
-        for i in 0..zip.len() {
-            let name = zip.by_index(i)?.name().to_lowercase();
-            if name.ends_with(".csv") {
-                let mut zf = zip.by_index(i)?;
-                let mut buf = Vec::new();
-                zf.read_to_end(&mut buf)?;
-                return Ok(Box::new(Cursor::new(buf)));
-            }
+fn expand_globs(csv_globs: &[String]) -> Result<Vec<String>> {
+    let mut files = Vec::<String>::new();
+    for g in csv_globs {
+        for entry in glob(g)? {
+            files.push(entry?.to_string_lossy().into_owned());
         }
-        anyhow::bail!("zip sans CSV");
-    } else {
-        let f = File::open(path)?;
-        Ok(Box::new(BufReader::new(f)))
     }
+    Ok(files)
 }
 
 // ---------- run_ingest (version PostgreSQL) ----------
 
-fn run_ingest(
-    csv_globs: Vec<String>,
-    mapping_path: PathBuf,
-    batch: String,
-    commit_every: usize,
-    log_every: usize,
-    delimiter: char,
-    dry_run: bool,
-) -> Result<()> {
+fn run_ingest(args: IngestArgs) -> Result<()> {
+    let IngestArgs {
+        csv: csv_globs,
+        sheet,
+        mapping: mapping_path,
+        mapping_overlay,
+        batch,
+        commit_every,
+        log_every,
+        delimiter,
+        encoding,
+        dry_run,
+        dry_run_rows,
+        preload_budget_mb,
+        strict_numbers,
+        status_port,
+        status_bind,
+        artifacts_dir,
+        normalize_option_codes,
+        parallel,
+        db_pool_size,
+        no_progress,
+        allow_unknown_types,
+        allow_missing_columns,
+        author_conflict,
+        email_salt,
+        range_violations,
+        report_unmapped_values,
+        zipcode_strict,
+        on_error,
+        submitted_at_format,
+        merge_into_existing,
+        geo_lookup,
+        stale_lock_secs,
+        steal_stale_lock,
+        show_changes,
+        max_changes,
+        skip_comments,
+        dictionary_texts,
+        dictionary_max_chars,
+        force,
+        skip_duplicates,
+        resume,
+        no_resume,
+        db_retry_attempts,
+        db_retry_base_ms,
+        skip_ingested,
+        force_reimport,
+        on_existing,
+        rules: rules_mode,
+        max_errors,
+        abort_on_file_error,
+        rejects_dir,
+        no_raw,
+        post_ingest_analyze,
+        post_ingest_vacuum,
+        vacuum_tables,
+    } = args;
+
     // mapping
-    let mapping_str = std::fs::read_to_string(&mapping_path)
-        .with_context(|| format!("lecture mapping {:?}", mapping_path))?;
-    let mapping: Mapping = serde_yaml::from_str(&mapping_str)?;
+    let mut mapping = match &mapping_overlay {
+        Some(overlay_path) => {
+            let (mapping, merged) = overlay::load(&mapping_path, overlay_path)?;
+            println!(
+                "[mapping] surcouche {:?}: {} questions modifiées, {} ajoutées",
+                overlay_path,
+                merged.overridden.len(),
+                merged.appended.len()
+            );
+            mapping
+        }
+        None => load_mapping(&mapping_path)?,
+    };
+
+    // réglages: CLI > section `ingest:` du mapping > défaut
+    let m = mapping.ingest.clone();
+    let mut eff = settings::Effective::default();
+    let commit_every = eff.note("commit_every", settings::pick(commit_every, m.commit_every, 10_000));
+    let log_every = eff.note("log_every", settings::pick(log_every, m.log_every, 2_000));
+    let delimiter = eff.note("delimiter", settings::pick(delimiter, m.delimiter, input::DelimiterChoice::Fixed(',')));
+    let encoding = eff.note("encoding", settings::pick(encoding, m.encoding, "auto".to_string()));
+    let encoding = match encoding.as_str() {
+        "auto" => None,
+        label => Some(encoding::parse_label(label)?),
+    };
+    let (strict_numbers, strict_numbers_source) = settings::pick_flag(strict_numbers, m.strict_numbers);
+    eff.note("strict_numbers", (strict_numbers, strict_numbers_source));
+    let allow_unknown_types = eff.note("allow_unknown_types", settings::pick_flag(allow_unknown_types, m.allow_unknown_types));
+    let parallel = eff.note("parallel", settings::pick(parallel, m.parallel, 1));
+    pool::init(db_pool_size.unwrap_or(parallel))?;
+    let preload_budget_mb = eff.note("preload_budget_mb", settings::pick(preload_budget_mb, m.preload_budget_mb, 256));
+    let normalize_option_codes = eff.note(
+        "normalize_option_codes",
+        settings::pick_flag(normalize_option_codes, m.normalize_option_codes),
+    );
+    let store_raw = eff.note("store_raw", settings::pick(no_raw.then_some(false), m.store_raw, true));
+    eff.log();
+
+    // politique d'erreurs, source par source dans l'ordre de précédence (cf. policy.rs)
+    let mut pb = policy::ErrorPolicy::builder();
+    let window_action = mapping.defaults.contribution.out_of_window.action();
+    if strict_numbers && strict_numbers_source == settings::Source::Mapping {
+        pb.action(policy::Category::BadNumber, policy::Action::Abort, "ingest.strict_numbers")
+            .map_err(anyhow::Error::msg)?;
+    }
+    if window_action != policy::Action::Warn {
+        pb.action(policy::Category::OutOfWindow, window_action, "defaults.contribution.out_of_window")
+            .map_err(anyhow::Error::msg)?;
+    }
+    for (&category, &rule) in &m.errors {
+        pb.set(category, rule, "ingest.errors").map_err(anyhow::Error::msg)?;
+    }
+    if strict_numbers && strict_numbers_source == settings::Source::Cli {
+        pb.action(policy::Category::BadNumber, policy::Action::Abort, "--strict-numbers")
+            .map_err(anyhow::Error::msg)?;
+    }
+    if let Some(r) = range_violations {
+        pb.action(policy::Category::OutOfRange, r.action(), "--range-violations")
+            .map_err(anyhow::Error::msg)?;
+    }
+    if zipcode_strict {
+        pb.action(policy::Category::BadZipcode, policy::Action::Reject, "--zipcode-strict")
+            .map_err(anyhow::Error::msg)?;
+    }
+    for (category, rule) in on_error {
+        pb.set(category, rule, "--on-error").map_err(anyhow::Error::msg)?;
+    }
+    let error_policy = pb.build();
+    error_policy.log();
+
+    if normalize_option_codes {
+        options::normalize_declared_codes(&mut mapping);
+    }
+
+    if !store_raw && (merge_into_existing || show_changes.is_some()) {
+        anyhow::bail!("--merge-into-existing et --show-changes lisent raw_json: incompatibles avec --no-raw (ingest.store_raw: false)");
+    }
+    if merge_into_existing && on_existing != OnExisting::Update {
+        anyhow::bail!("--merge-into-existing prolonge les contributions existantes: incompatible avec --on-existing skip|error");
+    }
+    let post_ingest = maintenance::PostIngest::new(post_ingest_analyze, post_ingest_vacuum, &vacuum_tables)?;
 
     // 🔍 VALIDATION CRITIQUE
-    validate_mapping(&mapping)?;
+    validate_mapping(&mapping, allow_unknown_types)?;
+
+    let files = expand_globs(&csv_globs)?;
+    let bars = bars::Bars::new(files.len(), !no_progress);
+    let comment = if skip_comments { Some('#') } else { mapping.defaults.comment_char };
+    if let Some(c) = comment {
+        println!("[ingest] lignes commençant par '{c}' ignorées (commentaires)");
+    }
+    let read_opts = input::ReadOptions { delimiter, sheet: sheet.as_deref(), encoding, comment: comment.map(|c| c as u8) };
 
     if dry_run {
         println!("[dry-run] Mode validation uniquement - aucune écriture DB");
-        return Ok(());
+        return dryrun::run(&files, &mapping, &read_opts, dry_run_rows, rules_mode, &bars);
+    }
+
+    // emails en clair: jamais sans sel (--dry-run n'écrit rien, il s'en passe)
+    let email_salt = pii::resolve_salt(email_salt);
+    if mapping.defaults.author.email.is_some() && email_salt.is_none() {
+        anyhow::bail!(
+            "defaults.author.email est mappé mais aucun sel fourni (--email-salt ou {})",
+            pii::SALT_ENV
+        );
     }
 
     // connex + form + caches
     let mut conn = open_conn()?;
+    let schema = session::opts().schema;
+    let extra_tables: Vec<&str> = dictionary_texts.then_some("text_values").into_iter().collect();
+    session::check_tables(&mut conn, &schema, &extra_tables)?;
+    if schema != session::DEFAULT_SCHEMA {
+        println!("[db] schéma '{schema}'");
+    }
+    forms::warn_duplicate_forms(&mut conn)?;
     let form_id = preload_form(&mut conn, &mapping.form)?;
-    let mut caches = preload_questions_and_options(&mut conn, form_id, &mapping)?;
+    options::warn_unnormalized_codes(&mut conn, form_id)?;
+    let json_columns = jsonb::detect(&mut conn)?;
+    let mut caches = preload_questions_and_options(&mut conn, form_id, &mapping, &json_columns)?;
+    existing::check_upsert_index(&mut conn)?;
+    if dictionary_texts {
+        dictionary::check_schema(&mut conn)?;
+    }
+    let normalize = normalize::NormalizeCaches::build(&mapping.questions, &mapping.defaults.transforms).map_err(anyhow::Error::msg)?;
+    let rules = rules::RuleSet::build(&mapping.questions).map_err(anyhow::Error::msg)?;
+    if !rules.is_empty() {
+        println!("[rules] {} questions avec règles de qualité, --rules {}", rules.len(), rules_mode.as_str());
+    }
+    let existing = existing::preload_existing(&mut conn, form_id, preload_budget_mb * 1024 * 1024)?;
+    sourcefiles::check_schema(&mut conn)?;
+    rawimports::check_schema(&mut conn)?;
+    if skip_ingested && force_reimport {
+        println!("[ingest] --force-reimport: fichiers déjà ingérés réingérés malgré --skip-ingested");
+    }
+    let mut checkpoints = checkpoint::Checkpoints::load(&mut conn, form_id, &batch, resume, no_resume)?;
     
     println!(
         "[ingest] form id={} name='{}' version='{}'", 
@@ -511,179 +2218,1736 @@ fn run_ingest(
         mapping.form.version.as_deref().unwrap_or("")
     );
 
-    // expand globs
-    let mut files = Vec::<String>::new();
-    for g in csv_globs {
-        for entry in glob(&g)? {
-            files.push(entry?.to_string_lossy().into_owned());
-        }
-    }
+    let progress = Arc::new(status::Progress::new(files.len()));
+    let _status_server = match status_port {
+        Some(port) => Some(status::StatusServer::start(&status_bind, port, Arc::clone(&progress))?),
+        None => None,
+    };
+    let counters = counters::DurableCounters::new(&batch, artifacts_dir)?;
 
-    let t0 = Instant::now();
-    let mut total = 0usize;
+    // valeurs "cochées" par question (multi_choice une colonne par option)
+    let truthy_by_code: HashMap<&str, Vec<String>> = mapping.questions.iter()
+        .filter(|qm| qm.qtype == "multi_choice" && qm.options_from_columns())
+        .map(|qm| (qm.code.as_str(), qm.truthy_values()))
+        .collect();
 
-    for path in files {
-        println!("[ingest] fichier: {path}");
-        
-        // open & csv reader
-        let mut reader = open_any(&path)?;
-        let (primed, delim_auto) = sniff_delimiter(&mut reader)?;
-        let delim = if delimiter == ',' || delimiter == ';' || delimiter == '\t' {
-            delimiter as u8
+    // auteurs: uniquement si au moins une colonne est mappée
+    let author_map = &mapping.defaults.author;
+    let with_authors = *author_map != AuthorMap::default();
+
+    let value_maps_by_code: HashMap<&str, values::ValueMap> = mapping.questions.iter()
+        .filter(|qm| !qm.value_map.is_empty())
+        .map(|qm| (qm.code.as_str(), qm.value_map()))
+        .collect();
+
+    // validé plus haut: expressions compilables
+    let comment_split_by_code: HashMap<&str, Regex> = mapping.questions.iter()
+        .filter(|qm| qm.qtype == "single_choice")
+        .filter_map(|qm| Some((qm.code.as_str(), qm.split_comment.as_ref()?.compile().ok()?)))
+        .collect();
+
+    let date_formats_by_code: HashMap<&str, Vec<String>> = mapping.questions.iter()
+        .filter(|qm| qm.qtype == "date")
+        .map(|qm| (qm.code.as_str(), qm.date_formats()))
+        .collect();
+
+    let boolean_values_by_code: HashMap<&str, values::BooleanValues> = mapping.questions.iter()
+        .filter(|qm| qm.qtype == "boolean")
+        .map(|qm| (qm.code.as_str(), qm.boolean_values()))
+        .collect();
+
+    let gender_map: HashMap<String, String> = mapping.defaults.author.gender_map.iter()
+        .map(|(raw, code)| (values::fold(raw), code.clone()))
+        .collect();
+
+    if let Some(f) = &submitted_at_format {
+        values::check_date_format(f).map_err(|e| anyhow::anyhow!("--submitted-at-format '{f}': {e}"))?;
+    }
+
+    let change_log = show_changes.as_deref().map(|p| changes::ChangeLog::create(p, max_changes)).transpose()?;
+
+    let geo = match &geo_lookup {
+        Some(path) => {
+            let geo = geo::GeoLookup::load(path)?;
+            println!("[geo] {} préfixes de codes postaux chargés depuis {:?}", geo.overrides(), path);
+            geo
+        }
+        None => geo::GeoLookup::default(),
+    };
+
+    // validé plus haut: bornes lisibles
+    let submitted_window = mapping.defaults.contribution.window().map_err(anyhow::Error::msg)?;
+    if let Some(w) = submitted_window {
+        println!(
+            "[ingest] submitted_at attendu dans {w} (hors fenêtre: {})",
+            error_policy.rule(policy::Category::OutOfWindow).action.as_str()
+        );
+    }
+
+    // verrou posé au dernier moment: une erreur de préparation ne laisse pas de ligne `running`
+    let dynamic_conn = open_conn()?;
+    let dictionary = match dictionary_texts {
+        true => Some(Mutex::new(dictionary::TextDictionary::new(open_conn()?, dictionary_max_chars))),
+        false => None,
+    };
+    let run_lock = runlock::RunLock::acquire(
+        &mut conn,
+        form_id,
+        &batch,
+        &mapping_path.to_string_lossy(),
+        stale_lock_secs,
+        steal_stale_lock,
+    )?;
+    checkpoints.begin(&mut conn)?;
+    if dictionary.is_some() {
+        println!("[dictionary] réponses texte ≤ {dictionary_max_chars} caractères mutualisées dans text_values");
+        run_lock.provenance(&mut conn, "dictionary_texts", json!({ "max_chars": dictionary_max_chars }))?;
+    }
+    if !store_raw {
+        println!("[ingest] raw_json non stocké (--no-raw): contributions non rejouables depuis raw_json");
+        run_lock.provenance(&mut conn, "store_raw", json!(false))?;
+    }
+    if let Some(overlay_path) = &mapping_overlay {
+        run_lock.provenance(&mut conn, "mapping_overlay", json!(overlay_path.to_string_lossy()))?;
+    }
+    // raw_hash ne dépend que de la ligne: mapping modifié depuis le batch
+    // précédent, les lignes inchangées sont réécrites quand même
+    let fingerprint = overlay::fingerprint(&mapping_path, mapping_overlay.as_deref())?;
+    let mapping_changed = match run_lock.previous_provenance(&mut conn, "mapping_sha256")? {
+        Some(previous) => previous.as_deref() != Some(fingerprint.as_str()),
+        None => false,
+    };
+    if mapping_changed && !force {
+        println!("[ingest] ⚠️  mapping modifié depuis le batch précédent (ou empreinte inconnue): lignes inchangées réécrites quand même");
+    }
+    run_lock.provenance(&mut conn, "mapping_sha256", json!(fingerprint))?;
+    let mark_trashed = softdelete::has_columns(&mut conn)?;
+    if !mark_trashed {
+        println!("[ingest] ⚠️  colonne contributions.deleted_at absente: lignes à la corbeille seulement sautées (alembic upgrade head)");
+    }
+    let ctx = IngestCtx {
+        mapping: &mapping,
+        form_id,
+        batch: &batch,
+        run_lock: &run_lock,
+        checkpoints: &checkpoints,
+        commit_every,
+        log_every,
+        policy: error_policy,
+        truthy_by_code,
+        comment_split_by_code,
+        normalize,
+        rules,
+        rules_mode,
+        max_errors: maxerrors::MaxErrors::new(max_errors, abort_on_file_error),
+        rejects_dir,
+        mark_trashed,
+        value_maps_by_code,
+        date_formats_by_code,
+        boolean_values_by_code,
+        author_map,
+        with_authors,
+        author_conflict,
+        email_salt: email_salt.as_deref(),
+        geo,
+        gender_map,
+        anomalies: mapping.ingest.anomalies.clone().unwrap_or_default(),
+        anomalies_tripped: Mutex::new(HashSet::new()),
+        submitted_window,
+        submitted_at_format: submitted_at_format.as_deref(),
+        merge_into_existing,
+        force: force || mapping_changed,
+        skip_duplicates,
+        retry: retry::Retry { attempts: db_retry_attempts, base_ms: db_retry_base_ms },
+        skip_ingested: skip_ingested && !force_reimport,
+        on_existing,
+        store_raw,
+        allow_missing_columns,
+        raw_json_kind: json_columns.raw_json,
+        title_dedup_question: mapping.defaults.contribution.dedup_title_against_text
+            .then(|| mapping.questions.iter().find(|qm| matches!(qm.qtype.as_str(), "text" | "free_text")))
+            .flatten(),
+        read_opts,
+        progress: Arc::clone(&progress),
+        bars,
+        existing: Mutex::new(existing),
+        dynamic: Mutex::new(DynamicOptions { conn: dynamic_conn, created: HashMap::new() }),
+        counters: Mutex::new(counters),
+        changes: change_log.map(Mutex::new),
+        dictionary,
+    };
+
+    let t0 = Instant::now();
+    let outcome = (|| -> Result<FileReport> {
+        let mut report = FileReport::default();
+        if parallel <= 1 {
+            for path in &files {
+                report.merge(ingest_file(&ctx, &mut *pool::get()?, &mut caches, path)?);
+            }
         } else {
-            delim_auto
+            // Un paquet de fichiers par thread, chacun avec sa copie des caches; une connexion du pool par fichier
+            let mut chunks: Vec<Vec<&str>> = vec![Vec::new(); parallel.min(files.len()).max(1)];
+            for (i, path) in files.iter().enumerate() {
+                let n = chunks.len();
+                chunks[i % n].push(path);
+            }
+            println!("[ingest] {} fichiers répartis sur {} threads", files.len(), chunks.len());
+            let pool = rayon::ThreadPoolBuilder::new().num_threads(chunks.len()).build()?;
+            let reports = pool.install(|| {
+                chunks
+                    .par_iter()
+                    .map(|chunk| -> Result<Vec<FileReport>> {
+                        let mut caches = caches.clone();
+                        chunk.iter().map(|path| ingest_file(&ctx, &mut *pool::get()?, &mut caches, path)).collect()
+                    })
+                    .collect::<Result<Vec<_>>>()
+            })?;
+            for r in reports.into_iter().flatten() {
+                report.merge(r);
+            }
+        }
+        Ok(report)
+    })();
+    run_lock.finish(&mut conn, &outcome, ctx.counters.lock().unwrap().totals())?;
+    if outcome.is_ok() {
+        checkpoints.clear(&mut conn)?;
+    }
+    let FileReport {
+        commits,
+        bad_dates,
+        bad_submitted_at,
+        contributions_extended,
+        rows_already_merged,
+        merge_conflicts,
+        titles_truncated,
+        titles_deduped,
+        bad_booleans,
+        authors_created,
+        authors_merged,
+        zipcodes_normalized,
+        zipcodes_rejected,
+        zipcodes_unlocated,
+        ages_normalized,
+        ages_unbucketed,
+        genders_normalized,
+        unmapped_genders,
+        duplicate_ranks,
+        out_of_window,
+        duplicates,
+        raw_rows,
+        raw_bytes_full,
+        raw_bytes_stored,
+        skips_by_code,
+        scale_report,
+        range_violations: range_report,
+        comments_by_code,
+        rules: rules_report,
+        file_errors,
+        files_skipped,
+        republished,
+    } = outcome?;
+    let total = progress.rows() as usize;
+    ctx.bars.finish();
+
+    if files_skipped > 0 {
+        println!("[ingest] {files_skipped} fichiers sautés: contenu déjà ingéré (--skip-ingested)");
+    }
+    if !republished.is_empty() {
+        println!("[ingest] 🚨 {} fichiers au contenu modifié depuis leur ingestion précédente:", republished.len());
+        for path in &republished {
+            println!("  {path}");
+        }
+    }
+
+    if !skips_by_code.is_empty() {
+        println!("[ingest] questions passées (record_skips):");
+        for qm in mapping.questions.iter().filter(|qm| qm.record_skips) {
+            let n = skips_by_code.get(qm.code.as_str()).copied().unwrap_or(0);
+            let rate = if total > 0 { 100.0 * n as f64 / total as f64 } else { 0.0 };
+            println!("  {:<24} {:>8} ({:.1} %)", qm.code, n, rate);
+        }
+    }
+    if scale_report.values().any(|&(c, s)| c + s > 0) {
+        println!("[ingest] échelles hors bornes ou illisibles:");
+        for qm in mapping.questions.iter().filter(|qm| qm.qtype == "scale") {
+            let (clamped, skipped) = scale_report.get(qm.code.as_str()).copied().unwrap_or_default();
+            println!("  {:<24} ramenées: {:>6}  écartées: {:>6}", qm.code, clamped, skipped);
+        }
+    }
+    if !comments_by_code.is_empty() {
+        println!("[ingest] commentaires accolés aux choix (split_comment):");
+        for qm in mapping.questions.iter().filter(|qm| qm.split_comment.is_some()) {
+            let n = comments_by_code.get(qm.code.as_str()).copied().unwrap_or(0);
+            let rate = if total > 0 { 100.0 * n as f64 / total as f64 } else { 0.0 };
+            println!("  {:<24} {:>8} ({:.1} %)", qm.code, n, rate);
+        }
+    }
+    if !range_report.is_empty() {
+        println!(
+            "[ingest] ⚠️  valeurs hors plage min/max (out_of_range: {}):",
+            ctx.policy.rule(policy::Category::OutOfRange).action.as_str()
+        );
+        for qm in mapping.questions.iter() {
+            if let Some(v) = range_report.get(qm.code.as_str()) {
+                println!("{}", v.line(qm));
+            }
+        }
+    }
+    if !ctx.rules.is_empty() {
+        print!("{}", rules_report.render(&ctx.rules, ctx.rules_mode));
+    }
+    if !file_errors.is_empty() {
+        run_lock.provenance(&mut conn, "row_errors", json!(file_errors))?;
+        println!("[ingest] ⚠️  lignes en erreur, sautées ({}):", ctx.max_errors.describe());
+        for f in &file_errors {
+            let failed = if f.failed { "  ❌ fichier en échec, transaction en cours annulée" } else { "" };
+            println!("  {:<40} {:>8}{failed}", f.file, f.errors);
+        }
+    }
+    if bad_dates > 0 {
+        let fate = match ctx.policy.rule(policy::Category::BadDate).action {
+            policy::Action::Drop => "aucune réponse écrite",
+            _ => "value_date NULL, texte conservé",
         };
-        let cursor = std::io::Cursor::new(primed);
-        let chained = cursor.chain(reader);
-        let mut rdr = csv::ReaderBuilder::new()
-            .delimiter(delim)
-            .has_headers(true)
-            .flexible(true)
-            .from_reader(chained);
-
-        let headers = rdr.headers()?.clone();
-
-        // transactions par batch
-        let mut pending = 0usize;
-        let mut tx = conn.transaction()?;
-
-        for rec in rdr.records() {
-            let rec = rec?;
-            
-            // skip trashed (logique inchangée)
-            let mut is_trashed = false;
-            if let Some(ix) = headers.iter().position(|h| h == "trashed") {
-                if let Some(v) = rec.get(ix) {
-                    let s = v.trim().to_lowercase();
-                    is_trashed = matches!(s.as_str(), "1" | "true" | "yes" | "vrai");
-                }
-            }
-            if !is_trashed {
-                if let Some(ix) = headers.iter().position(|h| h == "trashedStatus") {
-                    if let Some(v) = rec.get(ix) {
-                        let s = v.trim().to_lowercase();
-                        if !s.is_empty() && s != "kept" { is_trashed = true; }
+        println!("[ingest] ⚠️  {bad_dates} dates illisibles ({fate})");
+    }
+    if merge_into_existing {
+        println!(
+            "[ingest] fusion: {contributions_extended} contributions prolongées, {} créées, {rows_already_merged} lignes déjà fusionnées",
+            ctx.counters.lock().unwrap().totals().contributions as usize - contributions_extended
+        );
+        if merge_conflicts > 0 {
+            println!("[ingest] ⚠️  {merge_conflicts} réponses en conflit avec une réponse déjà en base (conservée, nouvelle ignorée)");
+        }
+    }
+    if titles_truncated + titles_deduped > 0 {
+        println!("[ingest] titres: {titles_truncated} tronqués, {titles_deduped} écartés (identiques à la réponse texte)");
+    }
+    if bad_submitted_at > 0 {
+        println!("[ingest] ⚠️  {bad_submitted_at} submitted_at illisibles (contributions sans date, cf. --submitted-at-format)");
+    }
+    if out_of_window.count > 0 {
+        println!(
+            "[ingest] ⚠️  {} contributions avec submitted_at hors fenêtre ({}), ex: {}",
+            out_of_window.count,
+            ctx.policy.rule(policy::Category::OutOfWindow).action.as_str(),
+            out_of_window.examples.join(", ")
+        );
+    }
+    if duplicates.count > 0 {
+        match ctx.skip_duplicates {
+            true => println!(
+                "[ingest] {} lignes déjà importées sautées (--skip-duplicates), batches d'origine: {}",
+                duplicates.count,
+                duplicates.describe()
+            ),
+            false => println!(
+                "[ingest] ⚠️  {} lignes identiques à des lignes déjà importées (raw_imports), batches d'origine: {} — --skip-duplicates pour les sauter",
+                duplicates.count,
+                duplicates.describe()
+            ),
+        }
+    }
+    if duplicate_ranks > 0 {
+        println!("[ingest] ⚠️  {duplicate_ranks} options classées plusieurs fois dans une même contribution (ranking)");
+    }
+    if bad_booleans > 0 {
+        println!("[ingest] ⚠️  {bad_booleans} valeurs oui/non non reconnues (aucune réponse écrite)");
+    }
+    if with_authors {
+        println!("[ingest] auteurs: {authors_created} créés, {authors_merged} fusionnés avec un auteur existant (--author-conflict {})", author_conflict.as_str());
+        if zipcodes_normalized + zipcodes_rejected > 0 {
+            let fate = match ctx.policy.rule(policy::Category::BadZipcode).action {
+                policy::Action::Reject => "lignes écartées dans rejects/",
+                policy::Action::Warn => "valeur brute conservée",
+                _ => "zipcode NULL",
+            };
+            println!("[ingest] codes postaux: {zipcodes_normalized} normalisés, {zipcodes_rejected} invalides ({fate})");
+        }
+        if zipcodes_unlocated > 0 {
+            println!("[ingest] ⚠️  {zipcodes_unlocated} codes postaux hors table des départements (department_code/region_code NULL, cf. --geo-lookup)");
+        }
+        if ages_normalized + ages_unbucketed > 0 {
+            println!("[ingest] tranches d'âge: {ages_normalized} normalisées, {ages_unbucketed} inclassables (conservées telles quelles)");
+        }
+        if genders_normalized + unmapped_genders.total > 0 {
+            println!(
+                "[ingest] genre: {genders_normalized} normalisés, {} hors gender_map (conservés tels quels)",
+                unmapped_genders.total
+            );
+            if report_unmapped_values {
+                unmapped_genders.print("gender");
+            }
+        }
+    }
+    if let Some(dictionary) = &ctx.dictionary {
+        let mut dictionary = dictionary.lock().unwrap();
+        dictionary.finish()?;
+        println!(
+            "[dictionary] {} réponses texte mutualisées ({} valeurs distinctes dans text_values)",
+            dictionary.encoded,
+            dictionary.values()
+        );
+    }
+    if let Some(log) = &ctx.changes {
+        let mut log = log.lock().unwrap();
+        log.finish()?;
+        println!(
+            "[changes] {} contributions modifiées, {} détaillées dans {:?}{}",
+            log.contributions,
+            log.written,
+            log.path(),
+            if log.written < log.contributions as usize { " (plafond --max-changes atteint)" } else { "" }
+        );
+        let top = log.top_columns(10);
+        if !top.is_empty() {
+            println!("[changes] colonnes les plus modifiées:");
+            for (column, n) in top {
+                println!("  {column:<32} {n:>8}");
+            }
+        }
+    }
+    if let (Some(full), Some(stored)) = (raw_bytes_full.checked_div(raw_rows), raw_bytes_stored.checked_div(raw_rows)) {
+        println!("[ingest] raw_json: ≈{full} → {stored} octets/ligne en moyenne");
+    }
+    let mut counters = ctx.counters.into_inner().unwrap();
+    counters.finish()?;
+    if counters.totals().unchanged > 0 {
+        println!(
+            "[ingest] {} lignes inchangées (raw_hash identique), non réécrites — --force pour les réécrire",
+            counters.totals().unchanged
+        );
+    }
+    if counters.totals().trashed_deleted > 0 {
+        println!(
+            "[ingest] {} contributions à la corbeille dans l'export, marquées supprimées (deleted_at)",
+            counters.totals().trashed_deleted
+        );
+    }
+    if counters.totals().skipped_existing > 0 {
+        println!(
+            "[ingest] {} lignes dont la référence était déjà en base, laissées intactes (--on-existing skip)",
+            counters.totals().skipped_existing
+        );
+    }
+    println!(
+        "[ingest] OK — {total} lignes en {:?} ({} contributions écrites: {} insérées, {} réécrites; {commits} commits).",
+        t0.elapsed(),
+        counters.totals().contributions,
+        counters.totals().inserted,
+        counters.totals().contributions - counters.totals().inserted
+    );
+    if let Some(post_ingest) = &post_ingest {
+        post_ingest.run(&mut conn)?;
+    }
+    Ok(())
+}
+
+/// Paramètres et état partagés par les fichiers d'une ingestion
+/// (entre threads avec `--parallel`)
+struct IngestCtx<'a> {
+    mapping: &'a Mapping,
+    form_id: i64,
+    batch: &'a str,
+    run_lock: &'a runlock::RunLock,
+    checkpoints: &'a checkpoint::Checkpoints,
+    commit_every: usize,
+    log_every: usize,
+    /// action par catégorie d'erreur de ligne (cf. policy.rs)
+    policy: policy::ErrorPolicy,
+    truthy_by_code: HashMap<&'a str, Vec<String>>,
+    comment_split_by_code: HashMap<&'a str, Regex>,
+    normalize: normalize::NormalizeCaches,
+    /// rules: du mapping et sort des lignes fautives (cf. rules.rs)
+    rules: rules::RuleSet<'a>,
+    rules_mode: rules::RulesMode,
+    /// --max-errors, --abort-on-file-error
+    max_errors: maxerrors::MaxErrors,
+    /// --rejects-dir
+    rejects_dir: PathBuf,
+    /// ligne à la corbeille: contribution de même référence marquée supprimée (cf. softdelete.rs)
+    mark_trashed: bool,
+    value_maps_by_code: HashMap<&'a str, values::ValueMap>,
+    date_formats_by_code: HashMap<&'a str, Vec<String>>,
+    boolean_values_by_code: HashMap<&'a str, values::BooleanValues>,
+    author_map: &'a AuthorMap,
+    with_authors: bool,
+    author_conflict: AuthorConflict,
+    email_salt: Option<&'a str>,
+    /// code postal → département, région
+    geo: geo::GeoLookup,
+    /// gender_map, clés repliées par `values::fold`
+    gender_map: HashMap<String, String>,
+    anomalies: anomalies::Thresholds,
+    /// règles d'anomalie déjà signalées (une fois par run)
+    anomalies_tripped: Mutex<HashSet<String>>,
+    submitted_window: Option<values::DateWindow>,
+    submitted_at_format: Option<&'a str>,
+    merge_into_existing: bool,
+    /// --force (ou mapping modifié depuis le batch précédent): pas de saut des lignes inchangées
+    force: bool,
+    /// --skip-duplicates: lignes déjà consignées dans raw_imports sautées
+    skip_duplicates: bool,
+    /// --db-retry-*: deadlocks
+    retry: retry::Retry,
+    /// --skip-ingested sans --force-reimport
+    skip_ingested: bool,
+    on_existing: OnExisting,
+    /// false: --no-raw
+    store_raw: bool,
+    allow_missing_columns: bool,
+    /// raw_json en jsonb ou en text (cf. jsonb.rs)
+    raw_json_kind: jsonb::Kind,
+    /// dedup_title_against_text: première question text/free_text du mapping
+    title_dedup_question: Option<&'a QuestionMap>,
+    read_opts: input::ReadOptions<'a>,
+    progress: Arc<status::Progress>,
+    bars: bars::Bars,
+    existing: Mutex<existing::ExistingContributions>,
+    /// --show-changes
+    changes: Option<Mutex<changes::ChangeLog>>,
+    /// --dictionary-texts
+    dictionary: Option<Mutex<dictionary::TextDictionary>>,
+    dynamic: Mutex<DynamicOptions>,
+    counters: Mutex<counters::DurableCounters>,
+}
+
+impl<'a> IngestCtx<'a> {
+    /// Règles d'anomalie franchies pour la première fois → avertissement
+    fn warn_anomalies(&self, detector: &mut anomalies::Detector, path: &str) {
+        let mut tripped = self.anomalies_tripped.lock().unwrap();
+        for w in detector.check(&mut tripped) {
+            say!(self.bars, "🔥 anomalie dans {path}: {w}");
+        }
+    }
+
+    /// number: contrôle de [min, max] selon la politique out_of_range.
+    /// Renvoie `false` si la réponse ne doit pas être écrite.
+    fn check_range(
+        &self,
+        qm: &'a QuestionMap,
+        v: f64,
+        report: &mut FileReport<'a>,
+        path: &str,
+        reference: &str,
+    ) -> Result<bool> {
+        if !qm.violates_range(v) {
+            return Ok(true);
+        }
+        let action = self.policy.record(policy::Category::OutOfRange);
+        if action == policy::Action::Abort {
+            anyhow::bail!(
+                "{path}: question '{}': valeur {} hors de {} (contribution {}, {})",
+                qm.code, v, qm.range_label(), reference, self.policy.why_abort(policy::Category::OutOfRange)
+            );
+        }
+        report.range_violations.entry(qm.code.as_str()).or_default().note(v);
+        Ok(action == policy::Action::Warn)
+    }
+}
+
+/// Valeurs brutes sans correspondance dans une table (ex: gender_map),
+/// pour compléter la table (`--report-unmapped-values`)
+#[derive(Default)]
+struct UnmappedValues {
+    total: usize,
+    /// valeur → occurrences, au plus MAX_DISTINCT valeurs distinctes
+    seen: BTreeMap<String, usize>,
+}
+
+impl UnmappedValues {
+    const MAX_DISTINCT: usize = 1000;
+
+    fn note(&mut self, raw: &str) {
+        self.total += 1;
+        if let Some(n) = self.seen.get_mut(raw) {
+            *n += 1;
+        } else if self.seen.len() < Self::MAX_DISTINCT {
+            self.seen.insert(raw.to_string(), 1);
+        }
+    }
+
+    fn merge(&mut self, other: UnmappedValues) {
+        self.total += other.total;
+        for (raw, n) in other.seen {
+            if let Some(m) = self.seen.get_mut(&raw) {
+                *m += n;
+            } else if self.seen.len() < Self::MAX_DISTINCT {
+                self.seen.insert(raw, n);
+            }
+        }
+    }
+
+    fn print(&self, field: &str) {
+        let mut by_count: Vec<_> = self.seen.iter().collect();
+        by_count.sort_by(|a, b| b.1.cmp(a.1).then(a.0.cmp(b.0)));
+        println!("[ingest] valeurs non mappées ({field}), {} distinctes:", by_count.len());
+        for (raw, n) in by_count {
+            println!("  {n:>8}  {raw:?}");
+        }
+    }
+}
+
+/// Bilan d'un fichier, agrégé une fois tous les fichiers traités
+#[derive(Default)]
+struct FileReport<'a> {
+    commits: usize,
+    bad_dates: usize,
+    bad_submitted_at: usize,
+    // --merge-into-existing: contributions prolongées, lignes déjà fusionnées,
+    // réponses en conflit avec la base (non écrites)
+    contributions_extended: usize,
+    rows_already_merged: usize,
+    merge_conflicts: usize,
+    titles_truncated: usize,
+    titles_deduped: usize,
+    bad_booleans: usize,
+    authors_created: usize,
+    authors_merged: usize,
+    zipcodes_normalized: usize,
+    zipcodes_rejected: usize,
+    /// codes postaux sans département connu
+    zipcodes_unlocated: usize,
+    ages_normalized: usize,
+    ages_unbucketed: usize,
+    genders_normalized: usize,
+    unmapped_genders: UnmappedValues,
+    duplicate_ranks: usize,
+    out_of_window: counters::OutOfWindow,
+    /// lignes identiques à une ligne de raw_imports
+    duplicates: rawimports::Duplicates,
+    // raw_json: lignes, taille ancien format complet, taille stockée (octets)
+    raw_rows: usize,
+    raw_bytes_full: usize,
+    raw_bytes_stored: usize,
+    skips_by_code: HashMap<&'a str, usize>,
+    // scale: (valeurs ramenées, valeurs écartées) par question
+    scale_report: HashMap<&'a str, (usize, usize)>,
+    range_violations: HashMap<&'a str, RangeViolation>,
+    // single_choice: lignes portant un commentaire accolé (split_comment)
+    comments_by_code: HashMap<&'a str, usize>,
+    rules: rules::Report<'a>,
+    /// --max-errors: fichiers avec lignes en erreur
+    file_errors: Vec<maxerrors::FileErrors>,
+    // --skip-ingested: fichiers sautés; fichiers de même nom, contenu modifié
+    files_skipped: usize,
+    republished: Vec<String>,
+}
+
+impl<'a> FileReport<'a> {
+    fn merge(&mut self, other: FileReport<'a>) {
+        self.commits += other.commits;
+        self.bad_dates += other.bad_dates;
+        self.bad_submitted_at += other.bad_submitted_at;
+        self.contributions_extended += other.contributions_extended;
+        self.rows_already_merged += other.rows_already_merged;
+        self.merge_conflicts += other.merge_conflicts;
+        self.titles_truncated += other.titles_truncated;
+        self.titles_deduped += other.titles_deduped;
+        self.bad_booleans += other.bad_booleans;
+        self.authors_created += other.authors_created;
+        self.authors_merged += other.authors_merged;
+        self.zipcodes_normalized += other.zipcodes_normalized;
+        self.zipcodes_rejected += other.zipcodes_rejected;
+        self.zipcodes_unlocated += other.zipcodes_unlocated;
+        self.ages_normalized += other.ages_normalized;
+        self.ages_unbucketed += other.ages_unbucketed;
+        self.genders_normalized += other.genders_normalized;
+        self.unmapped_genders.merge(other.unmapped_genders);
+        self.duplicate_ranks += other.duplicate_ranks;
+        self.out_of_window.merge(&other.out_of_window);
+        self.duplicates.merge(other.duplicates);
+        self.raw_rows += other.raw_rows;
+        self.raw_bytes_full += other.raw_bytes_full;
+        self.raw_bytes_stored += other.raw_bytes_stored;
+        for (k, v) in other.skips_by_code {
+            *self.skips_by_code.entry(k).or_default() += v;
+        }
+        for (k, (c, s)) in other.scale_report {
+            let e = self.scale_report.entry(k).or_default();
+            e.0 += c;
+            e.1 += s;
+        }
+        for (k, n) in other.comments_by_code {
+            *self.comments_by_code.entry(k).or_default() += n;
+        }
+        for (k, v) in other.range_violations {
+            self.range_violations.entry(k).or_default().merge(v);
+        }
+        self.rules.merge(other.rules);
+        self.file_errors.extend(other.file_errors);
+        self.files_skipped += other.files_skipped;
+        self.republished.extend(other.republished);
+    }
+}
+
+fn ingest_file<'a>(ctx: &IngestCtx<'a>, conn: &mut Client, caches: &mut Caches, path: &str) -> Result<FileReport<'a>> {
+    say!(ctx.bars, "[ingest] fichier: {path}");
+    ctx.progress.start_file(path);
+    let resume_after = match ctx.checkpoints.resume(path) {
+        checkpoint::Resume::Done => {
+            say!(ctx.bars, "  ⏭️  déjà complet (--resume), sauté");
+            ctx.bars.file_done(ctx.bars.rows(path));
+            return Ok(FileReport::default());
+        }
+        checkpoint::Resume::After { rows, line } => {
+            say!(
+                ctx.bars,
+                "  ⏩ reprise après {rows} enregistrements déjà committés (ligne {})",
+                line.map_or("?".to_string(), |l| l.to_string())
+            );
+            rows
+        }
+        checkpoint::Resume::Start => 0,
+    };
+
+    // --skip-ingested: empreinte des 64 Kio avant lecture; même début déjà
+    // ingéré → empreinte entière, contenu déjà ingéré → sauté
+    let mut republished = None;
+    if ctx.skip_ingested && sourcefiles::may_be_ingested(conn, ctx.form_id, &input::head_checksum(path)?)? {
+        let sum = input::checksum(path)?;
+        if let Some(prev) = sourcefiles::ingested(conn, ctx.form_id, &sum)? {
+            say!(
+                ctx.bars,
+                "[skip] already ingested: {path} (batch '{}', le {}, {} lignes)",
+                prev.batch, prev.completed_at, prev.rows
+            );
+            ctx.bars.file_done(ctx.bars.rows(path));
+            return Ok(FileReport { files_skipped: 1, ..FileReport::default() });
+        }
+        let previous = sourcefiles::republished(conn, ctx.form_id, path, &sum)?;
+        if !previous.is_empty() {
+            say!(ctx.bars, "{}", sourcefiles::republished_warning(path, &sum, &previous));
+        }
+        republished = Some(!previous.is_empty());
+    }
+    
+    // open & reader (CSV ou JSON Lines selon l'extension)
+    let rows = input::Rows::open(path, &ctx.read_opts)?;
+    let source_info = rows.info().clone();
+    // contributions.source_file: chemin passé en argument, ou membre du zip
+    let source_file = match (&source_info.compression, &source_info.member) {
+        (input::Compression::Zip, Some(member)) => member.as_str(),
+        _ => path,
+    };
+
+    let headers = rows.headers().clone();
+    let mut unmapped = unmapped::Unmapped::new(ctx.mapping, headers.iter());
+    // colonnes à plusieurs noms: candidat retenu pour ce fichier (cf. aliases.rs)
+    let aliases = aliases::Resolution::resolve(ctx.mapping, &headers);
+    for note in aliases.describe() {
+        say!(ctx.bars, "[alias] {path}: {note}");
+    }
+    let mapped_headers = aliases.headers(&headers);
+    let checksum = rows.checksum();
+    // clés raw_json assainies (en-têtes d'origine conservés si modifiés);
+    // en JSON Lines, recalculées quand les clés changent d'une ligne à l'autre
+    let mut raw_columns: Vec<String> = headers.iter().map(str::to_string).collect();
+    let (mut raw_keys, mut original_headers) = sanitize::sanitize_headers(headers.iter());
+    if !original_headers.is_empty() {
+        say!(ctx.bars, "⚠️  {} en-têtes assainis pour raw_json dans {path}", original_headers.len());
+    }
+
+    if let Some(col) = &ctx.mapping.defaults.contribution.submitted_at {
+        if !mapped_headers.iter().any(|h| h == col.as_str()) {
+            say!(ctx.bars, "⚠️  submitted_at: colonne '{col}' absente de l'en-tête de {path} (contributions sans date)");
+        }
+    }
+
+    // free_text/ranking: avertir une seule fois par fichier des colonnes absentes
+    for qm in &ctx.mapping.questions {
+        if !matches!(qm.qtype.as_str(), "free_text" | "ranking") {
+            continue;
+        }
+        if let Some(src) = &qm.source {
+            for col in &src.columns {
+                if !mapped_headers.iter().any(|h| h == col.as_str()) {
+                    say!(
+                        ctx.bars,
+                        "⚠️  Question '{}': colonne '{}' absente de l'en-tête de {path}",
+                        qm.code, col
+                    );
+                }
+            }
+        }
+    }
+
+    // colonnes des questions absentes: erreur, ou question ignorée (optional_column,
+    // --allow-missing-columns). JSON Lines: pas d'en-tête, seulement les clés de la première ligne
+    let absent_questions: HashSet<&str> = if input::InputFormat::detect(path) == input::InputFormat::JsonLines {
+        HashSet::new()
+    } else {
+        let absent = validate::required_columns(path, ctx.mapping, &mapped_headers, ctx.allow_missing_columns)?;
+        if let Some(missing) = validate::missing_required(path, ctx.mapping, &mapped_headers) {
+            say!(ctx.bars, "⚠️  {missing}\n(--allow-missing-columns: questions concernées ignorées pour ce fichier)");
+        }
+        absent.into_iter().collect()
+    };
+    for code in &absent_questions {
+        say!(ctx.bars, "⚠️  Question '{code}': colonne absente de l'en-tête de {path}, ignorée pour ce fichier");
+    }
+
+    // transactions par batch
+    let mut report = FileReport::default();
+    let mut counts = counters::Counters::default();
+    let mut total = ctx.progress.rows() as usize;
+    let mut pending = 0usize;
+    let rows_bar = ctx.bars.rows(path);
+    let mut file_rows = 0u64;
+    let (mut n_new, mut n_seen) = (0usize, 0usize);
+    let mut bad_numbers = 0usize;
+    let mut rejects = rejects::Rejects::new(&ctx.rejects_dir, path);
+    // --max-errors: lignes sautées, et références écrites dans la transaction
+    // en cours avec leur état précédent dans ctx.existing (rétabli si annulée)
+    let mut file_errors = 0usize;
+    let mut written_refs: Vec<(String, Option<Option<String>>)> = Vec::new();
+    let mut detector = anomalies::Detector::new(ctx.anomalies.clone());
+    // écritures par ligne, préparées sur la connexion de ce fichier (cf. prepared.rs)
+    let stmts = prepared::PreparedStatements::prepare(conn)?;
+    let mut tx = conn.transaction()?;
+    // enregistrements lus, pour le point de reprise
+    let (mut consumed, mut last_line) = (0u64, None);
+
+    for row in rows {
+        consumed += 1;
+        // --max-errors: ligne écrite sous SAVEPOINT, erreur comptée et ligne sautée (cf. maxerrors.rs)
+        let counts_before = ctx.max_errors.tolerant().then(|| counts.clone());
+        let written_before = written_refs.len();
+        let (mut in_savepoint, mut row_line) = (false, None);
+        // enregistrement gardé hors de la closure: ligne sautée recopiée dans rejects/
+        let (record, read_error) = match row {
+            Ok(record) => (Some(record), None),
+            Err(e) => (None, Some(e)),
+        };
+        let outcome = (|| -> Result<bool> {
+            let row = match (&record, read_error) {
+                (Some(record), _) => record.as_ref(),
+                (None, e) => return Err(e.expect("enregistrement ou erreur de lecture")),
+            };
+            let aliased = aliases.row(row);
+            let row: &dyn ColumnAccessor = &aliased;
+            last_line = row.line();
+            row_line = last_line;
+            if consumed <= resume_after {
+                return Ok(false);
+            }
+            counts.rows_read += 1;
+            if ctx.max_errors.tolerant() {
+                maxerrors::savepoint(&mut tx)?;
+                in_savepoint = true;
+            }
+        
+            // ligne à la corbeille: pas de contribution écrite, celle déjà en base est marquée supprimée
+            if is_trashed(row) {
+                counts.trashed += 1;
+                if let Some(reference) = row_reference(row).filter(|_| ctx.mark_trashed) {
+                    counts.trashed_deleted += tx.execute(softdelete::MARK_TRASHED, &[&ctx.form_id, &reference])?;
+                }
+                rejects.reject(row, &["corbeille (trashed)"])?;
+                return Ok(false);
+            }
+            // deux alias remplis différemment: ligne ambiguë
+            aliases
+                .check(row)
+                .with_context(|| format!("{path}, ligne {}", row.line().map_or("?".to_string(), |l| l.to_string())))?;
+            unmapped.count(row);
+
+            // raw_json pour audit + hash (calculé sur la forme stockée)
+            if !row.columns().map(|(k, _)| k).eq(raw_columns.iter().map(String::as_str)) {
+                raw_columns = row.columns().map(|(k, _)| k.to_string()).collect();
+                (raw_keys, original_headers) = sanitize::sanitize_headers(raw_columns.iter().map(String::as_str));
+            }
+            // --no-raw: ni Map ni texte, hash sur les valeurs (raw_json Null: ni fusion ni diff)
+            let (raw_json, raw_text, row_hash) = if ctx.store_raw {
+                let (raw_json, full_len) = rawjson::build(
+                    &raw_keys,
+                    row.columns().map(|(_, v)| v),
+                    &original_headers,
+                    &ctx.mapping.defaults.raw_json,
+                );
+                let raw_text = raw_json.to_string();
+                let row_hash = sha256_rowjson(&raw_text);
+                report.raw_rows += 1;
+                report.raw_bytes_full += full_len;
+                report.raw_bytes_stored += raw_text.len();
+                (raw_json, Some(raw_text), row_hash)
+            } else {
+                let row_hash = rawjson::values_hash(row.columns().map(|(k, _)| k), row.columns().map(|(_, v)| v));
+                (serde_json::Value::Null, None, row_hash)
+            };
+
+            // Créer ou récupérer la contribution
+            let reference = row_reference(row).unwrap_or_else(|| format!("import_{}", total));
+
+            // Ligne identique déjà consignée dans raw_imports (cf. rawimports.rs)
+            if let Some(earlier) = rawimports::Earlier::find(&mut tx, &stmts.raw_import_earlier, ctx.form_id, &row_hash)? {
+                if report.duplicates.note(&earlier) && !ctx.skip_duplicates {
+                    say!(
+                        ctx.bars,
+                        "⚠️  contribution {reference} ({path}) identique à la ligne {} de {} (batch {}); les suivantes sont seulement comptées",
+                        earlier.row_number,
+                        earlier.file,
+                        earlier.batch
+                    );
+                }
+                if ctx.skip_duplicates {
+                    total = ctx.progress.add_row() as usize;
+                    file_rows += 1;
+                    return Ok(false);
+                }
+            }
+
+            // Date de soumission, confrontée à la fenêtre plausible
+            let contribution_map = &ctx.mapping.defaults.contribution;
+            let submitted_raw = col_value(row, contribution_map.submitted_at.as_deref()).filter(|s| !s.trim().is_empty());
+            let mut submitted_at = submitted_raw.and_then(|raw| values::parse_timestamp(raw, ctx.submitted_at_format));
+            if let (Some(raw), None) = (submitted_raw, submitted_at) {
+                report.bad_submitted_at += 1;
+                counts.bad_submitted_at += 1;
+                if report.bad_submitted_at == 1 {
+                    say!(ctx.bars, "⚠️  submitted_at illisible '{}' (contribution {reference}, {path}); les suivants sont seulement comptés", raw.trim());
+                }
+            }
+            if let (Some(ts), Some(window)) = (submitted_at, ctx.submitted_window) {
+                if !window.contains(ts) {
+                    report.out_of_window.note(&reference);
+                    match ctx.policy.record(policy::Category::OutOfWindow) {
+                        policy::Action::Abort => anyhow::bail!(
+                            "{path}: contribution {reference}: submitted_at {ts} hors de la fenêtre {window} ({})",
+                            ctx.policy.why_abort(policy::Category::OutOfWindow)
+                        ),
+                        policy::Action::Reject => {
+                            rejects.reject(row, &[format!("submitted_at {ts} hors de la fenêtre {window}")])?;
+                            return Ok(false);
+                        }
+                        policy::Action::Drop => submitted_at = None,
+                        policy::Action::Warn => {}
                     }
                 }
             }
-            if is_trashed {
-                continue;
+
+            // Règles de qualité du mapping (cf. rules.rs)
+            if !ctx.rules.is_empty() {
+                let violations = ctx.rules.check(row, &ctx.normalize);
+                let first_seen = report.rules.note(&violations);
+                if let Some(v) = violations.first() {
+                    match ctx.rules_mode {
+                        rules::RulesMode::Error => {
+                            anyhow::bail!("{path}: contribution {reference}: {} (--rules error)", v.describe())
+                        }
+                        rules::RulesMode::Quarantine => {
+                            let reasons: Vec<String> = violations.iter().map(|v| v.describe()).collect();
+                            rejects.reject(row, &reasons)?;
+                            return Ok(false);
+                        }
+                        rules::RulesMode::Warn => {
+                            for v in first_seen {
+                                say!(ctx.bars, "⚠️  {} (contribution {reference}, {path}); les suivantes sont seulement comptées", v.describe());
+                            }
+                        }
+                    }
+                }
             }
 
-            // raw_json pour audit + hash
-            let mut rowmap = serde_json::Map::new();
-            for (i, h) in headers.iter().enumerate() {
-                if let Some(v) = rec.get(i) {
-                    rowmap.insert(h.to_string(), serde_json::Value::String(v.to_string()));
+            // Titre: écarté s'il répète la première réponse texte, puis tronqué
+            let mut title = col_value(row, contribution_map.title.as_deref())
+                .map(str::trim)
+                .filter(|t| !t.is_empty())
+                .map(str::to_string);
+            if let (Some(t), Some(qm)) = (&title, ctx.title_dedup_question) {
+                let text = match qm.qtype.as_str() {
+                    "free_text" => qm.free_text_value(row),
+                    _ => col_value(row, qm.source_column.as_deref()).map(|v| ctx.normalize.apply(&qm.code, v).trim().to_string()),
+                };
+                if text.as_deref() == Some(t.as_str()) {
+                    title = None;
+                    report.titles_deduped += 1;
+                }
+            }
+            if let Some(t) = &mut title {
+                if values::truncate_chars(t, contribution_map.title_max_chars.unwrap_or(DEFAULT_TITLE_MAX_CHARS)) {
+                    report.titles_truncated += 1;
                 }
             }
-            let raw_json = serde_json::Value::Object(rowmap);
-            let row_hash = sha256_rowjson(&raw_json);
 
-            // Créer ou récupérer la contribution
-            let reference = rec.get(headers.iter().position(|h| h == "reference").unwrap_or(0))
-                .map(|s| s.trim().to_string())
-                .unwrap_or_else(|| format!("import_{}", total));
-            
-            // Insérer la contribution (simple, sans auteur pour l'instant)
-            let contrib_id: i64 = tx.query_one(
-                "INSERT INTO contributions (form_id, source_contribution_id, raw_json) 
-                 VALUES ($1, $2, $3)
-                 ON CONFLICT (source_contribution_id) DO UPDATE SET raw_json = EXCLUDED.raw_json
-                 RETURNING id",
-                &[&form_id, &reference, &raw_json.to_string()]
-            )?.get(0);
-            
+            // Code postal auteur normalisé (la valeur d'origine reste dans raw_json)
+            let zipcode = match col_value(row, ctx.author_map.zipcode.as_deref()).filter(|_| ctx.with_authors) {
+                None => None,
+                Some(raw) => match values::normalize_zipcode(raw) {
+                    Some(z) => {
+                        if z != raw {
+                            report.zipcodes_normalized += 1;
+                            counts.zipcodes_normalized += 1;
+                        }
+                        Some(z)
+                    }
+                    None => {
+                        report.zipcodes_rejected += 1;
+                        counts.zipcodes_rejected += 1;
+                        match ctx.policy.record(policy::Category::BadZipcode) {
+                            policy::Action::Abort => anyhow::bail!(
+                                "{path}: contribution {reference}: code postal invalide '{raw}' ({})",
+                                ctx.policy.why_abort(policy::Category::BadZipcode)
+                            ),
+                            policy::Action::Reject => {
+                                rejects.reject(row, &[format!("code postal invalide '{raw}'")])?;
+                                return Ok(false);
+                            }
+                            policy::Action::Warn => Some(raw.trim().to_string()),
+                            policy::Action::Drop => None,
+                        }
+                    }
+                },
+            };
+
+            // Tranche d'âge canonique (valeur conservée telle quelle si inclassable)
+            let age_range = col_value(row, ctx.author_map.age_range.as_deref())
+                .filter(|_| ctx.with_authors)
+                .map(|raw| match age::normalize(raw, &ctx.author_map.age_range_map, ctx.mapping.form.reference_year) {
+                    Some(bucket) => {
+                        if bucket != raw {
+                            report.ages_normalized += 1;
+                            counts.ages_normalized += 1;
+                        }
+                        bucket.to_string()
+                    }
+                    None => {
+                        report.ages_unbucketed += 1;
+                        counts.ages_unbucketed += 1;
+                        raw.to_string()
+                    }
+                });
+            // Genre: code canonique de gender_map, sinon valeur brute (comptée)
+            let gender = col_value(row, ctx.author_map.gender.as_deref())
+                .filter(|_| ctx.with_authors)
+                .map(|raw| {
+                    if ctx.gender_map.is_empty() {
+                        return raw.to_string();
+                    }
+                    match ctx.gender_map.get(&values::fold(raw)) {
+                        Some(code) => {
+                            if code != raw {
+                                report.genders_normalized += 1;
+                                counts.genders_normalized += 1;
+                            }
+                            code.clone()
+                        }
+                        None => {
+                            report.unmapped_genders.note(raw);
+                            counts.genders_unmapped += 1;
+                            raw.to_string()
+                        }
+                    }
+                });
+            // Département et région depuis le code postal (NULL si non résolu)
+            let located = zipcode.as_deref().map(|z| ctx.geo.resolve(z));
+            if located == Some(None) {
+                report.zipcodes_unlocated += 1;
+            }
+            let (department_code, region_code) = match located.flatten() {
+                Some((d, r)) => (Some(d.to_string()), Some(r.to_string())),
+                None => (None, None),
+            };
+            let cleaned = CleanedAuthor { zipcode, age_range, gender, department_code, region_code };
+
+            let known = ctx.existing.lock().unwrap().lookup(&reference, |r| existing::select_existing(&mut tx, ctx.form_id, r))?;
+            if known.is_some() { n_seen += 1; } else { n_new += 1; }
+
+            // --on-existing: référence déjà en base (ou déjà lue dans ce run)
+            if known.is_some() {
+                match ctx.on_existing {
+                    OnExisting::Update => {}
+                    OnExisting::Skip => {
+                        counts.skipped_existing += 1;
+                        total = ctx.progress.add_row() as usize;
+                        file_rows += 1;
+                        return Ok(false);
+                    }
+                    OnExisting::Error => anyhow::bail!(
+                        "--on-existing error: référence '{reference}' déjà en base pour le formulaire ({path}, ligne {})",
+                        row.line().map_or("?".to_string(), |l| l.to_string())
+                    ),
+                }
+            }
+
+            // Ligne identique à celle déjà en base (même raw_hash): rien à réécrire.
+            // En fusion, le raw_hash stocké est celui du tableau: jamais égal.
+            if !ctx.force && known.as_ref().is_some_and(|h| h.as_deref() == Some(row_hash.as_str())) {
+                counts.unchanged += 1;
+                total = ctx.progress.add_row() as usize;
+                file_rows += 1;
+                return Ok(false);
+            }
+
+            // --show-changes: contribution modifiée, comparée avant réécriture
+            if let (Some(log), Some(stored_hash)) = (&ctx.changes, &known) {
+                if !ctx.merge_into_existing && stored_hash.as_deref() != Some(row_hash.as_str()) {
+                    let stored: Option<String> = tx.query_one(
+                        "SELECT raw_json::text FROM contributions WHERE form_id = $1 AND source_contribution_id = $2",
+                        &[&ctx.form_id, &reference],
+                    )?.get(0);
+                    let stored = stored.and_then(|s| serde_json::from_str(&s).ok()).unwrap_or(serde_json::Value::Null);
+                    log.lock().unwrap().record(path, &reference, &changes::diff(&stored, &raw_json))?;
+                }
+            }
+
+            let author_id = if ctx.with_authors {
+                match ensure_author(&mut tx, caches, ctx.author_map, ctx.author_conflict, ctx.email_salt, &cleaned, row)? {
+                    Some((id, true)) => {
+                        report.authors_created += 1;
+                        Some(id)
+                    }
+                    Some((id, false)) => {
+                        report.authors_merged += 1;
+                        Some(id)
+                    }
+                    None => None,
+                }
+            } else {
+                None
+            };
+
+            let origin = existing::Origin { file: source_file, line: row.line().map(|l| l as i64) };
+
+            // --merge-into-existing: la ligne prolonge une contribution déjà en base
+            let extend = if ctx.merge_into_existing && known.is_some() {
+                let fill = merge::Fill { author_id, submitted_at, title: title.as_deref(), raw_json_kind: ctx.raw_json_kind };
+                merge::extend_contribution(&mut tx, ctx.form_id, &reference, &raw_json, &fill, ctx.batch, &origin)?
+            } else {
+                merge::Extend::NotFound
+            };
+            let (contrib_id, merged) = match extend {
+                merge::Extend::AlreadyMerged => {
+                    report.rows_already_merged += 1;
+                    return Ok(false);
+                }
+                merge::Extend::Extended(id, hash, answers) => {
+                    ctx.existing.lock().unwrap().record(&reference, &hash);
+                    if ctx.max_errors.tolerant() {
+                        written_refs.push((reference.clone(), known.clone()));
+                    }
+                    report.contributions_extended += 1;
+                    (id, Some(answers))
+                }
+                merge::Extend::NotFound => {
+                    // Insérer la contribution (--on-existing skip: jamais de réécriture,
+                    // même pour une référence écrite entre-temps par un autre thread)
+                    let sql = match ctx.on_existing {
+                        OnExisting::Update => &stmts.upsert_contribution,
+                        OnExisting::Skip | OnExisting::Error => &stmts.insert_contribution,
+                    };
+                    let raw_param = ctx.raw_json_kind.param(raw_text.is_some().then_some(&raw_json), raw_text.as_deref());
+                    let params: [&(dyn postgres::types::ToSql + Sync); 10] = [
+                        &ctx.form_id, &reference, &raw_param, &row_hash, &author_id, &ctx.batch, &submitted_at, &title,
+                        &origin.file, &origin.line,
+                    ];
+                    let Some(row) = retry::in_savepoint(&mut tx, ctx.retry, |t| t.query_opt(sql, &params))? else {
+                        match ctx.on_existing {
+                            OnExisting::Error => anyhow::bail!(
+                                "--on-existing error: référence '{reference}' déjà en base pour le formulaire ({path})"
+                            ),
+                            _ => {
+                                counts.skipped_existing += 1;
+                                total = ctx.progress.add_row() as usize;
+                                file_rows += 1;
+                                return Ok(false);
+                            }
+                        }
+                    };
+                    ctx.existing.lock().unwrap().record(&reference, &row_hash);
+                    if ctx.max_errors.tolerant() {
+                        written_refs.push((reference.clone(), known.clone()));
+                    }
+                    if known.is_none() {
+                        counts.inserted += 1;
+                    }
+                    (row.get(0), None)
+                }
+            };
+            counts.contributions += 1;
+            let raw = raw_text.is_some().then_some(&raw_json);
+            tx.execute(&stmts.raw_import, &[&ctx.form_id, &ctx.batch, &source_file, &(consumed as i64), &contrib_id, &row_hash, &raw])?;
+        
             // questions - LOGIQUE CORRIGÉE
-            for qm in &mapping.questions {
+            for qm in &ctx.mapping.questions {
+                // type inconnu (--allow-unknown-types), colonne absente ou condition
+                // if_column non remplie pour cette ligne: question ignorée
+                if qm.kind().is_none() || absent_questions.contains(qm.code.as_str()) || !ctx.normalize.applies(qm, row) {
+                    continue;
+                }
+
+                // matrix: chaque cellule est résolue contre les options partagées
+                if qm.qtype == "matrix" {
+                    for (code, mrow) in qm.matrix_children() {
+                        let qid = caches.qid_by_code[&code];
+                        let raw = match cells::MatrixCell::of(row.cell(&mrow.source_column)) {
+                            cells::MatrixCell::Absent => continue,
+                            cells::MatrixCell::Empty => {
+                                if qm.record_skips && merged.is_none() {
+                                    tx.execute(&stmts.answer_skipped, &[&contrib_id, &qid, &1i32])?;
+                                    counts.skip(&code);
+                                    *report.skips_by_code.entry(qm.code.as_str()).or_default() += 1;
+                                }
+                                continue;
+                            }
+                            cells::MatrixCell::Value(raw) => raw,
+                        };
+                        let oid = if qm.options_from_values {
+                            ensure_dynamic_option_with_limits(caches, &ctx.dynamic, qid, raw, &code, qm.dynamic_limit(&ctx.mapping.defaults), ctx.retry)?
+                        } else if let Some(&oid) = caches.opt_by_qid_label.get(&(qid, raw.to_string())) {
+                            oid
+                        } else {
+                            say!(
+                                ctx.bars,
+                                "⚠️  Question '{}': Réponse '{}' non trouvée dans options prédéfinies, ignorée",
+                                code, raw
+                            );
+                            continue;
+                        };
+                        if let Some(m) = &merged {
+                            match m.choice(qid, oid) {
+                                merge::Choice::New => {}
+                                merge::Choice::Same => continue,
+                                merge::Choice::Conflict => {
+                                    report.merge_conflicts += 1;
+                                    say!(
+                                        ctx.bars,
+                                        "⚠️  Question '{}': contribution {} déjà répondue autrement, '{}' non fusionnée",
+                                        code, reference, raw
+                                    );
+                                    continue;
+                                }
+                            }
+                        }
+                        let answer_id: i64 = tx.query_one(&stmts.answer_choice, &[&contrib_id, &qid, &1i32])?.get(0);
+                        counts.answer(&code);
+                        tx.execute(&stmts.clear_answer_options, &[&answer_id])?;
+                        tx.execute(&stmts.answer_option, &[&answer_id, &oid])?;
+                    }
+                    continue;
+                }
+
                 let qid = *caches.qid_by_code.get(&qm.code).expect("qid");
-                match qm.qtype.as_str() {
-                    "single_choice" => {
+
+                // --merge-into-existing: position suivante si la question a déjà des réponses
+                let pos = merged.as_ref().map_or(1, |m| m.position(qid));
+
+                // question vue mais laissée vide: answer marquée skipped
+                // (pas sur une contribution prolongée: la réponse peut être sur une autre page;
+                // ni avec default_value, qui remplace la cellule vide)
+                if qm.record_skips && merged.is_none() && qm.default_value.is_none() {
+                    let truthy = ctx.truthy_by_code.get(qm.code.as_str()).map(Vec::as_slice);
+                    if question_cells_empty(qm, row, truthy) == Some(true) {
+                        tx.execute(&stmts.answer_skipped, &[&contrib_id, &qid, &1i32])?;
+                        counts.skip(&qm.code);
+                        *report.skips_by_code.entry(qm.code.as_str()).or_default() += 1;
+                        continue;
+                    }
+                }
+
+                match qm.kind() {
+                    Some(QType::SingleChoice) => {
                         if let Some(col) = &qm.source_column {
-                            if let Some(ix) = headers.iter().position(|h| h == col) {
-                                if let Some(v) = rec.get(ix) { 
-                                    let raw = v.trim();
-                                    if !raw.is_empty() {
-                                        let oid = if qm.options_from_values {
-                                            // 🛡️ VERSION SÉCURISÉE avec limites
-                                            ensure_dynamic_option_with_limits(&mut tx, &mut caches, qid, raw, &qm.code)?
+                            if let Some(v) = row.cell(col).or(qm.default_value.as_ref().map(|_| "")) {
+                                let v = ctx.normalize.apply(&qm.code, v);
+                                let (raw, comment) = match ctx.comment_split_by_code.get(qm.code.as_str()) {
+                                    Some(re) => cells::split_comment(re, v.trim()),
+                                    None => (v.trim(), None),
+                                };
+                                if comment.is_some() {
+                                    *report.comments_by_code.entry(qm.code.as_str()).or_default() += 1;
+                                    counts.comment(&qm.code);
+                                }
+                                let translated = ctx.value_maps_by_code.get(qm.code.as_str()).and_then(|m| m.translate(raw));
+                                let raw = translated.unwrap_or(raw);
+                                // default_value: après value_map, cherchée aussi par code
+                                let defaulted = raw.is_empty() && qm.default_value.is_some();
+                                let raw = if defaulted { qm.default_value.as_deref().unwrap_or_default().trim() } else { raw };
+                                if !raw.is_empty() {
+                                    let oid = if qm.options_from_values {
+                                        // 🛡️ VERSION SÉCURISÉE avec limites
+                                        ensure_dynamic_option_with_limits(caches, &ctx.dynamic, qid, raw, &qm.code, qm.dynamic_limit(&ctx.mapping.defaults), ctx.retry)?
+                                    } else {
+                                        if let Some(oid) = declared_option(caches, qid, raw, translated.is_some() || defaulted) {
+                                            oid
                                         } else {
-                                            if let Some(oid) = caches.opt_by_qid_label.get(&(qid, raw.to_string())) {
-                                                *oid
-                                            } else {
-                                                // ⚠️ FALLBACK SÉCURISÉ: Créer l'option manquante mais avec avertissement
-                                                println!(
-                                                    "⚠️  Question '{}': Réponse '{}' non trouvée dans options prédéfinies, création dynamique",
-                                                    qm.code, raw
+                                            match ctx.policy.record(policy::Category::UnmatchedOption) {
+                                                policy::Action::Abort => anyhow::bail!(
+                                                    "{path}: question '{}': réponse '{}' absente des options (contribution {}, {})",
+                                                    qm.code, raw, reference, ctx.policy.why_abort(policy::Category::UnmatchedOption)
+                                                ),
+                                                policy::Action::Drop => {
+                                                    say!(
+                                                        ctx.bars,
+                                                        "⚠️  Question '{}': Réponse '{}' non trouvée dans options prédéfinies, ignorée",
+                                                        qm.code, raw
+                                                    );
+                                                    continue;
+                                                }
+                                                _ => {
+                                                    // ⚠️ FALLBACK SÉCURISÉ: Créer l'option manquante mais avec avertissement
+                                                    say!(
+                                                        ctx.bars,
+                                                        "⚠️  Question '{}': Réponse '{}' non trouvée dans options prédéfinies, création dynamique",
+                                                        qm.code, raw
+                                                    );
+                                                    ensure_dynamic_option_with_limits(caches, &ctx.dynamic, qid, raw, &qm.code, qm.dynamic_limit(&ctx.mapping.defaults), ctx.retry)?
+                                                }
+                                            }
+                                        }
+                                    };
+                                    if let Some(m) = &merged {
+                                        match m.choice(qid, oid) {
+                                            merge::Choice::New => {}
+                                            merge::Choice::Same => continue,
+                                            merge::Choice::Conflict => {
+                                                report.merge_conflicts += 1;
+                                                say!(
+                                                    ctx.bars,
+                                                    "⚠️  Question '{}': contribution {} déjà répondue autrement, '{}' non fusionnée",
+                                                    qm.code, reference, raw
                                                 );
-                                                ensure_dynamic_option_with_limits(&mut tx, &mut caches, qid, raw, &qm.code)?
+                                                continue;
                                             }
-                                        };
-                                        // Créer l'answer avec l'option sélectionnée
-                                        let answer_id: i64 = tx.query_one(
-                                            "INSERT INTO answers (contribution_id, question_id, position) 
-                                             VALUES ($1, $2, $3)
-                                             ON CONFLICT (contribution_id, question_id, position) 
-                                             DO UPDATE SET contribution_id = EXCLUDED.contribution_id
-                                             RETURNING id",
-                                            &[&contrib_id, &qid, &1i32]
-                                        )?.get(0);
-                                        
-                                        // Créer la liaison answer_option
-                                        tx.execute(
-                                            "INSERT INTO answer_options (answer_id, option_id) 
-                                             VALUES ($1, $2)
-                                             ON CONFLICT (answer_id, option_id) DO NOTHING",
-                                            &[&answer_id, &oid]
-                                        )?;
+                                        }
                                     }
+                                    // Créer l'answer avec l'option sélectionnée (+ commentaire accolé)
+                                    let answer_id: i64 = tx.query_one(&stmts.answer_choice_text, &[&contrib_id, &qid, &pos, &comment])?.get(0);
+                                    counts.answer(&qm.code);
+                                    detector.choice(&qm.code, raw);
+                                
+                                    // Créer la liaison answer_option
+                                    tx.execute(&stmts.answer_option, &[&answer_id, &oid])?;
                                 }
                             }
                         }
                     }
-                    "text" | "number" | "scale" | "date" => {
+                    Some(QType::MultiChoice) if qm.options_from_columns() => {
+                        let truthy = &ctx.truthy_by_code[qm.code.as_str()];
+                        let mut oids: Vec<i64> = Vec::new();
+                        for opt in &qm.options {
+                            let Some(col) = &opt.source_column else { continue };
+                            let checked = row.cell(col)
+                                .map(|v| truthy.contains(&v.trim().to_lowercase()))
+                                .unwrap_or(false);
+                            if checked {
+                                if let Some(&oid) = caches.opt_by_qid_code.get(&(qid, opt.code.clone())) {
+                                    oids.push(oid);
+                                }
+                            }
+                        }
+                        if let Some(m) = &merged {
+                            oids.retain(|&oid| m.choice(qid, oid) != merge::Choice::Same);
+                        }
+                        if oids.is_empty() && merged.is_none() {
+                            // plus aucune case cochée depuis un import précédent
+                            tx.execute(&stmts.drop_answer, &[&contrib_id, &qid, &pos])?;
+                        } else if !oids.is_empty() {
+                            let answer_id: i64 = tx.query_one(&stmts.answer_choice, &[&contrib_id, &qid, &pos])?.get(0);
+                            counts.answer(&qm.code);
+                            if merged.is_none() {
+                                tx.execute(&stmts.clear_answer_options, &[&answer_id])?;
+                            }
+                            for oid in &oids {
+                                tx.execute(&stmts.answer_option, &[&answer_id, oid])?;
+                            }
+                        }
+                    }
+                    Some(QType::MultiChoice) => {
                         if let Some(col) = &qm.source_column {
-                            if let Some(ix) = headers.iter().position(|h| h == col) {
-                                if let Some(v) = rec.get(ix) { 
-                                    let raw = v.trim();
-                                    if !raw.is_empty() {
-                                        // Créer la réponse texte directement
-                                        tx.execute(
-                                            "INSERT INTO answers (contribution_id, question_id, position, \"text\") 
-                                             VALUES ($1, $2, $3, $4)
-                                             ON CONFLICT (contribution_id, question_id, position) 
-                                             DO UPDATE SET \"text\" = EXCLUDED.\"text\"",
-                                            &[&contrib_id, &qid, &1i32, &raw]
-                                        )?;
+                            if let Some(v) = row.cell(col).or(qm.default_value.as_ref().map(|_| "")) {
+                                let v = ctx.normalize.apply(&qm.code, v);
+                                // default_value: pas de value_map, cherchée aussi par code
+                                let defaulted = v.trim().is_empty() && qm.default_value.is_some();
+                                let v = if defaulted { qm.default_cell().unwrap_or_default() } else { v };
+                                let mut oids: Vec<i64> = Vec::new();
+                                for raw in cells::split_tokens(&v, qm.multi_delimiter()) {
+                                    let translated = if defaulted {
+                                        None
+                                    } else {
+                                        ctx.value_maps_by_code.get(qm.code.as_str()).and_then(|m| m.translate(raw))
+                                    };
+                                    let raw = translated.unwrap_or(raw);
+                                    let oid = if qm.options_from_values {
+                                        // 🛡️ Même garde-fou que single_choice (max_dynamic_options)
+                                        ensure_dynamic_option_with_limits(caches, &ctx.dynamic, qid, raw, &qm.code, qm.dynamic_limit(&ctx.mapping.defaults), ctx.retry)?
+                                    } else if let Some(oid) = declared_option(caches, qid, raw, translated.is_some() || defaulted) {
+                                        oid
+                                    } else {
+                                        // ⚠️ Option inconnue: on avertit sans créer d'option (sauf abort)
+                                        if ctx.policy.record(policy::Category::UnmatchedOption) == policy::Action::Abort {
+                                            anyhow::bail!(
+                                                "{path}: question '{}': réponse '{}' absente des options (contribution {}, {})",
+                                                qm.code, raw, reference, ctx.policy.why_abort(policy::Category::UnmatchedOption)
+                                            );
+                                        }
+                                        say!(
+                                            ctx.bars,
+                                            "⚠️  Question '{}': Réponse '{}' non trouvée dans options prédéfinies, ignorée",
+                                            qm.code, raw
+                                        );
+                                        continue;
+                                    };
+                                    if !oids.contains(&oid) {
+                                        oids.push(oid);
                                     }
                                 }
+                                if let Some(m) = &merged {
+                                    oids.retain(|&oid| m.choice(qid, oid) != merge::Choice::Same);
+                                }
+                                if oids.is_empty() && merged.is_none() {
+                                    // cellule vidée depuis un import précédent
+                                    tx.execute(&stmts.drop_answer, &[&contrib_id, &qid, &pos])?;
+                                } else if !oids.is_empty() {
+                                    // Une seule answer par contribution + question
+                                    let answer_id: i64 = tx.query_one(&stmts.answer_choice, &[&contrib_id, &qid, &pos])?.get(0);
+                                    counts.answer(&qm.code);
+                                    if merged.is_none() {
+                                        // ré-import: les options d'avant sont remplacées
+                                        tx.execute(&stmts.clear_answer_options, &[&answer_id])?;
+                                    }
+
+                                    // … et une liaison answer_option par option choisie
+                                    for oid in &oids {
+                                        tx.execute(&stmts.answer_option, &[&answer_id, oid])?;
+                                    }
+                                }
+                            }
+                        }
+                    }
+                    Some(QType::Ranking) => {
+                        let Some(src) = &qm.source else { continue };
+                        if merged.as_ref().is_some_and(|m| m.answered(qid)) {
+                            if src.columns.iter().any(|col| col_value(row, Some(col)).is_some_and(|v| !v.trim().is_empty())) {
+                                report.merge_conflicts += 1;
+                                say!(ctx.bars, "⚠️  Question '{}': contribution {} déjà classée, classement non fusionné", qm.code, reference);
+                            }
+                            continue;
+                        }
+                        for cells::Rank { position, raw, repeated } in cells::ranks(src.columns.iter().map(|col| row.cell(col))) {
+                            if repeated {
+                                say!(
+                                    ctx.bars,
+                                    "⚠️  Question '{}': '{}' classé plusieurs fois (contribution {})",
+                                    qm.code, raw, reference
+                                );
+                                report.duplicate_ranks += 1;
+                            }
+                            let oid = if qm.options_from_values {
+                                ensure_dynamic_option_with_limits(caches, &ctx.dynamic, qid, raw, &qm.code, qm.dynamic_limit(&ctx.mapping.defaults), ctx.retry)?
+                            } else if let Some(oid) = caches.opt_by_qid_label.get(&(qid, raw.to_string())) {
+                                *oid
+                            } else {
+                                say!(
+                                    ctx.bars,
+                                    "⚠️  Question '{}': Réponse '{}' non trouvée dans options prédéfinies, ignorée",
+                                    qm.code, raw
+                                );
+                                continue;
+                            };
+                            // Une answer par rang: position = rang (1 = premier choix)
+                            let answer_id: i64 = tx.query_one(&stmts.answer_choice, &[&contrib_id, &qid, &position])?.get(0);
+                            counts.answer(&qm.code);
+                            tx.execute(&stmts.clear_answer_options, &[&answer_id])?;
+                            tx.execute(&stmts.answer_option, &[&answer_id, &oid])?;
+                        }
+                    }
+                    Some(QType::FreeText) => {
+                        if let Some(text) = qm.free_text_value(row) {
+                            dictionary::write_text_answer(&mut tx, &stmts, ctx.dictionary.as_ref(), contrib_id, qid, pos, &text)?;
+                            counts.answer(&qm.code);
+                        }
+                    }
+                    Some(QType::Number) => {
+                        let cell = ctx.normalize.value(&qm.code, col_value(row, qm.source_column.as_deref()));
+                        if let Some(raw) = cell.or_else(|| qm.default_cell()) {
+                            let raw = raw.as_ref();
+                            // valeur brute conservée dans "text" pour audit
+                            let num = values::parse_number(raw);
+                            if num.is_none() {
+                                let action = ctx.policy.record(policy::Category::BadNumber);
+                                if action == policy::Action::Abort {
+                                    anyhow::bail!(
+                                        "{path}: question '{}': nombre illisible '{}' (contribution {}, {})",
+                                        qm.code, raw, reference, ctx.policy.why_abort(policy::Category::BadNumber)
+                                    );
+                                }
+                                bad_numbers += 1;
+                                counts.bad_numbers += 1;
+                                ctx.progress.add_errors(1);
+                                if action == policy::Action::Drop {
+                                    continue;
+                                }
+                            }
+                            if let Some(v) = num {
+                                detector.number(&qm.code, v);
+                                if !ctx.check_range(qm, v, &mut report, path, &reference)? {
+                                    continue;
+                                }
                             }
+                            tx.execute(&stmts.answer_float, &[&contrib_id, &qid, &pos, &raw, &num])?;
+                            counts.answer(&qm.code);
                         }
                     }
-                    // ... autres types de questions
-                    _ => {
-                        // Types de questions non encore implémentés
+                    Some(QType::Date) => {
+                        let cell = ctx.normalize.value(&qm.code, col_value(row, qm.source_column.as_deref()));
+                        if let Some(raw) = cell.or_else(|| qm.default_cell()) {
+                            let raw = raw.as_ref();
+                            let date = values::parse_date(raw, &ctx.date_formats_by_code[qm.code.as_str()]);
+                            if date.is_none() {
+                                let action = ctx.policy.record(policy::Category::BadDate);
+                                if action == policy::Action::Abort {
+                                    anyhow::bail!(
+                                        "{path}: question '{}': date illisible '{}' (contribution {}, {})",
+                                        qm.code, raw, reference, ctx.policy.why_abort(policy::Category::BadDate)
+                                    );
+                                }
+                                say!(ctx.bars, "⚠️  Question '{}': date illisible '{}' (contribution {})", qm.code, raw, reference);
+                                report.bad_dates += 1;
+                                counts.bad_dates += 1;
+                                ctx.progress.add_errors(1);
+                                if action == policy::Action::Drop {
+                                    continue;
+                                }
+                            }
+                            let text = qm.date_text(raw, date);
+                            tx.execute(&stmts.answer_date, &[&contrib_id, &qid, &pos, &text.as_ref(), &date])?;
+                            counts.answer(&qm.code);
+                        }
+                    }
+                    Some(QType::Boolean) => {
+                        let cell = qm.source_column.as_deref().and_then(|col| row.cell(col)).map(|raw| ctx.normalize.apply(&qm.code, raw));
+                        let cell = cell.filter(|raw| qm.default_value.is_none() || !raw.trim().is_empty());
+                        let Some(raw) = cell.or_else(|| qm.default_cell()) else { continue };
+                        let raw = raw.as_ref();
+                        let value = match ctx.boolean_values_by_code[qm.code.as_str()].parse(raw) {
+                            Some(values::BoolAnswer::Unknown) if !qm.allow_unknown => None,
+                            Some(v) => Some(v),
+                            None => {
+                                if ctx.policy.record(policy::Category::BadBoolean) == policy::Action::Abort {
+                                    anyhow::bail!(
+                                        "{path}: question '{}': valeur oui/non inconnue '{}' (contribution {}, {})",
+                                        qm.code, raw.trim(), reference, ctx.policy.why_abort(policy::Category::BadBoolean)
+                                    );
+                                }
+                                say!(ctx.bars, "⚠️  Question '{}': valeur oui/non inconnue '{}' (contribution {})", qm.code, raw.trim(), reference);
+                                report.bad_booleans += 1;
+                                counts.bad_booleans += 1;
+                                ctx.progress.add_errors(1);
+                                None
+                            }
+                        };
+                        if let Some(v) = value {
+                            tx.execute(&stmts.answer_float, &[&contrib_id, &qid, &pos, &v.as_str(), &v.as_num()])?;
+                            counts.answer(&qm.code);
+                        }
                     }
+                    Some(QType::Scale) => {
+                        let cell = ctx.normalize.value(&qm.code, col_value(row, qm.source_column.as_deref()));
+                        if let Some(raw) = cell.or_else(|| qm.default_cell()) {
+                            let raw = raw.as_ref();
+                            let stats = report.scale_report.entry(qm.code.as_str()).or_default();
+                            let value = match qm.scale_outcome(raw) {
+                                ScaleOutcome::InRange(v) => Some(v),
+                                ScaleOutcome::Clamped(v) => {
+                                    stats.0 += 1;
+                                    Some(v)
+                                }
+                                ScaleOutcome::Skipped(_) => {
+                                    stats.1 += 1;
+                                    None
+                                }
+                                ScaleOutcome::Error(Some(v)) => anyhow::bail!(
+                                    "{path}: question '{}': valeur {} hors de [{}, {}] (contribution {})",
+                                    qm.code, v, qm.scale_min.unwrap_or(i64::MIN), qm.scale_max.unwrap_or(i64::MAX), reference
+                                ),
+                                ScaleOutcome::Error(None) => anyhow::bail!(
+                                    "{path}: question '{}': valeur d'échelle illisible '{}' (contribution {})",
+                                    qm.code, raw, reference
+                                ),
+                            };
+                            if let Some(v) = value {
+                                detector.number(&qm.code, v as f64);
+                                tx.execute(&stmts.answer_int, &[&contrib_id, &qid, &pos, &raw, &v])?;
+                                counts.answer(&qm.code);
+                            }
+                        }
+                    }
+                    Some(QType::Text) => {
+                        let cell = qm.source_column.as_deref().and_then(|col| row.cell(col)).map(|v| ctx.normalize.apply(&qm.code, v));
+                        let raw = cell.as_deref().map(str::trim).filter(|v| !v.is_empty());
+                        if let Some(raw) = raw.or(qm.default_value.as_deref()) {
+                            // Créer la réponse texte directement
+                            dictionary::write_text_answer(&mut tx, &stmts, ctx.dictionary.as_ref(), contrib_id, qid, pos, raw)?;
+                            counts.answer(&qm.code);
+                        }
+                    }
+                    // sous-questions traitées plus haut
+                    Some(QType::Matrix) => {}
+                    // type inconnu: écarté plus haut (--allow-unknown-types)
+                    None => {}
                 }
             }
-
-            pending += 1;
-            total += 1;
-
-            if pending % commit_every == 0 {
-                tx.commit()?;
-                println!("  … {total} lignes (commit)");
-                tx = conn.transaction()?;
-                pending = 0;
-            } else if pending % log_every == 0 {
-                println!("  … {total}");
+            Ok(true)
+        })();
+        match outcome {
+            Ok(written) => {
+                if in_savepoint {
+                    maxerrors::release(&mut tx)?;
+                }
+                if !written {
+                    continue;
+                }
             }
+            Err(e) if ctx.max_errors.tolerant() => {
+                if consumed <= resume_after {
+                    continue;
+                }
+                if in_savepoint {
+                    maxerrors::rollback(&mut tx)?;
+                }
+                if let Some(before) = counts_before {
+                    counts = before;
+                }
+                counts.rows_read += 1;
+                // caches alignés sur la base: écritures de la ligne annulées
+                ctx.existing.lock().unwrap().restore(written_refs.drain(written_before..).rev());
+                caches.author_by_source.clear();
+                file_errors += 1;
+                if let Some(record) = &record {
+                    rejects.reject(record.as_ref(), &[format!("{e:#}")])?;
+                }
+                if let Some(msg) = maxerrors::MaxErrors::message(file_errors, path, row_line, &e) {
+                    say!(ctx.bars, "{msg}");
+                }
+                if ctx.max_errors.exceeded(file_errors) {
+                    drop(tx);
+                    ctx.existing.lock().unwrap().restore(written_refs.drain(..).rev());
+                    rejects.finish()?;
+                    ctx.bars.file_done(rows_bar);
+                    let why = format!(
+                        "{path}: {file_errors} lignes en erreur (plafond {} dépassé), transaction en cours annulée",
+                        ctx.max_errors.describe()
+                    );
+                    if ctx.max_errors.abort_on_file_error {
+                        anyhow::bail!("{why} (--abort-on-file-error); dernière erreur: {e:#}");
+                    }
+                    say!(ctx.bars, "❌ {why}: fichier en échec, run poursuivi (commits précédents conservés, reprise: --resume)");
+                    report.file_errors.push(maxerrors::FileErrors { file: path.to_string(), errors: file_errors, failed: true });
+                    return Ok(report);
+                }
+                continue;
+            }
+            Err(e) => return Err(e),
         }
 
-        tx.commit()?;
-        println!("  ✓ terminé pour {path} (total {total})");
+        pending += 1;
+        total = ctx.progress.add_row() as usize;
+        file_rows += 1;
+        if file_rows.is_multiple_of(ctx.log_every as u64) {
+            ctx.warn_anomalies(&mut detector, path);
+        }
+
+        if pending.is_multiple_of(ctx.commit_every) {
+            ctx.run_lock.heartbeat(&mut tx)?;
+            ctx.checkpoints.save(&mut tx, path, consumed, last_line, false)?;
+            tx.commit()?;
+            ctx.progress.committed();
+            report.commits += 1;
+            ctx.counters.lock().unwrap().committed(std::mem::take(&mut counts), path)?;
+            rows_bar.set_position(file_rows);
+            ctx.bars.progress_line(format!("  … {total} lignes (commit)"));
+            tx = conn.transaction()?;
+            written_refs.clear();
+            pending = 0;
+        } else if pending.is_multiple_of(ctx.log_every) {
+            rows_bar.set_position(file_rows);
+            ctx.bars.progress_line(format!("  … {total}"));
+        }
     }
 
-    println!("[ingest] OK — {total} lignes en {:?}.", t0.elapsed());
-    Ok(())
-}
\ No newline at end of file
+    ctx.run_lock.heartbeat(&mut tx)?;
+    ctx.checkpoints.save(&mut tx, path, consumed, last_line, true)?;
+    let sum = checksum.hex();
+    let republished = match republished {
+        Some(r) => r,
+        None => {
+            let previous = sourcefiles::republished(&mut tx, ctx.form_id, path, &sum)?;
+            if !previous.is_empty() {
+                say!(ctx.bars, "{}", sourcefiles::republished_warning(path, &sum, &previous));
+            }
+            !previous.is_empty()
+        }
+    };
+    if republished {
+        report.republished.push(path.to_string());
+    }
+    sourcefiles::record(&mut tx, ctx.form_id, ctx.batch, path, &checksum, consumed)?;
+    tx.commit()?;
+    ctx.progress.committed();
+    report.commits += 1;
+    {
+        let mut counters = ctx.counters.lock().unwrap();
+        counters.out_of_window(path, &report.out_of_window);
+        counters.file_done(std::mem::take(&mut counts), &source_info)?;
+    }
+    rows_bar.set_position(file_rows);
+    ctx.bars.file_done(rows_bar);
+    if bad_numbers > 0 {
+        say!(ctx.bars, "  ⚠️  {bad_numbers} valeurs numériques illisibles (value_num NULL, texte conservé)");
+    }
+    rejects.finish()?;
+    ctx.warn_anomalies(&mut detector, path);
+    if !report.range_violations.is_empty() {
+        say!(ctx.bars, "  ⚠️  valeurs hors plage min/max dans {path}:");
+        for qm in ctx.mapping.questions.iter() {
+            if let Some(v) = report.range_violations.get(qm.code.as_str()) {
+                say!(ctx.bars, "{}", v.line(qm));
+            }
+        }
+    }
+    if report.zipcodes_normalized + report.zipcodes_rejected > 0 {
+        say!(
+            ctx.bars,
+            "  codes postaux: {} normalisés, {} invalides",
+            report.zipcodes_normalized,
+            report.zipcodes_rejected
+        );
+    }
+    if report.ages_normalized + report.ages_unbucketed > 0 {
+        say!(
+            ctx.bars,
+            "  tranches d'âge: {} normalisées, {} inclassables",
+            report.ages_normalized,
+            report.ages_unbucketed
+        );
+    }
+    if report.genders_normalized + report.unmapped_genders.total > 0 {
+        say!(
+            ctx.bars,
+            "  genre: {} normalisés, {} hors gender_map",
+            report.genders_normalized,
+            report.unmapped_genders.total
+        );
+    }
+    if rejects.count > 0 {
+        say!(ctx.bars, "  ⚠️  {} lignes écartées → {}", rejects.count, rejects.path().display());
+    }
+    if file_errors > 0 {
+        say!(ctx.bars, "  ⚠️  {file_errors} lignes en erreur, sautées ({})", ctx.max_errors.describe());
+        report.file_errors.push(maxerrors::FileErrors { file: path.to_string(), errors: file_errors, failed: false });
+    }
+    if report.out_of_window.count > 0 {
+        say!(
+            ctx.bars,
+            "  ⚠️  {} contributions avec submitted_at hors fenêtre, ex: {}",
+            report.out_of_window.count,
+            report.out_of_window.examples.join(", ")
+        );
+    }
+    if let Some(msg) = unmapped.report(path) {
+        say!(ctx.bars, "  {msg}");
+    }
+    say!(ctx.bars, "  ✓ terminé pour {path} (total {total}; {n_new} nouvelles, {n_seen} déjà présentes)");
+    Ok(report)
+}
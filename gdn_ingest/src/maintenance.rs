@@ -0,0 +1,111 @@
+// ---------- Après l'ingestion: ANALYZE / VACUUM ANALYZE ----------
+//
+// Une grosse ingestion laisse au planificateur des statistiques périmées
+// (requêtes suivantes lentes jusqu'au prochain autovacuum). Avec
+// --post-ingest-analyze (ANALYZE) ou --post-ingest-vacuum (VACUUM ANALYZE:
+// en plus, place des lignes réécrites récupérée), chaque table est traitée
+// une fois toutes les transactions committées, sur la connexion de service:
+// VACUUM refuse de tourner dans une transaction. Durée affichée par table.
+//
+// Par défaut les six tables les plus écrites; --vacuum-tables (liste
+// séparée par des virgules) restreint aux plus grosses (answers,
+// answer_options) pour ne pas verrouiller les autres. Noms vérifiés contre
+// les tables de l'ingestion: ils sont insérés tels quels dans le SQL.
+// statement_timeout est levé pour ces commandes (un VACUUM de answers dépasse
+// vite 30 s); --db-lock-timeout-ms continue de borner l'attente des verrous.
+
+use anyhow::Result;
+use postgres::Client;
+use std::time::Instant;
+
+/// Tables traitées sans --vacuum-tables
+const CORE_TABLES: [&str; 6] = ["authors", "questions", "options", "contributions", "answers", "answer_options"];
+/// Noms admis par --vacuum-tables
+const KNOWN_TABLES: [&str; 12] = [
+    "authors",
+    "forms",
+    "questions",
+    "options",
+    "contributions",
+    "answers",
+    "answer_options",
+    "text_values",
+    "import_batches",
+    "ingest_checkpoints",
+    "source_files",
+    "schema_version",
+];
+
+#[derive(Debug, PartialEq)]
+pub struct PostIngest {
+    vacuum: bool,
+    tables: Vec<String>,
+}
+
+impl PostIngest {
+    /// None sans --post-ingest-analyze ni --post-ingest-vacuum
+    pub fn new(analyze: bool, vacuum: bool, tables: &[String]) -> Result<Option<Self>> {
+        if !analyze && !vacuum {
+            if !tables.is_empty() {
+                anyhow::bail!("--vacuum-tables sans --post-ingest-analyze ni --post-ingest-vacuum");
+            }
+            return Ok(None);
+        }
+        let mut chosen: Vec<String> = Vec::new();
+        for t in tables.iter().map(|t| t.trim()).filter(|t| !t.is_empty()) {
+            if !KNOWN_TABLES.contains(&t) {
+                anyhow::bail!("--vacuum-tables: table '{t}' inconnue (possibles: {})", KNOWN_TABLES.join(", "));
+            }
+            if !chosen.iter().any(|c| c == t) {
+                chosen.push(t.to_string());
+            }
+        }
+        if chosen.is_empty() {
+            chosen = CORE_TABLES.iter().map(|t| t.to_string()).collect();
+        }
+        Ok(Some(PostIngest { vacuum, tables: chosen }))
+    }
+
+    fn command(&self) -> &'static str {
+        if self.vacuum {
+            "VACUUM ANALYZE"
+        } else {
+            "ANALYZE"
+        }
+    }
+
+    pub fn run(&self, conn: &mut Client) -> Result<()> {
+        conn.batch_execute("SET statement_timeout = 0")?;
+        let t0 = Instant::now();
+        for table in &self.tables {
+            let t = Instant::now();
+            conn.batch_execute(&format!("{} {table}", self.command()))?;
+            println!("[maintenance] {} {table}: {:.1?}", self.command(), t.elapsed());
+        }
+        println!("[maintenance] ✅ {} tables en {:.1?}", self.tables.len(), t0.elapsed());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn list(s: &str) -> Vec<String> {
+        s.split(',').map(str::to_string).collect()
+    }
+
+    #[test]
+    fn tables_default_to_core_and_are_checked() {
+        assert_eq!(PostIngest::new(false, false, &[]).unwrap(), None);
+        let all = PostIngest::new(true, false, &[]).unwrap().unwrap();
+        assert_eq!(all.tables, CORE_TABLES);
+        assert_eq!(all.command(), "ANALYZE");
+        let some = PostIngest::new(false, true, &list("answers, answer_options,answers")).unwrap().unwrap();
+        assert_eq!(some.tables, ["answers", "answer_options"]);
+        assert_eq!(some.command(), "VACUUM ANALYZE");
+        let err = PostIngest::new(true, false, &list("answers;DROP TABLE forms")).unwrap_err().to_string();
+        assert!(err.contains("inconnue"), "{err}");
+        assert!(PostIngest::new(false, false, &list("answers")).is_err());
+    }
+}
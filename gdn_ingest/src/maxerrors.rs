@@ -0,0 +1,116 @@
+// ---------- --max-errors: lignes en erreur tolérées par fichier ----------
+//
+// Par défaut (--max-errors 0) la première erreur de ligne arrête le run,
+// comme avant. Avec N > 0 (ou négatif: sans plafond), une ligne en erreur
+// est comptée et sautée:
+//   - enregistrement illisible (CSV mal formé, encodage…);
+//   - valeur refusée par la politique d'erreurs (action abort, cf. policy.rs:
+//     --strict-numbers, bad_date=abort…), --rules error, --on-existing error;
+//   - requête refusée par la base (contrainte violée…).
+// Chaque ligne est alors écrite sous SAVEPOINT: une erreur n'annule que
+// ses propres écritures, pas le reste de la transaction (deux allers-retours
+// de plus par ligne, d'où le défaut à 0). La ligne sautée est recopiée dans
+// --rejects-dir avec l'erreur pour motif (cf. rejects.rs).
+//
+// Au-delà de N erreurs dans un fichier: transaction en cours annulée
+// (les lignes des commits précédents restent, reprise: --resume), fichier en
+// échec, et le run passe au fichier suivant — ou s'arrête avec
+// --abort-on-file-error. Erreurs par fichier dans le résumé final et dans
+// import_batches.provenance (clé `row_errors`).
+
+use anyhow::Result;
+use postgres::Transaction;
+use serde::Serialize;
+
+/// Erreurs affichées par fichier; les suivantes sont seulement comptées
+const MAX_SHOWN: usize = 5;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct MaxErrors {
+    /// None: sans plafond (valeur négative)
+    limit: Option<u64>,
+    pub abort_on_file_error: bool,
+}
+
+impl MaxErrors {
+    pub fn new(max_errors: i64, abort_on_file_error: bool) -> Self {
+        MaxErrors { limit: u64::try_from(max_errors).ok(), abort_on_file_error }
+    }
+
+    /// Erreurs de ligne tolérées (lignes sous SAVEPOINT)
+    pub fn tolerant(&self) -> bool {
+        self.limit != Some(0)
+    }
+
+    /// `n` erreurs dépassent le plafond: le fichier échoue
+    pub fn exceeded(&self, n: usize) -> bool {
+        self.limit.is_some_and(|max| n as u64 > max)
+    }
+
+    pub fn describe(&self) -> String {
+        match self.limit {
+            Some(max) => format!("--max-errors {max}"),
+            None => "--max-errors sans plafond".to_string(),
+        }
+    }
+
+    /// Message de la n-ième erreur d'un fichier (None au-delà de MAX_SHOWN)
+    pub fn message(n: usize, path: &str, line: Option<u64>, e: &anyhow::Error) -> Option<String> {
+        let at = line.map(|l| format!(" ligne {l}")).unwrap_or_default();
+        match n {
+            n if n < MAX_SHOWN => Some(format!("⚠️  {path}{at}: ligne sautée: {e:#}")),
+            n if n == MAX_SHOWN => Some(format!("⚠️  {path}{at}: ligne sautée: {e:#} (les suivantes sont seulement comptées)")),
+            _ => None,
+        }
+    }
+}
+
+/// Lignes en erreur d'un fichier (résumé et provenance du batch)
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct FileErrors {
+    pub file: String,
+    pub errors: usize,
+    /// plafond dépassé: transaction en cours annulée
+    pub failed: bool,
+}
+
+pub fn savepoint(tx: &mut Transaction) -> Result<()> {
+    tx.batch_execute("SAVEPOINT ingest_row")?;
+    Ok(())
+}
+
+pub fn release(tx: &mut Transaction) -> Result<()> {
+    tx.batch_execute("RELEASE SAVEPOINT ingest_row")?;
+    Ok(())
+}
+
+/// Écritures de la ligne annulées, transaction utilisable ensuite
+pub fn rollback(tx: &mut Transaction) -> Result<()> {
+    tx.batch_execute("ROLLBACK TO SAVEPOINT ingest_row; RELEASE SAVEPOINT ingest_row")?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn zero_is_fail_fast_and_negative_unlimited() {
+        let fail_fast = MaxErrors::new(0, false);
+        assert!(!fail_fast.tolerant() && fail_fast.exceeded(1));
+        let three = MaxErrors::new(3, false);
+        assert!(three.tolerant() && !three.exceeded(3) && three.exceeded(4));
+        assert_eq!(three.describe(), "--max-errors 3");
+        let unlimited = MaxErrors::new(-1, true);
+        assert!(unlimited.tolerant() && !unlimited.exceeded(usize::MAX));
+        assert_eq!(unlimited.describe(), "--max-errors sans plafond");
+    }
+
+    #[test]
+    fn only_first_errors_are_shown() {
+        let e = anyhow::anyhow!("nombre illisible");
+        assert_eq!(MaxErrors::message(1, "a.csv", Some(12), &e).unwrap(), "⚠️  a.csv ligne 12: ligne sautée: nombre illisible");
+        assert!(MaxErrors::message(MAX_SHOWN, "a.csv", None, &e).unwrap().ends_with("seulement comptées)"));
+        assert_eq!(MaxErrors::message(MAX_SHOWN + 1, "a.csv", None, &e), None);
+    }
+}
@@ -0,0 +1,177 @@
+// ---------- --merge-into-existing: contributions réparties sur plusieurs fichiers ----------
+//
+// Une même référence peut revenir dans un fichier ultérieur (courriers
+// transcrits par vagues, une page par fichier). Avec --merge-into-existing:
+//   - raw_json devient un tableau des lignes reçues, une par fichier
+//     (l'objet d'origine en premier); une ligne déjà présente est ignorée;
+//   - les réponses existantes sont conservées, les nouvelles prennent la
+//     position libre suivante de leur question;
+//   - single_choice / matrix déjà répondues: même option → rien à faire,
+//     autre option → conflit compté et signalé, réponse d'origine gardée;
+//   - ranking déjà classé: non réécrit (conflit);
+//   - pas de réponse `skipped` sur une contribution prolongée.
+
+use anyhow::Result;
+use postgres::GenericClient;
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// Issue de la fusion d'une ligne dans une contribution existante
+pub enum Extend {
+    /// référence absente du formulaire: insertion normale
+    NotFound,
+    /// ligne identique déjà fusionnée (fichier réimporté)
+    AlreadyMerged,
+    /// contribution prolongée: id, nouveau raw_hash, réponses déjà en base
+    Extended(i64, String, MergedAnswers),
+}
+
+/// Choix single_choice/matrix confronté à la réponse déjà en base
+#[derive(Debug, PartialEq)]
+pub enum Choice {
+    New,
+    Same,
+    Conflict,
+}
+
+/// Réponses d'une contribution prolongée, relues avant d'y ajouter la ligne
+#[derive(Debug, Default)]
+pub struct MergedAnswers {
+    /// dernière position occupée (skipped compris), par question
+    last_position: HashMap<i64, i32>,
+    /// options des réponses non skipped, par question
+    options: HashMap<i64, Vec<i64>>,
+}
+
+impl MergedAnswers {
+    fn load(tx: &mut impl GenericClient, contrib_id: i64) -> Result<Self> {
+        let mut merged = MergedAnswers::default();
+        for r in tx.query(
+            "SELECT a.question_id, MAX(a.position),
+                    COALESCE(array_agg(ao.option_id) FILTER (WHERE NOT a.skipped AND ao.option_id IS NOT NULL), '{}'),
+                    bool_or(NOT a.skipped)
+             FROM answers a LEFT JOIN answer_options ao ON ao.answer_id = a.id
+             WHERE a.contribution_id = $1
+             GROUP BY a.question_id",
+            &[&contrib_id],
+        )? {
+            let qid: i64 = r.get(0);
+            merged.last_position.insert(qid, r.get(1));
+            if r.get::<_, bool>(3) {
+                merged.options.insert(qid, r.get(2));
+            }
+        }
+        Ok(merged)
+    }
+
+    /// Position de la prochaine réponse de la question
+    pub fn position(&self, qid: i64) -> i32 {
+        self.last_position.get(&qid).map_or(1, |p| p + 1)
+    }
+
+    /// Question déjà répondue (hors skipped)
+    pub fn answered(&self, qid: i64) -> bool {
+        self.options.contains_key(&qid)
+    }
+
+    pub fn choice(&self, qid: i64, oid: i64) -> Choice {
+        match self.options.get(&qid) {
+            None => Choice::New,
+            Some(oids) if oids.contains(&oid) => Choice::Same,
+            Some(_) => Choice::Conflict,
+        }
+    }
+}
+
+/// raw_json prolongé par `row`; `None` si la ligne y figure déjà
+fn append_page(stored: Option<&str>, row: &Value) -> Option<Value> {
+    let stored = stored.and_then(|s| serde_json::from_str(s).ok()).unwrap_or(Value::Null);
+    let mut pages = match stored {
+        Value::Array(pages) => pages,
+        Value::Null => Vec::new(),
+        page => vec![page],
+    };
+    if pages.contains(row) {
+        return None;
+    }
+    pages.push(row.clone());
+    Some(Value::Array(pages))
+}
+
+/// Champs de la ligne qui complètent ceux de la contribution s'ils sont vides
+pub struct Fill<'a> {
+    pub author_id: Option<i64>,
+    pub submitted_at: Option<chrono::NaiveDateTime>,
+    pub title: Option<&'a str>,
+    /// type de la colonne raw_json (jsonb ou text, cf. jsonb.rs)
+    pub raw_json_kind: crate::jsonb::Kind,
+}
+
+/// Ajoute la ligne à la contribution `reference` du formulaire, si elle existe.
+pub fn extend_contribution(
+    tx: &mut impl GenericClient,
+    form_id: i64,
+    reference: &str,
+    row: &Value,
+    fill: &Fill,
+    batch: &str,
+    origin: &crate::existing::Origin,
+) -> Result<Extend> {
+    let Some(stored) = tx.query_opt(
+        "SELECT id, raw_json::text FROM contributions WHERE form_id = $1 AND source_contribution_id = $2 FOR UPDATE",
+        &[&form_id, &reference],
+    )?
+    else {
+        return Ok(Extend::NotFound);
+    };
+    let contrib_id: i64 = stored.get(0);
+    let Some(pages) = append_page(stored.get::<_, Option<&str>>(1), row) else {
+        return Ok(Extend::AlreadyMerged);
+    };
+    let raw_text = pages.to_string();
+    let raw_hash = crate::sha256_rowjson(&raw_text);
+    let raw_json = fill.raw_json_kind.param(Some(&pages), Some(&raw_text));
+    tx.execute(
+        "UPDATE contributions SET raw_json = $2, raw_hash = $3,
+             author_id = COALESCE(author_id, $4),
+             submitted_at = COALESCE(submitted_at, $5),
+             title = COALESCE(title, $6),
+             import_batch_id = $7,
+             source_file = $8, source_line = $9
+         WHERE id = $1",
+        &[&contrib_id, &raw_json, &raw_hash, &fill.author_id, &fill.submitted_at, &fill.title, &batch, &origin.file, &origin.line],
+    )?;
+    let merged = MergedAnswers::load(tx, contrib_id)?;
+    Ok(Extend::Extended(contrib_id, raw_hash, merged))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn pages_accumulate_once() {
+        let p1 = json!({"reference": "c1", "page": "1"});
+        let p2 = json!({"reference": "c1", "page": "2"});
+        let two = append_page(Some(&p1.to_string()), &p2).unwrap();
+        assert_eq!(two, json!([p1, p2]));
+        assert_eq!(append_page(Some(&two.to_string()), &p2), None);
+        assert_eq!(append_page(Some(&p1.to_string()), &p1), None);
+        assert_eq!(append_page(None, &p1), Some(json!([p1])));
+    }
+
+    #[test]
+    fn positions_and_choices() {
+        let mut m = MergedAnswers::default();
+        m.last_position.insert(1, 1);
+        m.options.insert(1, vec![10]);
+        m.last_position.insert(2, 1); // skipped seulement
+        assert_eq!(m.position(1), 2);
+        assert_eq!(m.position(3), 1);
+        assert!(!m.answered(2));
+        assert_eq!(m.choice(1, 10), Choice::Same);
+        assert_eq!(m.choice(1, 11), Choice::Conflict);
+        assert_eq!(m.choice(2, 11), Choice::New);
+    }
+}
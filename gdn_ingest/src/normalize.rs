@@ -0,0 +1,297 @@
+// ---------- Normalisation des valeurs (transformations, expressions régulières) ----------
+//
+// Chaque question peut déclarer des transformations simples puis une suite de
+// règles, appliquées dans l'ordre à la valeur brute, avant toute recherche
+// d'option ou écriture de réponse (ingestion, dry-run, explain):
+//
+//   transforms:                 # défaut: defaults.transforms du mapping
+//     - collapse_whitespace
+//     - lowercase
+//     - { replace: { from: "’", to: "'" } }
+//   normalize:
+//     - { pattern: "^(\\d+)\\s*ans?$", replace: "$1" }
+//     - { pattern: "(?i)^nsp$", replace: "" }
+//   normalize_trim: true        # défaut: trim avant la première transformation
+//
+// Transformations: trim, lowercase, uppercase, collapse_whitespace (espaces
+// consécutifs → un seul, bords retirés), strip_accents (translittération
+// ASCII, comme values::fold) et replace (toutes les occurrences, texte
+// littéral). Une liste `transforms:` de la question remplace celle des
+// defaults (`transforms: []` pour n'en appliquer aucune).
+// Chaque règle remplace la première correspondance (`Regex::replace`).
+// Les expressions sont compilées une fois au démarrage (`NormalizeCaches`).
+// Concerne les questions à cellule unique: text, number, date, boolean,
+// scale, single_choice et multi_choice en une colonne (cellule entière).
+//
+// Question conditionnelle: traitée seulement pour les lignes dont la cellule
+// brute (trim) de `if_column` vérifie toutes les conditions déclarées:
+//
+//   if_column: A_des_enfants
+//   if_value: "Oui"             # égale à
+//   if_not_value: "NSP"         # différente de
+//   if_regex: "(?i)^oui"        # correspond (compilée avec les règles)
+//
+// Sinon la question est ignorée pour la ligne (ni réponse, ni skipped).
+
+use regex::Regex;
+use serde::Deserialize;
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+use crate::input::ColumnAccessor;
+use crate::QuestionMap;
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct NormalizeRule {
+    pub pattern: String,
+    /// modèle de remplacement (`$1`, `${nom}`)
+    #[serde(default)]
+    pub replace: String,
+}
+
+/// Transformation de `transforms:`: un nom, ou `{ replace: { from, to } }`
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(try_from = "RawTransform")]
+pub enum Transform {
+    Trim,
+    Lowercase,
+    Uppercase,
+    CollapseWhitespace,
+    StripAccents,
+    /// texte littéral, toutes les occurrences
+    Replace { from: String, to: String },
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RawTransform {
+    Name(String),
+    Replace { replace: Replacement },
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Replacement {
+    from: String,
+    #[serde(default)]
+    to: String,
+}
+
+impl TryFrom<RawTransform> for Transform {
+    type Error = String;
+
+    fn try_from(raw: RawTransform) -> Result<Self, String> {
+        match raw {
+            RawTransform::Replace { replace: Replacement { from, to } } => Ok(Transform::Replace { from, to }),
+            RawTransform::Name(name) => match name.as_str() {
+                "trim" => Ok(Transform::Trim),
+                "lowercase" => Ok(Transform::Lowercase),
+                "uppercase" => Ok(Transform::Uppercase),
+                "collapse_whitespace" => Ok(Transform::CollapseWhitespace),
+                "strip_accents" => Ok(Transform::StripAccents),
+                _ => Err(format!(
+                    "transformation '{name}' inconnue (trim, lowercase, uppercase, collapse_whitespace, strip_accents, replace)"
+                )),
+            },
+        }
+    }
+}
+
+impl Transform {
+    fn apply(&self, value: &str) -> String {
+        match self {
+            Transform::Trim => value.trim().to_string(),
+            Transform::Lowercase => value.to_lowercase(),
+            Transform::Uppercase => value.to_uppercase(),
+            Transform::CollapseWhitespace => value.split_whitespace().collect::<Vec<_>>().join(" "),
+            Transform::StripAccents => deunicode::deunicode(value),
+            Transform::Replace { from, to } => value.replace(from.as_str(), to),
+        }
+    }
+}
+
+struct Rules {
+    trim: bool,
+    transforms: Vec<Transform>,
+    compiled: Vec<(Regex, String)>,
+}
+
+/// Règles compilées, par code de question
+#[derive(Default)]
+pub struct NormalizeCaches {
+    by_code: HashMap<String, Rules>,
+    /// if_regex compilées, par code de question
+    conditions: HashMap<String, Regex>,
+}
+
+impl NormalizeCaches {
+    /// Questions sans règle ni transformation ignorées; `validate_mapping` a
+    /// déjà vérifié les motifs. `defaults`: defaults.transforms du mapping
+    pub fn build<'q>(questions: impl IntoIterator<Item = &'q QuestionMap>, defaults: &[Transform]) -> Result<Self, String> {
+        let mut by_code = HashMap::new();
+        let mut conditions = HashMap::new();
+        for qm in questions {
+            if let Some(pattern) = &qm.if_regex {
+                let re = Regex::new(pattern).map_err(|e| format!("question '{}': if_regex: {e}", qm.code))?;
+                conditions.insert(qm.code.clone(), re);
+            }
+            let transforms = qm.transforms.as_deref().unwrap_or(defaults);
+            if qm.normalize.is_empty() && transforms.is_empty() {
+                continue;
+            }
+            let compiled = qm
+                .normalize
+                .iter()
+                .map(|r| Regex::new(&r.pattern).map(|re| (re, r.replace.clone())))
+                .collect::<Result<_, _>>()
+                .map_err(|e| format!("question '{}': normalize: {e}", qm.code))?;
+            by_code.insert(qm.code.clone(), Rules { trim: qm.normalize_trim, transforms: transforms.to_vec(), compiled });
+        }
+        Ok(NormalizeCaches { by_code, conditions })
+    }
+
+    /// Conditions if_column de la question remplies par la ligne (vrai sans if_column)
+    pub fn applies(&self, qm: &QuestionMap, row: &dyn ColumnAccessor) -> bool {
+        let Some(col) = &qm.if_column else {
+            return true;
+        };
+        let cell = row.cell(col).unwrap_or("").trim();
+        qm.if_value.as_deref().is_none_or(|v| cell == v.trim())
+            && qm.if_not_value.as_deref().is_none_or(|v| cell != v.trim())
+            && self.conditions.get(&qm.code).is_none_or(|re| re.is_match(cell))
+    }
+
+    /// Valeur après les règles de la question (inchangée si elle n'en a pas)
+    pub fn apply<'r>(&self, code: &str, raw: &'r str) -> Cow<'r, str> {
+        let Some(rules) = self.by_code.get(code) else {
+            return Cow::Borrowed(raw);
+        };
+        let mut value = Cow::Borrowed(if rules.trim { raw.trim() } else { raw });
+        for t in &rules.transforms {
+            value = Cow::Owned(t.apply(&value));
+        }
+        for (re, replace) in &rules.compiled {
+            if let Cow::Owned(s) = re.replace(&value, replace.as_str()) {
+                value = Cow::Owned(s);
+            }
+        }
+        value
+    }
+
+    /// Cellule normalisée; `None` si absente ou vidée par les règles
+    pub fn value<'r>(&self, code: &str, raw: Option<&'r str>) -> Option<Cow<'r, str>> {
+        raw.map(|r| self.apply(code, r)).filter(|v| !v.trim().is_empty())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn caches(yaml: &str) -> NormalizeCaches {
+        let qm: QuestionMap = serde_yaml::from_str(yaml).unwrap();
+        NormalizeCaches::build([&qm], &[]).unwrap()
+    }
+
+    #[test]
+    fn rules_apply_in_order_with_captures() {
+        let c = caches(
+            r#"
+code: age
+prompt: Âge
+type: number
+source_column: A
+normalize:
+  - { pattern: "^(\\d+)\\s*ans?$", replace: "$1" }
+  - { pattern: "(?i)^nsp$", replace: "" }
+"#,
+        );
+        assert_eq!(c.apply("age", "  42 ans "), "42");
+        assert_eq!(c.apply("age", "42"), "42");
+        assert_eq!(c.value("age", Some("NSP")), None);
+        assert_eq!(c.apply("autre", " 42 ans "), " 42 ans ");
+    }
+
+    #[test]
+    fn transforms_run_before_rules_and_override_defaults() {
+        let questions: Vec<QuestionMap> = serde_yaml::from_str(
+            r#"
+- code: avis
+  prompt: Avis
+  type: single_choice
+  source_column: A
+  transforms: [collapse_whitespace, strip_accents, { replace: { from: "ok", to: "oui" } }]
+  normalize:
+    - { pattern: "^Oui$", replace: "oui" }
+- { code: libre, prompt: L, type: text, source_column: L }
+- { code: brut, prompt: B, type: text, source_column: B, transforms: [], normalize_trim: false }
+"#,
+        )
+        .unwrap();
+        let defaults: Vec<Transform> = serde_yaml::from_str("[trim, uppercase]").unwrap();
+        let c = NormalizeCaches::build(&questions, &defaults).unwrap();
+        assert_eq!(c.apply("avis", "  Ôui "), "oui");
+        assert_eq!(c.apply("avis", "très   ok"), "tres oui");
+        assert_eq!(c.apply("libre", "  Oui "), "OUI");
+        assert_eq!(c.apply("brut", "  Oui "), "  Oui ");
+        let err = serde_yaml::from_str::<Vec<Transform>>("[titlecase]").unwrap_err().to_string();
+        assert!(err.contains("titlecase"), "{err}");
+    }
+
+    #[test]
+    fn trim_can_be_disabled() {
+        let c = caches(
+            r#"
+code: t
+prompt: T
+type: text
+source_column: T
+normalize_trim: false
+normalize:
+  - { pattern: "^ ", replace: "_" }
+"#,
+        );
+        assert_eq!(c.apply("t", " x"), "_x");
+    }
+
+    #[test]
+    fn conditions_on_another_column() {
+        use crate::input::CsvRow;
+        use csv::StringRecord;
+        use std::rc::Rc;
+
+        let questions: Vec<QuestionMap> = serde_yaml::from_str(
+            r#"
+- { code: ages, prompt: Âges, type: text, source_column: Ages, if_column: Enfants, if_value: Oui }
+- { code: pourquoi, prompt: P, type: text, source_column: P, if_column: Enfants, if_not_value: Oui }
+- { code: nb, prompt: N, type: number, source_column: N, if_column: Enfants, if_regex: "(?i)^oui", if_not_value: "oui, un" }
+- { code: libre, prompt: L, type: text, source_column: L }
+"#,
+        )
+        .unwrap();
+        let c = NormalizeCaches::build(&questions, &[]).unwrap();
+        let headers = Rc::new(StringRecord::from(vec!["Enfants"]));
+        let applies = |cell: &str| -> Vec<bool> {
+            let row = CsvRow::new(Rc::clone(&headers), StringRecord::from(vec![cell]));
+            questions.iter().map(|qm| c.applies(qm, &row)).collect()
+        };
+        assert_eq!(applies(" Oui "), vec![true, false, true, true]);
+        assert_eq!(applies("oui, un"), vec![false, true, false, true]);
+        assert_eq!(applies(""), vec![false, true, false, true]);
+
+        let bad: QuestionMap =
+            serde_yaml::from_str("{code: q8, prompt: Q, type: text, source_column: T, if_column: A, if_regex: '('}").unwrap();
+        assert!(NormalizeCaches::build([&bad], &[]).err().unwrap().starts_with("question 'q8': if_regex:"));
+    }
+
+    #[test]
+    fn invalid_pattern_names_the_question() {
+        let qm: QuestionMap = serde_yaml::from_str(
+            "{code: q9, prompt: Q, type: text, source_column: T, normalize: [{pattern: '(', replace: ''}]}",
+        )
+        .unwrap();
+        let err = NormalizeCaches::build([&qm], &[]).err().unwrap();
+        assert!(err.starts_with("question 'q9': normalize:"));
+    }
+}
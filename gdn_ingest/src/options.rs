@@ -0,0 +1,183 @@
+// ---------- Codes d'options: un seul format ----------
+//
+// Les codes dynamiques passent par `slugify`; les codes déclarés dans le YAML
+// devraient respecter le même format, sinon `Tout_a_fait` (déclaré) et
+// `tout-a-fait` (dynamique) coexistent et l'ON CONFLICT par code ne joue plus.
+// Un code hors format est signalé (avertissement) sans être refusé: des
+// mappings livrés (`bonne_chose`, `a_ameliorer`…) ont leurs codes en base, et
+// --normalize-option-codes sur une base existante créerait des doublons
+// (`bonne-chose` à côté de `bonne_chose`) faute de migration de options.code.
+//
+// Deux libellés distincts peuvent donner le même slug ("Oui !" et "Oui…",
+// ou deux longues réponses identiques sur leurs 64 premiers caractères):
+// l'option dynamique reçoit alors un suffixe (`oui-2`) au lieu d'être
+// fusionnée avec celle qui a déjà le code. Comme pour gen-mapping, "Oui" et
+// "oui" sont deux libellés distincts (`oui`, `oui-2`).
+
+use anyhow::Result;
+use postgres::Client;
+
+use std::collections::HashMap;
+
+use crate::{slugify, Mapping, QuestionMap};
+
+/// Longueur maximale d'un code d'option
+pub const MAX_CODE_LEN: usize = 64;
+
+/// Code normalisé: slug `[a-z0-9-]`, "na" si vide, tronqué à MAX_CODE_LEN
+pub fn normalize_code(s: &str) -> String {
+    let mut c = slugify(s);
+    if c.is_empty() {
+        c = "na".into();
+    }
+    if c.len() > MAX_CODE_LEN {
+        c.truncate(MAX_CODE_LEN);
+    }
+    c
+}
+
+/// n-ième code pour un même slug: `code` puis `code-2`, `code-3`…, toujours ≤ MAX_CODE_LEN
+pub fn suffixed_code(code: &str, n: u32) -> String {
+    if n <= 1 {
+        return code.to_string();
+    }
+    let suffix = format!("-{n}");
+    let mut base = code.to_string();
+    base.truncate(MAX_CODE_LEN - suffix.len());
+    format!("{}{suffix}", base.trim_end_matches('-'))
+}
+
+/// Même réponse: libellés égaux aux espaces de bord près
+pub fn same_label(a: &str, b: &str) -> bool {
+    a.trim() == b.trim()
+}
+
+/// Options déclarées d'une question avec un code ou un libellé déjà pris
+/// (ON CONFLICT par code: la seconde écraserait la première)
+pub fn duplicate_declared(qm: &QuestionMap) -> Vec<String> {
+    let mut out = Vec::new();
+    let mut codes: HashMap<&str, usize> = HashMap::new();
+    let mut labels: HashMap<&str, usize> = HashMap::new();
+    for (i, opt) in qm.options.iter().enumerate() {
+        match codes.get(opt.code.as_str()) {
+            Some(&first) => out.push(format!("option '{}' déclarée deux fois (options[{first}] et options[{i}])", opt.code)),
+            None => {
+                codes.insert(&opt.code, i);
+            }
+        }
+        match labels.get(opt.label.trim()) {
+            Some(&first) => out.push(format!(
+                "libellé '{}' porté par deux options (options[{first}] '{}' et options[{i}] '{}')",
+                opt.label, qm.options[first].code, opt.code
+            )),
+            None => {
+                labels.insert(opt.label.trim(), i);
+            }
+        }
+    }
+    out
+}
+
+pub fn is_normalized(code: &str) -> bool {
+    normalize_code(code) == code
+}
+
+/// `--normalize-option-codes`: réécrit les codes déclarés et signale chaque réécriture
+pub fn normalize_declared_codes(mapping: &mut Mapping) {
+    for qm in &mut mapping.questions {
+        for opt in &mut qm.options {
+            let code = normalize_code(&opt.code);
+            if code != opt.code {
+                println!("[options] question '{}': code '{}' → '{}'", qm.code, opt.code, code);
+                opt.code = code;
+            }
+        }
+    }
+}
+
+/// Audit base: options existantes du formulaire dont le code ne survivrait pas à la normalisation
+pub fn warn_unnormalized_codes(conn: &mut Client, form_id: i64) -> Result<()> {
+    let rows = conn.query(
+        "SELECT q.question_code, o.code
+         FROM options o
+         JOIN questions q ON q.id = o.question_id
+         WHERE q.form_id = $1
+         ORDER BY q.question_code, o.code",
+        &[&form_id],
+    )?;
+    let bad: Vec<(String, String)> = rows
+        .iter()
+        .map(|r| (r.get(0), r.get(1)))
+        .filter(|(_, code): &(String, String)| !is_normalized(code))
+        .collect();
+    if bad.is_empty() {
+        return Ok(());
+    }
+    println!("⚠️  {} options en base avec un code non normalisé:", bad.len());
+    for (question, code) in bad.iter().take(20) {
+        println!("  {}: '{}' (normalisé: '{}')", question, code, normalize_code(code));
+    }
+    if bad.len() > 20 {
+        println!("  … et {} autres", bad.len() - 20);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn declared_and_dynamic_codes_agree() {
+        assert_eq!(normalize_code("Tout_a_fait"), "tout-a-fait");
+        assert_eq!(normalize_code("tout a fait"), "tout-a-fait");
+        assert_eq!(normalize_code("  "), "na");
+        assert!(is_normalized("tout-a-fait"));
+        assert!(!is_normalized("Tout_a_fait"));
+        assert!(!is_normalized("-a"));
+    }
+
+    #[test]
+    fn colliding_labels_get_distinct_codes() {
+        let (a, b) = (normalize_code("Oui !"), normalize_code("Oui…"));
+        assert_eq!(a, b);
+        assert_eq!(suffixed_code(&a, 1), "oui");
+        assert_eq!(suffixed_code(&a, 2), "oui-2");
+        assert!(!same_label("Oui !", "Oui…"));
+        assert!(same_label(" Oui ", "Oui"));
+        assert!(!same_label("oui", "Oui"));
+
+        // accents retirés puis troncature à 64: seule la fin diffère
+        let long = "Électricité à énergie renouvelable produite localement et partagée entre voisins";
+        let code = normalize_code(&format!("{long} (é)"));
+        assert_eq!(code, normalize_code(&format!("{long} (è)")));
+        assert_eq!(code.len(), MAX_CODE_LEN);
+        let second = suffixed_code(&code, 2);
+        assert!(second.len() <= MAX_CODE_LEN);
+        assert!(second.ends_with("-2") && is_normalized(&second), "{second}");
+        assert!(suffixed_code(&code, 12).ends_with("-12"));
+    }
+
+    #[test]
+    fn duplicate_declared_options_are_reported() {
+        let qm: QuestionMap = serde_yaml::from_str(
+            "{ code: q, prompt: Q, type: single_choice, source_column: c, options: [{ code: oui, label: Oui }, { code: non, label: Non }, { code: oui, label: \"Oui \" }] }",
+        )
+        .unwrap();
+        assert_eq!(
+            duplicate_declared(&qm),
+            vec![
+                "option 'oui' déclarée deux fois (options[0] et options[2])",
+                "libellé 'Oui ' porté par deux options (options[0] 'oui' et options[2] 'oui')",
+            ]
+        );
+    }
+
+    #[test]
+    fn long_codes_are_truncated() {
+        let code = normalize_code(&"A".repeat(MAX_CODE_LEN + 10));
+        assert_eq!(code, "a".repeat(MAX_CODE_LEN));
+        assert!(is_normalized(&code));
+        assert!(!is_normalized(&"a".repeat(MAX_CODE_LEN + 1)));
+    }
+}
@@ -0,0 +1,194 @@
+// ---------- --mapping-overlay: mapping de base + surcouche ----------
+//
+// Un mapping commun (questions partagées) et, par export, une surcouche qui
+// ne décrit que ce qui change. La fusion se fait sur le YAML, avant
+// désérialisation (un champ absent de la surcouche n'écrase rien, même s'il
+// a une valeur par défaut):
+//   - tables (form, defaults, ingest, champs d'une question): clé par clé,
+//     récursivement;
+//   - `questions`: fusionnées par `code`; une question de la surcouche
+//     complète ou remplace les champs de celle de même code, une question
+//     nouvelle est ajoutée à la fin;
+//   - scalaires et autres listes (options, date_formats…): la surcouche
+//     remplace la valeur de base (`~` compris).
+
+use anyhow::{Context, Result};
+use serde_yaml::{Mapping as Table, Value};
+use sha2::{Digest, Sha256};
+use std::path::Path;
+
+use crate::Mapping;
+
+/// Effet de la surcouche sur les questions
+#[derive(Debug, Default, PartialEq)]
+pub struct Merged {
+    pub overridden: Vec<String>,
+    pub appended: Vec<String>,
+}
+
+fn code(question: &Value) -> Option<&str> {
+    question.get("code").and_then(Value::as_str)
+}
+
+fn merge_value(base: &mut Value, overlay: Value) {
+    match (base, overlay) {
+        (Value::Mapping(b), Value::Mapping(o)) => merge_table(b, o),
+        (b, o) => *b = o,
+    }
+}
+
+fn merge_table(base: &mut Table, overlay: Table) {
+    for (k, v) in overlay {
+        match base.get_mut(&k) {
+            Some(b) => merge_value(b, v),
+            None => {
+                base.insert(k, v);
+            }
+        }
+    }
+}
+
+/// Fusionne la surcouche dans le mapping de base (YAML brut)
+pub fn merge_mappings(base: Value, overlay: Value) -> Result<(Value, Merged)> {
+    let Value::Mapping(mut base) = base else {
+        anyhow::bail!("mapping de base: table YAML attendue");
+    };
+    let Value::Mapping(mut overlay) = overlay else {
+        anyhow::bail!("surcouche: table YAML attendue");
+    };
+    let mut merged = Merged::default();
+    if let Some(extra) = overlay.remove("questions") {
+        let Value::Sequence(extra) = extra else {
+            anyhow::bail!("surcouche: `questions` doit être une liste");
+        };
+        let questions = base.entry("questions".into()).or_insert_with(|| Value::Sequence(Vec::new()));
+        let Value::Sequence(questions) = questions else {
+            anyhow::bail!("mapping de base: `questions` doit être une liste");
+        };
+        for (i, q) in extra.into_iter().enumerate() {
+            let c = code(&q).with_context(|| format!("surcouche: questions[{i}] sans `code`"))?.to_string();
+            match questions.iter_mut().find(|b| code(b) == Some(c.as_str())) {
+                Some(b) => {
+                    merge_value(b, q);
+                    merged.overridden.push(c);
+                }
+                None => {
+                    questions.push(q);
+                    merged.appended.push(c);
+                }
+            }
+        }
+    }
+    merge_table(&mut base, overlay);
+    Ok((Value::Mapping(base), merged))
+}
+
+fn read_yaml(path: &Path) -> Result<Value> {
+    let text = std::fs::read_to_string(path).with_context(|| format!("lecture mapping {path:?}"))?;
+    serde_yaml::from_str(&text).with_context(|| format!("mapping {path:?}"))
+}
+
+/// Empreinte du mapping effectif (YAML fusionné, sans commentaires ni mise
+/// en page), consignée dans la provenance du batch: un mapping modifié
+/// réécrit les lignes inchangées (cf. run_ingest)
+pub fn fingerprint(base: &Path, overlay: Option<&Path>) -> Result<String> {
+    let value = match overlay {
+        Some(overlay) => merge_mappings(read_yaml(base)?, read_yaml(overlay)?)?.0,
+        None => read_yaml(base)?,
+    };
+    Ok(hex::encode(Sha256::digest(serde_yaml::to_string(&value)?.as_bytes())))
+}
+
+/// Mapping de base + surcouche, désérialisé après fusion
+pub fn load(base: &Path, overlay: &Path) -> Result<(Mapping, Merged)> {
+    let (value, merged) = merge_mappings(read_yaml(base)?, read_yaml(overlay)?)
+        .with_context(|| format!("fusion de {overlay:?} dans {base:?}"))?;
+    let mapping = serde_yaml::from_value(value).with_context(|| format!("mapping {base:?} + {overlay:?}"))?;
+    Ok((mapping, merged))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn yaml(s: &str) -> Value {
+        serde_yaml::from_str(s).unwrap()
+    }
+
+    const BASE: &str = r#"
+form: { name: Grand débat, reference_year: 2019 }
+defaults:
+  author: { zipcode: code_postal, age_range: age }
+  contribution: { submitted_at: date }
+questions:
+  - { code: q1, prompt: Q1, type: text, source_column: "Q1 - Texte" }
+  - code: q2
+    prompt: Q2
+    type: single_choice
+    source_column: Q2
+    value_map: { "o": "Oui" }
+    options: [ { code: oui, label: Oui }, { code: non, label: Non } ]
+"#;
+
+    #[test]
+    fn overlay_merges_tables_and_questions_by_code() {
+        let overlay = yaml(
+            r#"
+form: { reference_year: 2020 }
+defaults:
+  author: { zipcode: cp }
+questions:
+  - { code: q2, source_column: "Q2 (lot 3)", value_map: { "n": "Non" }, options: [ { code: oui, label: Oui } ] }
+  - { code: q3, prompt: Q3, type: number, source_column: Q3 }
+"#,
+        );
+        let (merged, effect) = merge_mappings(yaml(BASE), overlay).unwrap();
+        assert_eq!(effect, Merged { overridden: vec!["q2".into()], appended: vec!["q3".into()] });
+        assert_eq!(
+            merged,
+            yaml(
+                r#"
+form: { name: Grand débat, reference_year: 2020 }
+defaults:
+  author: { zipcode: cp, age_range: age }
+  contribution: { submitted_at: date }
+questions:
+  - { code: q1, prompt: Q1, type: text, source_column: "Q1 - Texte" }
+  - code: q2
+    prompt: Q2
+    type: single_choice
+    source_column: "Q2 (lot 3)"
+    value_map: { "o": "Oui", "n": "Non" }
+    options: [ { code: oui, label: Oui } ]
+  - { code: q3, prompt: Q3, type: number, source_column: Q3 }
+"#
+            )
+        );
+        let mapping: Mapping = serde_yaml::from_value(merged).unwrap();
+        assert_eq!(mapping.questions.len(), 3);
+    }
+
+    #[test]
+    fn overlay_questions_need_a_code() {
+        let err = merge_mappings(yaml(BASE), yaml("questions: [ { source_column: X } ]")).unwrap_err();
+        assert_eq!(err.to_string(), "surcouche: questions[0] sans `code`");
+    }
+
+    #[test]
+    fn fingerprint_ignores_comments_but_not_content() {
+        let dir = std::env::temp_dir().join(format!("gdn_overlay_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let write = |name: &str, text: &str| {
+            let path = dir.join(name);
+            std::fs::write(&path, text).unwrap();
+            path
+        };
+        let base = write("base.yml", BASE);
+        let commented = write("commented.yml", &format!("# relu\n{BASE}"));
+        let overlay = write("overlay.yml", "questions:\n  - { code: q1, source_column: Q1bis }\n");
+        let fp = fingerprint(&base, None).unwrap();
+        assert_eq!(fingerprint(&commented, None).unwrap(), fp);
+        assert_ne!(fingerprint(&base, Some(&overlay)).unwrap(), fp);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,48 @@
+// ---------- Données personnelles des auteurs ----------
+//
+// Les emails en clair ne sont jamais stockés: `defaults.author.email` désigne
+// une colonne d'emails, dont seul le hash salé est écrit dans
+// `authors.email_hash`. Le sel vient de `--email-salt` ou de la variable
+// d'environnement EMAIL_HASH_SALT; sans sel, l'ingestion refuse de démarrer
+// (un hash non salé d'email se retrouve par dictionnaire).
+
+use sha2::{Digest, Sha256};
+
+/// Variable d'environnement du sel, à défaut de `--email-salt`
+pub const SALT_ENV: &str = "EMAIL_HASH_SALT";
+
+/// SHA-256 hexadécimal de `sel || minuscules(trim(email))`
+pub fn hash_email(salt: &str, email: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(salt.as_bytes());
+    hasher.update(email.trim().to_lowercase().as_bytes());
+    hex::encode(hasher.finalize())
+}
+
+/// Sel effectif: flag, sinon variable d'environnement; vide = absent
+pub fn resolve_salt(flag: Option<String>) -> Option<String> {
+    flag.or_else(|| std::env::var(SALT_ENV).ok()).filter(|s| !s.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn email_is_normalized_then_salted() {
+        let h = hash_email("sel", "  Marie.Dupont@Example.FR ");
+        assert_eq!(h, hash_email("sel", "marie.dupont@example.fr"));
+        assert_ne!(h, hash_email("autre-sel", "marie.dupont@example.fr"));
+        assert_eq!(h.len(), 64);
+        // sel concaténé avant l'email
+        let mut hasher = Sha256::new();
+        hasher.update(b"selmarie.dupont@example.fr");
+        assert_eq!(h, hex::encode(hasher.finalize()));
+    }
+
+    #[test]
+    fn empty_salt_counts_as_missing() {
+        assert_eq!(resolve_salt(Some(String::new())), None);
+        assert_eq!(resolve_salt(Some("s".into())).as_deref(), Some("s"));
+    }
+}
@@ -0,0 +1,282 @@
+// ---------- Politique d'erreurs unifiée ----------
+//
+// Toutes les anomalies de ligne passent par une seule `ErrorPolicy`, résolue
+// au démarrage et affichée (`[erreurs]`): pour chaque catégorie, une action et
+// un plafond optionnel (au-delà du plafond: arrêt).
+//
+// Actions:
+//   warn   → ligne conservée, valeur brute gardée autant que possible, comptée
+//   drop   → valeur écartée (pas de réponse, ou champ NULL), comptée
+//   reject → ligne entière recopiée dans rejects/ et ignorée; seulement pour
+//            les contrôles faits avant l'écriture de la contribution
+//   abort  → arrêt de l'ingestion
+//
+// Précédence, de la plus faible à la plus forte:
+//   1. défaut de la catégorie (cf. `Category::default_action`)
+//   2. anciens réglages du mapping: ingest.strict_numbers,
+//      defaults.contribution.out_of_window
+//   3. mapping: ingest.errors.<catégorie>: { action, max }
+//   4. anciens flags: --strict-numbers, --range-violations, --zipcode-strict
+//   5. --on-error <catégorie>=<action>[:<plafond>]
+// Un plafond n'est remplacé que par une source qui en donne un.
+
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+#[derive(Deserialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum Category {
+    /// number illisible
+    BadNumber,
+    /// date illisible
+    BadDate,
+    /// valeur oui/non non reconnue
+    BadBoolean,
+    /// number hors de [min, max]
+    OutOfRange,
+    /// code postal auteur invalide
+    BadZipcode,
+    /// submitted_at hors de submitted_between
+    OutOfWindow,
+    /// valeur absente des options déclarées (single_choice, multi_choice)
+    UnmatchedOption,
+}
+
+#[derive(Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    Warn,
+    Drop,
+    Reject,
+    Abort,
+}
+
+impl Category {
+    pub const ALL: [Category; 7] = [
+        Category::BadNumber,
+        Category::BadDate,
+        Category::BadBoolean,
+        Category::OutOfRange,
+        Category::BadZipcode,
+        Category::OutOfWindow,
+        Category::UnmatchedOption,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Category::BadNumber => "bad_number",
+            Category::BadDate => "bad_date",
+            Category::BadBoolean => "bad_boolean",
+            Category::OutOfRange => "out_of_range",
+            Category::BadZipcode => "bad_zipcode",
+            Category::OutOfWindow => "out_of_window",
+            Category::UnmatchedOption => "unmatched_option",
+        }
+    }
+
+    fn parse(s: &str) -> Option<Category> {
+        Category::ALL.into_iter().find(|c| c.as_str() == s)
+    }
+
+    /// Comportement historique de chaque catégorie
+    pub fn default_action(self) -> Action {
+        match self {
+            Category::BadBoolean | Category::BadZipcode => Action::Drop,
+            _ => Action::Warn,
+        }
+    }
+
+    /// Actions possibles; `reject` seulement avant l'écriture de la contribution
+    pub fn allowed(self) -> &'static [Action] {
+        match self {
+            Category::BadBoolean => &[Action::Drop, Action::Abort],
+            Category::BadZipcode | Category::OutOfWindow => &[Action::Warn, Action::Drop, Action::Reject, Action::Abort],
+            _ => &[Action::Warn, Action::Drop, Action::Abort],
+        }
+    }
+}
+
+impl Action {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Action::Warn => "warn",
+            Action::Drop => "drop",
+            Action::Reject => "reject",
+            Action::Abort => "abort",
+        }
+    }
+
+    fn parse(s: &str) -> Option<Action> {
+        [Action::Warn, Action::Drop, Action::Reject, Action::Abort].into_iter().find(|a| a.as_str() == s)
+    }
+}
+
+/// Action d'une catégorie et plafond d'occurrences (au-delà: abort)
+#[derive(Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct Rule {
+    pub action: Action,
+    #[serde(default)]
+    pub max: Option<u64>,
+}
+
+/// `--on-error bad_number=abort`, `--on-error bad_date=warn:100`
+pub fn parse_on_error(s: &str) -> Result<(Category, Rule), String> {
+    let (cat, rest) = s.split_once('=').ok_or_else(|| format!("'{s}': attendu <catégorie>=<action>[:<plafond>]"))?;
+    let category = Category::parse(cat.trim()).ok_or_else(|| {
+        let names: Vec<&str> = Category::ALL.iter().map(|c| c.as_str()).collect();
+        format!("catégorie '{cat}' inconnue ({})", names.join(", "))
+    })?;
+    let (action, max) = match rest.split_once(':') {
+        Some((a, m)) => (a, Some(m.trim().parse::<u64>().map_err(|_| format!("plafond '{m}' invalide"))?)),
+        None => (rest, None),
+    };
+    let action = Action::parse(action.trim()).ok_or_else(|| format!("action '{action}' inconnue (warn, drop, reject, abort)"))?;
+    Ok((category, Rule { action, max }))
+}
+
+/// Résolution au démarrage, source par source dans l'ordre de précédence
+pub struct PolicyBuilder {
+    rules: BTreeMap<Category, (Rule, &'static str)>,
+}
+
+impl PolicyBuilder {
+    pub fn set(&mut self, category: Category, rule: Rule, origin: &'static str) -> Result<(), String> {
+        if !category.allowed().contains(&rule.action) {
+            let allowed: Vec<&str> = category.allowed().iter().map(|a| a.as_str()).collect();
+            return Err(format!(
+                "{origin}: action '{}' impossible pour {} ({})",
+                rule.action.as_str(),
+                category.as_str(),
+                allowed.join(", ")
+            ));
+        }
+        let entry = self.rules.get_mut(&category).expect("toutes les catégories ont un défaut");
+        let max = rule.max.or(entry.0.max);
+        *entry = (Rule { action: rule.action, max }, origin);
+        Ok(())
+    }
+
+    pub fn action(&mut self, category: Category, action: Action, origin: &'static str) -> Result<(), String> {
+        self.set(category, Rule { action, max: None }, origin)
+    }
+
+    pub fn build(self) -> ErrorPolicy {
+        ErrorPolicy { rules: self.rules, seen: Category::ALL.map(|_| AtomicU64::new(0)) }
+    }
+}
+
+/// Politique effective, partagée entre fichiers et threads
+#[derive(Debug)]
+pub struct ErrorPolicy {
+    rules: BTreeMap<Category, (Rule, &'static str)>,
+    seen: [AtomicU64; Category::ALL.len()],
+}
+
+impl Default for ErrorPolicy {
+    fn default() -> Self {
+        ErrorPolicy::builder().build()
+    }
+}
+
+impl ErrorPolicy {
+    pub fn builder() -> PolicyBuilder {
+        let rules = Category::ALL
+            .into_iter()
+            .map(|c| (c, (Rule { action: c.default_action(), max: None }, "défaut")))
+            .collect();
+        PolicyBuilder { rules }
+    }
+
+    pub fn rule(&self, category: Category) -> Rule {
+        self.rules[&category].0
+    }
+
+    /// Anomalie constatée: action à appliquer (`Action::Abort` une fois le plafond dépassé)
+    pub fn record(&self, category: Category) -> Action {
+        let n = self.seen[category as usize].fetch_add(1, Ordering::Relaxed) + 1;
+        let rule = self.rule(category);
+        if rule.max.is_some_and(|max| n > max) {
+            Action::Abort
+        } else {
+            rule.action
+        }
+    }
+
+    /// Motif d'arrêt, pour le message d'erreur
+    pub fn why_abort(&self, category: Category) -> String {
+        match self.rule(category) {
+            Rule { action: Action::Abort, .. } => format!("{}=abort", category.as_str()),
+            Rule { max, .. } => format!("{}: plafond {} dépassé", category.as_str(), max.unwrap_or(0)),
+        }
+    }
+
+    pub fn log(&self) {
+        println!("[erreurs]");
+        for (category, (rule, origin)) in &self.rules {
+            let max = rule.max.map(|m| format!(" (plafond {m})")).unwrap_or_default();
+            println!("  {:<16} = {}{max} ({origin})", category.as_str(), rule.action.as_str());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ACTIONS: [Action; 4] = [Action::Warn, Action::Drop, Action::Reject, Action::Abort];
+
+    #[test]
+    fn category_action_matrix() {
+        // (catégorie, warn, drop, reject, abort) — actions acceptées
+        let matrix = [
+            (Category::BadNumber, [true, true, false, true]),
+            (Category::BadDate, [true, true, false, true]),
+            (Category::BadBoolean, [false, true, false, true]),
+            (Category::OutOfRange, [true, true, false, true]),
+            (Category::BadZipcode, [true, true, true, true]),
+            (Category::OutOfWindow, [true, true, true, true]),
+            (Category::UnmatchedOption, [true, true, false, true]),
+        ];
+        assert_eq!(matrix.len(), Category::ALL.len());
+        for (category, accepted) in matrix {
+            for (action, ok) in ACTIONS.into_iter().zip(accepted) {
+                let mut b = ErrorPolicy::builder();
+                let res = b.action(category, action, "test");
+                assert_eq!(res.is_ok(), ok, "{} × {}", category.as_str(), action.as_str());
+                if ok {
+                    let p = b.build();
+                    assert_eq!(p.record(category), action, "{} × {}", category.as_str(), action.as_str());
+                    // les autres catégories gardent leur défaut
+                    for other in Category::ALL.into_iter().filter(|&c| c != category) {
+                        assert_eq!(p.rule(other).action, other.default_action());
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn later_sources_win_and_keep_the_cap() {
+        let mut b = ErrorPolicy::builder();
+        b.set(Category::BadDate, Rule { action: Action::Drop, max: Some(2) }, "mapping").unwrap();
+        b.action(Category::BadDate, Action::Warn, "--on-error").unwrap();
+        let p = b.build();
+        assert_eq!(p.rule(Category::BadDate), Rule { action: Action::Warn, max: Some(2) });
+        assert_eq!(p.record(Category::BadDate), Action::Warn);
+        assert_eq!(p.record(Category::BadDate), Action::Warn);
+        assert_eq!(p.record(Category::BadDate), Action::Abort);
+        assert_eq!(p.why_abort(Category::BadDate), "bad_date: plafond 2 dépassé");
+    }
+
+    #[test]
+    fn on_error_syntax() {
+        assert_eq!(parse_on_error("bad_number=abort"), Ok((Category::BadNumber, Rule { action: Action::Abort, max: None })));
+        assert_eq!(parse_on_error("bad_date=warn:100"), Ok((Category::BadDate, Rule { action: Action::Warn, max: Some(100) })));
+        assert!(parse_on_error("bad_number").is_err());
+        assert!(parse_on_error("typo=warn").is_err());
+        assert!(parse_on_error("bad_date=ignore").is_err());
+        assert!(parse_on_error("bad_date=warn:beaucoup").is_err());
+    }
+}
@@ -0,0 +1,168 @@
+// ---------- Pool de connexions PostgreSQL ----------
+//
+// Connexions des fichiers ingérés: prises dans un pool au début de chaque
+// fichier et rendues à la fin, au lieu d'une connexion par thread ouverte
+// pour tout le run. Un thread --parallel qui n'a plus rien à lire ne garde
+// pas de connexion; les connexions sont ouvertes à la demande (jamais plus
+// de --db-pool-size, défaut: --parallel) et réutilisées d'un fichier à
+// l'autre.
+//
+// Pool synchrone (Mutex + Condvar), comme le client `postgres::Client`: un
+// pool async (deadpool sur un runtime tokio) lâchait les connexions mortes
+// dans son `block_on`, et le drop du client, qui fait lui-même un
+// `block_on` sur son propre runtime, paniquait ("Cannot start a runtime
+// from within a runtime"). Ici une connexion fermée côté serveur (délai
+// d'inactivité, redémarrage) est écartée au retour ou à l'emprunt, hors de
+// tout runtime, et remplacée à la demande.
+//
+// Le pool est un singleton, créé au premier usage (taille fixée par `init`
+// si elle a été appelée avant). Les connexions de service du run (verrou,
+// options dynamiques, dictionnaire) restent hors pool.
+
+use anyhow::Result;
+use once_cell::sync::OnceCell;
+use postgres::Client;
+use std::ops::{Deref, DerefMut};
+use std::sync::{Condvar, Mutex};
+
+/// Défaut hors ingestion (ping)
+const DEFAULT_SIZE: usize = 1;
+
+pub struct Pool {
+    size: usize,
+    connect: fn() -> Result<Client>,
+    state: Mutex<State>,
+    freed: Condvar,
+}
+
+#[derive(Default)]
+struct State {
+    idle: Vec<Client>,
+    /// connexions ouvertes, prêtées ou libres
+    open: usize,
+}
+
+/// Connexion empruntée, rendue au pool quand elle est lâchée
+pub struct Conn {
+    client: Option<Client>,
+    pool: &'static Pool,
+}
+
+impl Deref for Conn {
+    type Target = Client;
+    fn deref(&self) -> &Client {
+        self.client.as_ref().expect("connexion rendue")
+    }
+}
+
+impl DerefMut for Conn {
+    fn deref_mut(&mut self) -> &mut Client {
+        self.client.as_mut().expect("connexion rendue")
+    }
+}
+
+impl Drop for Conn {
+    fn drop(&mut self) {
+        let Some(client) = self.client.take() else { return };
+        let mut state = self.pool.state.lock().unwrap();
+        if client.is_closed() {
+            state.open -= 1;
+            drop(state);
+            drop(client);
+        } else {
+            state.idle.push(client);
+        }
+        self.pool.freed.notify_one();
+    }
+}
+
+impl Pool {
+    fn new(size: usize, connect: fn() -> Result<Client>) -> Self {
+        Pool { size: size.max(1), connect, state: Mutex::new(State::default()), freed: Condvar::new() }
+    }
+
+    fn get(&'static self) -> Result<Conn> {
+        let mut state = self.state.lock().unwrap();
+        loop {
+            if let Some(client) = state.idle.pop() {
+                if client.is_closed() {
+                    state.open -= 1;
+                    drop(client);
+                    continue;
+                }
+                return Ok(Conn { client: Some(client), pool: self });
+            }
+            if state.open < self.size {
+                state.open += 1;
+                drop(state);
+                return match (self.connect)() {
+                    Ok(client) => Ok(Conn { client: Some(client), pool: self }),
+                    Err(e) => {
+                        self.state.lock().unwrap().open -= 1;
+                        self.freed.notify_one();
+                        Err(e.context("pool de connexions"))
+                    }
+                };
+            }
+            state = self.freed.wait(state).unwrap();
+        }
+    }
+}
+
+static POOL: OnceCell<Pool> = OnceCell::new();
+
+/// Taille du pool, avant le premier `get`
+pub fn init(size: usize) -> Result<()> {
+    POOL.get_or_init(|| Pool::new(size, crate::open_conn));
+    Ok(())
+}
+
+/// Connexion libre du pool (ouverte si besoin), en attendant qu'une se libère
+pub fn get() -> Result<Conn> {
+    POOL.get_or_init(|| Pool::new(DEFAULT_SIZE, crate::open_conn)).get()
+}
+
+/// `gdn_ingest ping`: une connexion du pool répond à SELECT 1
+pub fn run_ping() -> Result<()> {
+    let t0 = std::time::Instant::now();
+    let mut conn = get()?;
+    let one: i32 = conn.query_one("SELECT 1", &[])?.get(0);
+    anyhow::ensure!(one == 1, "SELECT 1 a renvoyé {one}");
+    println!("[ping] ✅ PostgreSQL répond ({:?})", t0.elapsed());
+    Ok(())
+}
+
+/// Tests sur base jetable (TEST_DATABASE_URL, même variable que les tests
+/// Python), hors pool et sans TLS. Avec `schema`: schéma vidé puis mis en
+/// search_path (tables à créer par dbinit::init), comme avec --schema.
+#[cfg(test)]
+pub fn test_conn(schema: Option<&str>) -> Client {
+    let url = std::env::var("TEST_DATABASE_URL").expect("TEST_DATABASE_URL");
+    let mut conn = Client::connect(&url.replace("postgresql://", "postgres://"), postgres::NoTls).unwrap();
+    if let Some(schema) = schema {
+        conn.batch_execute(&format!("DROP SCHEMA IF EXISTS {schema} CASCADE")).unwrap();
+        let opts = crate::session::SessionOpts { schema: schema.to_string(), ..Default::default() };
+        crate::session::configure_session(&mut conn, &opts).unwrap();
+    }
+    conn
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[ignore = "nécessite TEST_DATABASE_URL"]
+    fn dead_connection_is_replaced() {
+        let pool: &'static Pool = Box::leak(Box::new(Pool::new(1, || Ok(test_conn(None)))));
+        let pid: i32 = pool.get().unwrap().query_one("SELECT pg_backend_pid()", &[]).unwrap().get(0);
+        // connexion libre tuée côté serveur (comme un délai d'inactivité)
+        test_conn(None).execute("SELECT pg_terminate_backend($1)", &[&pid]).unwrap();
+        let mut conn = pool.get().unwrap();
+        let _ = conn.simple_query("SELECT 1");
+        drop(conn);
+        let other: i32 = pool.get().unwrap().query_one("SELECT pg_backend_pid()", &[]).unwrap().get(0);
+        assert_ne!(other, pid);
+        assert_eq!(pool.state.lock().unwrap().open, 1);
+    }
+}
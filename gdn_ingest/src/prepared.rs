@@ -0,0 +1,180 @@
+// ---------- Requêtes préparées des écritures par ligne ----------
+//
+// Chaque ligne ingérée écrit une contribution, ses réponses et leurs
+// options. Passées en texte, ces requêtes sont analysées et planifiées par
+// le serveur à chaque appel (une instruction anonyme par exécution). Elles
+// sont préparées une fois par fichier sur la connexion du fichier, puis
+// exécutées avec leurs seuls paramètres (cf. BENCHMARKS.md).
+//
+// Un `Statement` n'existe que sur la connexion qui l'a préparé: le jeu est
+// refait pour chaque connexion empruntée au pool (début de fichier), jamais
+// partagé entre threads. Les requêtes des chemins rares (fusion, auteurs,
+// options dynamiques) restent en texte.
+
+use anyhow::Result;
+use postgres::{Client, Statement};
+
+use crate::{dictionary, existing, rawimports};
+
+pub struct PreparedStatements {
+    /// existing::UPSERT_CONTRIBUTION
+    pub upsert_contribution: Statement,
+    /// existing::INSERT_CONTRIBUTION
+    pub insert_contribution: Statement,
+    /// $1 contribution, $2 question, $3 position: valeur et options d'un
+    /// import précédent effacées
+    pub answer_skipped: Statement,
+    /// $1 contribution, $2 question, $3 position → id (options liées ensuite)
+    pub answer_choice: Statement,
+    /// idem + $4 commentaire accolé (single_choice)
+    pub answer_choice_text: Statement,
+    /// $1 réponse, $2 option
+    pub answer_option: Statement,
+    /// $1 réponse: options d'un rang ou d'une cellule de matrice remplacées
+    pub clear_answer_options: Statement,
+    /// $1 contribution, $2 question, $3 position: réponse d'un import
+    /// précédent retirée avec ses options (choix multiple vidé)
+    pub drop_answer: Statement,
+    /// $4 texte brut, $5 valeur (float8: number, boolean)
+    pub answer_float: Statement,
+    /// $4 texte brut, $5 valeur (int8: scale)
+    pub answer_int: Statement,
+    /// $4 texte, $5 date
+    pub answer_date: Statement,
+    /// dictionary::ANSWER_TEXT
+    pub answer_text: Statement,
+    /// dictionary::ANSWER_TEXT_VALUE
+    pub answer_text_value: Statement,
+    /// rawimports::INSERT
+    pub raw_import: Statement,
+    /// rawimports::EARLIER
+    pub raw_import_earlier: Statement,
+}
+
+impl PreparedStatements {
+    pub fn prepare(conn: &mut Client) -> Result<Self> {
+        Ok(PreparedStatements {
+            upsert_contribution: conn.prepare(existing::UPSERT_CONTRIBUTION)?,
+            insert_contribution: conn.prepare(existing::INSERT_CONTRIBUTION)?,
+            answer_skipped: conn.prepare(
+                "WITH a AS (
+                     INSERT INTO answers (contribution_id, question_id, position, skipped)
+                     VALUES ($1, $2, $3, true)
+                     ON CONFLICT (contribution_id, question_id, position)
+                     DO UPDATE SET skipped = true, \"text\" = NULL, value_num = NULL, value_date = NULL, text_value_id = NULL
+                     RETURNING id
+                 )
+                 DELETE FROM answer_options WHERE answer_id IN (SELECT id FROM a)",
+            )?,
+            answer_choice: conn.prepare(
+                "INSERT INTO answers (contribution_id, question_id, position)
+                 VALUES ($1, $2, $3)
+                 ON CONFLICT (contribution_id, question_id, position)
+                 DO UPDATE SET skipped = false
+                 RETURNING id",
+            )?,
+            answer_choice_text: conn.prepare(
+                "INSERT INTO answers (contribution_id, question_id, position, \"text\")
+                 VALUES ($1, $2, $3, $4)
+                 ON CONFLICT (contribution_id, question_id, position)
+                 DO UPDATE SET skipped = false, \"text\" = EXCLUDED.\"text\"
+                 RETURNING id",
+            )?,
+            answer_option: conn.prepare(
+                "INSERT INTO answer_options (answer_id, option_id)
+                 VALUES ($1, $2)
+                 ON CONFLICT (answer_id, option_id) DO NOTHING",
+            )?,
+            clear_answer_options: conn.prepare("DELETE FROM answer_options WHERE answer_id = $1")?,
+            drop_answer: conn.prepare(
+                "WITH o AS (
+                     DELETE FROM answer_options WHERE answer_id IN (
+                         SELECT id FROM answers WHERE contribution_id = $1 AND question_id = $2 AND position = $3
+                     )
+                 )
+                 DELETE FROM answers WHERE contribution_id = $1 AND question_id = $2 AND position = $3",
+            )?,
+            answer_float: conn.prepare(
+                "INSERT INTO answers (contribution_id, question_id, position, \"text\", value_num)
+                 VALUES ($1, $2, $3, $4, $5::float8)
+                 ON CONFLICT (contribution_id, question_id, position)
+                 DO UPDATE SET skipped = false, \"text\" = EXCLUDED.\"text\", value_num = EXCLUDED.value_num",
+            )?,
+            answer_int: conn.prepare(
+                "INSERT INTO answers (contribution_id, question_id, position, \"text\", value_num)
+                 VALUES ($1, $2, $3, $4, $5::int8)
+                 ON CONFLICT (contribution_id, question_id, position)
+                 DO UPDATE SET skipped = false, \"text\" = EXCLUDED.\"text\", value_num = EXCLUDED.value_num",
+            )?,
+            answer_date: conn.prepare(
+                "INSERT INTO answers (contribution_id, question_id, position, \"text\", value_date)
+                 VALUES ($1, $2, $3, $4, $5)
+                 ON CONFLICT (contribution_id, question_id, position)
+                 DO UPDATE SET skipped = false, \"text\" = EXCLUDED.\"text\", value_date = EXCLUDED.value_date",
+            )?,
+            answer_text: conn.prepare(dictionary::ANSWER_TEXT)?,
+            answer_text_value: conn.prepare(dictionary::ANSWER_TEXT_VALUE)?,
+            raw_import: conn.prepare(rawimports::INSERT)?,
+            raw_import_earlier: conn.prepare(rawimports::EARLIER)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{dbinit, pool};
+
+    #[test]
+    #[ignore = "nécessite TEST_DATABASE_URL"]
+    fn reingest_switches_between_skipped_and_answered() {
+        let schema = "gdn_test_prepared";
+        let mut conn = pool::test_conn(Some(schema));
+        dbinit::init(&mut conn, schema, false, false).unwrap();
+        conn.batch_execute(
+            "INSERT INTO forms (id, name) VALUES (1, 'f');
+             INSERT INTO questions (id, form_id, question_code, prompt, type)
+             VALUES (1, 1, 'q1', 'Q', 'multi_choice'), (2, 1, 'q2', 'N', 'number');
+             INSERT INTO options (id, question_id, code, label) VALUES (1, 1, 'oui', 'Oui');
+             INSERT INTO contributions (id, form_id) VALUES (1, 1);",
+        )
+        .unwrap();
+        let stmts = PreparedStatements::prepare(&mut conn).unwrap();
+        let state = |conn: &mut Client| -> Vec<(bool, Option<String>, Option<f64>, i64)> {
+            conn.query(
+                "SELECT a.skipped, a.\"text\", a.value_num::float8, (SELECT COUNT(*) FROM answer_options o WHERE o.answer_id = a.id)
+                 FROM answers a ORDER BY a.question_id",
+                &[],
+            )
+            .unwrap()
+            .iter()
+            .map(|r| (r.get(0), r.get(1), r.get(2), r.get(3)))
+            .collect()
+        };
+
+        // 1er import: question vide, marquée skipped
+        for qid in [1_i64, 2] {
+            conn.execute(&stmts.answer_skipped, &[&1_i64, &qid, &1_i32]).unwrap();
+        }
+        assert_eq!(state(&mut conn), [(true, None, None, 0), (true, None, None, 0)]);
+
+        // ré-import: la question est répondue
+        let answer_id: i64 = conn.query_one(&stmts.answer_choice, &[&1_i64, &1_i64, &1_i32]).unwrap().get(0);
+        conn.execute(&stmts.answer_option, &[&answer_id, &1_i64]).unwrap();
+        conn.execute(&stmts.answer_float, &[&1_i64, &2_i64, &1_i32, &"4,5", &4.5_f64]).unwrap();
+        assert_eq!(state(&mut conn), [(false, None, None, 1), (false, Some("4,5".to_string()), Some(4.5), 0)]);
+
+        // nouveau ré-import: de nouveau vide, valeur et options effacées
+        for qid in [1_i64, 2] {
+            conn.execute(&stmts.answer_skipped, &[&1_i64, &qid, &1_i32]).unwrap();
+        }
+        assert_eq!(state(&mut conn), [(true, None, None, 0), (true, None, None, 0)]);
+
+        // choix multiple vidé hors record_skips: la réponse disparaît
+        let answer_id: i64 = conn.query_one(&stmts.answer_choice, &[&1_i64, &1_i64, &1_i32]).unwrap().get(0);
+        conn.execute(&stmts.answer_option, &[&answer_id, &1_i64]).unwrap();
+        conn.execute(&stmts.drop_answer, &[&1_i64, &1_i64, &1_i32]).unwrap();
+        assert_eq!(state(&mut conn), [(true, None, None, 0)]);
+        conn.batch_execute(&format!("DROP SCHEMA {schema} CASCADE")).unwrap();
+    }
+}
@@ -0,0 +1,196 @@
+// ---------- pseudonymize: identifiants synthétiques à la place des PII ----------
+//
+// Pour chaque auteur pas encore traité (authors.pseudonymized_at NULL):
+//   name       → Participant-XXXXXXXX, 8 premiers caractères hex de
+//                HMAC-SHA256(sel, source_author_id) (à défaut: l'id de la
+//                ligne): le même participant garde le même pseudonyme;
+//   email_hash → HMAC-SHA256(sel, email_hash) (double hachage: les
+//                auteurs restent distincts, le hash d'origine ne se retrouve
+//                plus depuis l'email sans le sel);
+//   zipcode    → deux premiers chiffres (département), NULL sinon;
+//   city       → NULL;
+// puis pseudonymized_at = now(). Un second passage avec le même sel ne
+// touche plus rien. --force retraite tous les auteurs: name, zipcode et city
+// sont stables, mais email_hash est haché une fois de plus.
+//
+// Par paquets de 1000 auteurs (ordre des id), un seul UPDATE … FROM unnest
+// par paquet, en autocommit: verrous tenus le temps d'un paquet seulement.
+// --dry-run: auteurs comptés, quelques pseudonymes affichés, rien d'écrit.
+//
+// Ne touche que authors: contributions.raw_json garde les colonnes de la
+// ligne source (cf. erase, ou --no-raw à l'ingestion). Un nouvel import ne
+// réécrit pas un auteur pseudonymisé, quel que soit --author-conflict.
+
+use anyhow::Result;
+use hmac::{Hmac, Mac};
+use postgres::GenericClient;
+use sha2::Sha256;
+
+/// Auteurs par UPDATE
+const BATCH: i64 = 1000;
+/// Pseudonymes affichés en --dry-run
+const PREVIEW: usize = 3;
+
+fn hmac_hex(salt: &str, value: &str) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(salt.as_bytes()).expect("HMAC accepte une clé de toute taille");
+    mac.update(value.as_bytes());
+    hex::encode(mac.finalize().into_bytes())
+}
+
+/// `Participant-` + 8 caractères hex, stable pour un sel et un participant
+fn pseudonym(salt: &str, source_author_id: Option<&str>, id: i64) -> String {
+    let key = source_author_id.map(str::to_string).unwrap_or_else(|| id.to_string());
+    format!("Participant-{}", &hmac_hex(salt, &key)[..8])
+}
+
+/// Département du code postal: deux premiers chiffres, None sinon
+fn department_prefix(zipcode: &str) -> Option<String> {
+    let prefix: String = zipcode.trim().chars().take(2).collect();
+    (prefix.len() == 2 && prefix.chars().all(|c| c.is_ascii_digit())).then_some(prefix)
+}
+
+/// Auteurs traités (ou à traiter en dry-run)
+pub fn pseudonymize(client: &mut impl GenericClient, salt: &str, force: bool, dry_run: bool) -> Result<(u64, Vec<String>)> {
+    let (mut done, mut preview, mut last_id) = (0u64, Vec::new(), 0i64);
+    loop {
+        let rows = client.query(
+            "SELECT id, source_author_id, email_hash, zipcode FROM authors
+             WHERE id > $1 AND (pseudonymized_at IS NULL OR $2)
+             ORDER BY id LIMIT $3",
+            &[&last_id, &force, &BATCH],
+        )?;
+        let Some(last) = rows.last() else {
+            break;
+        };
+        last_id = last.get(0);
+        let mut ids = Vec::with_capacity(rows.len());
+        let mut names = Vec::with_capacity(rows.len());
+        let mut hashes = Vec::with_capacity(rows.len());
+        let mut zipcodes = Vec::with_capacity(rows.len());
+        for row in &rows {
+            let id: i64 = row.get(0);
+            let source_author_id: Option<&str> = row.get(1);
+            let email_hash: Option<&str> = row.get(2);
+            let zipcode: Option<&str> = row.get(3);
+            ids.push(id);
+            names.push(pseudonym(salt, source_author_id, id));
+            hashes.push(email_hash.map(|h| hmac_hex(salt, h)));
+            zipcodes.push(zipcode.and_then(department_prefix));
+        }
+        done += rows.len() as u64;
+        if dry_run {
+            preview.extend(names.iter().take(PREVIEW.saturating_sub(preview.len())).cloned());
+            continue;
+        }
+        client.execute(
+            "UPDATE authors a SET name = v.name, email_hash = v.email_hash, zipcode = v.zipcode, city = NULL,
+                    pseudonymized_at = now()
+             FROM unnest($1::bigint[], $2::text[], $3::text[], $4::text[]) AS v(id, name, email_hash, zipcode)
+             WHERE a.id = v.id",
+            &[&ids, &names, &hashes, &zipcodes],
+        )?;
+        println!("[pseudonymize] … {done} auteurs");
+    }
+    Ok((done, preview))
+}
+
+pub fn run_pseudonymize(salt: &str, force: bool, dry_run: bool) -> Result<()> {
+    if salt.is_empty() {
+        anyhow::bail!("--salt vide");
+    }
+    let mut conn = crate::open_conn()?;
+    let present: bool = conn
+        .query_one(
+            "SELECT EXISTS (SELECT 1 FROM information_schema.columns
+                            WHERE table_schema = current_schema() AND table_name = 'authors' AND column_name = 'pseudonymized_at')",
+            &[],
+        )?
+        .get(0);
+    if !present {
+        anyhow::bail!("colonne authors.pseudonymized_at absente (alembic upgrade head, ou gdn_ingest db-init --if-not-exists)");
+    }
+    let (n, preview) = pseudonymize(&mut conn, salt, force, dry_run)?;
+    let scope = if force { "tous les auteurs (--force)" } else { "auteurs pas encore pseudonymisés" };
+    if dry_run {
+        println!("[dry-run] {n} {scope} à traiter, ex: {} — relancer sans --dry-run pour écrire", preview.join(", "));
+    } else {
+        println!("[pseudonymize] ✅ {n} {scope} traités (name, email_hash, zipcode, city)");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{dbinit, pool};
+    use postgres::Client;
+
+    #[test]
+    fn pseudonyms_are_stable_per_salt() {
+        let p = pseudonym("sel", Some("a1"), 1);
+        assert_eq!(p, pseudonym("sel", Some("a1"), 2));
+        assert_ne!(p, pseudonym("autre", Some("a1"), 1));
+        assert_eq!(p, format!("Participant-{}", &hmac_hex("sel", "a1")[..8]));
+        assert_eq!(pseudonym("sel", None, 7), pseudonym("sel", Some("7"), 0));
+        // RFC 4231, cas 2
+        assert_eq!(
+            hmac_hex("Jefe", "what do ya want for nothing?"),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+
+    #[test]
+    fn zipcode_keeps_department() {
+        assert_eq!(department_prefix(" 75001").as_deref(), Some("75"));
+        assert_eq!(department_prefix("2A004"), None);
+        assert_eq!(department_prefix("7"), None);
+    }
+
+    #[test]
+    #[ignore = "nécessite TEST_DATABASE_URL"]
+    fn second_run_is_a_no_op() {
+        let schema = "gdn_test_pseudonymize";
+        let mut conn = pool::test_conn(Some(schema));
+        dbinit::init(&mut conn, schema, false, false).unwrap();
+        conn.batch_execute(
+            "INSERT INTO authors (source_author_id, name, email_hash, zipcode, city)
+             SELECT 'a' || i, 'Nom ' || i, 'h' || i, '75001', 'Paris' FROM generate_series(1, 1500) AS i",
+        )
+        .unwrap();
+
+        let (n, preview) = pseudonymize(&mut conn, "sel", false, true).unwrap();
+        assert_eq!((n, preview.len()), (1500, PREVIEW));
+        assert_eq!(pseudonymize(&mut conn, "sel", false, false).unwrap().0, 1500);
+        let snapshot = |conn: &mut Client| -> Vec<[Option<String>; 4]> {
+            conn.query("SELECT name, email_hash, zipcode, city FROM authors ORDER BY id", &[])
+                .unwrap()
+                .iter()
+                .map(|r| [r.get(0), r.get(1), r.get(2), r.get(3)])
+                .collect()
+        };
+        let first = snapshot(&mut conn);
+        assert_eq!(first[0], [Some(pseudonym("sel", Some("a1"), 1)), Some(hmac_hex("sel", "h1")), Some("75".into()), None]);
+        assert_eq!(pseudonymize(&mut conn, "sel", false, false).unwrap().0, 0);
+        assert_eq!(snapshot(&mut conn), first);
+
+        // ré-import du même auteur: ni upsert ni mise à jour ne remettent de PII
+        let pii: [Option<&str>; 8] = [Some("Nom 1"), Some("h1"), Some("75001"), Some("Paris"), None, None, None, None];
+        let [name, email_hash, zipcode, city, age_range, gender, department_code, region_code] = &pii;
+        for conflict in [crate::AuthorConflict::Overwrite, crate::AuthorConflict::Fill] {
+            let id: i64 = conn
+                .query_one(
+                    conflict.upsert_sql("source_author_id").as_str(),
+                    &[&"a1", name, email_hash, zipcode, city, age_range, gender, department_code, region_code],
+                )
+                .unwrap()
+                .get(0);
+            conn.execute(
+                crate::UPDATE_AUTHOR,
+                &[&id, name, email_hash, zipcode, city, age_range, gender, department_code, region_code],
+            )
+            .unwrap();
+        }
+        assert_eq!(snapshot(&mut conn), first);
+        conn.batch_execute(&format!("DROP SCHEMA {schema} CASCADE")).unwrap();
+    }
+}
@@ -0,0 +1,156 @@
+// ---------- raw_imports: journal des lignes ingérées ----------
+//
+// Chaque ligne qui écrit une contribution (insérée, réécrite ou prolongée
+// par --merge-into-existing) est consignée dans raw_imports: batch, fichier
+// (comme contributions.source_file), numéro d'enregistrement (1 = première
+// ligne de données), contribution, hash et raw_json de la ligne (ceux
+// calculés pour contributions: raw_json NULL en --no-raw). Index unique sur
+// (form_id, row_hash): seule la première ingestion d'un contenu est gardée,
+// par formulaire (une même ligne dans deux formulaires n'est pas un doublon).
+//
+// Avant l'écriture, le hash de la ligne est cherché dans raw_imports pour le
+// formulaire: une ligne identique déjà importée (batch précédent, ou plus
+// tôt dans ce run)
+// est signalée (la première par fichier, les suivantes comptées, batches
+// d'origine listés en fin de run) puis ingérée comme les autres;
+// --skip-duplicates la saute. Lignes à la corbeille, écartées ou laissées
+// de côté (inchangées, --on-existing skip) ne sont pas consignées.
+//
+// La table est exigée par l'ingestion (session::check_tables, dans --schema
+// lui-même: les hashes d'un autre jeu ne doivent pas faire sauter de lignes).
+// delete-batch retire les lignes du batch annulé (un réimport n'est plus un
+// doublon); erase remet leur raw_json à NULL comme celui des contributions.
+
+use anyhow::Result;
+use postgres::{GenericClient, Statement};
+use std::collections::BTreeMap;
+
+/// $1 formulaire, $2 batch, $3 fichier, $4 numéro d'enregistrement, $5 contribution, $6 hash, $7 raw_json
+pub const INSERT: &str = "INSERT INTO raw_imports (form_id, batch_id, file_path, row_number, contribution_id, row_hash, raw_json)
+     VALUES ($1, $2, $3, $4, $5, $6, $7)
+     ON CONFLICT (form_id, row_hash) DO NOTHING";
+
+/// $1 formulaire, $2 hash → première ingestion de ce contenu
+pub const EARLIER: &str = "SELECT batch_id, file_path, row_number FROM raw_imports WHERE form_id = $1 AND row_hash = $2";
+
+/// Première ingestion d'une ligne identique
+#[derive(Debug, PartialEq)]
+pub struct Earlier {
+    pub batch: String,
+    pub file: String,
+    pub row_number: i64,
+}
+
+impl Earlier {
+    pub fn find(client: &mut impl GenericClient, stmt: &Statement, form_id: i64, row_hash: &str) -> Result<Option<Self>> {
+        Ok(client
+            .query_opt(stmt, &[&form_id, &row_hash])?
+            .map(|r| Earlier { batch: r.get(0), file: r.get(1), row_number: r.get(2) }))
+    }
+}
+
+/// Lignes déjà consignées, par batch d'origine
+#[derive(Debug, Default)]
+pub struct Duplicates {
+    pub count: u64,
+    batches: BTreeMap<String, u64>,
+}
+
+impl Duplicates {
+    /// Vrai pour le premier doublon (seul signalé en détail)
+    pub fn note(&mut self, earlier: &Earlier) -> bool {
+        self.count += 1;
+        *self.batches.entry(earlier.batch.clone()).or_default() += 1;
+        self.count == 1
+    }
+
+    pub fn merge(&mut self, other: Duplicates) {
+        self.count += other.count;
+        for (batch, n) in other.batches {
+            *self.batches.entry(batch).or_default() += n;
+        }
+    }
+
+    /// `batch (lignes)`, batches d'origine par ordre alphabétique
+    pub fn describe(&self) -> String {
+        let batches: Vec<String> = self.batches.iter().map(|(b, n)| format!("{b} ({n})")).collect();
+        batches.join(", ")
+    }
+}
+
+/// Table raw_imports présente dans le schéma courant (base migrée); pas celle
+/// de public vue par search_path avec --schema
+pub fn has_table(client: &mut impl GenericClient) -> Result<bool> {
+    Ok(client
+        .query_one(
+            "SELECT EXISTS (SELECT 1 FROM information_schema.tables
+                            WHERE table_schema = current_schema() AND table_name = 'raw_imports')",
+            &[],
+        )?
+        .get(0))
+}
+
+/// raw_imports avec form_id (base migrée): les lignes d'un autre formulaire
+/// ne comptent pas comme doublons
+pub fn check_schema(client: &mut impl GenericClient) -> Result<()> {
+    let ok: bool = client
+        .query_one(
+            "SELECT EXISTS (SELECT 1 FROM information_schema.columns
+                            WHERE table_schema = current_schema() AND table_name = 'raw_imports'
+                              AND column_name = 'form_id')",
+            &[],
+        )?
+        .get(0);
+    if !ok {
+        anyhow::bail!(
+            "table raw_imports sans form_id — appliquer les migrations \
+             (alembic upgrade head, ou gdn_ingest db-init --if-not-exists)"
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{dbinit, pool};
+
+    #[test]
+    fn duplicates_are_grouped_by_batch() {
+        let earlier = |batch: &str| Earlier { batch: batch.to_string(), file: "f.csv".to_string(), row_number: 1 };
+        let mut first = Duplicates::default();
+        assert!(first.note(&earlier("mars")));
+        assert!(!first.note(&earlier("janvier")));
+        let mut second = Duplicates::default();
+        second.note(&earlier("mars"));
+        first.merge(second);
+        assert_eq!((first.count, first.describe()), (3, "janvier (1), mars (2)".to_string()));
+    }
+
+    #[test]
+    #[ignore = "nécessite TEST_DATABASE_URL"]
+    fn first_ingestion_of_a_row_is_kept() {
+        let schema = "gdn_test_rawimports";
+        let mut conn = pool::test_conn(Some(schema));
+        // schéma vide: la table de public (search_path) ne compte pas
+        conn.batch_execute(&format!("CREATE SCHEMA {schema}")).unwrap();
+        assert!(!has_table(&mut conn).unwrap());
+        dbinit::init(&mut conn, schema, false, false).unwrap();
+        assert!(has_table(&mut conn).unwrap());
+        check_schema(&mut conn).unwrap();
+
+        conn.batch_execute("INSERT INTO forms (id, name) VALUES (1, 'f'), (2, 'f2')").unwrap();
+        let (insert, earlier) = (conn.prepare(INSERT).unwrap(), conn.prepare(EARLIER).unwrap());
+        assert_eq!(Earlier::find(&mut conn, &earlier, 1, "h1").unwrap(), None);
+        for (form, batch, file, row) in [(1_i64, "janvier", "a.csv", 3_i64), (1, "mars", "b.csv", 1), (2, "avril", "c.csv", 2)] {
+            let contribution: Option<i64> = None;
+            conn.execute(&insert, &[&form, &batch, &file, &row, &contribution, &"h1", &Some(serde_json::json!({}))]).unwrap();
+        }
+        let found = Earlier::find(&mut conn, &earlier, 1, "h1").unwrap();
+        assert_eq!(found, Some(Earlier { batch: "janvier".to_string(), file: "a.csv".to_string(), row_number: 3 }));
+        // même ligne dans un autre formulaire: sa propre première ingestion
+        let other = Earlier::find(&mut conn, &earlier, 2, "h1").unwrap();
+        assert_eq!(other, Some(Earlier { batch: "avril".to_string(), file: "c.csv".to_string(), row_number: 2 }));
+        conn.batch_execute(&format!("DROP SCHEMA {schema} CASCADE")).unwrap();
+    }
+}
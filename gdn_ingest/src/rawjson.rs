@@ -0,0 +1,135 @@
+// ---------- Construction de contributions.raw_json ----------
+//
+// `defaults.raw_json` dans le mapping:
+//   skip_empty_values: true   cellules vides omises (false = ancien format,
+//                              utile pour garder les mêmes raw_hash)
+//   max_columns: N            au-delà de N colonnes non vides, le reste est
+//                              résumé par `"__truncated": <nombre omis>` et
+//                              `"__truncated_sha256"` (colonnes et valeurs
+//                              omises: une ligne modifiée au-delà du plafond
+//                              change de hash)
+// Le hash de ligne est calculé sur la représentation effectivement stockée.
+//
+// --no-raw (ou `ingest.store_raw: false`): raw_json NULL, rien n'est construit;
+// le hash porte alors sur les couples colonne/valeur (`values_hash`). Il
+// diffère de celui du mode normal: changer de mode réécrit chaque ligne une
+// fois. Le batch le consigne (provenance `store_raw: false`): ses
+// contributions ne sont pas rejouables depuis raw_json.
+
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use sha2::{Digest, Sha256};
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct RawJsonOptions {
+    #[serde(default = "crate::default_true")]
+    pub skip_empty_values: bool,
+    #[serde(default)]
+    pub max_columns: Option<usize>,
+}
+
+impl Default for RawJsonOptions {
+    fn default() -> Self {
+        RawJsonOptions { skip_empty_values: true, max_columns: None }
+    }
+}
+
+/// raw_json de la ligne + taille approximative (octets) de l'ancien format complet
+pub fn build<'v>(
+    keys: &[String],
+    values: impl Iterator<Item = &'v str>,
+    original_headers: &[(String, String)],
+    opts: &RawJsonOptions,
+) -> (Value, usize) {
+    let mut map = Map::new();
+    let mut full_len = 2;
+    let mut truncated = 0usize;
+    let mut omitted = Sha256::new();
+    let mut kept = 0usize;
+    for (key, v) in keys.iter().zip(values) {
+        // "clé":"valeur",
+        full_len += key.len() + v.len() + 6;
+        if opts.skip_empty_values && v.is_empty() {
+            continue;
+        }
+        if opts.max_columns.is_some_and(|max| kept >= max) {
+            truncated += 1;
+            feed(&mut omitted, key, v);
+            continue;
+        }
+        map.insert(key.clone(), Value::String(v.to_string()));
+        kept += 1;
+    }
+    if truncated > 0 {
+        map.insert("__truncated".into(), Value::from(truncated));
+        map.insert("__truncated_sha256".into(), Value::String(hex::encode(omitted.finalize())));
+    }
+    if !original_headers.is_empty() {
+        let originals: Map<String, Value> = original_headers
+            .iter()
+            .map(|(k, h)| (k.clone(), Value::String(h.clone())))
+            .collect();
+        full_len += originals.iter().map(|(k, h)| k.len() + h.as_str().unwrap_or("").len() + 6).sum::<usize>() + 24;
+        map.insert("__original_headers".into(), Value::Object(originals));
+    }
+    (Value::Object(map), full_len)
+}
+
+fn feed(hasher: &mut Sha256, key: &str, v: &str) {
+    hasher.update(key.as_bytes());
+    hasher.update([0x1f]);
+    hasher.update(v.as_bytes());
+    hasher.update([0x1e]);
+}
+
+/// Hash de ligne sans raw_json (--no-raw): colonnes et valeurs, séparées
+pub fn values_hash<'v>(keys: impl Iterator<Item = &'v str>, values: impl Iterator<Item = &'v str>) -> String {
+    let mut hasher = Sha256::new();
+    for (key, v) in keys.zip(values) {
+        feed(&mut hasher, key, v);
+    }
+    hex::encode(hasher.finalize())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use csv::StringRecord;
+
+    fn keys(n: usize) -> Vec<String> {
+        (0..n).map(|i| format!("c{i}")).collect()
+    }
+
+    #[test]
+    fn empty_cells_are_skipped_by_default() {
+        let rec = StringRecord::from(vec!["a", "", "b"]);
+        let (v, _) = build(&keys(3), rec.iter(), &[], &RawJsonOptions::default());
+        assert_eq!(v, serde_json::json!({"c0": "a", "c2": "b"}));
+
+        let legacy = RawJsonOptions { skip_empty_values: false, max_columns: None };
+        let (v, full) = build(&keys(3), rec.iter(), &[], &legacy);
+        assert_eq!(v, serde_json::json!({"c0": "a", "c1": "", "c2": "b"}));
+        assert!(full >= v.to_string().len());
+    }
+
+    #[test]
+    fn overflow_columns_are_summarized() {
+        let rec = StringRecord::from(vec!["1", "", "2", "3", "4"]);
+        let opts = RawJsonOptions { skip_empty_values: true, max_columns: Some(2) };
+        let (v, _) = build(&keys(5), rec.iter(), &[], &opts);
+        let digest = values_hash(["c3", "c4"].into_iter(), ["3", "4"].into_iter());
+        assert_eq!(v, serde_json::json!({"c0": "1", "c2": "2", "__truncated": 2, "__truncated_sha256": digest}));
+
+        // valeur modifiée au-delà du plafond: raw_json (donc le hash) change
+        let edited = StringRecord::from(vec!["1", "", "2", "3", "5"]);
+        assert_ne!(build(&keys(5), edited.iter(), &[], &opts).0, v);
+    }
+
+    #[test]
+    fn values_hash_separates_cells() {
+        let h = |k: &[&'static str], v: &[&'static str]| values_hash(k.iter().copied(), v.iter().copied());
+        assert_eq!(h(&["a", "b"], &["1", "2"]), h(&["a", "b"], &["1", "2"]));
+        assert_ne!(h(&["a", "b"], &["12", ""]), h(&["a", "b"], &["1", "2"]));
+        assert_ne!(h(&["a", "b"], &["1", "2"]), h(&["b", "a"], &["1", "2"]));
+    }
+}
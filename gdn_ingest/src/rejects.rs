@@ -0,0 +1,164 @@
+// ---------- Lignes écartées ----------
+//
+// Une ligne écartée pendant l'ingestion n'est pas perdue: elle est recopiée
+// dans `<--rejects-dir>/<chemin du fichier>.rejects.csv` (défaut: `rejects/`;
+// le chemin d'entrée, sans racine ni `..`, sépare deux fichiers de même nom
+// dans des dossiers différents), colonnes d'origine + `_reject_reason` +
+// `_source_line` (ligne du fichier source, vide si inconnue), pour corriger
+// et réingérer ces seules lignes. Concernées:
+//   - lignes à la corbeille (trashed);
+//   - rejets de la politique d'erreurs (code postal avec --zipcode-strict,
+//     submitted_at hors fenêtre…), --rules quarantine;
+//   - lignes sautées avec --max-errors (valeur refusée, erreur de la base;
+//     cf. maxerrors.rs). Un enregistrement illisible n'a pas de colonnes à
+//     recopier: il est seulement compté.
+// Plusieurs motifs pour une même ligne: une seule ligne, motifs séparés par
+// `;`. Les lignes sont gardées en mémoire et écrites en fin de fichier: en
+// JSON Lines chaque ligne a ses clés, l'en-tête est leur union (ordre de
+// première apparition). Cellules échappées contre l'injection de formules
+// (sanitize::write_safe_record). Le fichier n'est créé que s'il y a des rejets.
+
+use anyhow::{Context, Result};
+use std::path::{Component, Path, PathBuf};
+
+use crate::input::ColumnAccessor;
+use crate::sanitize;
+
+/// Ligne écartée: (colonne, valeur), motifs, ligne source
+struct Rejected {
+    cells: Vec<(String, String)>,
+    reason: String,
+    line: String,
+}
+
+pub struct Rejects {
+    path: PathBuf,
+    columns: Vec<String>,
+    rows: Vec<Rejected>,
+    pub count: usize,
+}
+
+impl Rejects {
+    /// Rejets du fichier `input`, écrits dans `dir`
+    pub fn new(dir: &Path, input: &str) -> Self {
+        let relative: PathBuf = Path::new(input)
+            .components()
+            .filter_map(|c| match c {
+                Component::Normal(part) => Some(part),
+                _ => None,
+            })
+            .collect();
+        let mut name = relative.into_os_string();
+        name.push(".rejects.csv");
+        Rejects { path: dir.join(name), columns: Vec::new(), rows: Vec::new(), count: 0 }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Recopie la ligne avec ses motifs (doublons écartés)
+    pub fn reject<S: AsRef<str>>(&mut self, row: &dyn ColumnAccessor, reasons: &[S]) -> Result<()> {
+        let cells: Vec<(String, String)> = row.columns().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+        for (k, _) in &cells {
+            if !self.columns.contains(k) {
+                self.columns.push(k.clone());
+            }
+        }
+        let mut distinct: Vec<&str> = Vec::new();
+        for reason in reasons.iter().map(AsRef::as_ref) {
+            if !distinct.contains(&reason) {
+                distinct.push(reason);
+            }
+        }
+        let line = row.line().map(|l| l.to_string()).unwrap_or_default();
+        self.rows.push(Rejected { cells, reason: distinct.join(";"), line });
+        self.count += 1;
+        Ok(())
+    }
+
+    /// Écrit les lignes gardées (fichier créé au premier appel avec des rejets)
+    pub fn finish(&mut self) -> Result<()> {
+        if self.rows.is_empty() {
+            return Ok(());
+        }
+        if let Some(dir) = self.path.parent() {
+            std::fs::create_dir_all(dir).with_context(|| format!("création {:?}", dir))?;
+        }
+        let context = || format!("écriture {:?}", self.path);
+        let mut w = csv::Writer::from_path(&self.path).with_context(context)?;
+        sanitize::write_safe_record(&mut w, self.columns.iter().map(String::as_str).chain(["_reject_reason", "_source_line"]))
+            .with_context(context)?;
+        for r in self.rows.drain(..) {
+            let cell = |c: &String| r.cells.iter().find(|(k, _)| k == c).map_or("", |(_, v)| v.as_str());
+            sanitize::write_safe_record(&mut w, self.columns.iter().map(cell).chain([r.reason.as_str(), r.line.as_str()]))
+                .with_context(context)?;
+        }
+        w.flush().with_context(context)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::CsvRow;
+    use csv::StringRecord;
+    use std::rc::Rc;
+
+    #[test]
+    fn file_created_on_first_reject_only() {
+        let dir = std::env::temp_dir().join(format!("gdn_rejects_{}", std::process::id()));
+        let mut r = Rejects::new(&dir, "/data/LA_DEMOCRATIE.csv");
+        r.finish().unwrap();
+        assert!(!r.path().exists());
+
+        let headers = Rc::new(StringRecord::from(vec!["reference", "zip"]));
+        let row = CsvRow::new(headers.clone(), StringRecord::from(vec!["r1", "7"]));
+        r.reject(&row, &["code postal invalide '7'"]).unwrap();
+        let mut rec = StringRecord::from(vec!["r2", "75001"]);
+        let mut pos = csv::Position::new();
+        pos.set_line(3);
+        rec.set_position(Some(pos));
+        let reasons = ["question 'q': required, cellule vide", "question 'r': pattern non respecté 'x'", "question 'q': required, cellule vide"];
+        r.reject(&CsvRow::new(headers, rec), &reasons).unwrap();
+        r.finish().unwrap();
+        assert_eq!(r.path(), dir.join("data/LA_DEMOCRATIE.csv.rejects.csv"));
+        assert_eq!(
+            std::fs::read_to_string(r.path()).unwrap(),
+            "reference,zip,_reject_reason,_source_line\n\
+             r1,7,code postal invalide '7',\n\
+             r2,75001,\"question 'q': required, cellule vide;question 'r': pattern non respecté 'x'\",3\n"
+        );
+        assert_eq!(r.count, 2);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn same_file_name_in_two_folders_is_kept_apart() {
+        let dir = Path::new("rejects");
+        let (a, b) = (Rejects::new(dir, "2019/mars/export.csv"), Rejects::new(dir, "2019/avril/export.csv"));
+        assert_ne!(a.path(), b.path());
+        assert_eq!(Rejects::new(dir, "../in/./export.csv").path(), dir.join("in/export.csv.rejects.csv"));
+    }
+
+    #[test]
+    fn header_is_the_union_of_rejected_rows_and_cells_are_escaped() {
+        let dir = std::env::temp_dir().join(format!("gdn_rejects_union_{}", std::process::id()));
+        let mut r = Rejects::new(&dir, "export.jsonl");
+        // clés propres à chaque ligne, comme en JSON Lines
+        let first = CsvRow::new(Rc::new(StringRecord::from(vec!["reference"])), StringRecord::from(vec!["r1"]));
+        let second = CsvRow::new(Rc::new(StringRecord::from(vec!["reference", "avis"])), StringRecord::from(vec!["r2", "=1+1"]));
+        r.reject(&first, &["a"]).unwrap();
+        r.reject(&second, &["b"]).unwrap();
+        r.finish().unwrap();
+        assert_eq!(
+            std::fs::read_to_string(r.path()).unwrap(),
+            "reference,avis,_reject_reason,_source_line\n\
+             r1,,a,\n\
+             r2,'=1+1,b,\n"
+        );
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
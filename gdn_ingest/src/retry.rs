@@ -0,0 +1,118 @@
+// ---------- Deadlocks: nouvel essai avec attente exponentielle ----------
+//
+// Deux ingestions concurrentes (ou deux threads --parallel) peuvent se
+// bloquer mutuellement; PostgreSQL annule alors l'une des requêtes avec
+// 40P01 (deadlock detected). Plutôt que d'arrêter le run, l'opération est
+// rejouée jusqu'à --db-retry-attempts fois, après base * 2^essai ms
+// (--db-retry-base-ms) plus un aléa d'au plus la moitié, pour que les deux
+// parties ne repartent pas ensemble.
+//
+// Concernés: la création d'une option dynamique (autocommit, connexion
+// dédiée: rejouée telle quelle) et l'écriture de la contribution, rejouée
+// sous SAVEPOINT dans la transaction d'ingestion. Le SAVEPOINT ne libère pas
+// les verrous pris plus tôt dans la transaction: si le cycle passe par eux,
+// le deadlock se répète et l'erreur remonte après le dernier essai (reprise:
+// --resume). --db-retry-attempts 0: ni SAVEPOINT ni nouvel essai.
+
+use anyhow::Result;
+use postgres::error::SqlState;
+use postgres::Transaction;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Défaut de --db-retry-attempts
+pub const DEFAULT_ATTEMPTS: u32 = 3;
+/// Défaut de --db-retry-base-ms
+pub const DEFAULT_BASE_MS: u64 = 200;
+
+/// Réglages --db-retry-*
+#[derive(Debug, Clone, Copy)]
+pub struct Retry {
+    /// nouveaux essais après le premier (0: aucun)
+    pub attempts: u32,
+    pub base_ms: u64,
+}
+
+fn is_deadlock(e: &anyhow::Error) -> bool {
+    e.chain()
+        .filter_map(|c| c.downcast_ref::<postgres::Error>())
+        .any(|e| e.code() == Some(&SqlState::T_R_DEADLOCK_DETECTED))
+}
+
+/// Attente avant le nouvel essai n° `attempt` (1, 2…), sans l'aléa
+fn backoff(base_ms: u64, attempt: u32) -> u64 {
+    base_ms.saturating_mul(1u64 << (attempt - 1).min(16))
+}
+
+fn jitter(delay_ms: u64) -> u64 {
+    let nanos = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.subsec_nanos() as u64);
+    (nanos ^ std::process::id() as u64) % (delay_ms / 2 + 1)
+}
+
+/// Rejoue `f` sur deadlock, au plus `max_attempts` fois après le premier essai
+pub fn retry_on_deadlock<F, R>(mut f: F, max_attempts: u32, base_delay_ms: u64) -> Result<R>
+where
+    F: FnMut() -> Result<R>,
+{
+    let mut attempt = 0;
+    loop {
+        match f() {
+            Err(e) if attempt < max_attempts && is_deadlock(&e) => {
+                attempt += 1;
+                let delay = backoff(base_delay_ms, attempt);
+                let delay = delay + jitter(delay);
+                println!(
+                    "⚠️  deadlock ({}), nouvel essai {attempt}/{max_attempts} dans {delay} ms",
+                    SqlState::T_R_DEADLOCK_DETECTED.code()
+                );
+                std::thread::sleep(Duration::from_millis(delay));
+            }
+            outcome => return outcome,
+        }
+    }
+}
+
+/// Requête dans la transaction d'ingestion, sous SAVEPOINT pour pouvoir
+/// la rejouer sans perdre le reste de la transaction
+pub fn in_savepoint<R>(
+    tx: &mut Transaction,
+    retry: Retry,
+    mut f: impl FnMut(&mut Transaction) -> Result<R, postgres::Error>,
+) -> Result<R> {
+    if retry.attempts == 0 {
+        return Ok(f(tx)?);
+    }
+    retry_on_deadlock(
+        || {
+            let mut sp = tx.savepoint("deadlock_retry")?;
+            let r = f(&mut sp)?;
+            sp.commit()?;
+            Ok(r)
+        },
+        retry.attempts,
+        retry.base_ms,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backoff_doubles_and_retries_stop() {
+        assert_eq!([1, 2, 3].map(|a| backoff(DEFAULT_BASE_MS, a)), [200, 400, 800]);
+        assert!(jitter(400) <= 200);
+
+        // erreur autre qu'un deadlock: pas de nouvel essai
+        let mut calls = 0;
+        let r: Result<()> = retry_on_deadlock(
+            || {
+                calls += 1;
+                anyhow::bail!("autre erreur")
+            },
+            DEFAULT_ATTEMPTS,
+            0,
+        );
+        assert!(r.is_err());
+        assert_eq!(calls, 1);
+    }
+}
@@ -0,0 +1,163 @@
+// ---------- delete-batch: annuler un import ----------
+//
+// Contributions d'un batch:
+//   - créées: created_import_batch_id = batch → supprimées;
+//   - mises à jour: import_batch_id = batch mais créées par un autre batch
+//     (ou avant ce suivi: NULL) → signalées, supprimées seulement avec
+//     --include-updated. Leur version antérieure n'est pas restaurée: la
+//     contribution disparaît.
+// Une contribution créée par le batch puis réécrite par un batch suivant
+// part avec le batch qui l'a créée.
+//
+// Suppression par tranches de --chunk contributions (une transaction chacune,
+// pas de verrou sur toute la table), dans l'ordre des clés étrangères:
+// answer_options, answers, contribution_topics, contributions. Les lignes
+// import_batches du batch passent `deleted`, ses lignes de raw_imports sont
+// retirées (un réimport n'est pas un doublon). Auteurs et options
+// dynamiques restent en place.
+//
+// Refus si le batch est inconnu (ni import_batches ni contributions) ou si
+// une ingestion de ce batch est en cours.
+
+use anyhow::Result;
+use postgres::GenericClient;
+
+/// Défaut de --chunk
+pub const DEFAULT_CHUNK: usize = 1000;
+
+/// Lignes supprimées (ou à supprimer), par table
+#[derive(Debug, Default, PartialEq)]
+struct Removed {
+    answer_options: u64,
+    answers: u64,
+    contribution_topics: u64,
+    contributions: u64,
+}
+
+impl Removed {
+    fn add(&mut self, other: &Removed) {
+        self.answer_options += other.answer_options;
+        self.answers += other.answers;
+        self.contribution_topics += other.contribution_topics;
+        self.contributions += other.contributions;
+    }
+
+    /// Tables dans l'ordre de suppression
+    fn tables(&self) -> [(&'static str, u64); 4] {
+        [
+            ("answer_options", self.answer_options),
+            ("answers", self.answers),
+            ("contribution_topics", self.contribution_topics),
+            ("contributions", self.contributions),
+        ]
+    }
+
+    fn print(&self) {
+        for (table, n) in self.tables() {
+            println!("  {table:<20} {n:>10}");
+        }
+    }
+}
+
+fn count(conn: &mut impl GenericClient, ids: &[i64], topics: bool) -> Result<Removed> {
+    let mut n = |sql: &str| -> Result<u64> { Ok(conn.query_one(sql, &[&ids])?.get::<_, i64>(0) as u64) };
+    Ok(Removed {
+        answer_options: n("SELECT COUNT(*) FROM answer_options ao JOIN answers a ON a.id = ao.answer_id
+                           WHERE a.contribution_id = ANY($1)")?,
+        answers: n("SELECT COUNT(*) FROM answers WHERE contribution_id = ANY($1)")?,
+        contribution_topics: match topics {
+            true => n("SELECT COUNT(*) FROM contribution_topics WHERE contribution_id = ANY($1)")?,
+            false => 0,
+        },
+        contributions: ids.len() as u64,
+    })
+}
+
+fn delete(tx: &mut impl GenericClient, ids: &[i64], topics: bool) -> Result<Removed> {
+    let answer_options = tx.execute(
+        "DELETE FROM answer_options WHERE answer_id IN (SELECT id FROM answers WHERE contribution_id = ANY($1))",
+        &[&ids],
+    )?;
+    let answers = tx.execute("DELETE FROM answers WHERE contribution_id = ANY($1)", &[&ids])?;
+    let contribution_topics = match topics {
+        true => tx.execute("DELETE FROM contribution_topics WHERE contribution_id = ANY($1)", &[&ids])?,
+        false => 0,
+    };
+    let contributions = tx.execute("DELETE FROM contributions WHERE id = ANY($1)", &[&ids])?;
+    Ok(Removed { answer_options, answers, contribution_topics, contributions })
+}
+
+pub fn run_delete_batch(batch: &str, dry_run: bool, include_updated: bool, chunk: usize) -> Result<()> {
+    let mut conn = crate::open_conn()?;
+    if let Some(r) = conn.query_opt(
+        "SELECT id FROM import_batches WHERE batch = $1 AND status = 'running' LIMIT 1",
+        &[&batch],
+    )? {
+        anyhow::bail!("batch '{batch}' en cours d'ingestion (import_batches id={}): attendre sa fin", r.get::<_, i64>(0));
+    }
+    let runs: i64 = conn.query_one("SELECT COUNT(*) FROM import_batches WHERE batch = $1", &[&batch])?.get(0);
+    let ids = |conn: &mut postgres::Client, sql: &str| -> Result<Vec<i64>> {
+        Ok(conn.query(sql, &[&batch])?.iter().map(|r| r.get(0)).collect())
+    };
+    let created = ids(&mut conn, "SELECT id FROM contributions WHERE created_import_batch_id = $1 ORDER BY id")?;
+    let updated = ids(
+        &mut conn,
+        "SELECT id FROM contributions
+         WHERE import_batch_id = $1 AND created_import_batch_id IS DISTINCT FROM $1 ORDER BY id",
+    )?;
+    if runs == 0 && created.is_empty() && updated.is_empty() {
+        anyhow::bail!("batch '{batch}' inconnu (ni import_batches ni contributions)");
+    }
+    println!(
+        "[delete-batch] '{batch}': {runs} runs, {} contributions créées, {} mises à jour {}",
+        created.len(),
+        updated.len(),
+        if include_updated { "(supprimées aussi: --include-updated)" } else { "(conservées, cf. --include-updated)" }
+    );
+
+    let mut targets = created;
+    if include_updated {
+        targets.extend(updated);
+    }
+    let topics: bool = conn.query_one("SELECT to_regclass('contribution_topics') IS NOT NULL", &[])?.get(0);
+    if dry_run {
+        println!("[dry-run] lignes à supprimer:");
+        count(&mut conn, &targets, topics)?.print();
+        println!("[dry-run] aucune modification — relancer sans --dry-run");
+        return Ok(());
+    }
+
+    let mut removed = Removed::default();
+    for (i, part) in targets.chunks(chunk.max(1)).enumerate() {
+        let mut tx = conn.transaction()?;
+        removed.add(&delete(&mut tx, part, topics)?);
+        tx.commit()?;
+        if (i + 1) % 100 == 0 {
+            println!("  … {} contributions supprimées", removed.contributions);
+        }
+    }
+    conn.execute("UPDATE import_batches SET status = 'deleted' WHERE batch = $1", &[&batch])?;
+    println!("[delete-batch] ✅ lignes supprimées:");
+    removed.print();
+    if crate::rawimports::has_table(&mut conn)? {
+        let audit = conn.execute("DELETE FROM raw_imports WHERE batch_id = $1", &[&batch])?;
+        println!("  {:<20} {audit:>10}", "raw_imports");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tables_in_foreign_key_order() {
+        let mut total = Removed::default();
+        total.add(&Removed { answer_options: 4, answers: 3, contribution_topics: 0, contributions: 1 });
+        total.add(&Removed { answer_options: 1, answers: 2, contribution_topics: 2, contributions: 1 });
+        assert_eq!(
+            total.tables(),
+            [("answer_options", 5), ("answers", 5), ("contribution_topics", 2), ("contributions", 2)]
+        );
+    }
+}
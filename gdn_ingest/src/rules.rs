@@ -0,0 +1,347 @@
+// ---------- Règles de qualité par question ----------
+//
+// Contrôles légers déclarés dans le mapping, sur la cellule de source_column
+// après normalisation (cf. normalize.rs):
+//
+//   rules:
+//     required: true              # cellule non vide
+//     pattern: "^\\d{5}$"         # valeur conforme (Regex::is_match: ancrer le motif)
+//     max_length: 2000            # en caractères
+//     allowed_values: [Oui, Non]  # valeur exacte (après trim) parmi la liste
+//
+// Seul `required` porte sur une cellule vide ou une colonne absente; les
+// autres règles ne contrôlent que les valeurs présentes. Question
+// conditionnelle (if_column) non applicable à la ligne: pas de contrôle.
+//
+// Les infractions sont comptées par question et par règle, rapport en fin
+// d'ingestion (et en --dry-run, qui contrôle les mêmes règles sans base).
+// --rules décide du sort de la ligne fautive:
+//   warn       → ligne écrite, infraction comptée (première de chaque règle affichée)
+//   error      → arrêt de l'ingestion à la première infraction
+//   quarantine → ligne recopiée dans rejects/ (cf. rejects.rs) et ignorée
+
+use regex::Regex;
+use serde::Deserialize;
+use std::collections::HashMap;
+
+use crate::input::ColumnAccessor;
+use crate::normalize::NormalizeCaches;
+use crate::{col_value, stats, QuestionMap};
+
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub struct Rules {
+    #[serde(default)]
+    pub required: bool,
+    #[serde(default)]
+    pub pattern: Option<String>,
+    #[serde(default)]
+    pub max_length: Option<usize>,
+    #[serde(default)]
+    pub allowed_values: Option<Vec<String>>,
+}
+
+impl Rules {
+    /// Déclarations invalides (préfixées par la position de la question)
+    pub fn problems(&self, qm: &QuestionMap) -> Vec<String> {
+        let mut problems = Vec::new();
+        if qm.source_column.is_none() {
+            problems.push("rules: nécessite source_column (contrôle d'une cellule unique)".to_string());
+        }
+        if let Some(pattern) = &self.pattern {
+            if let Err(e) = Regex::new(pattern) {
+                problems.push(format!("rules: pattern '{pattern}' invalide ({e})"));
+            }
+        }
+        if self.max_length == Some(0) {
+            problems.push("rules: max_length doit être > 0".to_string());
+        }
+        if self.allowed_values.as_ref().is_some_and(Vec::is_empty) {
+            problems.push("rules: allowed_values vide".to_string());
+        }
+        problems
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum RuleKind {
+    Required,
+    Pattern,
+    MaxLength,
+    AllowedValues,
+}
+
+impl RuleKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            RuleKind::Required => "required",
+            RuleKind::Pattern => "pattern",
+            RuleKind::MaxLength => "max_length",
+            RuleKind::AllowedValues => "allowed_values",
+        }
+    }
+}
+
+/// `--rules`: sort d'une ligne qui enfreint une règle
+#[derive(Clone, Copy, Debug, Default, PartialEq, clap::ValueEnum)]
+pub enum RulesMode {
+    /// ligne écrite, infraction comptée
+    #[default]
+    Warn,
+    /// ingestion arrêtée à la première infraction
+    Error,
+    /// ligne recopiée dans rejects/ et ignorée
+    Quarantine,
+}
+
+impl RulesMode {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            RulesMode::Warn => "warn",
+            RulesMode::Error => "error",
+            RulesMode::Quarantine => "quarantine",
+        }
+    }
+
+    fn fate(self) -> &'static str {
+        match self {
+            RulesMode::Warn => "lignes écrites",
+            RulesMode::Error => "arrêt à la première",
+            RulesMode::Quarantine => "lignes écartées dans rejects/",
+        }
+    }
+}
+
+/// Règles d'une question, motif compilé
+struct Compiled<'q> {
+    qm: &'q QuestionMap,
+    required: bool,
+    pattern: Option<Regex>,
+    max_length: Option<usize>,
+    allowed_values: Option<&'q [String]>,
+}
+
+impl Compiled<'_> {
+    fn kinds(&self) -> impl Iterator<Item = RuleKind> + '_ {
+        [
+            (self.required, RuleKind::Required),
+            (self.pattern.is_some(), RuleKind::Pattern),
+            (self.max_length.is_some(), RuleKind::MaxLength),
+            (self.allowed_values.is_some(), RuleKind::AllowedValues),
+        ]
+        .into_iter()
+        .filter_map(|(on, kind)| on.then_some(kind))
+    }
+}
+
+/// Infraction constatée sur une ligne
+#[derive(Debug, PartialEq)]
+pub struct Violation<'q> {
+    pub question: &'q str,
+    pub rule: RuleKind,
+    pub value: String,
+}
+
+impl Violation<'_> {
+    pub fn describe(&self) -> String {
+        match self.rule {
+            RuleKind::Required => format!("question '{}': required, cellule vide", self.question),
+            rule => {
+                let mut value: String = self.value.chars().take(60).collect();
+                if value.len() < self.value.len() {
+                    value.push('…');
+                }
+                format!("question '{}': {} non respecté '{value}'", self.question, rule.as_str())
+            }
+        }
+    }
+}
+
+/// Règles de toutes les questions qui en déclarent, ordre du mapping
+pub struct RuleSet<'q> {
+    questions: Vec<Compiled<'q>>,
+}
+
+impl<'q> RuleSet<'q> {
+    /// Questions sans `rules` ignorées; `validate_mapping` a déjà vérifié les déclarations
+    pub fn build(questions: impl IntoIterator<Item = &'q QuestionMap>) -> Result<Self, String> {
+        let mut compiled = Vec::new();
+        for qm in questions {
+            let Some(rules) = &qm.rules else {
+                continue;
+            };
+            let pattern = rules
+                .pattern
+                .as_deref()
+                .map(Regex::new)
+                .transpose()
+                .map_err(|e| format!("question '{}': rules.pattern: {e}", qm.code))?;
+            compiled.push(Compiled {
+                qm,
+                required: rules.required,
+                pattern,
+                max_length: rules.max_length,
+                allowed_values: rules.allowed_values.as_deref(),
+            });
+        }
+        Ok(RuleSet { questions: compiled })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.questions.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.questions.len()
+    }
+
+    /// Infractions de la ligne, dans l'ordre du mapping
+    pub fn check(&self, row: &dyn ColumnAccessor, normalize: &NormalizeCaches) -> Vec<Violation<'q>> {
+        let mut violations = Vec::new();
+        for c in &self.questions {
+            if !normalize.applies(c.qm, row) {
+                continue;
+            }
+            let violation = |rule, value: &str| Violation { question: c.qm.code.as_str(), rule, value: value.to_string() };
+            let Some(value) = normalize.value(&c.qm.code, col_value(row, c.qm.source_column.as_deref())) else {
+                if c.required {
+                    violations.push(violation(RuleKind::Required, ""));
+                }
+                continue;
+            };
+            let value = value.trim();
+            if c.pattern.as_ref().is_some_and(|re| !re.is_match(value)) {
+                violations.push(violation(RuleKind::Pattern, value));
+            }
+            if c.max_length.is_some_and(|max| value.chars().count() > max) {
+                violations.push(violation(RuleKind::MaxLength, value));
+            }
+            if c.allowed_values.is_some_and(|allowed| !allowed.iter().any(|a| a.trim() == value)) {
+                violations.push(violation(RuleKind::AllowedValues, value));
+            }
+        }
+        violations
+    }
+}
+
+/// Lignes contrôlées et infractions par (question, règle)
+#[derive(Debug, Default)]
+pub struct Report<'q> {
+    pub rows: usize,
+    pub failed: usize,
+    counts: HashMap<(&'q str, RuleKind), usize>,
+}
+
+impl<'q> Report<'q> {
+    /// Infractions d'une ligne; renvoie celles vues pour la première fois
+    pub fn note<'v>(&mut self, violations: &'v [Violation<'q>]) -> Vec<&'v Violation<'q>> {
+        self.rows += 1;
+        self.failed += !violations.is_empty() as usize;
+        violations
+            .iter()
+            .filter(|v| {
+                let n = self.counts.entry((v.question, v.rule)).or_default();
+                *n += 1;
+                *n == 1
+            })
+            .collect()
+    }
+
+    pub fn merge(&mut self, other: Report<'q>) {
+        self.rows += other.rows;
+        self.failed += other.failed;
+        for (k, n) in other.counts {
+            *self.counts.entry(k).or_default() += n;
+        }
+    }
+
+    fn rate(&self, n: usize) -> String {
+        let rate = if self.rows == 0 { 0.0 } else { 100.0 * n as f64 / self.rows as f64 };
+        format!("{rate:.1} %")
+    }
+
+    /// Résumé et tableau: chaque règle déclarée, même sans infraction
+    pub fn render(&self, set: &RuleSet, mode: RulesMode) -> String {
+        let rows: Vec<Vec<String>> = set
+            .questions
+            .iter()
+            .flat_map(|c| c.kinds().map(move |kind| (c.qm.code.as_str(), kind)))
+            .map(|(code, kind)| {
+                let n = self.counts.get(&(code, kind)).copied().unwrap_or(0);
+                vec![code.to_string(), kind.as_str().to_string(), n.to_string(), self.rate(n)]
+            })
+            .collect();
+        format!(
+            "[rules] {} lignes contrôlées, {} en infraction ({}; {}, --rules {})\n{}",
+            self.rows,
+            self.failed,
+            self.rate(self.failed),
+            mode.fate(),
+            mode.as_str(),
+            stats::render_table(&["question", "règle", "infractions", "taux"], &rows)
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::CsvRow;
+    use csv::StringRecord;
+    use std::rc::Rc;
+
+    fn question(yaml: &str) -> QuestionMap {
+        serde_yaml::from_str(yaml).unwrap()
+    }
+
+    fn row(zip: &str, avis: &str) -> CsvRow {
+        let headers = Rc::new(StringRecord::from(vec!["cp", "avis"]));
+        CsvRow::new(headers, StringRecord::from(vec![zip, avis]))
+    }
+
+    #[test]
+    fn violations_counted_per_question_and_rule() {
+        let zip = question(
+            "{ code: zip, prompt: CP, type: text, source_column: cp, rules: { required: true, pattern: '^\\d{5}$' } }",
+        );
+        let avis = question(
+            "{ code: avis, prompt: Avis, type: single_choice, source_column: avis, \
+               rules: { max_length: 3, allowed_values: [Oui, Non] } }",
+        );
+        let plain = question("{ code: libre, prompt: L, type: text, source_column: avis }");
+        let questions = [&zip, &avis, &plain];
+        let set = RuleSet::build(questions).unwrap();
+        assert_eq!(set.len(), 2);
+        let normalize = NormalizeCaches::build(questions, &[]).unwrap();
+
+        let mut report = Report::default();
+        assert!(set.check(&row("75001", " Oui "), &normalize).is_empty());
+        let v = set.check(&row("", "Peut-être"), &normalize);
+        let rules: Vec<_> = v.iter().map(|v| (v.question, v.rule)).collect();
+        assert_eq!(
+            rules,
+            [("zip", RuleKind::Required), ("avis", RuleKind::MaxLength), ("avis", RuleKind::AllowedValues)]
+        );
+        assert_eq!(v[0].describe(), "question 'zip': required, cellule vide");
+        assert_eq!(report.note(&v).len(), 3);
+        let v = set.check(&row("7500", "Non"), &normalize);
+        assert_eq!(v[0].describe(), "question 'zip': pattern non respecté '7500'");
+        let again = set.check(&row("", ""), &normalize);
+        assert!(report.note(&v).len() == 1 && report.note(&again).is_empty());
+        report.note(&[]);
+
+        let out = report.render(&set, RulesMode::Quarantine);
+        assert!(out.starts_with("[rules] 4 lignes contrôlées, 3 en infraction (75.0 %; lignes écartées dans rejects/"), "{out}");
+        assert!(out.contains("| zip      | required       | 2           | 50.0 % |"), "{out}");
+        assert!(out.contains("| zip      | pattern        | 1           | 25.0 % |"), "{out}");
+        assert!(out.contains("| avis     | allowed_values | 1           | 25.0 % |"), "{out}");
+    }
+
+    #[test]
+    fn declarations_are_checked() {
+        let qm = question("{ code: q, prompt: Q, type: free_text, rules: { pattern: '(', max_length: 0, allowed_values: [] } }");
+        let problems = qm.rules.as_ref().unwrap().problems(&qm);
+        assert_eq!(problems.len(), 4, "{problems:?}");
+        assert!(serde_yaml::from_str::<QuestionMap>("{ code: q, prompt: Q, type: text, rules: { requird: true } }").is_err());
+    }
+}
@@ -0,0 +1,279 @@
+// ---------- Verrou d'ingestion et reprise après un run planté ----------
+//
+// Une seule ingestion à la fois par formulaire. Au démarrage, une ligne
+// `import_batches` en status `running` est posée, sous pg_advisory_xact_lock
+// pour que deux démarrages simultanés ne passent pas tous les deux (l'index
+// unique partiel sur form_id WHERE status = 'running' le garantit aussi).
+// Son heartbeat_at avance à chaque commit; en fin de run: `success`, ou
+// `error` avec le message (tronqué), et les décomptes committés (lignes lues,
+// lignes sans contribution écrite, valeurs en erreur). La ligne garde aussi
+// le fichier de mapping; les contributions écrites portent le nom du batch
+// (contributions.import_batch_id).
+//
+// Une ligne `running` dont le heartbeat date de plus de --stale-lock-secs
+// est celle d'un run planté (kill, OOM, machine perdue):
+//   - avec --steal-stale-lock, elle passe `abandoned` et le run démarre;
+//   - sinon, ses détails sont affichés et le programme sort avec EXIT_LOCKED,
+//     comme face à un run encore vivant. Un orchestrateur distingue ainsi
+//     "réessayer plus tard" d'un échec.
+//
+// `provenance` (jsonb) consigne les modes qui changent ce que les analystes
+// trouvent en base (ex: `dictionary_texts`).
+
+use anyhow::Result;
+use postgres::{Client, GenericClient};
+use std::fmt;
+
+use crate::counters::Counters;
+
+/// Code de sortie quand le formulaire est verrouillé (EX_TEMPFAIL)
+pub const EXIT_LOCKED: i32 = 75;
+
+/// Défaut de --stale-lock-secs: 15 min sans commit
+pub const DEFAULT_STALE_SECS: u64 = 900;
+
+/// Clé pg_advisory_xact_lock, combinée au form_id
+const ADVISORY_KEY: i32 = 0x6764_6e31; // "gdn1"
+
+const ERROR_MAX_CHARS: usize = 2048;
+
+/// Ligne `running` trouvée au démarrage
+#[derive(Debug)]
+pub struct Holder {
+    pub id: i64,
+    pub batch: String,
+    pub host: Option<String>,
+    pub pid: Option<i32>,
+    pub started_at: String,
+    /// secondes depuis le dernier heartbeat
+    pub idle_secs: f64,
+}
+
+impl fmt::Display for Holder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "batch '{}' (import_batches id={}), pid {} sur {}, démarré le {}, dernier commit il y a {:.0} s",
+            self.batch,
+            self.id,
+            self.pid.map_or("?".to_string(), |p| p.to_string()),
+            self.host.as_deref().unwrap_or("?"),
+            self.started_at,
+            self.idle_secs
+        )
+    }
+}
+
+/// Refus de démarrer; `main` le convertit en code de sortie EXIT_LOCKED
+#[derive(Debug)]
+pub struct Locked {
+    holder: Holder,
+    stale_after: u64,
+}
+
+impl fmt::Display for Locked {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.holder.idle_secs > self.stale_after as f64 {
+            write!(
+                f,
+                "ingestion probablement plantée (aucun commit depuis plus de {} s): {}; \
+                 relancer avec --steal-stale-lock pour la marquer abandoned et reprendre",
+                self.stale_after, self.holder
+            )
+        } else {
+            write!(f, "une ingestion est déjà en cours sur ce formulaire: {}", self.holder)
+        }
+    }
+}
+
+impl std::error::Error for Locked {}
+
+#[derive(Debug, PartialEq)]
+enum Verdict {
+    Free,
+    /// run planté, repris (--steal-stale-lock)
+    Steal,
+    Refuse,
+}
+
+fn judge(holder: Option<&Holder>, stale_after: u64, steal: bool) -> Verdict {
+    match holder {
+        None => Verdict::Free,
+        Some(h) if steal && h.idle_secs > stale_after as f64 => Verdict::Steal,
+        Some(_) => Verdict::Refuse,
+    }
+}
+
+/// Ligne `import_batches` du run en cours
+pub struct RunLock {
+    id: i64,
+}
+
+impl RunLock {
+    pub fn acquire(
+        conn: &mut Client,
+        form_id: i64,
+        batch: &str,
+        mapping_path: &str,
+        stale_after: u64,
+        steal: bool,
+    ) -> Result<RunLock> {
+        let mut tx = conn.transaction()?;
+        tx.execute("SELECT pg_advisory_xact_lock($1, $2)", &[&ADVISORY_KEY, &(form_id as i32)])?;
+        let holder = tx
+            .query_opt(
+                "SELECT id, batch, host, pid, to_char(started_at, 'YYYY-MM-DD HH24:MI:SS'),
+                        EXTRACT(EPOCH FROM now() - heartbeat_at)::float8
+                 FROM import_batches WHERE form_id = $1 AND status = 'running'",
+                &[&form_id],
+            )?
+            .map(|r| Holder {
+                id: r.get(0),
+                batch: r.get(1),
+                host: r.get(2),
+                pid: r.get(3),
+                started_at: r.get(4),
+                idle_secs: r.get(5),
+            });
+        match judge(holder.as_ref(), stale_after, steal) {
+            Verdict::Free => {}
+            Verdict::Steal => {
+                let h = holder.expect("Steal suppose une ligne running");
+                tx.execute(
+                    "UPDATE import_batches SET status = 'abandoned', finished_at = now(), error = $2 WHERE id = $1",
+                    &[&h.id, &format!("repris par le batch '{batch}' (--steal-stale-lock)")],
+                )?;
+                println!("[verrou] ⚠️  run planté marqué abandoned: {h}");
+            }
+            Verdict::Refuse => {
+                return Err(Locked { holder: holder.expect("Refuse suppose une ligne running"), stale_after }.into());
+            }
+        }
+        let host = std::env::var("HOSTNAME").ok();
+        let pid = std::process::id() as i32;
+        let id: i64 = tx
+            .query_one(
+                "INSERT INTO import_batches (batch, form_id, status, started_at, heartbeat_at, host, pid, mapping_path)
+                 VALUES ($1, $2, 'running', now(), now(), $3, $4, $5) RETURNING id",
+                &[&batch, &form_id, &host, &pid, &mapping_path],
+            )?
+            .get(0);
+        tx.commit()?;
+        println!("[verrou] import_batches id={id}: running (périmé après {stale_after} s sans commit)");
+        Ok(RunLock { id })
+    }
+
+    /// Consigne un réglage du run dans import_batches.provenance
+    pub fn provenance(&self, conn: &mut Client, key: &str, value: serde_json::Value) -> Result<()> {
+        conn.execute(
+            "UPDATE import_batches SET provenance = provenance || jsonb_build_object($2::text, $3::text::jsonb) WHERE id = $1",
+            &[&self.id, &key, &value.to_string()],
+        )?;
+        Ok(())
+    }
+
+    /// Réglage consigné par le batch précédent du formulaire: `None` s'il n'y
+    /// en a pas, `Some(None)` s'il ne l'a pas consigné
+    pub fn previous_provenance(&self, conn: &mut Client, key: &str) -> Result<Option<Option<String>>> {
+        let row = conn.query_opt(
+            "SELECT provenance ->> $2 FROM import_batches
+             WHERE form_id = (SELECT form_id FROM import_batches WHERE id = $1) AND id < $1
+             ORDER BY id DESC LIMIT 1",
+            &[&self.id, &key],
+        )?;
+        Ok(row.map(|r| r.get(0)))
+    }
+
+    /// Dans la transaction, juste avant son COMMIT
+    pub fn heartbeat(&self, tx: &mut impl GenericClient) -> Result<()> {
+        tx.execute("UPDATE import_batches SET heartbeat_at = now() WHERE id = $1", &[&self.id])?;
+        Ok(())
+    }
+
+    /// Fin du run: `success` ou `error` et décomptes committés, verrou libéré
+    pub fn finish<T>(&self, conn: &mut Client, outcome: &Result<T>, totals: &Counters) -> Result<()> {
+        let (status, error) = match outcome {
+            Ok(_) => ("success", None),
+            Err(e) => ("error", Some(format!("{e:#}").chars().take(ERROR_MAX_CHARS).collect::<String>())),
+        };
+        let counts = [totals.rows_read, totals.rows_skipped(), totals.errors()].map(|n| n as i64);
+        conn.execute(
+            "UPDATE import_batches SET status = $2, finished_at = now(), heartbeat_at = now(), error = $3,
+                 rows_total = $4, rows_skipped = $5, errors = $6
+             WHERE id = $1",
+            &[&self.id, &status, &error, &counts[0], &counts[1], &counts[2]],
+        )?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{dbinit, pool};
+
+    // ligne laissée par un run planté il y a une heure
+    fn crashed_run() -> Holder {
+        Holder {
+            id: 7,
+            batch: "nuit".into(),
+            host: Some("worker-2".into()),
+            pid: Some(4242),
+            started_at: "2024-03-01 02:00:00".into(),
+            idle_secs: 3600.0,
+        }
+    }
+
+    #[test]
+    fn stale_run_is_only_taken_over_on_request() {
+        let crashed = crashed_run();
+        assert_eq!(judge(None, DEFAULT_STALE_SECS, false), Verdict::Free);
+        assert_eq!(judge(Some(&crashed), DEFAULT_STALE_SECS, false), Verdict::Refuse);
+        assert_eq!(judge(Some(&crashed), DEFAULT_STALE_SECS, true), Verdict::Steal);
+        // fenêtre plus large que le silence: le run est supposé vivant
+        assert_eq!(judge(Some(&crashed), 7200, true), Verdict::Refuse);
+
+        let msg = Locked { holder: crashed_run(), stale_after: DEFAULT_STALE_SECS }.to_string();
+        assert!(msg.contains("--steal-stale-lock"), "{msg}");
+        assert!(msg.contains("batch 'nuit' (import_batches id=7), pid 4242 sur worker-2"), "{msg}");
+        let alive = Holder { idle_secs: 12.0, ..crashed_run() };
+        let msg = Locked { holder: alive, stale_after: DEFAULT_STALE_SECS }.to_string();
+        assert!(msg.starts_with("une ingestion est déjà en cours"), "{msg}");
+    }
+
+    #[test]
+    #[ignore = "nécessite TEST_DATABASE_URL"]
+    fn stale_running_row_is_stolen_only_with_steal() {
+        let schema = "gdn_test_runlock";
+        let mut conn = pool::test_conn(Some(schema));
+        dbinit::init(&mut conn, schema, false, false).unwrap();
+        conn.batch_execute("INSERT INTO forms (id, name) VALUES (1, 'f')").unwrap();
+        let crashed: i64 = conn
+            .query_one(
+                "INSERT INTO import_batches (batch, form_id, status, started_at, heartbeat_at, host, pid)
+                 VALUES ('nuit', 1, 'running', now() - interval '2 hours', now() - interval '1 hour', 'worker-2', 4242)
+                 RETURNING id",
+                &[],
+            )
+            .unwrap()
+            .get(0);
+
+        let err = RunLock::acquire(&mut conn, 1, "matin", "m.yml", DEFAULT_STALE_SECS, false).err().unwrap();
+        let locked = err.downcast_ref::<Locked>().expect("Locked");
+        assert_eq!((locked.holder.id, locked.holder.batch.as_str()), (crashed, "nuit"));
+        assert!(err.to_string().contains("--steal-stale-lock"), "{err}");
+
+        let lock = RunLock::acquire(&mut conn, 1, "matin", "m.yml", DEFAULT_STALE_SECS, true).unwrap();
+        assert_ne!(lock.id, crashed);
+        let statuses: Vec<(String, String)> = conn
+            .query("SELECT batch, status FROM import_batches ORDER BY started_at", &[])
+            .unwrap()
+            .iter()
+            .map(|r| (r.get(0), r.get(1)))
+            .collect();
+        let expected = [("nuit", "abandoned"), ("matin", "running")].map(|(b, s)| (b.to_string(), s.to_string()));
+        assert_eq!(statuses, expected);
+        assert_eq!(lock.previous_provenance(&mut conn, "mapping_sha256").unwrap(), Some(None));
+        conn.batch_execute(&format!("DROP SCHEMA {schema} CASCADE")).unwrap();
+    }
+}
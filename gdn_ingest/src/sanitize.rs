@@ -0,0 +1,129 @@
+// ---------- Assainissement des en-têtes et des cellules CSV ----------
+//
+// Règles partagées par raw_json et par tous les CSV écrits par l'outil
+// (rapports d'erreurs, échantillons QA, exports).
+
+use std::borrow::Cow;
+use std::collections::HashSet;
+
+/// Caractères qu'Excel/LibreOffice interprètent comme début de formule.
+const FORMULA_PREFIXES: [char; 4] = ['=', '+', '-', '@'];
+
+/// Nettoie un nom de colonne pour en faire une clé raw_json sûre:
+/// suppression des guillemets et caractères de contrôle, virgules
+/// remplacées par des espaces, préfixes de formule retirés, espaces
+/// compactés. Renvoie `None` si la clé est déjà propre.
+pub fn sanitize_header_key(h: &str) -> Option<String> {
+    let cleaned: String = h
+        .chars()
+        .filter(|c| !c.is_control() && !matches!(c, '"' | '\'' | '`'))
+        .map(|c| if c == ',' { ' ' } else { c })
+        .collect();
+    let cleaned = cleaned.trim_start_matches(|c: char| FORMULA_PREFIXES.contains(&c) || c.is_whitespace());
+    let cleaned = cleaned.split_whitespace().collect::<Vec<_>>().join(" ");
+    if cleaned == h { None } else { Some(cleaned) }
+}
+
+/// Clés raw_json assainies pour un en-tête complet, plus la table
+/// `clé → en-tête d'origine` des colonnes modifiées (vide si aucune).
+/// Les clés vides deviennent `col_<index>` et les doublons sont suffixés.
+pub fn sanitize_headers<'a, I>(headers: I) -> (Vec<String>, Vec<(String, String)>)
+where
+    I: IntoIterator<Item = &'a str>,
+{
+    let mut keys = Vec::new();
+    let mut originals = Vec::new();
+    let mut seen = HashSet::new();
+    for (i, h) in headers.into_iter().enumerate() {
+        let mut key = sanitize_header_key(h).unwrap_or_else(|| h.to_string());
+        if key.is_empty() {
+            key = format!("col_{}", i);
+        }
+        if seen.contains(&key) {
+            let base = key.clone();
+            let mut n = 2;
+            while seen.contains(&key) {
+                key = format!("{}_{}", base, n);
+                n += 1;
+            }
+        }
+        if key != h {
+            originals.push((key.clone(), h.to_string()));
+        }
+        seen.insert(key.clone());
+        keys.push(key);
+    }
+    (keys, originals)
+}
+
+/// Échappe une cellule contre l'injection de formules CSV:
+/// préfixe `'` si elle commence par `=`, `+`, `-` ou `@`.
+pub fn csv_safe_cell(v: &str) -> Cow<'_, str> {
+    if v.starts_with(FORMULA_PREFIXES) {
+        Cow::Owned(format!("'{}", v))
+    } else {
+        Cow::Borrowed(v)
+    }
+}
+
+/// Écrit un enregistrement avec `csv_safe_cell` appliqué à chaque cellule.
+/// À utiliser par chaque rédacteur de rapport CSV.
+pub fn write_safe_record<W, I, S>(w: &mut csv::Writer<W>, record: I) -> csv::Result<()>
+where
+    W: std::io::Write,
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    let cells: Vec<String> = record
+        .into_iter()
+        .map(|s| csv_safe_cell(s.as_ref()).into_owned())
+        .collect();
+    w.write_record(&cells)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clean_header_is_untouched() {
+        assert_eq!(sanitize_header_key("reference"), None);
+        assert_eq!(sanitize_header_key("Quel est votre avis ?"), None);
+    }
+
+    #[test]
+    fn header_quotes_commas_and_formulas_are_stripped() {
+        assert_eq!(sanitize_header_key("\"code\""), Some("code".into()));
+        assert_eq!(sanitize_header_key("ville, département"), Some("ville département".into()));
+        assert_eq!(sanitize_header_key("=HYPERLINK(1)"), Some("HYPERLINK(1)".into()));
+        assert_eq!(sanitize_header_key("  @cmd\t"), Some("cmd".into()));
+    }
+
+    #[test]
+    fn headers_get_unique_non_empty_keys() {
+        let (keys, originals) = sanitize_headers(["a", "\"a\"", "=", "b"]);
+        assert_eq!(keys, vec!["a", "a_2", "col_2", "b"]);
+        assert_eq!(
+            originals,
+            vec![("a_2".to_string(), "\"a\"".to_string()), ("col_2".to_string(), "=".to_string())]
+        );
+    }
+
+    #[test]
+    fn csv_cells_are_escaped() {
+        assert_eq!(csv_safe_cell("=SUM(A1)"), "'=SUM(A1)");
+        assert_eq!(csv_safe_cell("+33 6"), "'+33 6");
+        assert_eq!(csv_safe_cell("-1"), "'-1");
+        assert_eq!(csv_safe_cell("@x"), "'@x");
+        assert_eq!(csv_safe_cell("texte"), "texte");
+        assert_eq!(csv_safe_cell(""), "");
+    }
+
+    #[test]
+    fn safe_record_writer_escapes_every_cell() {
+        let mut w = csv::Writer::from_writer(vec![]);
+        write_safe_record(&mut w, ["=1", "ok", "a,b"]).unwrap();
+        let out = String::from_utf8(w.into_inner().unwrap()).unwrap();
+        assert_eq!(out, "'=1,ok,\"a,b\"\n");
+    }
+}
@@ -0,0 +1,155 @@
+// ---------- Réglages de session PostgreSQL (délais, --schema) ----------
+//
+// Chaque connexion ouverte par open_conn (ingestion, pool, options
+// dynamiques, dictionnaire, sous-commandes) reçoit, juste après la
+// connexion:
+//   - statement_timeout (--db-statement-timeout-ms, défaut 30 s): une requête
+//     plus longue est annulée (57014) au lieu de bloquer le run, par exemple
+//     un COUNT(*) de options sur une base chargée;
+//   - lock_timeout (--db-lock-timeout-ms, défaut: aucun): attente d'un verrou
+//     de table ou de ligne plafonnée (55P03);
+//   - search_path (--schema, défaut public): plusieurs jeux de données
+//     indépendants dans une même base, un schéma chacun. Les requêtes gardent
+//     des noms de tables nus, résolus dans le schéma choisi; public reste en
+//     second pour les extensions (unaccent, pg_trgm) qui y sont installées.
+//     check_tables vérifie que toutes les tables écrites par le run (reprise,
+//     empreintes de fichiers, raw_imports, et text_values avec
+//     --dictionary-texts) sont bien dans le schéma lui-même: sinon celles de
+//     public seraient lues et écrites en silence (reprise, fichiers ou lignes
+//     sautés d'après un autre jeu).
+// 0 = pas de délai. Réglages de session, posés hors transaction: ils valent
+// pour toutes les transactions de la connexion (un SET dans une transaction
+// annulée serait annulé avec elle).
+
+use anyhow::Result;
+use once_cell::sync::OnceCell;
+use postgres::error::SqlState;
+use postgres::GenericClient;
+
+/// Défaut de --db-statement-timeout-ms
+pub const DEFAULT_STATEMENT_TIMEOUT_MS: u64 = 30_000;
+/// Défaut de --schema
+pub const DEFAULT_SCHEMA: &str = "public";
+
+/// Tables écrites par toute ingestion, à trouver dans --schema
+const INGEST_TABLES: [&str; 11] = [
+    "forms",
+    "questions",
+    "options",
+    "authors",
+    "contributions",
+    "answers",
+    "answer_options",
+    "import_batches",
+    "ingest_checkpoints",
+    "source_files",
+    "raw_imports",
+];
+
+#[derive(Debug, Clone)]
+pub struct SessionOpts {
+    pub statement_timeout_ms: u64,
+    pub lock_timeout_ms: u64,
+    pub schema: String,
+}
+
+impl Default for SessionOpts {
+    fn default() -> Self {
+        SessionOpts {
+            statement_timeout_ms: DEFAULT_STATEMENT_TIMEOUT_MS,
+            lock_timeout_ms: 0,
+            schema: DEFAULT_SCHEMA.to_string(),
+        }
+    }
+}
+
+static OPTS: OnceCell<SessionOpts> = OnceCell::new();
+
+/// Réglages de la ligne de commande, posés une fois au démarrage
+pub fn configure(opts: SessionOpts) {
+    let _ = OPTS.set(opts);
+}
+
+pub fn opts() -> SessionOpts {
+    OPTS.get().cloned().unwrap_or_default()
+}
+
+/// search_path du schéma (identifiant cité côté serveur, cf. quote_ident)
+fn search_path_sql(schema: &str) -> &'static str {
+    if schema == DEFAULT_SCHEMA {
+        "quote_ident($3)"
+    } else {
+        "quote_ident($3) || ', public'"
+    }
+}
+
+pub fn configure_session(client: &mut impl GenericClient, opts: &SessionOpts) -> Result<()> {
+    client.execute(
+        &format!(
+            "SELECT set_config('statement_timeout', $1, false), set_config('lock_timeout', $2, false),
+                    set_config('search_path', {}, false)",
+            search_path_sql(&opts.schema)
+        ),
+        &[&opts.statement_timeout_ms.to_string(), &opts.lock_timeout_ms.to_string(), &opts.schema],
+    )?;
+    Ok(())
+}
+
+/// --schema existe et contient les tables de l'ingestion, plus `extra`
+/// (tables des options du run, ex: text_values)
+pub fn check_tables(client: &mut impl GenericClient, schema: &str, extra: &[&str]) -> Result<()> {
+    let exists: bool = client
+        .query_one("SELECT EXISTS (SELECT 1 FROM information_schema.schemata WHERE schema_name = $1)", &[&schema])?
+        .get(0);
+    if !exists {
+        anyhow::bail!("schéma '{schema}' absent (--schema): CREATE SCHEMA puis migrations avec ce search_path, ou gdn_ingest db-init");
+    }
+    let tables: Vec<String> = INGEST_TABLES.iter().chain(extra).map(|t| t.to_string()).collect();
+    let missing: Vec<String> = client
+        .query(
+            "SELECT t FROM unnest($2::text[]) AS t
+             WHERE NOT EXISTS (SELECT 1 FROM information_schema.tables WHERE table_schema = $1 AND table_name = t)",
+            &[&schema, &tables],
+        )?
+        .iter()
+        .map(|r| r.get(0))
+        .collect();
+    if !missing.is_empty() {
+        anyhow::bail!(
+            "schéma '{schema}': tables absentes ({}) — appliquer les migrations dans ce schéma (alembic upgrade head, ou gdn_ingest db-init)",
+            missing.join(", ")
+        );
+    }
+    Ok(())
+}
+
+/// Option à ajuster si l'erreur vient d'un des délais
+pub fn timeout_hint(e: &anyhow::Error) -> Option<&'static str> {
+    let code = e.chain().filter_map(|c| c.downcast_ref::<postgres::Error>()).find_map(|e| e.code())?;
+    if *code == SqlState::QUERY_CANCELED {
+        Some("requête annulée après --db-statement-timeout-ms (0: sans limite)")
+    } else if *code == SqlState::LOCK_NOT_AVAILABLE {
+        Some("verrou non obtenu dans --db-lock-timeout-ms (0: sans limite)")
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pool;
+
+    #[test]
+    #[ignore = "nécessite TEST_DATABASE_URL"]
+    fn statement_timeout_cancels_with_57014() {
+        let mut conn = pool::test_conn(None);
+        let opts = SessionOpts { statement_timeout_ms: 1, ..Default::default() };
+        configure_session(&mut conn, &opts).unwrap();
+        let mut tx = conn.transaction().unwrap();
+        let err = tx.execute("SELECT pg_sleep(0.2)", &[]).unwrap_err();
+        assert_eq!(err.code(), Some(&SqlState::QUERY_CANCELED), "{err}");
+        let hint = timeout_hint(&anyhow::Error::from(err)).unwrap();
+        assert!(hint.contains("--db-statement-timeout-ms"), "{hint}");
+    }
+}
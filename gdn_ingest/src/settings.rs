@@ -0,0 +1,158 @@
+// ---------- Réglages d'exécution: CLI > mapping > défaut ----------
+//
+// Section optionnelle `ingest:` du mapping, pour que le fichier YAML relu en
+// revue fixe aussi la façon d'ingérer le jeu de données:
+//
+//   ingest:
+//     commit_every: 5000
+//     log_every: 1000
+//     delimiter: ";"                # ou auto: deviné par fichier
+//     encoding: windows-1252        # défaut: détection automatique
+//     strict_numbers: true
+//     allow_unknown_types: false
+//     parallel: 4
+//     preload_budget_mb: 512
+//     normalize_option_codes: true
+//     anomalies:                    # cf. anomalies.rs
+//       dominant_share: 0.95
+//       range_blowup: 100
+//     errors:                       # cf. policy.rs
+//       bad_date: { action: warn, max: 100 }
+//       unmatched_option: { action: drop }
+//
+// Un flag passé en ligne de commande l'emporte toujours. Les valeurs
+// effectives sont affichées au démarrage avec leur provenance.
+
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fmt::Display;
+
+use crate::policy;
+
+#[derive(Deserialize, Debug, Default, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct IngestDefaults {
+    pub commit_every: Option<usize>,
+    pub log_every: Option<usize>,
+    pub delimiter: Option<crate::input::DelimiterChoice>,
+    pub encoding: Option<String>,
+    pub strict_numbers: Option<bool>,
+    pub allow_unknown_types: Option<bool>,
+    pub parallel: Option<usize>,
+    pub preload_budget_mb: Option<usize>,
+    pub normalize_option_codes: Option<bool>,
+    /// false: raw_json non stocké (cf. --no-raw)
+    pub store_raw: Option<bool>,
+    pub anomalies: Option<crate::anomalies::Thresholds>,
+    #[serde(default)]
+    pub errors: BTreeMap<policy::Category, policy::Rule>,
+}
+
+impl IngestDefaults {
+    /// Bornes des valeurs (erreurs au format de `validate_mapping`)
+    pub fn problems(&self) -> Vec<String> {
+        let mut out = Vec::new();
+        for (name, v) in [
+            ("commit_every", self.commit_every),
+            ("log_every", self.log_every),
+            ("parallel", self.parallel),
+        ] {
+            if v == Some(0) {
+                out.push(format!("ingest.{name}: doit être ≥ 1"));
+            }
+        }
+        if let Some(label) = &self.encoding {
+            if let Err(e) = crate::encoding::parse_label(label) {
+                out.push(format!("ingest.encoding: {e}"));
+            }
+        }
+        if let Some(a) = &self.anomalies {
+            out.extend(a.problems());
+        }
+        let mut builder = policy::ErrorPolicy::builder();
+        for (&category, &rule) in &self.errors {
+            if let Err(e) = builder.set(category, rule, "ingest.errors") {
+                out.push(e);
+            }
+        }
+        out
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Source {
+    Cli,
+    Mapping,
+    Default,
+}
+
+impl Source {
+    fn as_str(self) -> &'static str {
+        match self {
+            Source::Cli => "ligne de commande",
+            Source::Mapping => "mapping",
+            Source::Default => "défaut",
+        }
+    }
+}
+
+/// Valeur effective: CLI, sinon mapping, sinon défaut
+pub fn pick<T>(cli: Option<T>, mapping: Option<T>, default: T) -> (T, Source) {
+    match (cli, mapping) {
+        (Some(v), _) => (v, Source::Cli),
+        (None, Some(v)) => (v, Source::Mapping),
+        (None, None) => (default, Source::Default),
+    }
+}
+
+/// Flag booléen: présent en CLI = activé; absent, le mapping décide
+pub fn pick_flag(cli: bool, mapping: Option<bool>) -> (bool, Source) {
+    pick(cli.then_some(true), mapping, false)
+}
+
+/// Journal des réglages effectifs
+#[derive(Default)]
+pub struct Effective(Vec<(&'static str, String, Source)>);
+
+impl Effective {
+    pub fn note<T: Display>(&mut self, name: &'static str, (value, source): (T, Source)) -> T {
+        self.0.push((name, value.to_string(), source));
+        value
+    }
+
+    pub fn log(&self) {
+        let width = self.0.iter().map(|(n, _, _)| n.len()).max().unwrap_or(0);
+        println!("[réglages]");
+        for (name, value, source) in &self.0 {
+            let value = if value == "\t" { "\\t" } else { value.as_str() };
+            println!("  {name:<width$} = {value} ({})", source.as_str());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cli_wins_then_mapping_then_default() {
+        assert_eq!(pick(Some(10), Some(20), 30), (10, Source::Cli));
+        assert_eq!(pick(None, Some(20), 30), (20, Source::Mapping));
+        assert_eq!(pick(None, None, 30), (30, Source::Default));
+        assert_eq!(pick_flag(true, Some(false)), (true, Source::Cli));
+        assert_eq!(pick_flag(false, Some(true)), (true, Source::Mapping));
+        assert_eq!(pick_flag(false, None), (false, Source::Default));
+    }
+
+    #[test]
+    fn ranges_are_checked() {
+        let d: IngestDefaults = serde_yaml::from_str("{commit_every: 0, parallel: 2, delimiter: ';'}").unwrap();
+        assert_eq!(d.problems(), vec!["ingest.commit_every: doit être ≥ 1".to_string()]);
+        assert!(serde_yaml::from_str::<IngestDefaults>("{commit_evry: 5}").is_err());
+        assert!(serde_yaml::from_str::<IngestDefaults>("{delimiter: '§'}").is_err());
+        let d: IngestDefaults = serde_yaml::from_str("{delimiter: auto}").unwrap();
+        assert_eq!(d.delimiter, Some(crate::input::DelimiterChoice::Auto));
+        let d: IngestDefaults = serde_yaml::from_str("{errors: {bad_date: {action: warn, max: 10}, bad_boolean: {action: warn}}}").unwrap();
+        assert_eq!(d.problems(), vec!["ingest.errors: action 'warn' impossible pour bad_boolean (drop, abort)".to_string()]);
+    }
+}
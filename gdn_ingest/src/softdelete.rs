@@ -0,0 +1,135 @@
+// ---------- soft-delete: contributions retirées après l'export ----------
+//
+// Une contribution retirée sur la plateforme (après l'export, ou marquée à la
+// corbeille dans un export suivant) n'est pas supprimée: contributions.deleted_at
+// = now() (et delete_reason si donné), réponses conservées.
+//   - `gdn_ingest soft-delete --form F [--version V] --source-contribution-id a,b
+//     --reason …`: par référence dans ce formulaire (une même référence peut
+//     exister dans un autre); une référence déjà supprimée garde sa date et
+//     son motif, une référence inconnue est signalée;
+//   - ingestion: une ligne à la corbeille (trashed/trashedStatus) marque la
+//     contribution de même référence du formulaire (motif `trashed`), au lieu
+//     d'être seulement sautée.
+// Un réimport ne remet pas deleted_at à NULL (l'UPSERT ne touche pas la colonne).
+//
+// export --exclude-deleted les écarte; stats les écarte par défaut
+// (--include-deleted pour les compter).
+
+use anyhow::Result;
+use postgres::GenericClient;
+
+use crate::forms;
+
+/// $1 formulaire, $2 référence: contribution à la corbeille dans l'export
+pub const MARK_TRASHED: &str = "UPDATE contributions SET deleted_at = now(), delete_reason = 'trashed'
+     WHERE form_id = $1 AND source_contribution_id = $2 AND deleted_at IS NULL";
+
+#[derive(Debug, Default, PartialEq)]
+pub struct Outcome {
+    /// contributions marquées par cet appel
+    pub deleted: u64,
+    /// références déjà supprimées (date et motif conservés)
+    pub already: Vec<String>,
+    /// références absentes de la base
+    pub unknown: Vec<String>,
+}
+
+pub fn soft_delete(client: &mut impl GenericClient, form_id: i64, ids: &[String], reason: Option<&str>) -> Result<Outcome> {
+    let mut tx = client.transaction()?;
+    let mut outcome = Outcome::default();
+    // contributions du formulaire par référence, dont déjà supprimées
+    for row in tx.query(
+        "SELECT i.id, COUNT(c.id), COUNT(c.deleted_at) FROM unnest($2::text[]) AS i(id)
+         LEFT JOIN contributions c ON c.form_id = $1 AND c.source_contribution_id = i.id
+         GROUP BY i.id ORDER BY i.id",
+        &[&form_id, &ids],
+    )? {
+        let (id, found, deleted): (String, i64, i64) = (row.get(0), row.get(1), row.get(2));
+        if found == 0 {
+            outcome.unknown.push(id);
+        } else if deleted == found {
+            outcome.already.push(id);
+        }
+    }
+    outcome.deleted = tx.execute(
+        "UPDATE contributions SET deleted_at = now(), delete_reason = $3
+         WHERE form_id = $1 AND source_contribution_id = ANY($2) AND deleted_at IS NULL",
+        &[&form_id, &ids, &reason],
+    )?;
+    tx.commit()?;
+    Ok(outcome)
+}
+
+pub fn run_soft_delete(form: &str, version: Option<&str>, ids: &[String], reason: Option<&str>) -> Result<()> {
+    let ids: Vec<String> = ids.iter().map(|s| s.trim().to_string()).filter(|s| !s.is_empty()).collect();
+    if ids.is_empty() {
+        anyhow::bail!("--source-contribution-id vide");
+    }
+    let mut conn = crate::open_conn()?;
+    require_columns(&mut conn)?;
+    let form_id = forms::find_form_id(&mut conn, form, version)?;
+    let outcome = soft_delete(&mut conn, form_id, &ids, reason)?;
+    println!("[soft-delete] ✅ {} contributions marquées supprimées", outcome.deleted);
+    if !outcome.already.is_empty() {
+        println!("[soft-delete] déjà supprimées (inchangées): {}", outcome.already.join(", "));
+    }
+    if !outcome.unknown.is_empty() {
+        println!("[soft-delete] ⚠️  références inconnues: {}", outcome.unknown.join(", "));
+    }
+    Ok(())
+}
+
+/// contributions.deleted_at présente (base migrée)
+pub fn has_columns(client: &mut impl GenericClient) -> Result<bool> {
+    Ok(client
+        .query_one(
+            "SELECT EXISTS (SELECT 1 FROM information_schema.columns
+                            WHERE table_schema = current_schema() AND table_name = 'contributions' AND column_name = 'deleted_at')",
+            &[],
+        )?
+        .get(0))
+}
+
+pub fn require_columns(client: &mut impl GenericClient) -> Result<()> {
+    if !has_columns(client)? {
+        anyhow::bail!("colonne contributions.deleted_at absente (alembic upgrade head, ou gdn_ingest db-init --if-not-exists)");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{dbinit, pool};
+
+    #[test]
+    #[ignore = "nécessite TEST_DATABASE_URL"]
+    fn soft_delete_keeps_first_date_and_reports_unknown() {
+        let schema = "gdn_test_softdelete";
+        let mut conn = pool::test_conn(Some(schema));
+        dbinit::init(&mut conn, schema, false, false).unwrap();
+        // même références dans un autre formulaire (f2): jamais touchées
+        conn.batch_execute(
+            "INSERT INTO forms (id, name) VALUES (1, 'f'), (2, 'f2');
+             INSERT INTO contributions (source_contribution_id, form_id) SELECT r, id FROM forms, unnest('{r1,r2}'::text[]) AS r;",
+        )
+        .unwrap();
+        let form_id = forms::find_form_id(&mut conn, "f", None).unwrap();
+
+        let ids = |v: &[&str]| v.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        let first = soft_delete(&mut conn, form_id, &ids(&["r1"]), Some("retrait")).unwrap();
+        assert_eq!(first, Outcome { deleted: 1, ..Default::default() });
+        let second = soft_delete(&mut conn, form_id, &ids(&["r1", "r2", "r9"]), None).unwrap();
+        assert_eq!(second, Outcome { deleted: 1, already: ids(&["r1"]), unknown: ids(&["r9"]) });
+        let other: i64 = conn.query_one("SELECT COUNT(deleted_at) FROM contributions WHERE form_id = 2", &[]).unwrap().get(0);
+        assert_eq!(other, 0);
+        let reasons: Vec<Option<String>> = conn
+            .query("SELECT delete_reason FROM contributions WHERE deleted_at IS NOT NULL ORDER BY source_contribution_id", &[])
+            .unwrap()
+            .iter()
+            .map(|r| r.get(0))
+            .collect();
+        assert_eq!(reasons, [Some("retrait".to_string()), None]);
+        conn.batch_execute(&format!("DROP SCHEMA {schema} CASCADE")).unwrap();
+    }
+}
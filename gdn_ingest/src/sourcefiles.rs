@@ -0,0 +1,159 @@
+// ---------- Fichiers déjà ingérés (empreinte SHA-256) ----------
+//
+// Chaque fichier ingéré jusqu'au bout laisse une ligne `source_files`: nom
+// (sans le dossier), chemin, empreintes du contenu entier et de ses 64 Kio
+// premiers octets (cf. input::Checksum, sur les octets décompressés, prises
+// pendant la lecture), taille sur disque, enregistrements lus, batch et date
+// de fin. Écrite dans la dernière transaction du fichier: une ligne présente
+// = fichier entièrement committé.
+//
+// --skip-ingested (alias --incremental): avant lecture, seule l'empreinte
+// des 64 Kio premiers octets est calculée. Aucune ingestion de même début
+// (ou d'avant head_checksum) pour le formulaire: fichier nouveau, lu une
+// seule fois. Sinon l'empreinte entière confirme (une passe, au lieu de
+// l'ingestion) et un contenu déjà ingéré est sauté, quel que soit son nom
+// (`[skip] already ingested`). --force-reimport annule --skip-ingested, pour
+// les orchestrateurs qui passent toujours --incremental.
+//
+// Un fichier qui porte le nom d'un fichier déjà ingéré mais dont le contenu
+// diffère est signalé en gros (republication corrigée en amont?), au début
+// quand l'empreinte entière a été calculée avant lecture, en fin de fichier
+// sinon, et rappelé en fin de run.
+
+use anyhow::Result;
+use postgres::GenericClient;
+use std::path::Path;
+
+use crate::input::Checksum;
+
+/// Ingestion précédente d'un fichier
+#[derive(Debug)]
+pub struct Previous {
+    pub checksum: String,
+    pub batch: String,
+    pub rows: i64,
+    pub completed_at: String,
+}
+
+impl Previous {
+    fn from_row(r: &postgres::Row) -> Self {
+        Previous { checksum: r.get(0), batch: r.get(1), rows: r.get(2), completed_at: r.get(3) }
+    }
+}
+
+const COLUMNS: &str = "checksum, batch, rows, to_char(completed_at, 'YYYY-MM-DD HH24:MI:SS')";
+
+/// Nom sans le dossier: les dépôts mensuels changent de dossier, pas de nom
+pub fn file_name(path: &str) -> &str {
+    Path::new(path).file_name().and_then(|n| n.to_str()).unwrap_or(path)
+}
+
+pub fn check_schema(conn: &mut impl GenericClient) -> Result<()> {
+    let ok: bool = conn
+        .query_one(
+            "SELECT EXISTS (SELECT 1 FROM information_schema.columns
+                            WHERE table_schema = current_schema() AND table_name = 'source_files'
+                              AND column_name = 'head_checksum')",
+            &[],
+        )?
+        .get(0);
+    if !ok {
+        anyhow::bail!(
+            "table source_files absente ou sans head_checksum — appliquer les migrations \
+             (alembic upgrade head, ou gdn_ingest db-init --if-not-exists)"
+        );
+    }
+    Ok(())
+}
+
+/// Ingestion de même début (64 Kio), ou sans head_checksum (avant la
+/// colonne): l'empreinte entière est à comparer
+pub fn may_be_ingested(conn: &mut impl GenericClient, form_id: i64, head_checksum: &str) -> Result<bool> {
+    Ok(conn
+        .query_one(
+            "SELECT EXISTS (SELECT 1 FROM source_files
+                            WHERE form_id = $1 AND (head_checksum = $2 OR head_checksum IS NULL))",
+            &[&form_id, &head_checksum],
+        )?
+        .get(0))
+}
+
+/// Dernière ingestion complète de ce contenu pour le formulaire
+pub fn ingested(conn: &mut impl GenericClient, form_id: i64, checksum: &str) -> Result<Option<Previous>> {
+    let row = conn.query_opt(
+        &format!(
+            "SELECT {COLUMNS} FROM source_files WHERE form_id = $1 AND checksum = $2 ORDER BY completed_at DESC LIMIT 1"
+        ),
+        &[&form_id, &checksum],
+    )?;
+    Ok(row.as_ref().map(Previous::from_row))
+}
+
+/// Ingestions d'un fichier de même nom mais de contenu différent
+pub fn republished(conn: &mut impl GenericClient, form_id: i64, path: &str, checksum: &str) -> Result<Vec<Previous>> {
+    let rows = conn.query(
+        &format!(
+            "SELECT {COLUMNS} FROM source_files WHERE form_id = $1 AND file_name = $2 AND checksum <> $3
+             ORDER BY completed_at DESC"
+        ),
+        &[&form_id, &file_name(path), &checksum],
+    )?;
+    Ok(rows.iter().map(Previous::from_row).collect())
+}
+
+/// Avertissement bien visible, une ligne par ingestion précédente
+pub fn republished_warning(path: &str, checksum: &str, previous: &[Previous]) -> String {
+    let mut msg = format!(
+        "🚨🚨 {path}: CONTENU MODIFIÉ — même nom qu'un fichier déjà ingéré, empreinte différente \
+         (republication corrigée en amont?)\n     maintenant: sha256 {}",
+        &checksum[..12.min(checksum.len())]
+    );
+    for p in previous {
+        msg.push_str(&format!(
+            "\n     déjà ingéré: sha256 {}, {} lignes, batch '{}', le {}",
+            &p.checksum[..12.min(p.checksum.len())],
+            p.rows,
+            p.batch,
+            p.completed_at
+        ));
+    }
+    msg
+}
+
+/// Dans la dernière transaction du fichier
+pub fn record(
+    tx: &mut impl GenericClient,
+    form_id: i64,
+    batch: &str,
+    path: &str,
+    checksum: &Checksum,
+    rows: u64,
+) -> Result<()> {
+    let size = std::fs::metadata(path).map(|m| m.len() as i64).ok();
+    tx.execute(
+        "INSERT INTO source_files (form_id, file_name, path, checksum, head_checksum, size_bytes, rows, batch, completed_at)
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, now())",
+        &[&form_id, &file_name(path), &path, &checksum.hex(), &checksum.head_hex(), &size, &(rows as i64), &batch],
+    )?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn republished_files_are_named_with_their_history() {
+        assert_eq!(file_name("drops/2024-03/export.csv.gz"), "export.csv.gz");
+        let previous = [Previous {
+            checksum: "0123456789abcdef".into(),
+            batch: "mars".into(),
+            rows: 1200,
+            completed_at: "2024-03-02 04:00:00".into(),
+        }];
+        let msg = republished_warning("drops/2024-04/export.csv", "fedcba9876543210", &previous);
+        assert!(msg.starts_with("🚨🚨 drops/2024-04/export.csv: CONTENU MODIFIÉ"), "{msg}");
+        assert!(msg.contains("maintenant: sha256 fedcba987654"), "{msg}");
+        assert!(msg.contains("déjà ingéré: sha256 0123456789ab, 1200 lignes, batch 'mars', le 2024-03-02 04:00:00"), "{msg}");
+    }
+}
@@ -0,0 +1,273 @@
+// ---------- Sous-commande stats ----------
+//
+// Statistiques de réponse par formulaire et par question, calculées en SQL,
+// rendues en tableau ASCII ou en JSON (`--json`). Les contributions
+// supprimées (deleted_at, cf. softdelete.rs) sont écartées et seulement
+// comptées, sauf --include-deleted. --batch se limite aux contributions
+// écrites en dernier par un batch d'import (contributions.import_batch_id,
+// comme export --batch; run et décomptes: import_batches, cf. runlock.rs).
+
+use anyhow::Result;
+use serde_json::json;
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use crate::{forms, load_mapping, open_conn, softdelete};
+
+pub struct StatsArgs {
+    pub form: Option<String>,
+    pub version: Option<String>,
+    pub mapping: Option<PathBuf>,
+    pub top: i64,
+    pub json: bool,
+    pub include_deleted: bool,
+    pub batch: Option<String>,
+}
+
+struct QuestionStats {
+    code: String,
+    qtype: String,
+    answered: i64,
+    skipped: i64,
+    unique: i64,
+    mean: Option<f64>,
+    stddev: Option<f64>,
+    top: Vec<(String, i64)>,
+}
+
+pub fn run_stats(args: StatsArgs) -> Result<()> {
+    // --mapping: nom/version repris de la section `form` du YAML
+    let (name, version) = match (&args.form, &args.mapping) {
+        (Some(f), _) => (f.clone(), args.version.clone()),
+        (None, Some(path)) => {
+            let mapping = load_mapping(path)?;
+            (mapping.form.name, args.version.clone().or(mapping.form.version))
+        }
+        (None, None) => anyhow::bail!("--form ou --mapping requis"),
+    };
+
+    let mut conn = open_conn()?;
+    let form_id = forms::find_form_id(&mut conn, &name, version.as_deref())?;
+
+    // contributions retenues (base sans deleted_at: toutes)
+    let live = if !args.include_deleted && softdelete::has_columns(&mut conn)? { "c.deleted_at IS NULL" } else { "true" };
+    // $2 de chaque requête: --batch
+    let in_batch = "($2::text IS NULL OR c.import_batch_id = $2)";
+    let batch = args.batch.as_deref();
+    if let Some(b) = batch {
+        let known: bool = conn
+            .query_one("SELECT EXISTS (SELECT 1 FROM import_batches WHERE form_id = $1 AND batch = $2)", &[&form_id, &b])?
+            .get(0);
+        if !known {
+            anyhow::bail!("batch '{b}' inconnu pour le formulaire '{name}' (import_batches)");
+        }
+    }
+    let counts = conn.query_one(
+        &format!(
+            "SELECT COUNT(*) FILTER (WHERE {live}), COUNT(*) FILTER (WHERE NOT {live})
+             FROM contributions c WHERE form_id = $1 AND {in_batch}"
+        ),
+        &[&form_id, &batch],
+    )?;
+    let (contributions, deleted_excluded): (i64, i64) = (counts.get(0), counts.get(1));
+
+    let rows = conn.query(
+        &format!(
+            "SELECT q.id, q.question_code, q.type,
+                COUNT(a.id) FILTER (WHERE NOT a.skipped),
+                COUNT(a.id) FILTER (WHERE a.skipped),
+                COUNT(DISTINCT COALESCE(a.\"text\", tv.value)),
+                AVG(a.value_num)::float8,
+                STDDEV_POP(a.value_num)::float8
+         FROM questions q
+         LEFT JOIN (answers a JOIN contributions c ON c.id = a.contribution_id AND {live} AND {in_batch})
+                ON a.question_id = q.id
+         LEFT JOIN text_values tv ON tv.id = a.text_value_id
+         WHERE q.form_id = $1
+         GROUP BY q.id
+         ORDER BY q.position NULLS LAST, q.id"
+        ),
+        &[&form_id, &batch],
+    )?;
+
+    // questions à choix: valeurs distinctes et top-N des options
+    let unique_options: HashMap<i64, i64> = conn
+        .query(
+            &format!(
+                "SELECT a.question_id, COUNT(DISTINCT ao.option_id)
+                 FROM answers a
+                 JOIN contributions c ON c.id = a.contribution_id AND {live} AND {in_batch}
+                 JOIN answer_options ao ON ao.answer_id = a.id
+                 JOIN questions q ON q.id = a.question_id
+                 WHERE q.form_id = $1
+                 GROUP BY a.question_id"
+            ),
+            &[&form_id, &batch],
+        )?
+        .iter()
+        .map(|r| (r.get(0), r.get(1)))
+        .collect();
+    let mut top: HashMap<i64, Vec<(String, i64)>> = HashMap::new();
+    for r in conn.query(
+        &format!(
+            "SELECT question_id, label, n FROM (
+                 SELECT o.question_id, o.label, COUNT(*) AS n,
+                        ROW_NUMBER() OVER (PARTITION BY o.question_id ORDER BY COUNT(*) DESC, o.label) AS rk
+                 FROM answer_options ao
+                 JOIN answers a ON a.id = ao.answer_id
+                 JOIN contributions c ON c.id = a.contribution_id AND {live} AND {in_batch}
+                 JOIN options o ON o.id = ao.option_id
+                 JOIN questions q ON q.id = o.question_id
+                 WHERE q.form_id = $1
+                 GROUP BY o.question_id, o.id, o.label
+             ) t
+             WHERE rk <= $3
+             ORDER BY question_id, rk"
+        ),
+        &[&form_id, &batch, &args.top],
+    )? {
+        top.entry(r.get(0)).or_default().push((r.get(1), r.get(2)));
+    }
+
+    // boolean: value_num 1/0 → la moyenne est la part de "oui"
+    let numeric = |t: &str| matches!(t, "number" | "scale" | "boolean");
+    let stats: Vec<QuestionStats> = rows
+        .iter()
+        .map(|r| {
+            let qid: i64 = r.get(0);
+            let qtype: String = r.get(2);
+            let is_num = numeric(&qtype);
+            QuestionStats {
+                code: r.get(1),
+                answered: r.get(3),
+                skipped: r.get(4),
+                unique: unique_options.get(&qid).copied().unwrap_or_else(|| r.get(5)),
+                mean: if is_num { r.get(6) } else { None },
+                stddev: if is_num { r.get(7) } else { None },
+                top: top.remove(&qid).unwrap_or_default(),
+                qtype,
+            }
+        })
+        .collect();
+
+    let rate = |n: i64| if contributions > 0 { n as f64 / contributions as f64 } else { 0.0 };
+
+    if args.json {
+        let questions: Vec<serde_json::Value> = stats
+            .iter()
+            .map(|s| {
+                json!({
+                    "code": s.code,
+                    "type": s.qtype,
+                    "answers": s.answered,
+                    "skipped": s.skipped,
+                    "response_rate": rate(s.answered),
+                    "unique_values": s.unique,
+                    "mean": s.mean,
+                    "stddev": s.stddev,
+                    "top": s.top.iter().map(|(l, n)| json!({"label": l, "count": n})).collect::<Vec<_>>(),
+                })
+            })
+            .collect();
+        let out = json!({
+            "form": { "id": form_id, "name": name, "version": version },
+            "batch": batch,
+            "contributions": contributions,
+            "deleted_excluded": deleted_excluded,
+            "questions": questions,
+        });
+        println!("{}", serde_json::to_string_pretty(&out)?);
+        return Ok(());
+    }
+
+    let excluded = if deleted_excluded > 0 { format!(" ({deleted_excluded} supprimées exclues)") } else { String::new() };
+    let scope = batch.map(|b| format!(", batch '{b}'")).unwrap_or_default();
+    println!("Formulaire '{}' (id={}{}) — {} contributions{}", name, form_id, scope, contributions, excluded);
+    let fmt_opt = |v: Option<f64>| v.map(|x| format!("{:.2}", x)).unwrap_or_default();
+    let table: Vec<Vec<String>> = stats
+        .iter()
+        .map(|s| {
+            vec![
+                s.code.clone(),
+                s.qtype.clone(),
+                s.answered.to_string(),
+                format!("{:.1} %", 100.0 * rate(s.answered)),
+                s.skipped.to_string(),
+                s.unique.to_string(),
+                fmt_opt(s.mean),
+                fmt_opt(s.stddev),
+            ]
+        })
+        .collect();
+    print!(
+        "{}",
+        render_table(
+            &["question", "type", "réponses", "taux", "passées", "uniques", "moyenne", "écart-type"],
+            &table
+        )
+    );
+
+    for s in stats.iter().filter(|s| !s.top.is_empty()) {
+        println!("\n{} — top {}", s.code, s.top.len());
+        let rows: Vec<Vec<String>> = s
+            .top
+            .iter()
+            .map(|(label, n)| vec![label.clone(), n.to_string(), format!("{:.1} %", 100.0 * rate(*n))])
+            .collect();
+        print!("{}", render_table(&["option", "n", "% contrib."], &rows));
+    }
+    Ok(())
+}
+
+/// Tableau ASCII minimal (largeurs en caractères, pas en octets)
+pub fn render_table(headers: &[&str], rows: &[Vec<String>]) -> String {
+    let mut widths: Vec<usize> = headers.iter().map(|h| h.chars().count()).collect();
+    for row in rows {
+        for (i, cell) in row.iter().enumerate() {
+            if i < widths.len() {
+                widths[i] = widths[i].max(cell.chars().count());
+            }
+        }
+    }
+    let sep: String = widths.iter().map(|w| format!("+{}", "-".repeat(w + 2))).collect::<String>() + "+\n";
+    let line = |cells: Vec<&str>| -> String {
+        let mut out = String::new();
+        for (i, w) in widths.iter().enumerate() {
+            let cell = cells.get(i).copied().unwrap_or("");
+            let pad = w - cell.chars().count();
+            out.push_str(&format!("| {}{} ", cell, " ".repeat(pad)));
+        }
+        out + "|\n"
+    };
+
+    let mut out = sep.clone();
+    out.push_str(&line(headers.to_vec()));
+    out.push_str(&sep);
+    for row in rows {
+        out.push_str(&line(row.iter().map(String::as_str).collect()));
+    }
+    out.push_str(&sep);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn table_is_aligned_on_characters() {
+        let out = render_table(
+            &["question", "taux"],
+            &[vec!["q1".into(), "50.0 %".into()], vec!["écologie".into(), "5 %".into()]],
+        );
+        assert_eq!(
+            out,
+            "+----------+--------+\n\
+             | question | taux   |\n\
+             +----------+--------+\n\
+             | q1       | 50.0 % |\n\
+             | écologie | 5 %    |\n\
+             +----------+--------+\n"
+        );
+    }
+}
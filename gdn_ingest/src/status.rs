@@ -0,0 +1,180 @@
+// ---------- Compteurs de progression + endpoint HTTP de suivi ----------
+//
+// `Progress` est partagé entre la boucle d'ingestion (logs console) et le
+// petit serveur HTTP en lecture seule activé par `--status-port`:
+//   GET /         → document JSON des compteurs
+//   GET /healthz  → 200 tant que l'ingestion tourne
+
+use anyhow::Result;
+use serde_json::json;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+pub struct Progress {
+    started: Instant,
+    current_file: Mutex<String>,
+    rows: AtomicU64,
+    errors: AtomicU64,
+    last_commit: AtomicU64, // secondes UNIX, 0 = aucun commit
+    queue_depth: AtomicU64,
+}
+
+impl Progress {
+    pub fn new(files: usize) -> Self {
+        Progress {
+            started: Instant::now(),
+            current_file: Mutex::new(String::new()),
+            rows: AtomicU64::new(0),
+            errors: AtomicU64::new(0),
+            last_commit: AtomicU64::new(0),
+            queue_depth: AtomicU64::new(files as u64),
+        }
+    }
+
+    /// Début d'un fichier: il quitte la file d'attente
+    pub fn start_file(&self, path: &str) {
+        *self.current_file.lock().unwrap() = path.to_string();
+        let _ = self.queue_depth.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |q| q.checked_sub(1));
+    }
+
+    pub fn add_row(&self) -> u64 {
+        self.rows.fetch_add(1, Ordering::Relaxed) + 1
+    }
+
+    pub fn add_errors(&self, n: usize) {
+        self.errors.fetch_add(n as u64, Ordering::Relaxed);
+    }
+
+    pub fn committed(&self) {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+        self.last_commit.store(now, Ordering::Relaxed);
+    }
+
+    pub fn rows(&self) -> u64 {
+        self.rows.load(Ordering::Relaxed)
+    }
+
+    pub fn snapshot(&self) -> serde_json::Value {
+        let rows = self.rows();
+        let elapsed = self.started.elapsed().as_secs_f64();
+        let last_commit = self.last_commit.load(Ordering::Relaxed);
+        json!({
+            "current_file": *self.current_file.lock().unwrap(),
+            "rows_processed": rows,
+            "rows_per_sec": if elapsed > 0.0 { (rows as f64 / elapsed).round() } else { 0.0 },
+            "errors": self.errors.load(Ordering::Relaxed),
+            "last_commit_unix": if last_commit > 0 { json!(last_commit) } else { json!(null) },
+            "queue_depth": self.queue_depth.load(Ordering::Relaxed),
+            "elapsed_secs": elapsed.round(),
+        })
+    }
+}
+
+pub struct StatusServer {
+    server: Arc<tiny_http::Server>,
+    handle: Option<JoinHandle<()>>,
+    port: u16,
+}
+
+impl StatusServer {
+    pub fn start(bind: &str, port: u16, progress: Arc<Progress>) -> Result<Self> {
+        let server = tiny_http::Server::http((bind, port))
+            .map_err(|e| anyhow::anyhow!("status: écoute sur {}:{} impossible: {}", bind, port, e))?;
+        let server = Arc::new(server);
+        let port = server.server_addr().to_ip().map(|a| a.port()).unwrap_or(port);
+
+        let srv = Arc::clone(&server);
+        let handle = std::thread::spawn(move || {
+            for req in srv.incoming_requests() {
+                let resp = match req.url() {
+                    "/healthz" => tiny_http::Response::from_string("ok"),
+                    "/" | "/status" => tiny_http::Response::from_string(progress.snapshot().to_string())
+                        .with_header(
+                            "Content-Type: application/json"
+                                .parse::<tiny_http::Header>()
+                                .expect("en-tête valide"),
+                        ),
+                    _ => tiny_http::Response::from_string("not found").with_status_code(404),
+                };
+                let _ = req.respond(resp);
+            }
+        });
+        println!("[status] http://{}:{}/ (lecture seule)", bind, port);
+        Ok(StatusServer { server, handle: Some(handle), port })
+    }
+
+    #[cfg(test)]
+    pub fn port(&self) -> u16 {
+        self.port
+    }
+}
+
+/// Arrêt propre en fin d'ingestion (y compris sur erreur)
+impl Drop for StatusServer {
+    fn drop(&mut self) {
+        self.server.unblock();
+        if let Some(h) = self.handle.take() {
+            let _ = h.join();
+        }
+        println!("[status] arrêté (port {})", self.port);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Read, Write};
+    use std::net::TcpStream;
+    use std::time::Duration;
+
+    fn get(port: u16, path: &str) -> std::io::Result<String> {
+        let mut s = TcpStream::connect(("127.0.0.1", port))?;
+        write!(s, "GET {path} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")?;
+        let mut out = String::new();
+        s.read_to_string(&mut out)?;
+        Ok(out)
+    }
+
+    fn body_json(resp: &str) -> serde_json::Value {
+        let body = resp.split("\r\n\r\n").nth(1).unwrap();
+        serde_json::from_str(body).unwrap()
+    }
+
+    #[test]
+    fn status_is_served_during_a_slow_ingest() {
+        let progress = Arc::new(Progress::new(2));
+        let server = StatusServer::start("127.0.0.1", 0, Arc::clone(&progress)).unwrap();
+        let port = server.port();
+
+        // ingestion factice lente
+        let p = Arc::clone(&progress);
+        let ingest = std::thread::spawn(move || {
+            p.start_file("fixture.csv");
+            for i in 0..50 {
+                p.add_row();
+                if i % 10 == 9 {
+                    p.committed();
+                }
+                std::thread::sleep(Duration::from_millis(4));
+            }
+        });
+
+        std::thread::sleep(Duration::from_millis(40));
+        let first = body_json(&get(port, "/").unwrap());
+        assert_eq!(first["current_file"], "fixture.csv");
+        assert_eq!(first["queue_depth"], 1);
+        ingest.join().unwrap();
+        let last = body_json(&get(port, "/").unwrap());
+        assert!(last["rows_processed"].as_u64().unwrap() >= first["rows_processed"].as_u64().unwrap());
+        assert_eq!(last["rows_processed"], 50);
+        assert!(last["last_commit_unix"].is_u64());
+
+        assert!(get(port, "/healthz").unwrap().starts_with("HTTP/1.1 200"));
+        assert!(get(port, "/stop").unwrap().starts_with("HTTP/1.1 404"));
+
+        drop(server);
+        assert!(get(port, "/healthz").is_err());
+    }
+}
@@ -0,0 +1,319 @@
+// ---------- export-subject: droit d'accès RGPD ----------
+//
+// Demande d'accès identifiée par `authors.email_hash` (comme l'effacement,
+// cf. gdpr.rs): fiche de l'auteur et toutes les réponses de ses
+// contributions, reconstruites comme dans l'export (options choisies, texte,
+// text_values, valeurs numériques et dates); réponses `skipped` omises.
+//
+//   --format json: { author: {…}, contributions: [{ form, submitted_at,
+//                    title, source_contribution_id, answers: [{ question, prompt, value }] }] }
+//   --format csv:  une ligne par réponse, colonnes form,question_code,prompt,value
+//
+// Fichier par défaut: subject_export_<8 premiers caractères du hash>.json
+// (.csv en --format csv). --redact-fields name,zipcode,value: champs
+// remplacés par "[masqué]" avant écriture (noms vérifiés contre FIELDS;
+// `question` vaut aussi pour la colonne question_code du CSV).
+
+use anyhow::{Context, Result};
+use clap::ValueEnum;
+use postgres::GenericClient;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+use crate::sanitize::write_safe_record;
+
+#[derive(Clone, Copy, Debug, PartialEq, ValueEnum)]
+pub enum SubjectFormat {
+    /// Auteur et contributions imbriquées
+    Json,
+    /// Une ligne par réponse
+    Csv,
+}
+
+impl SubjectFormat {
+    fn extension(self) -> &'static str {
+        match self {
+            SubjectFormat::Json => "json",
+            SubjectFormat::Csv => "csv",
+        }
+    }
+}
+
+/// Valeur écrite à la place d'un champ masqué
+const REDACTED: &str = "[masqué]";
+
+/// Champs admis par --redact-fields
+const FIELDS: [&str; 16] = [
+    "source_author_id",
+    "name",
+    "email_hash",
+    "zipcode",
+    "city",
+    "age_range",
+    "gender",
+    "department_code",
+    "region_code",
+    "form",
+    "submitted_at",
+    "title",
+    "source_contribution_id",
+    "question",
+    "prompt",
+    "value",
+];
+
+#[derive(Debug, Default, Serialize, PartialEq)]
+struct Author {
+    source_author_id: Option<String>,
+    name: Option<String>,
+    email_hash: Option<String>,
+    zipcode: Option<String>,
+    city: Option<String>,
+    age_range: Option<String>,
+    gender: Option<String>,
+    department_code: Option<String>,
+    region_code: Option<String>,
+}
+
+#[derive(Debug, Default, Serialize, PartialEq)]
+struct Answer {
+    question: Option<String>,
+    prompt: Option<String>,
+    value: Option<String>,
+}
+
+#[derive(Debug, Default, Serialize, PartialEq)]
+struct Contribution {
+    form: Option<String>,
+    submitted_at: Option<String>,
+    title: Option<String>,
+    source_contribution_id: Option<String>,
+    answers: Vec<Answer>,
+}
+
+#[derive(Debug, Default, Serialize, PartialEq)]
+struct SubjectExport {
+    author: Author,
+    contributions: Vec<Contribution>,
+}
+
+impl SubjectExport {
+    fn answers(&self) -> usize {
+        self.contributions.iter().map(|c| c.answers.len()).sum()
+    }
+
+    /// Champs listés remplacés par REDACTED (valeurs absentes comprises)
+    fn redact(&mut self, fields: &[String]) {
+        let hide = |name: &str, value: &mut Option<String>| {
+            if fields.iter().any(|f| f == name) {
+                *value = Some(REDACTED.to_string());
+            }
+        };
+        let a = &mut self.author;
+        for (name, value) in [
+            ("source_author_id", &mut a.source_author_id),
+            ("name", &mut a.name),
+            ("email_hash", &mut a.email_hash),
+            ("zipcode", &mut a.zipcode),
+            ("city", &mut a.city),
+            ("age_range", &mut a.age_range),
+            ("gender", &mut a.gender),
+            ("department_code", &mut a.department_code),
+            ("region_code", &mut a.region_code),
+        ] {
+            hide(name, value);
+        }
+        for c in &mut self.contributions {
+            hide("form", &mut c.form);
+            hide("submitted_at", &mut c.submitted_at);
+            hide("title", &mut c.title);
+            hide("source_contribution_id", &mut c.source_contribution_id);
+            for answer in &mut c.answers {
+                hide("question", &mut answer.question);
+                hide("prompt", &mut answer.prompt);
+                hide("value", &mut answer.value);
+            }
+        }
+    }
+
+    fn write_csv(&self, path: &Path) -> Result<()> {
+        let mut w = csv::Writer::from_path(path).with_context(|| format!("écriture {:?}", path))?;
+        write_safe_record(&mut w, ["form", "question_code", "prompt", "value"])?;
+        for c in &self.contributions {
+            for a in &c.answers {
+                let cells = [&c.form, &a.question, &a.prompt, &a.value].map(|v| v.as_deref().unwrap_or(""));
+                write_safe_record(&mut w, cells)?;
+            }
+        }
+        w.flush()?;
+        Ok(())
+    }
+
+    fn write_json(&self, path: &Path) -> Result<()> {
+        let file = std::fs::File::create(path).with_context(|| format!("écriture {:?}", path))?;
+        serde_json::to_writer_pretty(std::io::BufWriter::new(file), self)?;
+        Ok(())
+    }
+}
+
+/// Fichier écrit sans --output
+fn default_output(email_hash: &str, format: SubjectFormat) -> PathBuf {
+    let prefix: String = email_hash.trim().chars().take(8).collect();
+    PathBuf::from(format!("subject_export_{prefix}.{}", format.extension()))
+}
+
+fn collect(client: &mut impl GenericClient, email_hash: &str) -> Result<Option<SubjectExport>> {
+    let Some(row) = client.query_opt(
+        "SELECT id, source_author_id, name, email_hash, zipcode, city, age_range, gender, department_code, region_code
+         FROM authors WHERE email_hash = $1",
+        &[&email_hash.trim()],
+    )?
+    else {
+        return Ok(None);
+    };
+    let author_id: i64 = row.get(0);
+    let mut export = SubjectExport {
+        author: Author {
+            source_author_id: row.get(1),
+            name: row.get(2),
+            email_hash: row.get(3),
+            zipcode: row.get(4),
+            city: row.get(5),
+            age_range: row.get(6),
+            gender: row.get(7),
+            department_code: row.get(8),
+            region_code: row.get(9),
+        },
+        contributions: Vec::new(),
+    };
+
+    // une ligne par réponse (options d'une réponse agrégées), contributions sans réponse comprises
+    let rows = client.query(
+        "SELECT c.id, f.name, to_char(c.submitted_at, 'YYYY-MM-DD\"T\"HH24:MI:SS'), c.title, c.source_contribution_id,
+                q.question_code, q.prompt,
+                COALESCE(
+                    (SELECT string_agg(o.label, ';' ORDER BY o.position NULLS LAST, o.id)
+                     FROM answer_options ao JOIN options o ON o.id = ao.option_id WHERE ao.answer_id = a.id),
+                    a.\"text\", tv.value, a.value_num::text, to_char(a.value_date, 'YYYY-MM-DD'))
+         FROM contributions c
+         JOIN forms f ON f.id = c.form_id
+         LEFT JOIN answers a ON a.contribution_id = c.id AND NOT a.skipped
+         LEFT JOIN text_values tv ON tv.id = a.text_value_id
+         LEFT JOIN questions q ON q.id = a.question_id
+         WHERE c.author_id = $1
+         ORDER BY c.submitted_at NULLS LAST, c.id, q.position NULLS LAST, q.id, a.position",
+        &[&author_id],
+    )?;
+    let mut current = None;
+    for row in rows {
+        let cid: i64 = row.get(0);
+        if current != Some(cid) {
+            current = Some(cid);
+            export.contributions.push(Contribution {
+                form: row.get(1),
+                submitted_at: row.get(2),
+                title: row.get(3),
+                source_contribution_id: row.get(4),
+                answers: Vec::new(),
+            });
+        }
+        let question: Option<String> = row.get(5);
+        if question.is_some() {
+            let contribution = export.contributions.last_mut().expect("contribution ajoutée ci-dessus");
+            contribution.answers.push(Answer { question, prompt: row.get(6), value: row.get(7) });
+        }
+    }
+    Ok(Some(export))
+}
+
+pub fn run_export_subject(
+    email_hash: &str,
+    output: Option<&Path>,
+    format: SubjectFormat,
+    redact_fields: &[String],
+) -> Result<()> {
+    if email_hash.trim().is_empty() {
+        anyhow::bail!("--email-hash vide");
+    }
+    let redact: Vec<String> = redact_fields.iter().map(|f| f.trim().to_string()).filter(|f| !f.is_empty()).collect();
+    if let Some(unknown) = redact.iter().find(|f| !FIELDS.contains(&f.as_str())) {
+        anyhow::bail!("--redact-fields: champ '{unknown}' inconnu (possibles: {})", FIELDS.join(", "));
+    }
+    let mut conn = crate::open_conn()?;
+    let Some(mut export) = collect(&mut conn, email_hash)? else {
+        anyhow::bail!("aucun auteur avec ce hash: rien à exporter");
+    };
+    export.redact(&redact);
+    let path = output.map(Path::to_path_buf).unwrap_or_else(|| default_output(email_hash, format));
+    match format {
+        SubjectFormat::Json => export.write_json(&path)?,
+        SubjectFormat::Csv => export.write_csv(&path)?,
+    }
+    let masked = if redact.is_empty() { String::new() } else { format!(", champs masqués: {}", redact.join(", ")) };
+    println!(
+        "[export-subject] ✅ {} contributions, {} réponses → {}{masked} (données personnelles: fichier à transmettre puis supprimer)",
+        export.contributions.len(),
+        export.answers(),
+        path.display()
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{dbinit, pool};
+
+    #[test]
+    fn default_output_and_redaction() {
+        assert_eq!(default_output(" 0123456789abcdef ", SubjectFormat::Json), PathBuf::from("subject_export_01234567.json"));
+        assert_eq!(default_output("abc", SubjectFormat::Csv), PathBuf::from("subject_export_abc.csv"));
+
+        let mut export = SubjectExport {
+            author: Author { name: Some("Marie".into()), zipcode: Some("75001".into()), ..Default::default() },
+            contributions: vec![Contribution {
+                form: Some("f".into()),
+                answers: vec![Answer { question: Some("q1".into()), prompt: Some("Q".into()), value: Some("oui".into()) }],
+                ..Default::default()
+            }],
+        };
+        export.redact(&["name".to_string(), "value".to_string(), "city".to_string()]);
+        assert_eq!(export.author.name.as_deref(), Some(REDACTED));
+        assert_eq!(export.author.city.as_deref(), Some(REDACTED));
+        assert_eq!(export.author.zipcode.as_deref(), Some("75001"));
+        let answer = &export.contributions[0].answers[0];
+        assert_eq!((answer.question.as_deref(), answer.value.as_deref()), (Some("q1"), Some(REDACTED)));
+    }
+
+    #[test]
+    #[ignore = "nécessite TEST_DATABASE_URL"]
+    fn answers_are_reconstructed() {
+        let schema = "gdn_test_subject";
+        let mut conn = pool::test_conn(Some(schema));
+        dbinit::init(&mut conn, schema, false, false).unwrap();
+        conn.batch_execute(
+            "INSERT INTO forms (id, name) VALUES (1, 'f');
+             INSERT INTO questions (id, form_id, question_code, prompt, type, position) VALUES
+                 (1, 1, 'avis', 'Votre avis', 'multi_choice', 1), (2, 1, 'age', 'Âge', 'number', 2), (3, 1, 'nsp', 'NSP', 'text', 3);
+             INSERT INTO options (id, question_id, code, label, position) VALUES (1, 1, 'a', 'Pour', 1), (2, 1, 'b', 'Contre', 2);
+             INSERT INTO authors (id, name, email_hash) VALUES (1, 'Marie', 'h1'), (2, 'Paul', 'h2');
+             INSERT INTO contributions (id, form_id, author_id, source_contribution_id) VALUES (1, 1, 1, 'r1'), (2, 1, 2, 'r2');
+             INSERT INTO answers (id, contribution_id, question_id, \"text\", value_num, skipped) VALUES
+                 (1, 1, 1, NULL, NULL, false), (2, 1, 2, NULL, 42, false), (3, 1, 3, NULL, NULL, true), (4, 2, 2, NULL, 7, false);
+             INSERT INTO answer_options (answer_id, option_id) VALUES (1, 2), (1, 1);",
+        )
+        .unwrap();
+
+        assert_eq!(collect(&mut conn, "inconnu").unwrap(), None);
+        let export = collect(&mut conn, "h1").unwrap().unwrap();
+        assert_eq!(export.author.name.as_deref(), Some("Marie"));
+        assert_eq!(export.contributions.len(), 1);
+        let values: Vec<_> = export.contributions[0]
+            .answers
+            .iter()
+            .map(|a| (a.question.as_deref().unwrap(), a.value.as_deref().unwrap()))
+            .collect();
+        assert_eq!(values, [("avis", "Pour;Contre"), ("age", "42")]);
+        conn.batch_execute(&format!("DROP SCHEMA {schema} CASCADE")).unwrap();
+    }
+}
@@ -0,0 +1,185 @@
+// ---------- Connexion PostgreSQL chiffrée (--db-tls) ----------
+//
+// Modes, comme le sslmode de libpq:
+//   - disable: pas de TLS (défaut, comportement historique);
+//   - require: connexion chiffrée, certificat serveur non vérifié;
+//   - verify-ca: certificat signé par une autorité connue (magasin système
+//     ou --db-ca-cert), nom d'hôte non vérifié;
+//   - verify-full: autorité et nom d'hôte vérifiés.
+// TLS via native-tls, qui s'appuie sur OpenSSL sous Linux (postgres-openssl
+// n'est pas disponible dans le miroir de crates du build; native-tls couvre
+// les mêmes modes de vérification). Sans --db-tls, le paramètre `sslmode=`
+// de DATABASE_URL est repris, en URL (`?sslmode=`) comme en forme libpq
+// `host=… sslmode=…`, et retiré dans tous les cas: le client postgres ne
+// connaît pas verify-ca/verify-full. allow et prefer (défaut libpq, repli
+// sans TLS) valent disable.
+// --db-tls et --db-ca-cert valent pour toutes les sous-commandes.
+
+use anyhow::{Context, Result};
+use once_cell::sync::OnceCell;
+use postgres::config::SslMode;
+use postgres::{Client, Config, NoTls};
+use std::path::{Path, PathBuf};
+
+#[derive(Clone, Copy, Debug, PartialEq, clap::ValueEnum)]
+pub enum TlsMode {
+    Disable,
+    Require,
+    VerifyCa,
+    VerifyFull,
+}
+
+impl TlsMode {
+    fn from_sslmode(value: &str) -> Result<Self> {
+        Ok(match value {
+            "disable" | "allow" | "prefer" => TlsMode::Disable,
+            "require" => TlsMode::Require,
+            "verify-ca" => TlsMode::VerifyCa,
+            "verify-full" => TlsMode::VerifyFull,
+            other => anyhow::bail!(
+                "DATABASE_URL: sslmode '{other}' inconnu (disable, allow, prefer, require, verify-ca, verify-full)"
+            ),
+        })
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            TlsMode::Disable => "disable",
+            TlsMode::Require => "require",
+            TlsMode::VerifyCa => "verify-ca",
+            TlsMode::VerifyFull => "verify-full",
+        }
+    }
+}
+
+/// Réglages --db-tls / --db-ca-cert, posés une fois au démarrage
+struct TlsSettings {
+    mode: Option<TlsMode>,
+    ca_cert: Option<PathBuf>,
+}
+
+static SETTINGS: OnceCell<TlsSettings> = OnceCell::new();
+
+pub fn configure(mode: Option<TlsMode>, ca_cert: Option<PathBuf>) {
+    let _ = SETTINGS.set(TlsSettings { mode, ca_cert });
+}
+
+/// Forme libpq `clé=valeur …` (pas une URL)
+pub fn is_key_value(dsn: &str) -> bool {
+    !dsn.contains("://") && dsn.contains('=')
+}
+
+/// Paires `clé=valeur` (sans espace autour de `=`) séparées par des espaces; valeurs entre apostrophes
+/// possibles (`password='a b'`, `\'` et `\\` échappés), gardées telles quelles
+fn key_value_pairs(dsn: &str) -> Vec<&str> {
+    let (mut pairs, mut start, mut quoted, mut escaped) = (Vec::new(), None, false, false);
+    for (i, c) in dsn.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if quoted => escaped = true,
+            '\'' => quoted = !quoted,
+            c if c.is_whitespace() && !quoted => {
+                if let Some(s) = start.take() {
+                    pairs.push(&dsn[s..i]);
+                }
+                continue;
+            }
+            _ => {}
+        }
+        start.get_or_insert(i);
+    }
+    if let Some(s) = start {
+        pairs.push(&dsn[s..]);
+    }
+    pairs
+}
+
+/// URL (ou forme `clé=valeur`) sans `sslmode`, et le mode qu'il demandait
+pub fn split_sslmode(url: &str) -> Result<(String, Option<TlsMode>)> {
+    if is_key_value(url) {
+        let mut mode = None;
+        let mut kept = Vec::new();
+        for pair in key_value_pairs(url) {
+            match pair.split_once('=') {
+                Some((key, value)) if key.trim() == "sslmode" => mode = Some(TlsMode::from_sslmode(value.trim().trim_matches('\''))?),
+                _ => kept.push(pair),
+            }
+        }
+        return Ok((kept.join(" "), mode));
+    }
+    let Some((base, query)) = url.split_once('?') else {
+        return Ok((url.to_string(), None));
+    };
+    let mut mode = None;
+    let mut kept = Vec::new();
+    for param in query.split('&') {
+        match param.strip_prefix("sslmode=") {
+            Some(value) => mode = Some(TlsMode::from_sslmode(value)?),
+            None => kept.push(param),
+        }
+    }
+    let url = if kept.is_empty() { base.to_string() } else { format!("{base}?{}", kept.join("&")) };
+    Ok((url, mode))
+}
+
+/// Mode effectif: --db-tls, sinon sslmode de l'URL, sinon disable
+pub fn resolve(url_mode: Option<TlsMode>) -> TlsMode {
+    SETTINGS.get().and_then(|s| s.mode).or(url_mode).unwrap_or(TlsMode::Disable)
+}
+
+pub fn open_conn_tls(url: &str, mode: TlsMode) -> Result<Client> {
+    let ca_cert = SETTINGS.get().and_then(|s| s.ca_cert.as_deref());
+    connect(url, mode, ca_cert)
+}
+
+fn connect(url: &str, mode: TlsMode, ca_cert: Option<&Path>) -> Result<Client> {
+    let mut config: Config = url.parse().context("DATABASE_URL illisible")?;
+    if mode == TlsMode::Disable {
+        return Ok(config.connect(NoTls)?);
+    }
+    let mut builder = native_tls::TlsConnector::builder();
+    match mode {
+        TlsMode::Require => {
+            builder.danger_accept_invalid_certs(true).danger_accept_invalid_hostnames(true);
+        }
+        TlsMode::VerifyCa => {
+            builder.danger_accept_invalid_hostnames(true);
+        }
+        TlsMode::VerifyFull | TlsMode::Disable => {}
+    }
+    if let Some(path) = ca_cert {
+        let pem = std::fs::read(path).with_context(|| format!("--db-ca-cert {path:?}"))?;
+        builder.add_root_certificate(
+            native_tls::Certificate::from_pem(&pem).with_context(|| format!("--db-ca-cert {path:?}: certificat PEM illisible"))?,
+        );
+    }
+    let connector = postgres_native_tls::MakeTlsConnector::new(builder.build()?);
+    config.ssl_mode(SslMode::Require);
+    config.connect(connector).with_context(|| format!("connexion TLS ({})", mode.as_str()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sslmode_is_taken_out_of_the_url() {
+        let (url, mode) = split_sslmode("postgres://u@db.example/gdn?sslmode=verify-full&application_name=x").unwrap();
+        assert_eq!((url.as_str(), mode), ("postgres://u@db.example/gdn?application_name=x", Some(TlsMode::VerifyFull)));
+        let (url, mode) = split_sslmode("postgres://u@db.example/gdn?sslmode=prefer").unwrap();
+        assert_eq!((url.as_str(), mode), ("postgres://u@db.example/gdn", Some(TlsMode::Disable)));
+        assert_eq!(split_sslmode("postgres://u@h/gdn").unwrap(), ("postgres://u@h/gdn".to_string(), None));
+        assert!(split_sslmode("postgres://u@h/gdn?sslmode=always").is_err());
+    }
+
+    #[test]
+    fn sslmode_is_taken_out_of_a_key_value_dsn() {
+        let (dsn, mode) = split_sslmode("host=db.example dbname=gdn sslmode=verify-full password='a b\\'c'").unwrap();
+        assert_eq!((dsn.as_str(), mode), ("host=db.example dbname=gdn password='a b\\'c'", Some(TlsMode::VerifyFull)));
+        let (dsn, mode) = split_sslmode("  host=h   sslmode='require' ").unwrap();
+        assert_eq!((dsn.as_str(), mode), ("host=h", Some(TlsMode::Require)));
+        assert_eq!(split_sslmode("host=h user=u").unwrap(), ("host=h user=u".to_string(), None));
+        assert!(split_sslmode("host=h sslmode=always").is_err());
+        assert!(!is_key_value("postgres://u@h/gdn?sslmode=require"));
+    }
+}
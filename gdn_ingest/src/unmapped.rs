@@ -0,0 +1,88 @@
+// ---------- Colonnes du fichier qu'aucune question ne lit ----------
+//
+// Pendant de validate::check_headers: les colonnes de l'en-tête que le mapping
+// ne référence nulle part (source_column, colonnes free_text/ranking, colonnes
+// d'options, matrice, if_column, auteur, contribution), pour repérer les
+// questions oubliées. Les colonnes techniques de l'export (IGNORED) et celles
+// de `ignore_columns:` dans le mapping ne sont pas signalées. Chaque colonne
+// est donnée avec son nombre de cellules non vides, pour trier ce qui compte.
+// Affiché par fichier à l'ingestion (fin de fichier) et en --dry-run.
+
+use std::collections::HashSet;
+
+use crate::input::ColumnAccessor;
+use crate::{validate, Mapping};
+
+/// Colonnes techniques des exports de la plateforme
+const IGNORED: [&str; 9] =
+    ["id", "reference", "createdAt", "publishedAt", "updatedAt", "trashed", "trashedStatus", "trashedAt", "authorType"];
+
+pub struct Unmapped {
+    /// (colonne, cellules non vides)
+    columns: Vec<(String, usize)>,
+}
+
+impl Unmapped {
+    pub fn new<'h>(mapping: &Mapping, headers: impl IntoIterator<Item = &'h str>) -> Self {
+        let mapped = validate::mapped_columns(mapping);
+        let mut seen = HashSet::new();
+        let columns = headers
+            .into_iter()
+            .filter(|h| !h.is_empty() && !mapped.contains(h) && !IGNORED.contains(h))
+            .filter(|h| !mapping.ignore_columns.iter().any(|c| c == h))
+            .filter(|h| seen.insert(*h))
+            .map(|h| (h.to_string(), 0))
+            .collect();
+        Unmapped { columns }
+    }
+
+    pub fn count(&mut self, row: &dyn ColumnAccessor) {
+        for (column, n) in &mut self.columns {
+            if row.cell(column).is_some_and(|v| !v.trim().is_empty()) {
+                *n += 1;
+            }
+        }
+    }
+
+    /// Colonnes non lues, les plus remplies d'abord; None s'il n'y en a pas
+    pub fn report(&self, path: &str) -> Option<String> {
+        if self.columns.is_empty() {
+            return None;
+        }
+        let mut columns: Vec<&(String, usize)> = self.columns.iter().collect();
+        columns.sort_by_key(|c| std::cmp::Reverse(c.1));
+        let mut msg = format!(
+            "ℹ️  {path}: {} colonne(s) lue(s) par aucune question (ignore_columns: pour les taire)",
+            columns.len()
+        );
+        for (column, n) in columns {
+            msg.push_str(&format!("\n     - '{column}': {n} valeurs non vides"));
+        }
+        Some(msg)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::CsvRow;
+    use csv::StringRecord;
+    use std::rc::Rc;
+
+    #[test]
+    fn unread_columns_are_counted() {
+        let mapping: Mapping = serde_yaml::from_str(
+            "form: { name: t }\nignore_columns: [notes]\ndefaults: { author: { zipcode: cp } }\nquestions:\n  - { code: q1, prompt: Q, type: text, source_column: Q1 }\n",
+        )
+        .unwrap();
+        let headers = StringRecord::from(vec!["reference", "cp", "Q1", "Q2", "Q3", "notes"]);
+        let mut unmapped = Unmapped::new(&mapping, headers.iter());
+        for cells in [["r1", "75001", "a", "b", "", "x"], ["r2", "", "a", "c", "d", "y"]] {
+            unmapped.count(&CsvRow::new(Rc::new(headers.clone()), StringRecord::from(cells.to_vec())));
+        }
+        let report = unmapped.report("f.csv").unwrap();
+        assert!(report.starts_with("ℹ️  f.csv: 2 colonne(s)"), "{report}");
+        assert!(report.ends_with("- 'Q2': 2 valeurs non vides\n     - 'Q3': 1 valeurs non vides"), "{report}");
+        assert!(Unmapped::new(&mapping, ["reference", "Q1"]).report("f.csv").is_none());
+    }
+}
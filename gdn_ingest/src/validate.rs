@@ -0,0 +1,548 @@
+// ---------- Sous-commande validate (sans base de données) ----------
+//
+// Vérifie le mapping, confronte chaque colonne référencée aux en-têtes
+// réels des fichiers, puis lit à blanc les premières lignes (--sample-rows)
+// comme --dry-run: enregistrements illisibles, règles, couverture par
+// question. Ni DATABASE_URL ni sel des emails: de quoi valider un mapping en
+// CI avant tout import. Rapport en texte ou en JSON (--format json, seul sur
+// stdout). Code de sortie 1 s'il y a des erreurs, 0 sinon.
+
+use anyhow::Result;
+use clap::ValueEnum;
+use csv::StringRecord;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+
+use crate::aliases;
+use crate::dryrun::Coverage;
+use crate::input::ColumnAccessor;
+use crate::normalize::NormalizeCaches;
+use crate::{expand_globs, input, load_mapping, mapping_problems, options, rules, stats, unmapped, Mapping, QuestionMap};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+#[derive(Debug)]
+pub struct ValidationError {
+    pub file: String,
+    pub question: String,
+    pub column: String,
+    pub severity: Severity,
+    /// en-tête identique à la casse près (la comparaison reste sensible à la casse)
+    pub other_case: Option<String>,
+}
+
+impl ValidationError {
+    /// Colonne manquante, et l'en-tête qui ne diffère que par la casse
+    pub fn column_note(&self) -> String {
+        match &self.other_case {
+            Some(h) => format!("'{}' (l'en-tête a '{h}': la casse diffère)", self.column),
+            None => format!("'{}'", self.column),
+        }
+    }
+}
+
+/// Colonnes référencées par le mapping absentes de `headers` (comparaison exacte)
+pub fn check_headers(file: &str, mapping: &Mapping, headers: &StringRecord) -> Vec<ValidationError> {
+    let mut out = Vec::new();
+    for_each_column(mapping, |question, column, severity| {
+        if !headers.iter().any(|h| h == column) {
+            let lower = column.to_lowercase();
+            out.push(ValidationError {
+                file: file.to_string(),
+                question: question.to_string(),
+                column: column.to_string(),
+                severity,
+                other_case: headers.iter().find(|h| h.to_lowercase() == lower).map(str::to_string),
+            });
+        }
+    });
+    out
+}
+
+/// Codes de question en double (erreurs) et questions de même type sur une
+/// même source_column (avertissements), avec les positions dans le YAML.
+/// Deux types différents sur une colonne (single_choice + boolean dérivé)
+/// restent admis.
+pub fn duplicate_questions(mapping: &Mapping) -> (Vec<String>, Vec<String>) {
+    let (mut errors, mut warnings) = (Vec::new(), Vec::new());
+    let mut codes: HashMap<&str, usize> = HashMap::new();
+    let mut columns: HashMap<(&str, &str), usize> = HashMap::new();
+    for (i, qm) in mapping.questions.iter().enumerate() {
+        match codes.get(qm.code.as_str()) {
+            Some(&first) => errors.push(format!(
+                "question[{first}] et question[{i}]: code '{}' déclaré deux fois (les réponses iraient à une seule des deux)",
+                qm.code
+            )),
+            None => {
+                codes.insert(&qm.code, i);
+            }
+        }
+        let Some(col) = qm.source_column.as_deref() else { continue };
+        match columns.get(&(col, qm.qtype.as_str())) {
+            Some(&first) => warnings.push(format!(
+                "question[{first}] '{}' et question[{i}] '{}': deux questions {} sur la colonne '{col}'",
+                mapping.questions[first].code, qm.code, qm.qtype
+            )),
+            None => {
+                columns.insert((col, &qm.qtype), i);
+            }
+        }
+    }
+    (errors, warnings)
+}
+
+/// Toutes les colonnes lues par le mapping (questions, options, auteur,
+/// contribution), alias compris
+pub fn mapped_columns(mapping: &Mapping) -> HashSet<&str> {
+    let mut out = HashSet::new();
+    for_each_column(mapping, |_, column, _| {
+        out.insert(column);
+    });
+    out.extend(aliases::with_aliases(mapping).into_iter().flat_map(|col| col.candidates()));
+    out
+}
+
+/// Chaque colonne référencée par le mapping: (question ou champ, colonne,
+/// gravité si elle manque)
+fn for_each_column<'m>(mapping: &'m Mapping, mut check: impl FnMut(&str, &'m str, Severity)) {
+    for qm in &mapping.questions {
+        let required = if qm.optional_column || qm.default_value.is_some() { Severity::Warning } else { Severity::Error };
+        if let Some(col) = &qm.source_column {
+            check(&qm.code, col, required);
+        }
+        if qm.qtype == "multi_choice" && qm.options_from_columns() {
+            for opt in &qm.options {
+                if let Some(col) = &opt.source_column {
+                    check(&format!("{}/{}", qm.code, opt.code), col, required);
+                }
+            }
+        }
+        for (code, row) in qm.matrix_children() {
+            check(&code, &row.source_column, required);
+        }
+        // colonne absente: condition jamais remplie (sauf if_not_value)
+        if let Some(col) = &qm.if_column {
+            check(&qm.code, col, Severity::Warning);
+        }
+        // free_text: colonnes concaténées, la question reste si certaines
+        // manquent (optional_column) mais une colonne mal nommée est une erreur
+        if let Some(src) = &qm.source {
+            for col in &src.columns {
+                check(&qm.code, col, required);
+            }
+        }
+    }
+
+    let a = &mapping.defaults.author;
+    for (field, col) in [
+        ("author.source_author_id", &a.source_author_id),
+        ("author.name", &a.name),
+        ("author.email", &a.email),
+        ("author.email_hash", &a.email_hash),
+        ("author.zipcode", &a.zipcode),
+        ("author.city", &a.city),
+        ("author.age_range", &a.age_range),
+        ("author.gender", &a.gender),
+    ] {
+        if let Some(col) = col {
+            check(field, col, Severity::Warning);
+        }
+    }
+
+    let c = &mapping.defaults.contribution;
+    for (field, col) in [
+        ("contribution.source_contribution_id", &c.source_contribution_id),
+        ("contribution.submitted_at", &c.submitted_at),
+        ("contribution.title", &c.title),
+        ("contribution.source", &c.source),
+    ] {
+        if let Some(col) = col {
+            check(field, col, Severity::Warning);
+        }
+    }
+}
+
+/// source_column de la question, de ses options (une colonne par option)
+/// et de ses lignes de matrice
+pub fn question_columns(qm: &QuestionMap) -> impl Iterator<Item = &str> {
+    let options = qm.options.iter().filter(|_| qm.qtype == "multi_choice" && qm.options_from_columns());
+    qm.source_column
+        .as_deref()
+        .into_iter()
+        .chain(options.filter_map(|o| o.source_column.as_deref()))
+        .chain(qm.rows.iter().map(|r| r.source_column.as_str()))
+}
+
+/// Colonnes de questions absentes de l'en-tête (hors optional_column), une
+/// ligne par question et colonne; None si rien ne manque
+pub fn missing_required(file: &str, mapping: &Mapping, headers: &StringRecord) -> Option<String> {
+    let missing: Vec<String> = check_headers(file, mapping, headers)
+        .into_iter()
+        .filter(|p| p.severity == Severity::Error)
+        .map(|p| format!("  - question '{}': colonne {}", p.question, p.column_note()))
+        .collect();
+    (!missing.is_empty()).then(|| {
+        format!("{file}: {} colonne(s) du mapping absente(s) de l'en-tête:\n{}", missing.len(), missing.join("\n"))
+    })
+}
+
+/// Avant l'ingestion d'un fichier: toutes les colonnes de questions absentes
+/// de l'en-tête en une seule erreur (cf. missing_required); les questions
+/// `optional_column: true` concernées (toutes avec `allow_missing`,
+/// --allow-missing-columns) sont renvoyées, pour être ignorées dans ce
+/// fichier (sauf si elles ont une `default_value`, qui remplit la colonne absente).
+pub fn required_columns<'m>(
+    file: &str,
+    mapping: &'m Mapping,
+    headers: &StringRecord,
+    allow_missing: bool,
+) -> Result<Vec<&'m str>> {
+    if let (Some(missing), false) = (missing_required(file, mapping, headers), allow_missing) {
+        anyhow::bail!(
+            "{missing}\n(optional_column: true pour ignorer la question dans ce fichier, ou --allow-missing-columns)"
+        );
+    }
+    Ok(mapping
+        .questions
+        .iter()
+        .filter(|qm| (qm.optional_column || allow_missing) && qm.default_value.is_none())
+        .filter(|qm| question_columns(qm).any(|c| !headers.iter().any(|h| h == c)))
+        .map(|qm| qm.code.as_str())
+        .collect())
+}
+
+/// Sortie de `validate`
+#[derive(Clone, Copy, Debug, Default, PartialEq, ValueEnum)]
+pub enum ReportFormat {
+    /// Tableau lisible
+    #[default]
+    Text,
+    /// Rapport JSON sur stdout (erreurs, avertissements, couverture par question)
+    Json,
+}
+
+/// Rapport de `validate --format json`
+#[derive(Debug, Default, Serialize)]
+pub struct Report {
+    pub errors: Vec<String>,
+    pub warnings: Vec<String>,
+    pub files: Vec<FileSample>,
+    pub questions: Vec<QuestionCoverage>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FileSample {
+    pub file: String,
+    pub columns: usize,
+    /// lignes lues (au plus --sample-rows)
+    pub rows: usize,
+    pub trashed: usize,
+    /// colonnes à alias: candidat retenu pour ce fichier
+    pub aliases: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct QuestionCoverage {
+    pub code: String,
+    #[serde(rename = "type")]
+    pub qtype: String,
+    /// lignes de l'échantillon qui produiraient une réponse
+    pub answered: usize,
+    /// en % des lignes lues
+    pub coverage: f64,
+}
+
+/// Mapping, en-têtes de chaque fichier, puis lecture à blanc des
+/// --sample-rows premières lignes (toutes sans plafond): enregistrements
+/// illisibles (erreurs), règles `rules:` enfreintes, questions sans aucune
+/// réponse et colonnes qu'aucune question ne lit (avertissements).
+pub fn check(files: &[String], mapping: &Mapping, read_opts: &input::ReadOptions, sample_rows: Option<usize>) -> Report {
+    let (errors, warnings) = mapping_problems(mapping, false);
+    let mut report = Report { errors, warnings, ..Default::default() };
+    // motifs invalides: déjà dans les erreurs du mapping, pas de lecture à blanc
+    let caches = NormalizeCaches::build(&mapping.questions, &mapping.defaults.transforms)
+        .ok()
+        .zip(rules::RuleSet::build(&mapping.questions).ok());
+    let mut coverage = Coverage::new(mapping);
+    let mut quality = rules::Report::default();
+    for path in files {
+        let rows = match input::Rows::open(path, read_opts) {
+            Ok(rows) => rows,
+            Err(e) => {
+                report.errors.push(format!("{path}: illisible: {e:#}"));
+                continue;
+            }
+        };
+        let resolution = aliases::Resolution::resolve(mapping, rows.headers());
+        for p in check_headers(path, mapping, &resolution.headers(rows.headers())) {
+            let msg = format!("{path}: question '{}': colonne {} absente", p.question, p.column_note());
+            match p.severity {
+                Severity::Error => report.errors.push(msg),
+                Severity::Warning => report.warnings.push(msg),
+            }
+        }
+        let columns = rows.headers().len();
+        let (rows_before, trashed_before) = (coverage.rows, coverage.trashed);
+        if let Some((normalize, quality_rules)) = &caches {
+            let mut unread = unmapped::Unmapped::new(mapping, rows.headers().iter());
+            for row in rows.take(sample_rows.unwrap_or(usize::MAX)) {
+                let row = match row {
+                    Ok(row) => row,
+                    Err(e) => {
+                        report.errors.push(format!("{path}: enregistrement illisible: {e:#}"));
+                        continue;
+                    }
+                };
+                let row = resolution.row(row.as_ref());
+                if !coverage.count(mapping, normalize, &row) {
+                    continue;
+                }
+                let at = row.line().map(|l| format!(" ligne {l}")).unwrap_or_default();
+                if let Err(e) = resolution.check(&row) {
+                    report.errors.push(format!("{path}{at}: {e}"));
+                }
+                unread.count(&row);
+                if !quality_rules.is_empty() {
+                    let violations = quality_rules.check(&row, normalize);
+                    for v in quality.note(&violations) {
+                        report.warnings.push(format!("{path}{at}: {}", v.describe()));
+                    }
+                }
+            }
+            report.warnings.extend(unread.report(path));
+        }
+        report.files.push(FileSample {
+            file: path.clone(),
+            columns,
+            rows: coverage.rows - rows_before,
+            trashed: coverage.trashed - trashed_before,
+            aliases: resolution.describe(),
+        });
+    }
+    if caches.is_some() && coverage.rows > coverage.trashed {
+        for (qm, &n) in mapping.questions.iter().zip(&coverage.answered) {
+            if n == 0 {
+                report.warnings.push(format!("question '{}': aucune réponse dans les lignes lues", qm.code));
+            }
+        }
+    }
+    report.questions = mapping
+        .questions
+        .iter()
+        .zip(&coverage.answered)
+        .map(|(qm, &n)| QuestionCoverage { code: qm.code.clone(), qtype: qm.qtype.clone(), answered: n, coverage: coverage.rate(n) })
+        .collect();
+    report
+}
+
+pub fn run_validate(
+    csv_globs: &[String],
+    mapping_path: &PathBuf,
+    delimiter: input::DelimiterChoice,
+    normalize_option_codes: bool,
+    sample_rows: Option<usize>,
+    format: ReportFormat,
+) -> Result<()> {
+    let mut mapping = load_mapping(mapping_path)?;
+    if normalize_option_codes {
+        options::normalize_declared_codes(&mut mapping);
+    }
+    let files = expand_globs(csv_globs)?;
+    let read_opts = input::ReadOptions { delimiter, ..Default::default() };
+    let report = check(&files, &mapping, &read_opts, sample_rows);
+
+    match format {
+        ReportFormat::Json => println!("{}", serde_json::to_string_pretty(&report)?),
+        ReportFormat::Text => {
+            if files.is_empty() {
+                println!("[validate] ⚠️  aucun fichier CSV trouvé");
+            }
+            for f in &report.files {
+                println!("[validate] {}: {} colonnes, {} lignes lues ({} à la corbeille)", f.file, f.columns, f.rows, f.trashed);
+                for note in &f.aliases {
+                    println!("  [alias] {note}");
+                }
+            }
+            for w in &report.warnings {
+                println!("  ⚠️  {w}");
+            }
+            for e in &report.errors {
+                println!("  ❌ {e}");
+            }
+            let rows: Vec<Vec<String>> = report
+                .questions
+                .iter()
+                .map(|q| vec![q.code.clone(), q.qtype.clone(), q.answered.to_string(), format!("{:.1} %", q.coverage)])
+                .collect();
+            if report.files.iter().any(|f| f.rows > 0) {
+                print!("{}", stats::render_table(&["question", "type", "réponses", "couverture"], &rows));
+            }
+            let sample = sample_rows.map(|n| format!(" (au plus {n} lignes par fichier)")).unwrap_or_default();
+            println!(
+                "[validate] {} fichiers{sample}, {} erreurs, {} avertissements",
+                files.len(),
+                report.errors.len(),
+                report.warnings.len()
+            );
+        }
+    }
+    if !report.errors.is_empty() {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mapping() -> Mapping {
+        serde_yaml::from_str(
+            r#"
+form: { name: test }
+defaults:
+  author: { zipcode: code_postal }
+questions:
+  - { code: q1, prompt: Q1, type: text, source_column: col_a }
+  - code: q2
+    prompt: Q2
+    type: multi_choice
+    options:
+      - { code: o1, label: O1, source_column: opt_1 }
+      - { code: o2, label: O2, source_column: opt_2 }
+  - { code: q3, prompt: Q3, type: free_text, source: { columns: [long_a, long_b] } }
+"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn missing_columns_are_reported_with_severity() {
+        let headers = StringRecord::from(vec!["col_a", "opt_1", "long_a"]);
+        let problems = check_headers("f.csv", &mapping(), &headers);
+        let found: Vec<(&str, &str, Severity)> = problems
+            .iter()
+            .map(|p| (p.question.as_str(), p.column.as_str(), p.severity))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                ("q2/o2", "opt_2", Severity::Error),
+                ("q3", "long_b", Severity::Error),
+                ("author.zipcode", "code_postal", Severity::Warning),
+            ]
+        );
+    }
+
+    #[test]
+    fn missing_required_columns_fail_together() {
+        let headers = StringRecord::from(vec!["opt_1", "long_a"]);
+        let err = required_columns("f.csv", &mapping(), &headers, false).unwrap_err().to_string();
+        assert!(err.starts_with("f.csv: 3 colonne(s) du mapping absente(s)"), "{err}");
+        assert!(err.contains("  - question 'q1': colonne 'col_a'\n  - question 'q2/o2': colonne 'opt_2'"), "{err}");
+        assert!(err.contains("  - question 'q3': colonne 'long_b'"), "{err}");
+        // --allow-missing-columns: questions ignorées au lieu d'une erreur
+        assert_eq!(required_columns("f.csv", &mapping(), &headers, true).unwrap(), vec!["q1", "q2"]);
+
+        let mut optional = mapping();
+        for qm in &mut optional.questions {
+            qm.optional_column = true;
+        }
+        assert_eq!(required_columns("f.csv", &optional, &headers, false).unwrap(), vec!["q1", "q2"]);
+        let problems = check_headers("f.csv", &optional, &headers);
+        assert!(problems.iter().all(|p| p.severity == Severity::Warning));
+
+        // default_value: la colonne absente est remplie, la question reste
+        let mut defaulted = mapping();
+        defaulted.questions[0].default_value = Some("région Nord".into());
+        defaulted.questions[1].optional_column = true;
+        defaulted.questions[2].optional_column = true;
+        assert_eq!(required_columns("f.csv", &defaulted, &headers, false).unwrap(), vec!["q2"]);
+    }
+
+    #[test]
+    fn case_only_differences_are_pointed_out() {
+        let headers = StringRecord::from(vec!["COL_A", "opt_1", "opt_2", "long_a", "long_b", "code_postal"]);
+        let err = required_columns("f.csv", &mapping(), &headers, false).unwrap_err().to_string();
+        assert!(err.contains("  - question 'q1': colonne 'col_a' (l'en-tête a 'COL_A': la casse diffère)"), "{err}");
+    }
+
+    #[test]
+    fn duplicate_codes_and_columns_are_reported() {
+        let mapping: Mapping = serde_yaml::from_str(
+            r#"
+form: { name: test }
+questions:
+  - { code: q1, prompt: Q1, type: text, source_column: col_a }
+  - { code: q2, prompt: Q2, type: single_choice, source_column: col_b, options: [{ code: oui, label: Oui }] }
+  - { code: q3, prompt: Q3, type: boolean, source_column: col_b }
+  - { code: q1, prompt: Q1 bis, type: text, source_column: col_a }
+"#,
+        )
+        .unwrap();
+        let (errors, warnings) = duplicate_questions(&mapping);
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].starts_with("question[0] et question[3]: code 'q1'"), "{}", errors[0]);
+        assert_eq!(warnings, vec!["question[0] 'q1' et question[3] 'q1': deux questions text sur la colonne 'col_a'"]);
+        assert!(crate::validate_mapping(&mapping, false).is_err());
+    }
+
+    #[test]
+    fn sample_rows_bound_the_dry_parse() {
+        let path = std::env::temp_dir().join(format!("gdn_validate_{}.csv", std::process::id()));
+        std::fs::write(&path, "col_a,opt_1,long_a,extra\nx,1,,v\ny,,,\nz,1,,\n").unwrap();
+        let files = vec![path.to_string_lossy().into_owned()];
+        let report = check(&files, &mapping(), &input::ReadOptions::default(), Some(2));
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(report.errors.len(), 2, "{:?}", report.errors);
+        assert!(report.errors[0].ends_with("question 'q2/o2': colonne 'opt_2' absente"), "{:?}", report.errors);
+        assert_eq!((report.files[0].columns, report.files[0].rows), (4, 2));
+        let answered: Vec<(&str, usize)> = report.questions.iter().map(|q| (q.code.as_str(), q.answered)).collect();
+        assert_eq!(answered, vec![("q1", 2), ("q2", 1), ("q3", 0)]);
+        assert!(report.warnings.iter().any(|w| w == "question 'q3': aucune réponse dans les lignes lues"), "{:?}", report.warnings);
+        assert!(report.warnings.iter().any(|w| w.contains("'extra'")), "{:?}", report.warnings);
+    }
+
+    #[test]
+    fn complete_headers_are_clean() {
+        let headers = StringRecord::from(vec!["col_a", "opt_1", "opt_2", "long_a", "long_b", "code_postal"]);
+        assert!(check_headers("f.csv", &mapping(), &headers).is_empty());
+    }
+
+    #[test]
+    fn scale_bounds_are_declared_once() {
+        let mapping: Mapping = serde_yaml::from_str(
+            "form: { name: test }
+questions:
+  - { code: note, prompt: Note, type: scale, source_column: n, scale_min: 1, scale_max: 5, max: 10 }
+  - { code: age, prompt: Âge, type: number, source_column: a, min: 0, max: 120 }",
+        )
+        .unwrap();
+        let (errors, _) = mapping_problems(&mapping, false);
+        assert_eq!(errors.len(), 1, "{errors:?}");
+        assert!(errors[0].starts_with("question[0] 'note' (scale): min/max et scale_min/scale_max sont exclusifs"), "{errors:?}");
+    }
+
+    #[test]
+    fn shipped_mappings_are_valid() {
+        let dir = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("../ingest/mappings");
+        let mut checked = 0;
+        for entry in std::fs::read_dir(&dir).unwrap() {
+            let path = entry.unwrap().path();
+            let (errors, warnings) = mapping_problems(&load_mapping(&path).unwrap(), false);
+            assert!(errors.is_empty(), "{}: {errors:?}", path.display());
+            // codes hors slug encore en base: signalés, pas refusés
+            if path.ends_with("questions_rapides_democratie.yml") {
+                assert!(warnings.iter().any(|w| w.contains("'a_ameliorer' non normalisé")), "{warnings:?}");
+            }
+            checked += 1;
+        }
+        assert!(checked >= 7, "{}", dir.display());
+    }
+}
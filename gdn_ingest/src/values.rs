@@ -0,0 +1,481 @@
+// ---------- Analyse des valeurs typées (number, date, boolean, …) ----------
+
+use chrono::{DateTime, NaiveDate, NaiveDateTime};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::collections::HashMap;
+
+/// Espaces utilisés comme séparateurs de milliers dans les exports français
+const SPACES: [char; 4] = [' ', '\u{00a0}', '\u{2009}', '\u{202f}'];
+
+/// Nombre au format français ou anglais: `1 234,5`, `12,5 %`, `1.234,56 €`,
+/// `3.14`. Renvoie `None` si la valeur n'est pas un nombre.
+pub fn parse_number(raw: &str) -> Option<f64> {
+    let s = raw.trim();
+    let s = s.strip_suffix('€').or_else(|| s.strip_suffix('%')).unwrap_or(s);
+    let s: String = s.chars().filter(|c| !SPACES.contains(c)).collect();
+    if s.is_empty() {
+        return None;
+    }
+
+    let normalized = match (s.rfind(','), s.rfind('.')) {
+        // les deux: le dernier séparateur est la décimale
+        (Some(c), Some(d)) if c > d => s.replace('.', "").replace(',', "."),
+        (Some(_), Some(_)) => s.replace(',', ""),
+        (Some(_), None) => s.replace(',', "."),
+        _ => s,
+    };
+    normalized.parse::<f64>().ok().filter(|v| v.is_finite())
+}
+
+/// Entier (échelles): même tolérance de format que `parse_number`,
+/// mais une partie décimale non nulle est refusée.
+pub fn parse_integer(raw: &str) -> Option<i64> {
+    let v = parse_number(raw)?;
+    if v.fract() == 0.0 && v.abs() < 9.0e15 {
+        Some(v as i64)
+    } else {
+        None
+    }
+}
+
+/// Formats essayés par défaut (après RFC 3339), jour avant mois
+pub const DEFAULT_DATE_FORMATS_DAY_FIRST: [&str; 6] = [
+    "%Y-%m-%d %H:%M:%S",
+    "%Y-%m-%dT%H:%M:%S",
+    "%Y-%m-%d",
+    "%d/%m/%Y %H:%M:%S",
+    "%d/%m/%Y %H:%M",
+    "%d/%m/%Y",
+];
+
+/// Variante mois avant jour (`day_first: false`)
+pub const DEFAULT_DATE_FORMATS_MONTH_FIRST: [&str; 6] = [
+    "%Y-%m-%d %H:%M:%S",
+    "%Y-%m-%dT%H:%M:%S",
+    "%Y-%m-%d",
+    "%m/%d/%Y %H:%M:%S",
+    "%m/%d/%Y %H:%M",
+    "%m/%d/%Y",
+];
+
+/// Date/heure (UTC si un fuseau est fourni). RFC 3339 est toujours accepté,
+/// puis chaque format `strftime` est essayé dans l'ordre (date seule → minuit).
+pub fn parse_date<S: AsRef<str>>(raw: &str, formats: &[S]) -> Option<NaiveDateTime> {
+    let s = raw.trim();
+    if s.is_empty() {
+        return None;
+    }
+    if let Ok(dt) = DateTime::parse_from_rfc3339(s) {
+        return Some(dt.naive_utc());
+    }
+    for f in formats {
+        let f = f.as_ref();
+        if let Ok(dt) = DateTime::parse_from_str(s, f) {
+            return Some(dt.naive_utc());
+        }
+        if let Ok(dt) = NaiveDateTime::parse_from_str(s, f) {
+            return Some(dt);
+        }
+        if let Ok(d) = NaiveDate::parse_from_str(s, f) {
+            return d.and_hms_opt(0, 0, 0);
+        }
+    }
+    None
+}
+
+/// Tronque `s` à `max` caractères (pas octets), sans espace final;
+/// `true` s'il a été raccourci
+pub fn truncate_chars(s: &mut String, max: usize) -> bool {
+    match s.char_indices().nth(max) {
+        Some((cut, _)) => {
+            s.truncate(cut);
+            s.truncate(s.trim_end().len());
+            true
+        }
+        None => false,
+    }
+}
+
+/// submitted_at: format imposé (`--submitted-at-format`, `%s` = secondes epoch),
+/// sinon secondes epoch (9 ou 10 chiffres) puis les formats par défaut
+pub fn parse_timestamp(raw: &str, format: Option<&str>) -> Option<NaiveDateTime> {
+    let s = raw.trim();
+    if let Some(f) = format {
+        return parse_date(s, &[f]);
+    }
+    if (9..=10).contains(&s.len()) && s.bytes().all(|b| b.is_ascii_digit()) {
+        return s.parse().ok().and_then(|secs| DateTime::from_timestamp(secs, 0)).map(|dt| dt.naive_utc());
+    }
+    parse_date(s, &DEFAULT_DATE_FORMATS_DAY_FIRST)
+}
+
+/// Format strftime utilisable: une date témoin formatée avec `f` doit être relue à l'identique
+pub fn check_date_format(f: &str) -> Result<(), String> {
+    use std::fmt::Write;
+    let probe = NaiveDate::from_ymd_opt(2019, 3, 25)
+        .and_then(|d| d.and_hms_opt(14, 30, 45))
+        .expect("date témoin valide");
+    let mut text = String::new();
+    if write!(text, "{}", probe.format(f)).is_err() {
+        return Err("spécificateur inconnu".to_string());
+    }
+    match parse_date(&text, &[f]) {
+        Some(d) if d.date() == probe.date() => Ok(()),
+        Some(_) => Err(format!("'{text}' relu comme une autre date")),
+        None => Err(format!("'{text}' illisible avec ce même format")),
+    }
+}
+
+/// Fenêtre de dates plausibles, bornes incluses (jour entier)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DateWindow {
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+}
+
+impl DateWindow {
+    /// Bornes `[début, fin]` lues avec les formats par défaut (jour en premier)
+    pub fn parse(bounds: &[String; 2]) -> Result<Self, String> {
+        let [from, to] = bounds.each_ref().map(|b| {
+            parse_date(b, &DEFAULT_DATE_FORMATS_DAY_FIRST)
+                .map(|d| d.date())
+                .ok_or_else(|| format!("date '{b}' illisible"))
+        });
+        let (from, to) = (from?, to?);
+        if from > to {
+            return Err(format!("{from} postérieure à {to}"));
+        }
+        Ok(DateWindow { from, to })
+    }
+
+    pub fn contains(&self, ts: NaiveDateTime) -> bool {
+        (self.from..=self.to).contains(&ts.date())
+    }
+}
+
+impl std::fmt::Display for DateWindow {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "[{}, {}]", self.from, self.to)
+    }
+}
+
+/// Code postal en tête de cellule: `75 011`, `75011 PARIS`, `2A004`, ou 4 chiffres
+/// (zéro initial perdu par un tableur)
+static ZIPCODE_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^(?:(\d{2}|2[AB])\s*(\d{3})|(\d{4}))(?:[\s,\-]+\D.*|[^\d\s,\-].*)?$").unwrap()
+});
+
+/// Code postal normalisé sur 5 caractères (`75011`, `07501`, `2A004`, `97400`),
+/// ou `None` si la valeur n'en est pas un
+pub fn normalize_zipcode(raw: &str) -> Option<String> {
+    let s = raw.trim().to_uppercase();
+    let caps = ZIPCODE_RE.captures(&s)?;
+    let code = match caps.get(3) {
+        Some(four) => format!("0{}", four.as_str()),
+        None => format!("{}{}", &caps[1], &caps[2]),
+    };
+    // pas de département 00
+    (!code.starts_with("00")).then_some(code)
+}
+
+/// Clé de comparaison tolérante: sans accents, minuscules, espaces simples
+/// (`  Je ne souhaite PAS répondre ` → `je ne souhaite pas repondre`)
+pub fn fold(raw: &str) -> String {
+    deunicode::deunicode(raw).to_lowercase().split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Table de traduction `value_map` d'une question (clés trimées,
+/// en minuscules si `value_map_case_insensitive`)
+#[derive(Debug, Clone)]
+pub struct ValueMap {
+    map: HashMap<String, String>,
+    case_insensitive: bool,
+}
+
+impl ValueMap {
+    pub fn new(map: &HashMap<String, String>, case_insensitive: bool) -> Self {
+        let key = |k: &str| if case_insensitive { k.trim().to_lowercase() } else { k.trim().to_string() };
+        ValueMap {
+            map: map.iter().map(|(k, v)| (key(k), v.trim().to_string())).collect(),
+            case_insensitive,
+        }
+    }
+
+    /// Valeur traduite, ou `None` si `raw` n'est pas une clé de la table
+    pub fn translate(&self, raw: &str) -> Option<&str> {
+        let raw = raw.trim();
+        let found = if self.case_insensitive {
+            self.map.get(&raw.to_lowercase())
+        } else {
+            self.map.get(raw)
+        };
+        found.map(String::as_str)
+    }
+}
+
+/// Valeurs oui/non reconnues par défaut (comparées en minuscules, trimées)
+pub const DEFAULT_TRUE_VALUES: [&str; 8] = ["oui", "o", "yes", "y", "vrai", "true", "1", "x"];
+pub const DEFAULT_FALSE_VALUES: [&str; 6] = ["non", "n", "no", "faux", "false", "0"];
+pub const DEFAULT_UNKNOWN_VALUES: [&str; 5] = ["nsp", "ne sait pas", "ne se prononce pas", "sans opinion", "?"];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BoolAnswer {
+    Yes,
+    No,
+    /// Cellule vide ou NSP
+    Unknown,
+}
+
+impl BoolAnswer {
+    /// Valeur canonique écrite dans answers.text
+    pub fn as_str(self) -> &'static str {
+        match self {
+            BoolAnswer::Yes => "true",
+            BoolAnswer::No => "false",
+            BoolAnswer::Unknown => "unknown",
+        }
+    }
+
+    /// value_num: 1 / 0, NULL pour l'état inconnu
+    pub fn as_num(self) -> Option<f64> {
+        match self {
+            BoolAnswer::Yes => Some(1.0),
+            BoolAnswer::No => Some(0.0),
+            BoolAnswer::Unknown => None,
+        }
+    }
+}
+
+/// Jeux de valeurs d'une question `boolean` (déjà en minuscules)
+#[derive(Debug, Clone)]
+pub struct BooleanValues {
+    pub yes: Vec<String>,
+    pub no: Vec<String>,
+    pub unknown: Vec<String>,
+}
+
+impl BooleanValues {
+    pub fn new(yes: Option<&[String]>, no: Option<&[String]>, unknown: Option<&[String]>) -> Self {
+        let norm = |v: Option<&[String]>, default: &[&str]| -> Vec<String> {
+            match v {
+                Some(v) => v.iter().map(|s| s.trim().to_lowercase()).collect(),
+                None => default.iter().map(|s| s.to_string()).collect(),
+            }
+        };
+        BooleanValues {
+            yes: norm(yes, &DEFAULT_TRUE_VALUES),
+            no: norm(no, &DEFAULT_FALSE_VALUES),
+            unknown: norm(unknown, &DEFAULT_UNKNOWN_VALUES),
+        }
+    }
+
+    /// Vide → Unknown; `None` si la valeur n'appartient à aucun jeu
+    pub fn parse(&self, raw: &str) -> Option<BoolAnswer> {
+        let s = raw.trim().to_lowercase();
+        if s.is_empty() || self.unknown.contains(&s) {
+            Some(BoolAnswer::Unknown)
+        } else if self.yes.contains(&s) {
+            Some(BoolAnswer::Yes)
+        } else if self.no.contains(&s) {
+            Some(BoolAnswer::No)
+        } else {
+            None
+        }
+    }
+
+    /// Valeurs présentes dans plusieurs jeux (configuration ambiguë)
+    pub fn overlaps(&self) -> Vec<String> {
+        let mut out: Vec<String> = self
+            .yes
+            .iter()
+            .filter(|v| self.no.contains(v) || self.unknown.contains(v))
+            .chain(self.no.iter().filter(|v| self.unknown.contains(v)))
+            .cloned()
+            .collect();
+        out.sort();
+        out.dedup();
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn french_numbers() {
+        assert_eq!(parse_number("12,5"), Some(12.5));
+        assert_eq!(parse_number("1 234,56"), Some(1234.56));
+        assert_eq!(parse_number("1\u{202f}234"), Some(1234.0));
+        assert_eq!(parse_number("1\u{00a0}000\u{00a0}€"), Some(1000.0));
+        assert_eq!(parse_number("35 %"), Some(35.0));
+        assert_eq!(parse_number("1.234,5"), Some(1234.5));
+        assert_eq!(parse_number("-3,0"), Some(-3.0));
+    }
+
+    #[test]
+    fn plain_numbers() {
+        assert_eq!(parse_number("42"), Some(42.0));
+        assert_eq!(parse_number("2.75"), Some(2.75));
+        assert_eq!(parse_number("1,234.5"), Some(1234.5));
+    }
+
+    #[test]
+    fn integers_for_scales() {
+        assert_eq!(parse_integer("7"), Some(7));
+        assert_eq!(parse_integer("10,0"), Some(10));
+        assert_eq!(parse_integer("-2"), Some(-2));
+        assert_eq!(parse_integer("7,5"), None);
+        assert_eq!(parse_integer("sept"), None);
+    }
+
+    #[test]
+    fn garbage_is_rejected() {
+        assert_eq!(parse_number(""), None);
+        assert_eq!(parse_number("€"), None);
+        assert_eq!(parse_number("beaucoup"), None);
+        assert_eq!(parse_number("12 ans"), None);
+        assert_eq!(parse_number("NaN"), None);
+    }
+
+    fn ymd_hms(y: i32, m: u32, d: u32, h: u32, mi: u32, sec: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap().and_hms_opt(h, mi, sec).unwrap()
+    }
+
+    #[test]
+    fn fold_ignores_case_accents_and_spaces() {
+        assert_eq!(fold("  Je ne souhaite PAS  répondre "), "je ne souhaite pas repondre");
+        assert_eq!(fold("Féminin"), fold("feminin"));
+    }
+
+    #[test]
+    fn value_map_translation() {
+        let table = HashMap::from([(
+            "Je suis tout à fait d'accord avec cette proposition".to_string(),
+            "Tout à fait d'accord".to_string(),
+        )]);
+        let exact = ValueMap::new(&table, false);
+        assert_eq!(
+            exact.translate(" Je suis tout à fait d'accord avec cette proposition "),
+            Some("Tout à fait d'accord")
+        );
+        assert_eq!(exact.translate("JE SUIS TOUT À FAIT D'ACCORD AVEC CETTE PROPOSITION"), None);
+        let ci = ValueMap::new(&table, true);
+        assert_eq!(
+            ci.translate("JE SUIS TOUT À FAIT D'ACCORD AVEC CETTE PROPOSITION"),
+            Some("Tout à fait d'accord")
+        );
+        assert_eq!(ci.translate("Pas d'accord"), None);
+    }
+
+    #[test]
+    fn gdn_zipcodes() {
+        assert_eq!(normalize_zipcode("75011").as_deref(), Some("75011"));
+        assert_eq!(normalize_zipcode(" 75 011 ").as_deref(), Some("75011"));
+        assert_eq!(normalize_zipcode("75011 PARIS").as_deref(), Some("75011"));
+        assert_eq!(normalize_zipcode("75011-Paris").as_deref(), Some("75011"));
+        assert_eq!(normalize_zipcode("7501").as_deref(), Some("07501"));
+        assert_eq!(normalize_zipcode("2a004 Ajaccio").as_deref(), Some("2A004"));
+        assert_eq!(normalize_zipcode("97400").as_deref(), Some("97400"));
+        assert_eq!(normalize_zipcode("2A"), None);
+        assert_eq!(normalize_zipcode("750112"), None);
+        assert_eq!(normalize_zipcode("00100"), None);
+        assert_eq!(normalize_zipcode("Paris"), None);
+    }
+
+    #[test]
+    fn truncation_counts_chars() {
+        let mut t = "Fiscalité écologique".to_string();
+        assert!(!truncate_chars(&mut t, 20));
+        assert!(truncate_chars(&mut t, 10));
+        assert_eq!(t, "Fiscalité");
+    }
+
+    #[test]
+    fn submitted_at_sniffing_and_override() {
+        let t = ymd_hms(2019, 2, 18, 16, 12, 9);
+        assert_eq!(parse_timestamp("2019-02-18T16:12:09Z", None), Some(t));
+        assert_eq!(parse_timestamp("18/02/2019 16:12", None), Some(ymd_hms(2019, 2, 18, 16, 12, 0)));
+        assert_eq!(parse_timestamp(" 1550506329 ", None), Some(t));
+        assert_eq!(parse_timestamp("12345", None), None);
+        assert_eq!(parse_timestamp("1550506329", Some("%s")), Some(t));
+        assert_eq!(parse_timestamp("02/18/2019 16:12:09", Some("%m/%d/%Y %H:%M:%S")), Some(t));
+        assert_eq!(parse_timestamp("02/18/2019 16:12:09", None), None);
+        assert!(check_date_format("%s").is_ok());
+    }
+
+    #[test]
+    fn gdn_date_formats() {
+        let f = &DEFAULT_DATE_FORMATS_DAY_FIRST;
+        assert_eq!(parse_date("2019-02-18T16:12:09Z", f), Some(ymd_hms(2019, 2, 18, 16, 12, 9)));
+        assert_eq!(parse_date("2019-02-18T17:12:09+01:00", f), Some(ymd_hms(2019, 2, 18, 16, 12, 9)));
+        assert_eq!(parse_date("2019-02-18 16:12:09", f), Some(ymd_hms(2019, 2, 18, 16, 12, 9)));
+        assert_eq!(parse_date("18/02/2019", f), Some(ymd_hms(2019, 2, 18, 0, 0, 0)));
+        assert_eq!(parse_date("2019-02-18", f), Some(ymd_hms(2019, 2, 18, 0, 0, 0)));
+    }
+
+    #[test]
+    fn ambiguous_dates_follow_day_first() {
+        assert_eq!(
+            parse_date("02/03/2019", &DEFAULT_DATE_FORMATS_DAY_FIRST),
+            Some(ymd_hms(2019, 3, 2, 0, 0, 0))
+        );
+        assert_eq!(
+            parse_date("02/03/2019", &DEFAULT_DATE_FORMATS_MONTH_FIRST),
+            Some(ymd_hms(2019, 2, 3, 0, 0, 0))
+        );
+    }
+
+    #[test]
+    fn custom_formats_and_invalid_dates() {
+        assert_eq!(parse_date("18.02.2019", &["%d.%m.%Y"]), Some(ymd_hms(2019, 2, 18, 0, 0, 0)));
+        assert_eq!(parse_date("18.02.2019", &DEFAULT_DATE_FORMATS_DAY_FIRST), None);
+        assert_eq!(parse_date("31/02/2019", &DEFAULT_DATE_FORMATS_DAY_FIRST), None);
+        assert_eq!(parse_date("hier", &DEFAULT_DATE_FORMATS_DAY_FIRST), None);
+    }
+
+    #[test]
+    fn date_window_bounds_are_inclusive() {
+        let w = DateWindow::parse(&["2018-11-01".into(), "31/12/2019".into()]).unwrap();
+        assert!(w.contains(ymd_hms(2018, 11, 1, 0, 0, 0)));
+        assert!(w.contains(ymd_hms(2019, 12, 31, 23, 59, 59)));
+        assert!(!w.contains(ymd_hms(1970, 1, 1, 0, 0, 0)));
+        assert!(!w.contains(ymd_hms(2093, 3, 1, 12, 0, 0)));
+        assert!(DateWindow::parse(&["2019-12-31".into(), "2018-11-01".into()]).is_err());
+        assert!(DateWindow::parse(&["demain".into(), "2018-11-01".into()]).is_err());
+    }
+
+    #[test]
+    fn date_format_probe() {
+        assert!(check_date_format("%d.%m.%Y").is_ok());
+        assert!(check_date_format("%Y%m%d").is_ok());
+        assert!(check_date_format("%d/%m").is_err());
+        assert!(check_date_format("%Q").is_err());
+    }
+
+    #[test]
+    fn french_booleans() {
+        let b = BooleanValues::new(None, None, None);
+        assert_eq!(b.parse("Oui"), Some(BoolAnswer::Yes));
+        assert_eq!(b.parse(" oui "), Some(BoolAnswer::Yes));
+        assert_eq!(b.parse("Non"), Some(BoolAnswer::No));
+        assert_eq!(b.parse("NSP"), Some(BoolAnswer::Unknown));
+        assert_eq!(b.parse(""), Some(BoolAnswer::Unknown));
+        assert_eq!(b.parse("peut-être"), None);
+        assert!(b.overlaps().is_empty());
+    }
+
+    #[test]
+    fn custom_boolean_values() {
+        let yes = vec!["D'accord".to_string()];
+        let no = vec!["Pas d'accord".to_string(), "oui".to_string()];
+        let b = BooleanValues::new(Some(&yes), Some(&no), None);
+        assert_eq!(b.parse("d'accord"), Some(BoolAnswer::Yes));
+        assert_eq!(b.parse("Oui"), Some(BoolAnswer::No));
+        assert_eq!(b.parse("non"), None);
+
+        let overlap = BooleanValues::new(Some(&["oui".to_string()]), Some(&["Oui".to_string()]), None);
+        assert_eq!(overlap.overlaps(), vec!["oui".to_string()]);
+    }
+}
@@ -196,7 +196,7 @@ def preload_questions_and_options(s: Session, form: Form, mapping: Dict[str, Any
                 section=qm.get("section"),
                 position=qm.get("position"),
                 type=qm["type"],
-                options_json=json.dumps(qm.get("meta") or {}, ensure_ascii=False)
+                options_json=qm.get("meta") or {}
             )
             s.add(q); s.flush()
         caches.q_by_code[code] = q
@@ -206,7 +206,7 @@ def preload_questions_and_options(s: Session, form: Form, mapping: Dict[str, Any
                 .scalars().first()
             if not o:
                 o = Option(question_id=q.id, code=opt["code"], label=opt["label"],
-                           position=opt.get("position"), meta_json=json.dumps(opt.get("meta") or {}, ensure_ascii=False))
+                           position=opt.get("position"), meta_json=opt.get("meta") or {})
                 s.add(o); s.flush()
             caches.static_opt_by_label[q.id][o.label] = o
     return caches
@@ -250,7 +250,7 @@ def get_or_create_contribution(s: Session, cmap: Dict[str, Any], row: Dict[str,s
         title=row.get(cmap.get("title")) if cmap.get("title") else None,
         import_batch_id=import_batch_id,
         raw_hash=raw_hash,
-        raw_json=json.dumps(row, ensure_ascii=False),
+        raw_json=row,
     )
     s.add(c); s.flush()
     return c
@@ -0,0 +1,35 @@
+"""contributions_source_line
+
+Revision ID: 3f7a2c9e5d14
+Revises: d2a6f48b91e3
+Create Date: 2025-09-24 16:02:41.118305
+
+"""
+from typing import Sequence, Union
+
+from alembic import op
+import sqlalchemy as sa
+
+
+# revision identifiers, used by Alembic.
+revision: str = '3f7a2c9e5d14'
+down_revision: Union[str, Sequence[str], None] = 'd2a6f48b91e3'
+branch_labels: Union[str, Sequence[str], None] = None
+depends_on: Union[str, Sequence[str], None] = None
+
+
+def upgrade() -> None:
+    """Record which file and line produced each contribution (gdn_ingest, audit).
+
+    source_file is the path given to gdn_ingest (entry name for a zip), source_line
+    the 1-based line where the CSV record starts; both are overwritten on every
+    re-import. raw_hash already holds the row hash.
+    """
+    op.add_column("contributions", sa.Column("source_file", sa.Text, nullable=True))
+    op.add_column("contributions", sa.Column("source_line", sa.BigInteger, nullable=True))
+
+
+def downgrade() -> None:
+    """Drop the source file and line of contributions."""
+    op.drop_column("contributions", "source_line")
+    op.drop_column("contributions", "source_file")
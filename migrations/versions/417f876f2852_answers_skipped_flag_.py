@@ -0,0 +1,31 @@
+"""answers_skipped_flag
+
+Revision ID: 417f876f2852
+Revises: eba106d6b1d3
+Create Date: 2025-09-06 19:17:36.083017
+
+"""
+from typing import Sequence, Union
+
+from alembic import op
+import sqlalchemy as sa
+
+
+# revision identifiers, used by Alembic.
+revision: str = '417f876f2852'
+down_revision: Union[str, Sequence[str], None] = 'eba106d6b1d3'
+branch_labels: Union[str, Sequence[str], None] = None
+depends_on: Union[str, Sequence[str], None] = None
+
+
+def upgrade() -> None:
+    """Add answers.skipped: question shown to the respondent but left empty."""
+    op.add_column(
+        "answers",
+        sa.Column("skipped", sa.Boolean, nullable=False, server_default=sa.text("false")),
+    )
+
+
+def downgrade() -> None:
+    """Drop answers.skipped."""
+    op.drop_column("answers", "skipped")
@@ -0,0 +1,32 @@
+"""contributions_unique_per_form
+
+Revision ID: 5b0d9e7f3c12
+Revises: 8e3f1c6d2a47
+Create Date: 2025-09-18 14:03:27.651904
+
+"""
+from typing import Sequence, Union
+
+from alembic import op
+import sqlalchemy as sa
+
+
+# revision identifiers, used by Alembic.
+revision: str = '5b0d9e7f3c12'
+down_revision: Union[str, Sequence[str], None] = '8e3f1c6d2a47'
+branch_labels: Union[str, Sequence[str], None] = None
+depends_on: Union[str, Sequence[str], None] = None
+
+
+def upgrade() -> None:
+    """A contribution reference is unique within its form, not across forms (gdn_ingest upsert target)."""
+    op.create_unique_constraint(
+        "uq_contributions_form_source_id", "contributions", ["form_id", "source_contribution_id"]
+    )
+    op.drop_constraint("uq_contributions_source_id", "contributions", type_="unique")
+
+
+def downgrade() -> None:
+    """Back to a global unique reference (fails if two forms share one)."""
+    op.create_unique_constraint("uq_contributions_source_id", "contributions", ["source_contribution_id"])
+    op.drop_constraint("uq_contributions_form_source_id", "contributions", type_="unique")
@@ -0,0 +1,41 @@
+"""ingest_checkpoints
+
+Revision ID: 5d3e8a1f6c47
+Revises: e81b5c2d7a94
+Create Date: 2025-10-02 10:41:18.226904
+
+"""
+from typing import Sequence, Union
+
+from alembic import op
+import sqlalchemy as sa
+
+
+# revision identifiers, used by Alembic.
+revision: str = '5d3e8a1f6c47'
+down_revision: Union[str, Sequence[str], None] = 'e81b5c2d7a94'
+branch_labels: Union[str, Sequence[str], None] = None
+depends_on: Union[str, Sequence[str], None] = None
+
+
+def upgrade() -> None:
+    """Per-file resume points of gdn_ingest runs (--resume).
+
+    Written in the ingestion transaction before each commit, deleted when the run succeeds.
+    """
+    op.create_table(
+        "ingest_checkpoints",
+        sa.Column("batch", sa.String, nullable=False),
+        sa.Column("form_id", sa.BigInteger, sa.ForeignKey("forms.id"), nullable=False),
+        sa.Column("file", sa.Text, nullable=False),
+        sa.Column("rows_done", sa.BigInteger, nullable=False),
+        sa.Column("last_line", sa.BigInteger, nullable=True),
+        sa.Column("complete", sa.Boolean, nullable=False, server_default=sa.text("false")),
+        sa.Column("updated_at", sa.DateTime(timezone=True), server_default=sa.func.now(), nullable=False),
+        sa.PrimaryKeyConstraint("batch", "form_id", "file"),
+    )
+
+
+def downgrade() -> None:
+    """Drop resume points."""
+    op.drop_table("ingest_checkpoints")
@@ -0,0 +1,28 @@
+"""answers_value_num_numeric
+
+Revision ID: 7a89de7db46d
+Revises: critical_fts_fix
+Create Date: 2025-09-06 18:09:25.919032
+
+"""
+from typing import Sequence, Union
+
+from alembic import op
+import sqlalchemy as sa
+
+
+# revision identifiers, used by Alembic.
+revision: str = '7a89de7db46d'
+down_revision: Union[str, Sequence[str], None] = 'critical_fts_fix'
+branch_labels: Union[str, Sequence[str], None] = None
+depends_on: Union[str, Sequence[str], None] = None
+
+
+def upgrade() -> None:
+    """Add answers.value_num for parsed number answers."""
+    op.add_column("answers", sa.Column("value_num", sa.Numeric))
+
+
+def downgrade() -> None:
+    """Drop answers.value_num."""
+    op.drop_column("answers", "value_num")
@@ -0,0 +1,48 @@
+"""import_batches_run_lock
+
+Revision ID: 8e3f1c6d2a47
+Revises: c41d7e9a2b58
+Create Date: 2025-09-16 09:12:44.108352
+
+"""
+from typing import Sequence, Union
+
+from alembic import op
+import sqlalchemy as sa
+
+
+# revision identifiers, used by Alembic.
+revision: str = '8e3f1c6d2a47'
+down_revision: Union[str, Sequence[str], None] = 'c41d7e9a2b58'
+branch_labels: Union[str, Sequence[str], None] = None
+depends_on: Union[str, Sequence[str], None] = None
+
+
+def upgrade() -> None:
+    """Add import_batches: one row per ingest run, `running` rows act as a per-form lock."""
+    op.create_table(
+        "import_batches",
+        sa.Column("id", sa.BigInteger, primary_key=True),
+        sa.Column("batch", sa.String, nullable=False),
+        sa.Column("form_id", sa.BigInteger, sa.ForeignKey("forms.id"), nullable=False),
+        sa.Column("status", sa.String, nullable=False),
+        sa.Column("started_at", sa.DateTime(timezone=True), server_default=sa.func.now(), nullable=False),
+        sa.Column("heartbeat_at", sa.DateTime(timezone=True), nullable=True),
+        sa.Column("finished_at", sa.DateTime(timezone=True), nullable=True),
+        sa.Column("host", sa.String, nullable=True),
+        sa.Column("pid", sa.Integer, nullable=True),
+        sa.Column("error", sa.Text, nullable=True),
+    )
+    op.create_index(
+        "uq_import_batches_running_form",
+        "import_batches",
+        ["form_id"],
+        unique=True,
+        postgresql_where=sa.text("status = 'running'"),
+    )
+
+
+def downgrade() -> None:
+    """Drop import_batches."""
+    op.drop_index("uq_import_batches_running_form", table_name="import_batches")
+    op.drop_table("import_batches")
@@ -0,0 +1,40 @@
+"""import_batches_counts
+
+Revision ID: 9c4e1b7a3f26
+Revises: 3f7a2c9e5d14
+Create Date: 2025-09-26 10:41:18.552093
+
+"""
+from typing import Sequence, Union
+
+from alembic import op
+import sqlalchemy as sa
+
+
+# revision identifiers, used by Alembic.
+revision: str = '9c4e1b7a3f26'
+down_revision: Union[str, Sequence[str], None] = '3f7a2c9e5d14'
+branch_labels: Union[str, Sequence[str], None] = None
+depends_on: Union[str, Sequence[str], None] = None
+
+
+def upgrade() -> None:
+    """Record the mapping file and the row counts of each gdn_ingest run.
+
+    Counts are those committed when the run ends (partial for an error run).
+    Contributions point back to their run by batch name (contributions.import_batch_id).
+    """
+    op.add_column("import_batches", sa.Column("mapping_path", sa.Text, nullable=True))
+    op.add_column("import_batches", sa.Column("rows_total", sa.BigInteger, nullable=True))
+    op.add_column("import_batches", sa.Column("rows_skipped", sa.BigInteger, nullable=True))
+    op.add_column("import_batches", sa.Column("errors", sa.BigInteger, nullable=True))
+    op.create_index("idx_import_batches_started_at", "import_batches", ["started_at"])
+
+
+def downgrade() -> None:
+    """Drop the mapping file and row counts of import_batches."""
+    op.drop_index("idx_import_batches_started_at", table_name="import_batches")
+    op.drop_column("import_batches", "errors")
+    op.drop_column("import_batches", "rows_skipped")
+    op.drop_column("import_batches", "rows_total")
+    op.drop_column("import_batches", "mapping_path")
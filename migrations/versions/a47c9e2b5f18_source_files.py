@@ -0,0 +1,43 @@
+"""source_files
+
+Revision ID: a47c9e2b5f18
+Revises: 5d3e8a1f6c47
+Create Date: 2025-10-03 15:07:42.619380
+
+"""
+from typing import Sequence, Union
+
+from alembic import op
+import sqlalchemy as sa
+
+
+# revision identifiers, used by Alembic.
+revision: str = 'a47c9e2b5f18'
+down_revision: Union[str, Sequence[str], None] = '5d3e8a1f6c47'
+branch_labels: Union[str, Sequence[str], None] = None
+depends_on: Union[str, Sequence[str], None] = None
+
+
+def upgrade() -> None:
+    """Files fully ingested by gdn_ingest, by content checksum (--skip-ingested)."""
+    op.create_table(
+        "source_files",
+        sa.Column("id", sa.BigInteger, primary_key=True),
+        sa.Column("form_id", sa.BigInteger, sa.ForeignKey("forms.id"), nullable=False),
+        sa.Column("file_name", sa.Text, nullable=False),
+        sa.Column("path", sa.Text, nullable=False),
+        sa.Column("checksum", sa.String(64), nullable=False),
+        sa.Column("size_bytes", sa.BigInteger, nullable=True),
+        sa.Column("rows", sa.BigInteger, nullable=False),
+        sa.Column("batch", sa.String, nullable=False),
+        sa.Column("completed_at", sa.DateTime(timezone=True), server_default=sa.func.now(), nullable=False),
+    )
+    op.create_index("idx_source_files_form_checksum", "source_files", ["form_id", "checksum"])
+    op.create_index("idx_source_files_form_file_name", "source_files", ["form_id", "file_name"])
+
+
+def downgrade() -> None:
+    """Drop the ingested files table."""
+    op.drop_index("idx_source_files_form_file_name", table_name="source_files")
+    op.drop_index("idx_source_files_form_checksum", table_name="source_files")
+    op.drop_table("source_files")
@@ -0,0 +1,28 @@
+"""source_files head_checksum
+
+Revision ID: b8d3e6f1a4c7
+Revises: f1c3a8e5b2d9
+Create Date: 2025-10-14 09:26:51.318407
+
+"""
+from typing import Sequence, Union
+
+from alembic import op
+import sqlalchemy as sa
+
+
+# revision identifiers, used by Alembic.
+revision: str = 'b8d3e6f1a4c7'
+down_revision: Union[str, Sequence[str], None] = 'f1c3a8e5b2d9'
+branch_labels: Union[str, Sequence[str], None] = None
+depends_on: Union[str, Sequence[str], None] = None
+
+
+def upgrade() -> None:
+    """SHA-256 of the first 64 KiB: gdn_ingest --skip-ingested checks it before a full pass."""
+    op.add_column("source_files", sa.Column("head_checksum", sa.String(64), nullable=True))
+
+
+def downgrade() -> None:
+    """Drop the head checksum."""
+    op.drop_column("source_files", "head_checksum")
@@ -0,0 +1,38 @@
+"""json columns jsonb
+
+Revision ID: b93f6d2e8c15
+Revises: a47c9e2b5f18
+Create Date: 2025-10-06 10:21:37.804512
+
+"""
+from typing import Sequence, Union
+
+from alembic import op
+
+
+# revision identifiers, used by Alembic.
+revision: str = 'b93f6d2e8c15'
+down_revision: Union[str, Sequence[str], None] = 'a47c9e2b5f18'
+branch_labels: Union[str, Sequence[str], None] = None
+depends_on: Union[str, Sequence[str], None] = None
+
+# (table, colonne) écrites en JSON par les ingesteurs
+COLUMNS = [
+    ("contributions", "raw_json"),
+    ("questions", "options_json"),
+    ("options", "meta_json"),
+]
+
+
+def upgrade() -> None:
+    """Store raw rows and question/option meta as jsonb (queried without casts)."""
+    for table, column in COLUMNS:
+        op.execute(
+            f"ALTER TABLE {table} ALTER COLUMN {column} TYPE jsonb USING NULLIF({column}, '')::jsonb"
+        )
+
+
+def downgrade() -> None:
+    """Back to text columns."""
+    for table, column in COLUMNS:
+        op.execute(f"ALTER TABLE {table} ALTER COLUMN {column} TYPE text USING {column}::text")
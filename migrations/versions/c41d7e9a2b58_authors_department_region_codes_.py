@@ -0,0 +1,30 @@
+"""authors_department_region_codes
+
+Revision ID: c41d7e9a2b58
+Revises: 417f876f2852
+Create Date: 2025-09-14 10:42:18.517203
+
+"""
+from typing import Sequence, Union
+
+from alembic import op
+import sqlalchemy as sa
+
+
+# revision identifiers, used by Alembic.
+revision: str = 'c41d7e9a2b58'
+down_revision: Union[str, Sequence[str], None] = '417f876f2852'
+branch_labels: Union[str, Sequence[str], None] = None
+depends_on: Union[str, Sequence[str], None] = None
+
+
+def upgrade() -> None:
+    """Add authors.department_code / region_code, derived from the zipcode at ingest."""
+    op.add_column("authors", sa.Column("department_code", sa.String, nullable=True))
+    op.add_column("authors", sa.Column("region_code", sa.String, nullable=True))
+
+
+def downgrade() -> None:
+    """Drop authors.department_code / region_code."""
+    op.drop_column("authors", "region_code")
+    op.drop_column("authors", "department_code")
@@ -0,0 +1,34 @@
+"""gdpr erasures
+
+Revision ID: c5e1a7d3f920
+Revises: b93f6d2e8c15
+Create Date: 2025-10-08 15:42:11.207316
+
+"""
+from typing import Sequence, Union
+
+from alembic import op
+import sqlalchemy as sa
+
+
+# revision identifiers, used by Alembic.
+revision: str = 'c5e1a7d3f920'
+down_revision: Union[str, Sequence[str], None] = 'b93f6d2e8c15'
+branch_labels: Union[str, Sequence[str], None] = None
+depends_on: Union[str, Sequence[str], None] = None
+
+
+def upgrade() -> None:
+    """Audit trail of `gdn_ingest erase` requests (no email hash kept, only its SHA-256)."""
+    op.create_table(
+        "gdpr_erasures",
+        sa.Column("id", sa.BigInteger, primary_key=True),
+        sa.Column("erased_at", sa.DateTime(timezone=True), server_default=sa.func.now(), nullable=False),
+        sa.Column("email_hash_sha256", sa.String(64), nullable=False),
+        sa.Column("rows_affected", sa.BigInteger, nullable=False),
+    )
+
+
+def downgrade() -> None:
+    """Drop the erasure audit trail."""
+    op.drop_table("gdpr_erasures")
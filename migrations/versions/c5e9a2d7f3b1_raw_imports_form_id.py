@@ -0,0 +1,40 @@
+"""raw_imports form_id
+
+Revision ID: c5e9a2d7f3b1
+Revises: b8d3e6f1a4c7
+Create Date: 2025-10-15 11:02:13.774120
+
+"""
+from typing import Sequence, Union
+
+from alembic import op
+import sqlalchemy as sa
+
+
+# revision identifiers, used by Alembic.
+revision: str = 'c5e9a2d7f3b1'
+down_revision: Union[str, Sequence[str], None] = 'b8d3e6f1a4c7'
+branch_labels: Union[str, Sequence[str], None] = None
+depends_on: Union[str, Sequence[str], None] = None
+
+
+def upgrade() -> None:
+    """Scope the row-level audit by form: the same row in two forms is not a duplicate."""
+    op.add_column("raw_imports", sa.Column("form_id", sa.BigInteger, sa.ForeignKey("forms.id"), nullable=True))
+    op.execute(
+        "UPDATE raw_imports r SET form_id = c.form_id FROM contributions c "
+        "WHERE c.id = r.contribution_id AND r.form_id IS NULL"
+    )
+    op.drop_index("uq_raw_imports_row_hash", table_name="raw_imports")
+    op.create_index("uq_raw_imports_form_row_hash", "raw_imports", ["form_id", "row_hash"], unique=True)
+
+
+def downgrade() -> None:
+    """Back to one row per content across forms (later duplicates are dropped)."""
+    op.drop_index("uq_raw_imports_form_row_hash", table_name="raw_imports")
+    op.execute(
+        "DELETE FROM raw_imports r USING raw_imports e "
+        "WHERE e.row_hash = r.row_hash AND e.id < r.id"
+    )
+    op.create_index("uq_raw_imports_row_hash", "raw_imports", ["row_hash"], unique=True)
+    op.drop_column("raw_imports", "form_id")
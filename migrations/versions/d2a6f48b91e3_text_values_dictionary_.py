@@ -0,0 +1,56 @@
+"""text_values_dictionary
+
+Revision ID: d2a6f48b91e3
+Revises: 5b0d9e7f3c12
+Create Date: 2025-09-20 11:27:05.374610
+
+"""
+from typing import Sequence, Union
+
+from alembic import op
+import sqlalchemy as sa
+from sqlalchemy.dialects import postgresql
+
+
+# revision identifiers, used by Alembic.
+revision: str = 'd2a6f48b91e3'
+down_revision: Union[str, Sequence[str], None] = '5b0d9e7f3c12'
+branch_labels: Union[str, Sequence[str], None] = None
+depends_on: Union[str, Sequence[str], None] = None
+
+
+def upgrade() -> None:
+    """Add the text_values dictionary (gdn_ingest --dictionary-texts) and import_batches.provenance.
+
+    A dictionary-encoded answer has text NULL and text_value_id set: read it with
+    COALESCE(answers.text, text_values.value).
+    """
+    op.create_table(
+        "text_values",
+        sa.Column("id", sa.BigInteger, primary_key=True),
+        sa.Column("value", sa.Text, nullable=False, unique=True),
+        sa.Column("refcount", sa.BigInteger, nullable=False, server_default="0"),
+    )
+    op.add_column("answers", sa.Column("text_value_id", sa.BigInteger, sa.ForeignKey("text_values.id"), nullable=True))
+    op.create_index(
+        "idx_answers_text_value_id",
+        "answers",
+        ["text_value_id"],
+        postgresql_where=sa.text("text_value_id IS NOT NULL"),
+    )
+    op.add_column(
+        "import_batches",
+        sa.Column("provenance", postgresql.JSONB, nullable=False, server_default=sa.text("'{}'::jsonb")),
+    )
+
+
+def downgrade() -> None:
+    """Drop the dictionary (decode answers first with COALESCE(text, value)) and import_batches.provenance."""
+    op.execute(
+        "UPDATE answers a SET text = tv.value FROM text_values tv "
+        "WHERE tv.id = a.text_value_id AND a.text IS NULL"
+    )
+    op.drop_column("import_batches", "provenance")
+    op.drop_index("idx_answers_text_value_id", table_name="answers")
+    op.drop_column("answers", "text_value_id")
+    op.drop_table("text_values")
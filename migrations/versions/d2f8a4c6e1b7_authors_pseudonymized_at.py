@@ -0,0 +1,28 @@
+"""authors pseudonymized_at
+
+Revision ID: d2f8a4c6e1b7
+Revises: c5e1a7d3f920
+Create Date: 2025-10-09 10:17:45.532190
+
+"""
+from typing import Sequence, Union
+
+from alembic import op
+import sqlalchemy as sa
+
+
+# revision identifiers, used by Alembic.
+revision: str = 'd2f8a4c6e1b7'
+down_revision: Union[str, Sequence[str], None] = 'c5e1a7d3f920'
+branch_labels: Union[str, Sequence[str], None] = None
+depends_on: Union[str, Sequence[str], None] = None
+
+
+def upgrade() -> None:
+    """Set by `gdn_ingest pseudonymize` once an author's PII has been replaced."""
+    op.add_column("authors", sa.Column("pseudonymized_at", sa.DateTime(timezone=True), nullable=True))
+
+
+def downgrade() -> None:
+    """Drop the pseudonymization marker."""
+    op.drop_column("authors", "pseudonymized_at")
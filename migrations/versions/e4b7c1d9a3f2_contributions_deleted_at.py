@@ -0,0 +1,30 @@
+"""contributions deleted_at
+
+Revision ID: e4b7c1d9a3f2
+Revises: d2f8a4c6e1b7
+Create Date: 2025-10-10 09:42:11.204518
+
+"""
+from typing import Sequence, Union
+
+from alembic import op
+import sqlalchemy as sa
+
+
+# revision identifiers, used by Alembic.
+revision: str = 'e4b7c1d9a3f2'
+down_revision: Union[str, Sequence[str], None] = 'd2f8a4c6e1b7'
+branch_labels: Union[str, Sequence[str], None] = None
+depends_on: Union[str, Sequence[str], None] = None
+
+
+def upgrade() -> None:
+    """Soft delete: set by `gdn_ingest soft-delete` or by a trashed row at ingestion."""
+    op.add_column("contributions", sa.Column("deleted_at", sa.DateTime(timezone=True), nullable=True))
+    op.add_column("contributions", sa.Column("delete_reason", sa.Text(), nullable=True))
+
+
+def downgrade() -> None:
+    """Drop the soft-delete columns."""
+    op.drop_column("contributions", "delete_reason")
+    op.drop_column("contributions", "deleted_at")
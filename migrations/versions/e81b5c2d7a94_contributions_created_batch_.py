@@ -0,0 +1,36 @@
+"""contributions_created_batch
+
+Revision ID: e81b5c2d7a94
+Revises: 9c4e1b7a3f26
+Create Date: 2025-09-29 09:12:55.803417
+
+"""
+from typing import Sequence, Union
+
+from alembic import op
+import sqlalchemy as sa
+
+
+# revision identifiers, used by Alembic.
+revision: str = 'e81b5c2d7a94'
+down_revision: Union[str, Sequence[str], None] = '9c4e1b7a3f26'
+branch_labels: Union[str, Sequence[str], None] = None
+depends_on: Union[str, Sequence[str], None] = None
+
+
+def upgrade() -> None:
+    """Remember the batch that created each contribution (gdn_ingest delete-batch).
+
+    import_batch_id is overwritten by every re-import; created_import_batch_id is
+    set on insert only. Existing rows stay NULL: delete-batch treats them as updated.
+    """
+    op.add_column("contributions", sa.Column("created_import_batch_id", sa.String, nullable=True))
+    op.create_index("idx_contributions_import_batch_id", "contributions", ["import_batch_id"])
+    op.create_index("idx_contributions_created_import_batch_id", "contributions", ["created_import_batch_id"])
+
+
+def downgrade() -> None:
+    """Drop the creating batch of contributions."""
+    op.drop_index("idx_contributions_created_import_batch_id", table_name="contributions")
+    op.drop_index("idx_contributions_import_batch_id", table_name="contributions")
+    op.drop_column("contributions", "created_import_batch_id")
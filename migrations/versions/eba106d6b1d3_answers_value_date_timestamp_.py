@@ -0,0 +1,28 @@
+"""answers_value_date_timestamp
+
+Revision ID: eba106d6b1d3
+Revises: 7a89de7db46d
+Create Date: 2025-09-06 18:41:30.917663
+
+"""
+from typing import Sequence, Union
+
+from alembic import op
+import sqlalchemy as sa
+
+
+# revision identifiers, used by Alembic.
+revision: str = 'eba106d6b1d3'
+down_revision: Union[str, Sequence[str], None] = '7a89de7db46d'
+branch_labels: Union[str, Sequence[str], None] = None
+depends_on: Union[str, Sequence[str], None] = None
+
+
+def upgrade() -> None:
+    """Add answers.value_date for parsed date answers."""
+    op.add_column("answers", sa.Column("value_date", sa.TIMESTAMP(timezone=False)))
+
+
+def downgrade() -> None:
+    """Drop answers.value_date."""
+    op.drop_column("answers", "value_date")
@@ -0,0 +1,43 @@
+"""raw_imports
+
+Revision ID: f1c3a8e5b2d9
+Revises: e4b7c1d9a3f2
+Create Date: 2025-10-13 10:18:37.552914
+
+"""
+from typing import Sequence, Union
+
+from alembic import op
+import sqlalchemy as sa
+from sqlalchemy.dialects import postgresql
+
+
+# revision identifiers, used by Alembic.
+revision: str = 'f1c3a8e5b2d9'
+down_revision: Union[str, Sequence[str], None] = 'e4b7c1d9a3f2'
+branch_labels: Union[str, Sequence[str], None] = None
+depends_on: Union[str, Sequence[str], None] = None
+
+
+def upgrade() -> None:
+    """Row-level audit of gdn_ingest: one row per ingested content (row_hash)."""
+    op.create_table(
+        "raw_imports",
+        sa.Column("id", sa.BigInteger, primary_key=True),
+        sa.Column("batch_id", sa.String, nullable=False),
+        sa.Column("file_path", sa.Text, nullable=False),
+        sa.Column("row_number", sa.BigInteger, nullable=False),
+        sa.Column("contribution_id", sa.BigInteger, sa.ForeignKey("contributions.id", ondelete="SET NULL"), nullable=True),
+        sa.Column("row_hash", sa.String, nullable=False),
+        sa.Column("raw_json", postgresql.JSONB, nullable=True),
+        sa.Column("ingested_at", sa.DateTime(timezone=True), server_default=sa.func.now(), nullable=False),
+    )
+    op.create_index("uq_raw_imports_row_hash", "raw_imports", ["row_hash"], unique=True)
+    op.create_index("idx_raw_imports_batch_id", "raw_imports", ["batch_id"])
+
+
+def downgrade() -> None:
+    """Drop the row-level audit table."""
+    op.drop_index("idx_raw_imports_batch_id", table_name="raw_imports")
+    op.drop_index("uq_raw_imports_row_hash", table_name="raw_imports")
+    op.drop_table("raw_imports")